@@ -0,0 +1,601 @@
+use lattice_core::{
+    hashing::{shake256, concat_for_hash},
+    ntt::{ntt_forward, ntt_inverse, ntt_pointwise_mul, NTTParams},
+    polynomial::Polynomial,
+    vector_matrix::PolyVector,
+    zq::ZqElement,
+};
+use rand::{Rng, rngs::OsRng};
+
+use crate::error::Error;
+use crate::packing::{
+    encode_w1, pack_hint, pack_signed_vector, unpack_hint, unpack_signed_vector,
+};
+use crate::params::{SecurityLevel, D, N, Q, poly_modulus, poly_modulus_ntt, sizes};
+use crate::rounding::{centered, high_bits, low_bits, make_hint, power2round, use_hint};
+use crate::sampling::{expand_a, expand_mask, expand_s, sample_in_ball};
+
+/// Represents a Dilithium public key
+#[derive(Debug, Clone, PartialEq)]
+pub struct PublicKey {
+    /// Seed for generating matrix A (rho in the paper)
+    pub rho: [u8; 32],
+    /// High-order bits t1 of t = As1 + s2
+    pub t1: PolyVector,
+    /// Security level
+    pub security_level: SecurityLevel,
+}
+
+/// Represents a Dilithium secret key
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecretKey {
+    /// Seed for generating matrix A
+    pub rho: [u8; 32],
+    /// Seed K for deriving the per-signature masking randomness
+    pub key: [u8; 32],
+    /// Hash tr = H(pk) of the public key
+    pub tr: [u8; 64],
+    /// Secret vector s1 with coefficients in [-η, η]
+    pub s1: PolyVector,
+    /// Secret vector s2 with coefficients in [-η, η]
+    pub s2: PolyVector,
+    /// Low-order bits t0 of t = As1 + s2
+    pub t0: PolyVector,
+    /// Security level
+    pub security_level: SecurityLevel,
+}
+
+/// Represents a Dilithium signature (c~, z, h)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    /// Commitment hash c~ from which the challenge c is derived
+    pub c_tilde: Vec<u8>,
+    /// Response z = y + cs1
+    pub z: PolyVector,
+    /// Hint h for recovering the high bits of w
+    pub h: Vec<Vec<bool>>,
+    /// Security level
+    pub security_level: SecurityLevel,
+}
+
+/// NTT parameters for Dilithium: q = 8380417 has the primitive 512th root of unity 1753,
+/// so X^256 + 1 splits completely.
+pub fn get_ntt_params() -> NTTParams {
+    NTTParams::new(Q, N, 1753)
+}
+
+/// Converts every polynomial of a vector to NTT form
+pub(crate) fn ntt_vector(vec: &PolyVector, ntt_params: &NTTParams) -> PolyVector {
+    let entries = vec.entries.iter().map(|p| ntt_forward(p, ntt_params)).collect();
+    PolyVector::new(entries, poly_modulus_ntt())
+}
+
+/// Converts every polynomial of a vector back from NTT form
+pub(crate) fn inverse_ntt_vector(vec: &PolyVector, ntt_params: &NTTParams) -> PolyVector {
+    let entries = vec.entries.iter().map(|p| ntt_inverse(p, ntt_params)).collect();
+    PolyVector::new(entries, poly_modulus())
+}
+
+/// Multiplies every entry of an NTT-form vector by the NTT-form polynomial c_hat
+pub(crate) fn scale_vector(c_hat: &Polynomial, vec_hat: &PolyVector, ntt_params: &NTTParams) -> PolyVector {
+    let entries = vec_hat.entries.iter()
+        .map(|p| ntt_inverse(&ntt_pointwise_mul(c_hat, p, ntt_params), ntt_params))
+        .collect();
+    PolyVector::new(entries, poly_modulus())
+}
+
+/// Applies f to every coefficient of every polynomial of a vector
+pub(crate) fn map_coeffs(vec: &PolyVector, f: impl Fn(i32) -> i32) -> Vec<Vec<i32>> {
+    vec.entries.iter()
+        .map(|p| p.coeffs.iter().map(|c| f(c.value())).collect())
+        .collect()
+}
+
+/// Builds a vector of polynomials from integer coefficients
+pub(crate) fn from_coeffs(coeffs: &[Vec<i32>]) -> PolyVector {
+    let entries = coeffs.iter()
+        .map(|c| Polynomial::new(c.iter().map(|&v| ZqElement::new(v, Q)).collect(), poly_modulus()))
+        .collect();
+    PolyVector::new(entries, poly_modulus())
+}
+
+/// Formats the message M' = 0 || |ctx| || ctx || M of pure ML-DSA
+fn format_message(msg: &[u8], ctx: &[u8]) -> Result<Vec<u8>, Error> {
+    if ctx.len() > 255 {
+        return Err(Error::ContextTooLong);
+    }
+
+    Ok(concat_for_hash(&[&[0u8, ctx.len() as u8], ctx, msg]))
+}
+
+/// Implements the KeyGen algorithm from a 32-byte seed ξ (ML-DSA.KeyGen_internal)
+pub fn keygen_internal(xi: &[u8; 32], security_level: SecurityLevel) -> (PublicKey, SecretKey) {
+    let (k, l) = (security_level.k(), security_level.l());
+    let ntt_params = get_ntt_params();
+
+    // (ρ, ρ', K) = H(ξ || k || l)
+    let expanded = shake256(&concat_for_hash(&[xi, &[k as u8, l as u8]]), 128);
+    let mut rho = [0u8; 32];
+    let mut rho_prime = [0u8; 64];
+    let mut key = [0u8; 32];
+    rho.copy_from_slice(&expanded[0..32]);
+    rho_prime.copy_from_slice(&expanded[32..96]);
+    key.copy_from_slice(&expanded[96..128]);
+
+    // A is sampled directly in NTT form, s1 and s2 have small coefficients
+    let a_hat = expand_a(&rho, security_level);
+    let (s1, s2) = expand_s(&rho_prime, security_level);
+
+    // Compute t = As1 + s2
+    let as1 = inverse_ntt_vector(&a_hat.mul_vec(&ntt_vector(&s1, &ntt_params), Some(&ntt_params)), &ntt_params);
+    let t = as1 + s2.clone();
+
+    // Split t into high and low bits: t = t1 * 2^d + t0
+    let t1 = from_coeffs(&map_coeffs(&t, |v| power2round(v).0));
+    let t0 = from_coeffs(&map_coeffs(&t, |v| power2round(v).1));
+
+    let pk = PublicKey { rho, t1, security_level };
+
+    let mut tr = [0u8; 64];
+    tr.copy_from_slice(&shake256(&pk_to_bytes(&pk), 64));
+
+    let sk = SecretKey { rho, key, tr, s1, s2, t0, security_level };
+    debug_assert_eq!(sk.s1.len(), l);
+
+    (pk, sk)
+}
+
+/// Recomputes the public key (ρ, t1) belonging to a secret key
+pub fn public_key_from_secret(sk: &SecretKey) -> PublicKey {
+    let ntt_params = get_ntt_params();
+    let a_hat = expand_a(&sk.rho, sk.security_level);
+
+    let as1 = inverse_ntt_vector(&a_hat.mul_vec(&ntt_vector(&sk.s1, &ntt_params), Some(&ntt_params)), &ntt_params);
+    let t = as1 + sk.s2.clone();
+    let t1 = from_coeffs(&map_coeffs(&t, |v| power2round(v).0));
+
+    PublicKey { rho: sk.rho, t1, security_level: sk.security_level }
+}
+
+/// Implements the KeyGen algorithm with a fresh random seed
+pub fn keygen(security_level: SecurityLevel) -> (PublicKey, SecretKey) {
+    let mut xi = [0u8; 32];
+    OsRng.fill(&mut xi);
+
+    keygen_internal(&xi, security_level)
+}
+
+/// Implements the Sign algorithm on the formatted message M' (ML-DSA.Sign_internal)
+///
+/// This is the Fiat-Shamir with aborts loop: a fresh mask y is drawn until the
+/// response z = y + cs1 and the hint leak nothing about the secret key.
+pub fn sign_internal(sk: &SecretKey, m_prime: &[u8], rnd: &[u8; 32]) -> Signature {
+    let level = sk.security_level;
+    let (gamma1, gamma2, beta) = (level.gamma1(), level.gamma2(), level.beta());
+    let ntt_params = get_ntt_params();
+
+    let s1_hat = ntt_vector(&sk.s1, &ntt_params);
+    let s2_hat = ntt_vector(&sk.s2, &ntt_params);
+    let t0_hat = ntt_vector(&sk.t0, &ntt_params);
+    let a_hat = expand_a(&sk.rho, level);
+
+    // μ = H(tr || M'), ρ'' = H(K || rnd || μ)
+    let mu = shake256(&concat_for_hash(&[&sk.tr, m_prime]), 64);
+    let mut rho_pp = [0u8; 64];
+    rho_pp.copy_from_slice(&shake256(&concat_for_hash(&[&sk.key, rnd, &mu]), 64));
+
+    let mut kappa = 0;
+    loop {
+        // Commitment w = Ay and its high bits w1
+        let y = expand_mask(&rho_pp, kappa, level);
+        kappa += level.l();
+        let w = inverse_ntt_vector(&a_hat.mul_vec(&ntt_vector(&y, &ntt_params), Some(&ntt_params)), &ntt_params);
+        let w1 = map_coeffs(&w, |v| high_bits(v, gamma2));
+
+        // Challenge c = SampleInBall(H(μ || w1))
+        let c_tilde = shake256(&concat_for_hash(&[&mu, &encode_w1(&w1, sizes::w1_bits(level))]), level.lambda() / 4);
+        let c_hat = ntt_forward(&sample_in_ball(&c_tilde, level.tau()), &ntt_params);
+
+        // Response z = y + cs1
+        let z = y + scale_vector(&c_hat, &s1_hat, &ntt_params);
+        let w_cs2 = w - scale_vector(&c_hat, &s2_hat, &ntt_params);
+
+        // Reject if z or the low bits of w - cs2 could leak s1 or s2
+        if z.infinity_norm() >= gamma1 - beta {
+            continue;
+        }
+        let r0_norm = map_coeffs(&w_cs2, |v| low_bits(v, gamma2).abs())
+            .iter().flatten().copied().max().unwrap_or(0);
+        if r0_norm >= gamma2 - beta {
+            continue;
+        }
+
+        // Hint for recovering HighBits(w - cs2) from w - cs2 + ct0
+        let ct0 = scale_vector(&c_hat, &t0_hat, &ntt_params);
+        if ct0.infinity_norm() >= gamma2 {
+            continue;
+        }
+        let h: Vec<Vec<bool>> = ct0.entries.iter()
+            .zip(w_cs2.entries.iter())
+            .map(|(ct0_i, w_i)| {
+                ct0_i.coeffs.iter()
+                    .zip(w_i.coeffs.iter())
+                    .map(|(c, w)| make_hint(-centered(c.value()), w.value() + c.value(), gamma2))
+                    .collect()
+            })
+            .collect();
+        if h.iter().flatten().filter(|&&bit| bit).count() > level.omega() {
+            continue;
+        }
+
+        return Signature { c_tilde, z, h, security_level: level };
+    }
+}
+
+/// Implements the Verify algorithm on the formatted message M' (ML-DSA.Verify_internal)
+pub fn verify_internal(pk: &PublicKey, m_prime: &[u8], sig: &Signature) -> bool {
+    let level = pk.security_level;
+    if sig.security_level != level {
+        return false;
+    }
+    if sig.z.infinity_norm() >= level.gamma1() - level.beta() {
+        return false;
+    }
+
+    let ntt_params = get_ntt_params();
+    let a_hat = expand_a(&pk.rho, level);
+
+    // μ = H(H(pk) || M')
+    let tr = shake256(&pk_to_bytes(pk), 64);
+    let mu = shake256(&concat_for_hash(&[&tr, m_prime]), 64);
+
+    // w'approx = Az - ct1 * 2^d
+    let c_hat = ntt_forward(&sample_in_ball(&sig.c_tilde, level.tau()), &ntt_params);
+    let t1_scaled = from_coeffs(&map_coeffs(&pk.t1, |v| v << D));
+    let az = inverse_ntt_vector(&a_hat.mul_vec(&ntt_vector(&sig.z, &ntt_params), Some(&ntt_params)), &ntt_params);
+    let w_approx = az - scale_vector(&c_hat, &ntt_vector(&t1_scaled, &ntt_params), &ntt_params);
+
+    // w1' = UseHint(h, w'approx)
+    let w1: Vec<Vec<i32>> = w_approx.entries.iter()
+        .zip(sig.h.iter())
+        .map(|(w_i, h_i)| {
+            w_i.coeffs.iter()
+                .zip(h_i.iter())
+                .map(|(w, &hint)| use_hint(hint, w.value(), level.gamma2()))
+                .collect()
+        })
+        .collect();
+
+    let c_tilde = shake256(&concat_for_hash(&[&mu, &encode_w1(&w1, sizes::w1_bits(level))]), level.lambda() / 4);
+    c_tilde == sig.c_tilde
+}
+
+/// Signs a message with a context string and explicit randomness rnd
+///
+/// An all-zero rnd gives the deterministic variant of ML-DSA.
+pub fn sign_with_context(sk: &SecretKey, msg: &[u8], ctx: &[u8], rnd: &[u8; 32]) -> Result<Signature, Error> {
+    let m_prime = format_message(msg, ctx)?;
+    Ok(sign_internal(sk, &m_prime, rnd))
+}
+
+/// Verifies a signature over a message with a context string
+pub fn verify_with_context(pk: &PublicKey, msg: &[u8], ctx: &[u8], sig: &Signature) -> bool {
+    match format_message(msg, ctx) {
+        Ok(m_prime) => verify_internal(pk, &m_prime, sig),
+        Err(_) => false,
+    }
+}
+
+/// Signs a message (hedged variant with fresh randomness, empty context)
+pub fn sign(sk: &SecretKey, msg: &[u8]) -> Signature {
+    let mut rnd = [0u8; 32];
+    OsRng.fill(&mut rnd);
+
+    sign_with_context(sk, msg, &[], &rnd).expect("empty context is valid")
+}
+
+/// Signs a message deterministically (rnd = 0, empty context)
+pub fn sign_deterministic(sk: &SecretKey, msg: &[u8]) -> Signature {
+    sign_with_context(sk, msg, &[], &[0u8; 32]).expect("empty context is valid")
+}
+
+/// Verifies a signature over a message (empty context)
+pub fn verify(pk: &PublicKey, msg: &[u8], sig: &Signature) -> bool {
+    verify_with_context(pk, msg, &[], sig)
+}
+
+/// Serializes a public key to bytes: rho || SimpleBitPack(t1, 10 bits)
+pub fn pk_to_bytes(pk: &PublicKey) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(sizes::public_key_bytes(pk.security_level));
+    bytes.extend_from_slice(&pk.rho);
+    bytes.extend_from_slice(&pk.t1.to_bytes(10));
+    bytes
+}
+
+/// Deserializes a public key from bytes, rejecting inputs of the wrong length
+pub fn pk_from_bytes(bytes: &[u8], security_level: SecurityLevel) -> Result<PublicKey, Error> {
+    let expected = sizes::public_key_bytes(security_level);
+    if bytes.len() != expected {
+        return Err(Error::InvalidLength { expected, actual: bytes.len() });
+    }
+
+    let mut rho = [0u8; 32];
+    rho.copy_from_slice(&bytes[0..32]);
+
+    let t1 = PolyVector::from_bytes(&bytes[32..], poly_modulus(), security_level.k(), 10);
+
+    Ok(PublicKey { rho, t1, security_level })
+}
+
+/// Serializes a secret key to bytes: rho || K || tr || s1 || s2 || t0
+pub fn sk_to_bytes(sk: &SecretKey) -> Vec<u8> {
+    let level = sk.security_level;
+    let eta = level.eta() as i32;
+    let eta_bits = sizes::eta_bits(level);
+
+    let mut bytes = Vec::with_capacity(sizes::secret_key_bytes(level));
+    bytes.extend_from_slice(&sk.rho);
+    bytes.extend_from_slice(&sk.key);
+    bytes.extend_from_slice(&sk.tr);
+    bytes.extend_from_slice(&pack_signed_vector(&sk.s1, eta_bits, eta));
+    bytes.extend_from_slice(&pack_signed_vector(&sk.s2, eta_bits, eta));
+    bytes.extend_from_slice(&pack_signed_vector(&sk.t0, D, 1 << (D - 1)));
+    bytes
+}
+
+/// Deserializes a secret key from bytes, rejecting inputs of the wrong length
+pub fn sk_from_bytes(bytes: &[u8], security_level: SecurityLevel) -> Result<SecretKey, Error> {
+    let expected = sizes::secret_key_bytes(security_level);
+    if bytes.len() != expected {
+        return Err(Error::InvalidLength { expected, actual: bytes.len() });
+    }
+
+    let (k, l) = (security_level.k(), security_level.l());
+    let eta = security_level.eta() as i32;
+    let eta_bits = sizes::eta_bits(security_level);
+    let poly_eta = N * eta_bits / 8;
+
+    let mut rho = [0u8; 32];
+    let mut key = [0u8; 32];
+    let mut tr = [0u8; 64];
+    rho.copy_from_slice(&bytes[0..32]);
+    key.copy_from_slice(&bytes[32..64]);
+    tr.copy_from_slice(&bytes[64..128]);
+
+    let s1_offset = 128;
+    let s2_offset = s1_offset + l * poly_eta;
+    let t0_offset = s2_offset + k * poly_eta;
+    let s1 = unpack_signed_vector(&bytes[s1_offset..s2_offset], l, eta_bits, eta, eta);
+    let s2 = unpack_signed_vector(&bytes[s2_offset..t0_offset], k, eta_bits, eta, eta);
+    let t0 = unpack_signed_vector(&bytes[t0_offset..], k, D, (1 << (D - 1)) - 1, 1 << (D - 1));
+
+    Ok(SecretKey { rho, key, tr, s1, s2, t0, security_level })
+}
+
+/// Serializes a signature to bytes: c~ || BitPack(z) || HintBitPack(h)
+pub fn signature_to_bytes(sig: &Signature) -> Vec<u8> {
+    let level = sig.security_level;
+
+    let mut bytes = Vec::with_capacity(sizes::signature_bytes(level));
+    bytes.extend_from_slice(&sig.c_tilde);
+    bytes.extend_from_slice(&pack_signed_vector(&sig.z, sizes::gamma1_bits(level), level.gamma1()));
+    bytes.extend_from_slice(&pack_hint(&sig.h, level.omega()));
+    bytes
+}
+
+/// Deserializes a signature from bytes, rejecting malformed hints
+pub fn signature_from_bytes(bytes: &[u8], security_level: SecurityLevel) -> Result<Signature, Error> {
+    let expected = sizes::signature_bytes(security_level);
+    if bytes.len() != expected {
+        return Err(Error::InvalidLength { expected, actual: bytes.len() });
+    }
+
+    let c_len = security_level.lambda() / 4;
+    let z_len = security_level.l() * N * sizes::gamma1_bits(security_level) / 8;
+    let gamma1 = security_level.gamma1();
+
+    let c_tilde = bytes[..c_len].to_vec();
+    let z = unpack_signed_vector(
+        &bytes[c_len..c_len + z_len],
+        security_level.l(),
+        sizes::gamma1_bits(security_level),
+        gamma1 - 1,
+        gamma1,
+    );
+    let h = unpack_hint(&bytes[c_len + z_len..], security_level.k(), security_level.omega())
+        .ok_or(Error::InvalidSignature)?;
+
+    Ok(Signature { c_tilde, z, h, security_level })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::{Dilithium2, Dilithium3, Dilithium5, ParameterSet};
+    use crate::types::{Signature as TypedSignature, SigningKey, VerifyingKey};
+    use signature::{Keypair, Signer, Verifier};
+
+    const LEVELS: [SecurityLevel; 3] = [
+        SecurityLevel::Dilithium2,
+        SecurityLevel::Dilithium3,
+        SecurityLevel::Dilithium5,
+    ];
+
+    /// One entry of `fixtures/ml-dsa-kat.txt`
+    struct Kat {
+        parameter_set: String,
+        seed: [u8; 32],
+        msg: Vec<u8>,
+        ctx: Vec<u8>,
+        pk: Vec<u8>,
+        sk: Vec<u8>,
+        sig: Vec<u8>,
+    }
+
+    /// Parses the `name = value` blocks of the KAT file, one block per `parameter_set`
+    fn kats() -> Vec<Kat> {
+        let text = include_str!("../fixtures/ml-dsa-kat.txt");
+        let mut kats = Vec::new();
+        for block in text.split("\n\n").filter(|b| b.contains("parameter_set = ")) {
+            // An empty value (the empty context) is written as `name =`
+            let field = |name: &str| {
+                let line = block.lines().find(|l| l.starts_with(&format!("{} =", name))).unwrap();
+                line[name.len() + 2..].trim().to_string()
+            };
+            let bytes = |name: &str| hex::decode(field(name)).unwrap();
+            kats.push(Kat {
+                parameter_set: field("parameter_set"),
+                seed: bytes("seed").try_into().unwrap(),
+                msg: bytes("msg"),
+                ctx: bytes("ctx"),
+                pk: bytes("pk"),
+                sk: bytes("sk"),
+                sig: bytes("sig"),
+            });
+        }
+        kats
+    }
+
+    /// Runs one KAT through the typed keys of `types`
+    fn check_typed_kat<P: ParameterSet>(kat: &Kat) {
+        let sk = SigningKey::<P>::from_seed(&kat.seed);
+        let vk = sk.verifying_key();
+        assert_eq!(vk.as_bytes().as_ref(), kat.pk.as_slice());
+        assert_eq!(sk.as_bytes().as_ref(), kat.sk.as_slice());
+
+        // The encodings decode to the same keys
+        assert_eq!(VerifyingKey::<P>::try_from(kat.pk.as_slice()).unwrap(), vk);
+        assert_eq!(SigningKey::<P>::try_from(kat.sk.as_slice()).unwrap().as_key(), sk.as_key());
+
+        let sig = TypedSignature::<P>::try_from(kat.sig.as_slice()).unwrap();
+        assert!(verify_with_context(vk.as_key(), &kat.msg, &kat.ctx, sig.as_signature()));
+
+        // `Signer` is deterministic signing with the empty context
+        if kat.ctx.is_empty() {
+            let signed: TypedSignature<P> = sk.sign(&kat.msg);
+            assert_eq!(signed.as_bytes().as_ref(), kat.sig.as_slice());
+            assert!(vk.verify(&kat.msg, &sig).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_fips204_kat() {
+        let kats = kats();
+        assert_eq!(kats.len(), 6);
+
+        for kat in kats {
+            let level = match kat.parameter_set.as_str() {
+                "ML-DSA-44" => SecurityLevel::Dilithium2,
+                "ML-DSA-65" => SecurityLevel::Dilithium3,
+                "ML-DSA-87" => SecurityLevel::Dilithium5,
+                other => panic!("unknown parameter set {}", other),
+            };
+
+            let (pk, sk) = keygen_internal(&kat.seed, level);
+            assert_eq!(pk_to_bytes(&pk), kat.pk);
+            assert_eq!(sk_to_bytes(&sk), kat.sk);
+
+            let sig = sign_with_context(&sk, &kat.msg, &kat.ctx, &[0u8; 32]).unwrap();
+            assert_eq!(signature_to_bytes(&sig), kat.sig);
+            assert!(verify_with_context(&pk, &kat.msg, &kat.ctx, &sig));
+
+            match level {
+                SecurityLevel::Dilithium2 => check_typed_kat::<Dilithium2>(&kat),
+                SecurityLevel::Dilithium3 => check_typed_kat::<Dilithium3>(&kat),
+                SecurityLevel::Dilithium5 => check_typed_kat::<Dilithium5>(&kat),
+            }
+        }
+    }
+
+    #[test]
+    fn test_sign_verify() {
+        for level in LEVELS {
+            let (pk, sk) = keygen(level);
+            let msg = b"Basic Lattice Cryptography";
+
+            let sig = sign(&sk, msg);
+            assert!(verify(&pk, msg, &sig), "Valid signature rejected for {:?}", level);
+            assert!(!verify(&pk, b"another message", &sig));
+        }
+    }
+
+    #[test]
+    fn test_serialization_sizes() {
+        // Sizes of ML-DSA-44/65/87 in FIPS 204, Table 2
+        let expected = [(1312, 2560, 2420), (1952, 4032, 3309), (2592, 4896, 4627)];
+
+        for (level, (pk_len, sk_len, sig_len)) in LEVELS.into_iter().zip(expected) {
+            let (pk, sk) = keygen(level);
+            let sig = sign(&sk, b"sizes");
+
+            let pk_bytes = pk_to_bytes(&pk);
+            let sk_bytes = sk_to_bytes(&sk);
+            let sig_bytes = signature_to_bytes(&sig);
+            assert_eq!((pk_bytes.len(), sk_bytes.len(), sig_bytes.len()), (pk_len, sk_len, sig_len));
+
+            // Encodings are lossless
+            assert_eq!(pk_from_bytes(&pk_bytes, level).unwrap(), pk);
+            assert_eq!(sk_from_bytes(&sk_bytes, level).unwrap(), sk);
+            assert_eq!(signature_from_bytes(&sig_bytes, level).unwrap(), sig);
+
+            // Truncated or padded keys are rejected rather than sliced
+            assert_eq!(
+                pk_from_bytes(&pk_bytes[1..], level),
+                Err(Error::InvalidLength { expected: pk_len, actual: pk_len - 1 })
+            );
+            let mut long_sk = sk_bytes.clone();
+            long_sk.push(0);
+            assert_eq!(
+                sk_from_bytes(&long_sk, level),
+                Err(Error::InvalidLength { expected: sk_len, actual: sk_len + 1 })
+            );
+        }
+    }
+
+    #[test]
+    fn test_deterministic_keygen_and_signing() {
+        let level = SecurityLevel::Dilithium2;
+        let (pk1, sk1) = keygen_internal(&[42u8; 32], level);
+        let (pk2, sk2) = keygen_internal(&[42u8; 32], level);
+        assert_eq!(pk1, pk2);
+        assert_eq!(sk1, sk2);
+
+        let sig1 = sign_deterministic(&sk1, b"message");
+        let sig2 = sign_deterministic(&sk2, b"message");
+        assert_eq!(sig1, sig2);
+        assert!(verify(&pk1, b"message", &sig1));
+    }
+
+    #[test]
+    fn test_context_binding() {
+        let level = SecurityLevel::Dilithium2;
+        let (pk, sk) = keygen(level);
+
+        let sig = sign_with_context(&sk, b"message", b"ctx-a", &[1u8; 32]).unwrap();
+        assert!(verify_with_context(&pk, b"message", b"ctx-a", &sig));
+        assert!(!verify_with_context(&pk, b"message", b"ctx-b", &sig));
+        assert!(!verify(&pk, b"message", &sig));
+
+        assert_eq!(sign_with_context(&sk, b"m", &[0u8; 256], &[0u8; 32]).unwrap_err(), Error::ContextTooLong);
+    }
+
+    #[test]
+    fn test_tampered_signature_rejected() {
+        let level = SecurityLevel::Dilithium2;
+        let (pk, sk) = keygen(level);
+        let sig = sign(&sk, b"message");
+        let bytes = signature_to_bytes(&sig);
+
+        // Flip one bit in c~ and one in z
+        for index in [0, 100] {
+            let mut tampered = bytes.clone();
+            tampered[index] ^= 1;
+            let tampered_sig = signature_from_bytes(&tampered, level).unwrap();
+            assert!(!verify(&pk, b"message", &tampered_sig));
+        }
+
+        // A signature for another key does not verify
+        let (other_pk, _) = keygen(level);
+        assert!(!verify(&other_pk, b"message", &sig));
+    }
+}
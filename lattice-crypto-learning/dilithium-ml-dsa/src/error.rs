@@ -0,0 +1,43 @@
+use std::fmt;
+
+/// Errors returned when decoding Dilithium keys and signatures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The input does not have the length required by the parameter set
+    InvalidLength {
+        /// Expected length in bytes
+        expected: usize,
+        /// Actual length in bytes
+        actual: usize,
+    },
+    /// The signature encoding is malformed (e.g. a non-canonical hint)
+    InvalidSignature,
+    /// The signing key encoding is not canonical or its tr does not match the key
+    InvalidSigningKey,
+    /// The context string is longer than 255 bytes
+    ContextTooLong,
+    /// The key belongs to a different parameter set than requested
+    SecurityLevelMismatch,
+    /// A Σ-protocol message arrived when the party was not expecting it
+    OutOfTurn,
+    /// A Σ-protocol message belongs to a different protocol variant
+    VariantMismatch,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidLength { expected, actual } => {
+                write!(f, "invalid length: expected {} bytes, got {}", expected, actual)
+            }
+            Error::InvalidSignature => write!(f, "malformed signature encoding"),
+            Error::InvalidSigningKey => write!(f, "malformed signing key encoding"),
+            Error::ContextTooLong => write!(f, "context string exceeds 255 bytes"),
+            Error::SecurityLevelMismatch => write!(f, "key belongs to a different parameter set"),
+            Error::OutOfTurn => write!(f, "protocol message sent out of turn"),
+            Error::VariantMismatch => write!(f, "protocol message belongs to a different variant"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
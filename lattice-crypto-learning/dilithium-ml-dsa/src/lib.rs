@@ -1,9 +1,15 @@
 pub mod params;
+pub mod rounding;
+pub mod packing;
+pub mod sampling;
+pub mod dsa;
+pub mod error;
+pub mod types;
+pub mod pkcs8;
+pub mod seed;
+pub mod sigma;
 
-// Placeholder for the CRYSTALS-Dilithium (ML-DSA) implementation
-// This will be implemented in a future update
-
-/// Placeholder version function
+/// Crate version
 pub fn version() -> &'static str {
     "0.1.0"
 }
@@ -15,4 +21,4 @@ mod tests {
         let result = 2 + 2;
         assert_eq!(result, 4);
     }
-} 
\ No newline at end of file
+}
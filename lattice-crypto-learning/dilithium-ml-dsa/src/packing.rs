@@ -0,0 +1,160 @@
+use lattice_core::{
+    polynomial::Polynomial,
+    vector_matrix::PolyVector,
+    zq::ZqElement,
+};
+
+use crate::params::{N, Q, poly_modulus};
+
+/// BitPack: encodes a polynomial with coefficients in [-a, b] using `bits` bits each
+///
+/// Each coefficient w is stored as b - w, which lies in [0, a + b].
+pub fn pack_signed(poly: &Polynomial, bits: usize, b: i32) -> Vec<u8> {
+    let coeffs = poly.coeffs.iter().map(|c| ZqElement::new(b - c.value(), Q)).collect();
+    Polynomial::new(coeffs, poly_modulus()).to_bytes(bits)
+}
+
+/// BitUnpack: inverse of [`pack_signed`]
+///
+/// `a` is only used to document the range; values above a + b can only come from
+/// malformed input and are reduced modulo q like any other coefficient.
+pub fn unpack_signed(bytes: &[u8], bits: usize, a: i32, b: i32) -> Polynomial {
+    debug_assert!(a + b < 1 << bits, "Range [-a, b] must fit in the given number of bits");
+    let stored = Polynomial::from_bytes(bytes, poly_modulus(), bits);
+    let coeffs = stored.coeffs.iter().map(|c| ZqElement::new(b - c.value(), Q)).collect();
+    Polynomial::new(coeffs, poly_modulus())
+}
+
+/// Packs every polynomial of a vector with [`pack_signed`]
+pub fn pack_signed_vector(vec: &PolyVector, bits: usize, b: i32) -> Vec<u8> {
+    vec.entries.iter()
+        .flat_map(|poly| pack_signed(poly, bits, b))
+        .collect()
+}
+
+/// Unpacks `length` polynomials with [`unpack_signed`]
+pub fn unpack_signed_vector(bytes: &[u8], length: usize, bits: usize, a: i32, b: i32) -> PolyVector {
+    let bytes_per_poly = N * bits / 8;
+    let entries = (0..length)
+        .map(|i| unpack_signed(&bytes[i * bytes_per_poly..(i + 1) * bytes_per_poly], bits, a, b))
+        .collect();
+
+    PolyVector::new(entries, poly_modulus())
+}
+
+/// HintBitPack: stores the positions of the ones of h in ω + k bytes
+///
+/// The first ω bytes list positions, the last k bytes hold the running count
+/// of positions after each polynomial.
+pub fn pack_hint(h: &[Vec<bool>], omega: usize) -> Vec<u8> {
+    let k = h.len();
+    let mut bytes = vec![0u8; omega + k];
+    let mut index = 0;
+
+    for (i, poly) in h.iter().enumerate() {
+        for (j, &bit) in poly.iter().enumerate() {
+            if bit {
+                bytes[index] = j as u8;
+                index += 1;
+            }
+        }
+        bytes[omega + i] = index as u8;
+    }
+
+    bytes
+}
+
+/// HintBitUnpack: inverse of [`pack_hint`], returning None for malformed encodings
+///
+/// Positions must be strictly increasing within each polynomial and unused
+/// position bytes must be zero, which makes the encoding unique (strong unforgeability).
+pub fn unpack_hint(bytes: &[u8], k: usize, omega: usize) -> Option<Vec<Vec<bool>>> {
+    let mut h = vec![vec![false; N]; k];
+    let mut index = 0usize;
+
+    for (i, poly) in h.iter_mut().enumerate() {
+        let end = bytes[omega + i] as usize;
+        if end < index || end > omega {
+            return None;
+        }
+
+        let first = index;
+        while index < end {
+            if index > first && bytes[index - 1] >= bytes[index] {
+                return None;
+            }
+            poly[bytes[index] as usize] = true;
+            index += 1;
+        }
+    }
+
+    if bytes[index..omega].iter().any(|&b| b != 0) {
+        return None;
+    }
+
+    Some(h)
+}
+
+/// w1Encode: packs the high bits w1 (coefficients in [0, (q-1)/(2γ2))) for hashing
+pub fn encode_w1(w1: &[Vec<i32>], bits: usize) -> Vec<u8> {
+    w1.iter()
+        .flat_map(|coeffs| {
+            let poly = Polynomial::new(
+                coeffs.iter().map(|&c| ZqElement::new(c, Q)).collect(),
+                poly_modulus(),
+            );
+            poly.to_bytes(bits)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signed_packing_roundtrip() {
+        let coeffs = (0..N as i32).map(|i| ZqElement::new(i % 9 - 4, Q)).collect();
+        let poly = Polynomial::new(coeffs, poly_modulus());
+
+        let bytes = pack_signed(&poly, 4, 4);
+        assert_eq!(bytes.len(), N * 4 / 8);
+        assert_eq!(unpack_signed(&bytes, 4, 4, 4), poly);
+    }
+
+    #[test]
+    fn test_hint_packing_roundtrip() {
+        let mut h = vec![vec![false; N]; 4];
+        h[0][3] = true;
+        h[0][200] = true;
+        h[2][0] = true;
+        h[3][255] = true;
+
+        let bytes = pack_hint(&h, 80);
+        assert_eq!(bytes.len(), 84);
+        assert_eq!(unpack_hint(&bytes, 4, 80), Some(h));
+    }
+
+    #[test]
+    fn test_malformed_hint_rejected() {
+        let mut h = vec![vec![false; N]; 4];
+        h[1][10] = true;
+        h[1][20] = true;
+        let bytes = pack_hint(&h, 80);
+
+        // Unordered positions
+        let mut swapped = bytes.clone();
+        swapped.swap(0, 1);
+        assert_eq!(unpack_hint(&swapped, 4, 80), None);
+
+        // Non-zero padding
+        let mut padded = bytes.clone();
+        padded[5] = 1;
+        assert_eq!(unpack_hint(&padded, 4, 80), None);
+
+        // Count exceeding ω
+        let mut overflow = bytes;
+        overflow[83] = 81;
+        assert_eq!(unpack_hint(&overflow, 4, 80), None);
+    }
+}
@@ -3,9 +3,11 @@ use lattice_core::params::{PolyModulusInfo, dilithium};
 // Common Dilithium parameters
 pub const Q: i32 = dilithium::Q;
 pub const N: usize = dilithium::N;
+/// Number of dropped bits of t (t = t1 * 2^d + t0)
+pub const D: usize = dilithium::D;
 
 /// Represents the security parameter sets for Dilithium/ML-DSA
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SecurityLevel {
     /// Dilithium2 (ML-DSA-44)
     Dilithium2,
@@ -24,6 +26,55 @@ impl SecurityLevel {
             SecurityLevel::Dilithium5 => dilithium::dilithium_5(),
         }
     }
+    
+    /// Returns the number of rows k of the matrix A
+    pub fn k(&self) -> usize {
+        self.dilithium_params().base.k
+    }
+    
+    /// Returns the number of columns l of the matrix A
+    pub fn l(&self) -> usize {
+        self.dilithium_params().l
+    }
+    
+    /// Returns the secret coefficient bound η
+    pub fn eta(&self) -> usize {
+        self.dilithium_params().base.eta
+    }
+    
+    /// Returns the number of ±1 coefficients τ of the challenge
+    pub fn tau(&self) -> usize {
+        self.dilithium_params().tau
+    }
+    
+    /// Returns the rejection bound β = τ·η
+    pub fn beta(&self) -> i32 {
+        self.dilithium_params().beta
+    }
+    
+    /// Returns the masking range γ1
+    pub fn gamma1(&self) -> i32 {
+        self.dilithium_params().gamma1
+    }
+    
+    /// Returns the low-order rounding range γ2
+    pub fn gamma2(&self) -> i32 {
+        self.dilithium_params().gamma2
+    }
+    
+    /// Returns the maximum number of ones ω in the hint
+    pub fn omega(&self) -> usize {
+        self.dilithium_params().omega
+    }
+    
+    /// Returns the collision strength λ of the commitment hash c~ in bits
+    pub fn lambda(&self) -> usize {
+        match self {
+            SecurityLevel::Dilithium2 => 128,
+            SecurityLevel::Dilithium3 => 192,
+            SecurityLevel::Dilithium5 => 256,
+        }
+    }
 }
 
 /// Creates the polynomial modulus info for Dilithium
@@ -42,4 +93,116 @@ pub fn poly_modulus_ntt() -> PolyModulusInfo {
         q: Q,
         is_ntt_form: true,
     }
-} 
\ No newline at end of file
+} 
+/// Key and signature sizes in bytes
+pub mod sizes {
+    use super::SecurityLevel;
+    
+    /// Seed size in bytes (ρ, K and ξ)
+    pub const SEED_BYTES: usize = 32;
+    /// Size of the public key hash tr in bytes
+    pub const TR_BYTES: usize = 64;
+    
+    /// Bits per coefficient of s1 and s2 (bitlen(2η))
+    pub fn eta_bits(level: SecurityLevel) -> usize {
+        match level.eta() {
+            2 => 3,
+            _ => 4,
+        }
+    }
+    
+    /// Bits per coefficient of z (1 + bitlen(γ1 - 1))
+    pub fn gamma1_bits(level: SecurityLevel) -> usize {
+        (level.gamma1().trailing_zeros() + 1) as usize
+    }
+    
+    /// Bits per coefficient of w1 (bitlen((q - 1)/(2γ2) - 1))
+    pub fn w1_bits(level: SecurityLevel) -> usize {
+        let max_w1 = (super::Q - 1) / (2 * level.gamma2()) - 1;
+        (32 - max_w1.leading_zeros()) as usize
+    }
+    
+    /// Calculate public key size based on security level
+    pub fn public_key_bytes(level: SecurityLevel) -> usize {
+        SEED_BYTES + level.k() * super::N * 10 / 8  // rho + t1 (10 bits per coefficient)
+    }
+    
+    /// Calculate secret key size based on security level
+    pub fn secret_key_bytes(level: SecurityLevel) -> usize {
+        let poly_eta = super::N * eta_bits(level) / 8;
+        2 * SEED_BYTES + TR_BYTES +                       // rho, K, tr
+        (level.k() + level.l()) * poly_eta +             // s1, s2
+        level.k() * super::N * super::D / 8              // t0 (13 bits per coefficient)
+    }
+    
+    /// Calculate signature size based on security level
+    pub fn signature_bytes(level: SecurityLevel) -> usize {
+        level.lambda() / 4 +                                  // c~
+        level.l() * super::N * gamma1_bits(level) / 8 +       // z
+        level.omega() + level.k()                             // h
+    }
+}
+
+/// Fixed-size byte array used for typed key and signature encodings
+pub trait ByteArray:
+    AsRef<[u8]> + AsMut<[u8]> + Clone + std::fmt::Debug + PartialEq + Eq + for<'a> TryFrom<&'a [u8]>
+{
+    /// Length of the array in bytes
+    const LEN: usize;
+    
+    /// Returns an all-zero array
+    fn zeroed() -> Self;
+}
+
+impl<const L: usize> ByteArray for [u8; L] {
+    const LEN: usize = L;
+    
+    fn zeroed() -> Self {
+        [0u8; L]
+    }
+}
+
+/// Compile-time marker for a Dilithium/ML-DSA parameter set
+pub trait ParameterSet: Clone + Copy + std::fmt::Debug + PartialEq + Eq + 'static {
+    /// The runtime security level corresponding to this marker
+    const SECURITY_LEVEL: SecurityLevel;
+    /// Encoded verifying (public) key
+    type VerifyingKeyBytes: ByteArray;
+    /// Encoded signing (secret) key
+    type SigningKeyBytes: ByteArray;
+    /// Encoded signature
+    type SignatureBytes: ByteArray;
+}
+
+/// Dilithium2 (ML-DSA-44) parameter set marker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dilithium2;
+
+/// Dilithium3 (ML-DSA-65) parameter set marker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dilithium3;
+
+/// Dilithium5 (ML-DSA-87) parameter set marker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dilithium5;
+
+impl ParameterSet for Dilithium2 {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Dilithium2;
+    type VerifyingKeyBytes = [u8; 1312];
+    type SigningKeyBytes = [u8; 2560];
+    type SignatureBytes = [u8; 2420];
+}
+
+impl ParameterSet for Dilithium3 {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Dilithium3;
+    type VerifyingKeyBytes = [u8; 1952];
+    type SigningKeyBytes = [u8; 4032];
+    type SignatureBytes = [u8; 3309];
+}
+
+impl ParameterSet for Dilithium5 {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Dilithium5;
+    type VerifyingKeyBytes = [u8; 2592];
+    type SigningKeyBytes = [u8; 4896];
+    type SignatureBytes = [u8; 4627];
+}
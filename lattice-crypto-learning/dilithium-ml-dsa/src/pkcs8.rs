@@ -0,0 +1,210 @@
+use ::pkcs8::spki::{self, SubjectPublicKeyInfoRef};
+use ::pkcs8::{Document, EncodePrivateKey, EncodePublicKey, ObjectIdentifier, PrivateKeyInfo, SecretDocument};
+use lattice_core::pkcs8::{decode_private_key, decode_public_key, encode_private_key, encode_public_key, SeedPrivateKey};
+
+use crate::params::{ParameterSet, SecurityLevel};
+use crate::types::{SigningKey, VerifyingKey};
+
+pub use lattice_core::pkcs8::PrivateKeyFormat;
+
+// SubjectPublicKeyInfo and PKCS#8 OneAsymmetricKey encodings following
+// draft-ietf-lamps-dilithium-certificates. The CHOICE and DER plumbing live in
+// `lattice_core::pkcs8`; this module supplies the OIDs and the 32-byte seed. The
+// external crate is spelled `::pkcs8` to avoid a clash with this module.
+
+/// OID of ML-DSA-44 (id-ml-dsa-44)
+pub const ML_DSA_44_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.3.17");
+/// OID of ML-DSA-65 (id-ml-dsa-65)
+pub const ML_DSA_65_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.3.18");
+/// OID of ML-DSA-87 (id-ml-dsa-87)
+pub const ML_DSA_87_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.3.19");
+
+/// Returns the NIST-assigned OID of a parameter set
+pub fn oid(level: SecurityLevel) -> ObjectIdentifier {
+    match level {
+        SecurityLevel::Dilithium2 => ML_DSA_44_OID,
+        SecurityLevel::Dilithium3 => ML_DSA_65_OID,
+        SecurityLevel::Dilithium5 => ML_DSA_87_OID,
+    }
+}
+
+/// Returns the parameter set identified by an OID
+pub fn security_level_from_oid(oid: ObjectIdentifier) -> Option<SecurityLevel> {
+    match oid {
+        ML_DSA_44_OID => Some(SecurityLevel::Dilithium2),
+        ML_DSA_65_OID => Some(SecurityLevel::Dilithium3),
+        ML_DSA_87_OID => Some(SecurityLevel::Dilithium5),
+        _ => None,
+    }
+}
+
+impl<P: ParameterSet> SeedPrivateKey for SigningKey<P> {
+    fn algorithm_oid() -> ObjectIdentifier {
+        oid(P::SECURITY_LEVEL)
+    }
+
+    fn seed_bytes(&self) -> Option<&[u8]> {
+        self.seed().map(|seed| seed.as_slice())
+    }
+
+    fn expanded_bytes(&self) -> &[u8] {
+        self.as_bytes().as_ref()
+    }
+
+    fn from_seed_bytes(seed: &[u8]) -> Option<Self> {
+        let seed: &[u8; 32] = seed.try_into().ok()?;
+        Some(Self::from_seed(seed))
+    }
+
+    fn from_expanded_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::try_from(bytes).ok()
+    }
+}
+
+impl<P: ParameterSet> SigningKey<P> {
+    /// Encodes the key as PKCS#8 DER using the given private key format
+    ///
+    /// The seed and both forms need the seed, so they fail for keys decoded
+    /// from an expanded encoding.
+    pub fn to_pkcs8_der_with_format(&self, format: PrivateKeyFormat) -> ::pkcs8::Result<SecretDocument> {
+        encode_private_key(self, format)
+    }
+}
+
+impl<P: ParameterSet> EncodePrivateKey for SigningKey<P> {
+    /// Uses the compact seed form when the seed is known, the expanded form otherwise
+    fn to_pkcs8_der(&self) -> ::pkcs8::Result<SecretDocument> {
+        let format = if self.seed().is_some() { PrivateKeyFormat::Seed } else { PrivateKeyFormat::ExpandedKey };
+        self.to_pkcs8_der_with_format(format)
+    }
+}
+
+impl<P: ParameterSet> TryFrom<PrivateKeyInfo<'_>> for SigningKey<P> {
+    type Error = ::pkcs8::Error;
+
+    fn try_from(info: PrivateKeyInfo<'_>) -> ::pkcs8::Result<Self> {
+        decode_private_key(info)
+    }
+}
+
+impl<P: ParameterSet> EncodePublicKey for VerifyingKey<P> {
+    fn to_public_key_der(&self) -> spki::Result<Document> {
+        encode_public_key(oid(P::SECURITY_LEVEL), self.as_bytes().as_ref())
+    }
+}
+
+impl<P: ParameterSet> TryFrom<SubjectPublicKeyInfoRef<'_>> for VerifyingKey<P> {
+    type Error = spki::Error;
+
+    fn try_from(info: SubjectPublicKeyInfoRef<'_>) -> spki::Result<Self> {
+        let bytes = decode_public_key(&info, oid(P::SECURITY_LEVEL))?;
+        Self::try_from(bytes).map_err(|_| spki::Error::KeyMalformed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::pkcs8::der::asn1::OctetStringRef;
+    use ::pkcs8::der::{AnyRef, Encode, Tag};
+    use ::pkcs8::spki::AlgorithmIdentifierRef;
+    use crate::params::{Dilithium2, Dilithium3, Dilithium5};
+    use crate::types::Signature;
+    use ::pkcs8::{DecodePrivateKey, DecodePublicKey, LineEnding};
+    use signature::{Keypair, Signer, Verifier};
+
+    /// Seed 00 01 02 ... 1f used by the fixtures
+    fn fixture_seed() -> [u8; 32] {
+        core::array::from_fn(|i| i as u8)
+    }
+
+    fn check_fixtures<P: ParameterSet>(public_pem: &str, seed_pem: &str, expanded_pem: &str) {
+        let sk = SigningKey::<P>::from_seed(&fixture_seed());
+        let vk = sk.verifying_key();
+
+        // Encoding reproduces the fixtures byte for byte
+        assert_eq!(vk.to_public_key_pem(LineEnding::LF).unwrap(), public_pem);
+        assert_eq!(sk.to_pkcs8_pem(LineEnding::LF).unwrap().as_str(), seed_pem);
+        let expanded_der = sk.to_pkcs8_der_with_format(PrivateKeyFormat::ExpandedKey).unwrap();
+        assert_eq!(expanded_der.to_pem("PRIVATE KEY", LineEnding::LF).unwrap().as_str(), expanded_pem);
+
+        // Decoding the fixtures yields the same keys
+        let vk2 = VerifyingKey::<P>::from_public_key_pem(public_pem).unwrap();
+        let sk_seed = SigningKey::<P>::from_pkcs8_pem(seed_pem).unwrap();
+        let sk_expanded = SigningKey::<P>::from_pkcs8_pem(expanded_pem).unwrap();
+        assert_eq!(vk2, vk);
+        assert_eq!(sk_seed, sk);
+        assert_eq!(sk_seed.seed(), Some(&fixture_seed()));
+        assert_eq!(sk_expanded, sk);
+        assert_eq!(sk_expanded.seed(), None);
+
+        let sig: Signature<P> = sk_expanded.sign(b"fixture");
+        assert!(vk2.verify(b"fixture", &sig).is_ok());
+    }
+
+    #[test]
+    fn test_fixtures() {
+        check_fixtures::<Dilithium2>(
+            include_str!("../fixtures/ml-dsa-44-public.pem"),
+            include_str!("../fixtures/ml-dsa-44-seed.pem"),
+            include_str!("../fixtures/ml-dsa-44-expanded.pem"),
+        );
+        check_fixtures::<Dilithium3>(
+            include_str!("../fixtures/ml-dsa-65-public.pem"),
+            include_str!("../fixtures/ml-dsa-65-seed.pem"),
+            include_str!("../fixtures/ml-dsa-65-expanded.pem"),
+        );
+        check_fixtures::<Dilithium5>(
+            include_str!("../fixtures/ml-dsa-87-public.pem"),
+            include_str!("../fixtures/ml-dsa-87-seed.pem"),
+            include_str!("../fixtures/ml-dsa-87-expanded.pem"),
+        );
+    }
+
+    #[test]
+    fn test_seed_form_layout() {
+        let sk = SigningKey::<Dilithium3>::from_seed(&fixture_seed());
+        let der = sk.to_pkcs8_der().unwrap();
+
+        // SEQUENCE { INTEGER 0, SEQUENCE { OID }, OCTET STRING { [0] seed } }
+        let info = PrivateKeyInfo::try_from(der.as_bytes()).unwrap();
+        assert_eq!(info.algorithm.oid, ML_DSA_65_OID);
+        assert_eq!(&info.private_key[..2], &[0x80, 0x20]);
+        assert_eq!(&info.private_key[2..], &fixture_seed());
+        assert_eq!(der.as_bytes().len(), 54);
+    }
+
+    #[test]
+    fn test_both_form() {
+        let sk = SigningKey::<Dilithium2>::from_seed(&fixture_seed());
+        let der = sk.to_pkcs8_der_with_format(PrivateKeyFormat::Both).unwrap();
+        assert_eq!(SigningKey::<Dilithium2>::from_pkcs8_der(der.as_bytes()).unwrap(), sk);
+
+        // An expanded key that does not match the seed is rejected
+        let other = SigningKey::<Dilithium2>::generate();
+        let mut inner = OctetStringRef::new(&fixture_seed()).unwrap().to_der().unwrap();
+        inner.extend_from_slice(&OctetStringRef::new(other.as_bytes()).unwrap().to_der().unwrap());
+        let choice = AnyRef::new(Tag::Sequence, &inner).unwrap().to_der().unwrap();
+        let algorithm = AlgorithmIdentifierRef { oid: ML_DSA_44_OID, parameters: None };
+        let mismatched = SecretDocument::try_from(PrivateKeyInfo::new(algorithm, &choice)).unwrap();
+        assert!(SigningKey::<Dilithium2>::from_pkcs8_der(mismatched.as_bytes()).is_err());
+
+        // Keys without a seed can only be exported in expanded form
+        let expanded_only = SigningKey::<Dilithium2>::try_from(sk.as_bytes().as_ref()).unwrap();
+        assert!(expanded_only.to_pkcs8_der_with_format(PrivateKeyFormat::Seed).is_err());
+    }
+
+    #[test]
+    fn test_wrong_oid_rejected() {
+        let sk = SigningKey::<Dilithium2>::generate();
+        let public_der = sk.verifying_key().to_public_key_der().unwrap();
+        let private_der = sk.to_pkcs8_der().unwrap();
+
+        assert_eq!(
+            VerifyingKey::<Dilithium3>::from_public_key_der(public_der.as_bytes()).unwrap_err(),
+            spki::Error::OidUnknown { oid: ML_DSA_44_OID },
+        );
+        assert!(SigningKey::<Dilithium5>::from_pkcs8_der(private_der.as_bytes()).is_err());
+        assert_eq!(security_level_from_oid(ML_DSA_87_OID), Some(SecurityLevel::Dilithium5));
+    }
+}
@@ -0,0 +1,95 @@
+use crate::params::{D, Q};
+
+/// Returns r mod± m, the representative of r modulo m in (-m/2, m/2]
+pub fn mod_pm(r: i32, m: i32) -> i32 {
+    let r = r.rem_euclid(m);
+    if r > m / 2 { r - m } else { r }
+}
+
+/// Returns the centered representative of r modulo q
+pub fn centered(r: i32) -> i32 {
+    mod_pm(r, Q)
+}
+
+/// Power2Round: splits r into (r1, r0) with r = r1 * 2^d + r0 and r0 in (-2^(d-1), 2^(d-1)]
+pub fn power2round(r: i32) -> (i32, i32) {
+    let r_plus = r.rem_euclid(Q);
+    let r0 = mod_pm(r_plus, 1 << D);
+    ((r_plus - r0) >> D, r0)
+}
+
+/// Decompose: splits r into (r1, r0) with r = r1 * 2γ2 + r0 (Section 5, HIGHS/LOWS)
+///
+/// The corner case r+ - r0 = q - 1 is folded into r1 = 0 so that r1 ranges over
+/// [0, (q - 1)/(2γ2)).
+pub fn decompose(r: i32, gamma2: i32) -> (i32, i32) {
+    let r_plus = r.rem_euclid(Q);
+    let r0 = mod_pm(r_plus, 2 * gamma2);
+    if r_plus - r0 == Q - 1 {
+        (0, r0 - 1)
+    } else {
+        ((r_plus - r0) / (2 * gamma2), r0)
+    }
+}
+
+/// HighBits: the r1 component of [`decompose`]
+pub fn high_bits(r: i32, gamma2: i32) -> i32 {
+    decompose(r, gamma2).0
+}
+
+/// LowBits: the r0 component of [`decompose`]
+pub fn low_bits(r: i32, gamma2: i32) -> i32 {
+    decompose(r, gamma2).1
+}
+
+/// MakeHint: whether adding z to r changes its high bits
+pub fn make_hint(z: i32, r: i32, gamma2: i32) -> bool {
+    high_bits(r, gamma2) != high_bits(r + z, gamma2)
+}
+
+/// UseHint: recovers HighBits(r + z) from r and the hint produced by [`make_hint`]
+pub fn use_hint(hint: bool, r: i32, gamma2: i32) -> i32 {
+    let m = (Q - 1) / (2 * gamma2);
+    let (r1, r0) = decompose(r, gamma2);
+
+    if !hint {
+        r1
+    } else if r0 > 0 {
+        (r1 + 1).rem_euclid(m)
+    } else {
+        (r1 - 1).rem_euclid(m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_power2round() {
+        for r in [0, 1, 4096, 4097, 8191, 8192, Q - 1, 1234567] {
+            let (r1, r0) = power2round(r);
+            assert_eq!((r1 << D) + r0, r);
+            assert!(r0 > -(1 << (D - 1)) && r0 <= 1 << (D - 1));
+        }
+    }
+
+    #[test]
+    fn test_decompose_and_hints() {
+        for gamma2 in [(Q - 1) / 88, (Q - 1) / 32] {
+            let m = (Q - 1) / (2 * gamma2);
+            for r in (0..Q).step_by(9973).chain([Q - 1, Q - gamma2, gamma2]) {
+                let (r1, r0) = decompose(r, gamma2);
+                assert!((0..m).contains(&r1));
+                assert!(r0 >= -gamma2 && r0 <= gamma2);
+                assert_eq!((r1 * 2 * gamma2 + r0).rem_euclid(Q), r);
+
+                // UseHint(MakeHint(z, r), r) = HighBits(r + z) for small z
+                for z in [-gamma2, -17, 0, 1, gamma2 / 2, gamma2] {
+                    let hint = make_hint(z, r, gamma2);
+                    assert_eq!(use_hint(hint, r, gamma2), high_bits(r + z, gamma2));
+                }
+            }
+        }
+    }
+}
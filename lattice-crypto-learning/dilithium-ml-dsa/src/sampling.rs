@@ -0,0 +1,180 @@
+use lattice_core::{
+    hashing::shake256,
+    polynomial::Polynomial,
+    simd::{self, Backend},
+    vector_matrix::{PolyMatrix, PolyVector},
+    zq::ZqElement,
+};
+use sha3::{Shake128, Shake256, digest::{Update, ExtendableOutput, XofReader}};
+
+use crate::packing::unpack_signed;
+use crate::params::{SecurityLevel, N, Q, poly_modulus, poly_modulus_ntt, sizes};
+
+/// RejNTTPoly: samples a uniform polynomial in NTT form from SHAKE128(seed)
+///
+/// Three bytes are read at a time, the top bit is dropped and values >= q are rejected.
+pub fn rej_ntt_poly(seed: &[u8]) -> Polynomial {
+    let mut shake = Shake128::default();
+    shake.update(seed);
+    let mut reader = shake.finalize_xof();
+
+    // Squeeze one SHAKE128 block (168 bytes = 56 candidates) at a time
+    let backend = Backend::detect();
+    let mut values = [0i32; N];
+    let mut filled = 0;
+    let mut block = [0u8; 168];
+    while filled < N {
+        reader.read(&mut block);
+        filled = simd::rej_uniform(backend, &block, Q, &mut values, filled);
+    }
+
+    let coeffs = values.iter().map(|&v| ZqElement::new(v, Q)).collect();
+    Polynomial::new(coeffs, poly_modulus_ntt())
+}
+
+/// RejBoundedPoly: samples a polynomial with coefficients in [-η, η] from SHAKE256(seed)
+pub fn rej_bounded_poly(seed: &[u8], eta: usize) -> Polynomial {
+    let mut shake = Shake256::default();
+    shake.update(seed);
+    let mut reader = shake.finalize_xof();
+
+    let mut coeffs = Vec::with_capacity(N);
+    let mut byte = [0u8; 1];
+    while coeffs.len() < N {
+        reader.read(&mut byte);
+        for z in [byte[0] & 0x0F, byte[0] >> 4] {
+            // CoeffFromHalfByte: reject nibbles that would bias the distribution
+            let value = match eta {
+                2 if z < 15 => Some(2 - (z % 5) as i32),
+                4 if z < 9 => Some(4 - z as i32),
+                _ => None,
+            };
+            if let (Some(value), true) = (value, coeffs.len() < N) {
+                coeffs.push(ZqElement::new(value, Q));
+            }
+        }
+    }
+
+    Polynomial::new(coeffs, poly_modulus())
+}
+
+/// ExpandA: generates the k × l matrix A_hat (in NTT form) from the seed ρ
+pub fn expand_a(rho: &[u8; 32], level: SecurityLevel) -> PolyMatrix {
+    let (k, l) = (level.k(), level.l());
+    let modulus_info_ntt = poly_modulus_ntt();
+
+    let rows = (0..k)
+        .map(|r| {
+            let entries = (0..l)
+                .map(|s| {
+                    let mut seed = rho.to_vec();
+                    seed.push(s as u8);
+                    seed.push(r as u8);
+                    rej_ntt_poly(&seed)
+                })
+                .collect();
+            PolyVector::new(entries, modulus_info_ntt)
+        })
+        .collect();
+
+    PolyMatrix::new(rows, k, l, modulus_info_ntt)
+}
+
+/// ExpandS: generates the secret vectors s1 (length l) and s2 (length k) from ρ'
+pub fn expand_s(rho_prime: &[u8; 64], level: SecurityLevel) -> (PolyVector, PolyVector) {
+    let (k, l, eta) = (level.k(), level.l(), level.eta());
+
+    let sample = |nonce: usize| {
+        let mut seed = rho_prime.to_vec();
+        seed.extend_from_slice(&(nonce as u16).to_le_bytes());
+        rej_bounded_poly(&seed, eta)
+    };
+
+    let s1 = (0..l).map(sample).collect();
+    let s2 = (l..l + k).map(sample).collect();
+
+    (PolyVector::new(s1, poly_modulus()), PolyVector::new(s2, poly_modulus()))
+}
+
+/// ExpandMask: generates the masking vector y with coefficients in (-γ1, γ1]
+pub fn expand_mask(rho_pp: &[u8; 64], kappa: usize, level: SecurityLevel) -> PolyVector {
+    let gamma1 = level.gamma1();
+    let bits = sizes::gamma1_bits(level);
+
+    let entries = (0..level.l())
+        .map(|r| {
+            let mut seed = rho_pp.to_vec();
+            seed.extend_from_slice(&((kappa + r) as u16).to_le_bytes());
+            let bytes = shake256(&seed, N * bits / 8);
+            unpack_signed(&bytes, bits, gamma1 - 1, gamma1)
+        })
+        .collect();
+
+    PolyVector::new(entries, poly_modulus())
+}
+
+/// SampleInBall: derives the challenge c with exactly τ coefficients in {-1, 1}
+///
+/// Deterministic counterpart of `lattice_core::sampling::sample_challenge`.
+pub fn sample_in_ball(c_tilde: &[u8], tau: usize) -> Polynomial {
+    let mut shake = Shake256::default();
+    shake.update(c_tilde);
+    let mut reader = shake.finalize_xof();
+
+    // The first 8 bytes provide the signs
+    let mut sign_bytes = [0u8; 8];
+    reader.read(&mut sign_bytes);
+    let signs = u64::from_le_bytes(sign_bytes);
+
+    let mut values = [0i32; N];
+    let mut byte = [0u8; 1];
+    for (t, i) in (N - tau..N).enumerate() {
+        // Fisher-Yates step with rejection: draw j uniform in [0, i]
+        let j = loop {
+            reader.read(&mut byte);
+            if (byte[0] as usize) <= i {
+                break byte[0] as usize;
+            }
+        };
+        values[i] = values[j];
+        values[j] = if (signs >> t) & 1 == 1 { -1 } else { 1 };
+    }
+
+    let coeffs = values.iter().map(|&v| ZqElement::new(v, Q)).collect();
+    Polynomial::new(coeffs, poly_modulus())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rounding::centered;
+
+    #[test]
+    fn test_sample_in_ball() {
+        let c = sample_in_ball(b"challenge seed", 39);
+        let weights: Vec<i32> = c.coeffs.iter().map(|x| centered(x.value())).collect();
+
+        assert_eq!(weights.iter().filter(|&&w| w != 0).count(), 39);
+        assert!(weights.iter().all(|w| (-1..=1).contains(w)));
+        assert_eq!(c, sample_in_ball(b"challenge seed", 39));
+    }
+
+    #[test]
+    fn test_expand_s_bounds() {
+        for level in [SecurityLevel::Dilithium2, SecurityLevel::Dilithium3] {
+            let (s1, s2) = expand_s(&[7u8; 64], level);
+            assert_eq!(s1.len(), level.l());
+            assert_eq!(s2.len(), level.k());
+            assert!(s1.infinity_norm() <= level.eta() as i32);
+            assert!(s2.infinity_norm() <= level.eta() as i32);
+        }
+    }
+
+    #[test]
+    fn test_expand_mask_bounds() {
+        let level = SecurityLevel::Dilithium2;
+        let y = expand_mask(&[3u8; 64], 0, level);
+        assert!(y.infinity_norm() <= level.gamma1());
+        assert_ne!(y, expand_mask(&[3u8; 64], level.l(), level));
+    }
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use ::pkcs8::{EncodePrivateKey, PrivateKeyInfo, SecretDocument};
+use lattice_core::pkcs8::encode_seed_private_key;
+use rand::{Rng, rngs::OsRng};
+use signature::Keypair;
+
+use crate::error::Error;
+use crate::params::ParameterSet;
+use crate::pkcs8::oid;
+use crate::types::{SigningKey, VerifyingKey};
+
+/// Size of the seed ξ in bytes
+pub const SEED_KEY_BYTES: usize = 32;
+
+/// Compact ML-DSA private key holding only the 32-byte seed ξ
+///
+/// FIPS 204 key generation is deterministic in ξ, so the seed is all the secret
+/// material that has to be stored. The full signing key (2.5 KB to 4.9 KB) is
+/// recomputed by [`SeedKey::expand`] when it is needed.
+pub struct SeedKey<P: ParameterSet> {
+    /// The seed ξ
+    seed: [u8; SEED_KEY_BYTES],
+    _params: PhantomData<P>,
+}
+
+impl<P: ParameterSet> SeedKey<P> {
+    /// Generates a fresh random seed
+    pub fn generate() -> Self {
+        let mut seed = [0u8; SEED_KEY_BYTES];
+        OsRng.fill(&mut seed);
+        Self::from_bytes(&seed)
+    }
+
+    /// Wraps an existing seed ξ
+    pub fn from_bytes(seed: &[u8; SEED_KEY_BYTES]) -> Self {
+        Self { seed: *seed, _params: PhantomData }
+    }
+
+    /// Returns the 32-byte encoding ξ
+    pub fn as_bytes(&self) -> &[u8; SEED_KEY_BYTES] {
+        &self.seed
+    }
+
+    /// Expands the seed into the full signing key
+    pub fn expand(&self) -> SigningKey<P> {
+        SigningKey::from_seed(&self.seed)
+    }
+
+    /// Returns the matching verifying key (this expands the seed)
+    pub fn verifying_key(&self) -> VerifyingKey<P> {
+        self.expand().verifying_key()
+    }
+}
+
+impl<P: ParameterSet> SigningKey<P> {
+    /// Returns the compact seed form of the key, if the seed is known
+    pub fn to_seed_key(&self) -> Option<SeedKey<P>> {
+        self.seed().map(SeedKey::from_bytes)
+    }
+}
+
+impl<P: ParameterSet> TryFrom<&[u8]> for SeedKey<P> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let seed: &[u8; SEED_KEY_BYTES] = bytes
+            .try_into()
+            .map_err(|_| Error::InvalidLength { expected: SEED_KEY_BYTES, actual: bytes.len() })?;
+
+        Ok(Self::from_bytes(seed))
+    }
+}
+
+impl<P: ParameterSet> EncodePrivateKey for SeedKey<P> {
+    /// Always uses the seed-only `[0] IMPLICIT OCTET STRING` form
+    fn to_pkcs8_der(&self) -> ::pkcs8::Result<SecretDocument> {
+        encode_seed_private_key(oid(P::SECURITY_LEVEL), &self.seed)
+    }
+}
+
+impl<P: ParameterSet> TryFrom<PrivateKeyInfo<'_>> for SeedKey<P> {
+    type Error = ::pkcs8::Error;
+
+    /// Accepts the seed and both forms; an expanded-only key has no seed to keep
+    fn try_from(info: PrivateKeyInfo<'_>) -> ::pkcs8::Result<Self> {
+        SigningKey::<P>::try_from(info)?
+            .to_seed_key()
+            .ok_or(::pkcs8::Error::KeyMalformed)
+    }
+}
+
+impl<P: ParameterSet> Clone for SeedKey<P> {
+    fn clone(&self) -> Self {
+        Self::from_bytes(&self.seed)
+    }
+}
+
+impl<P: ParameterSet> PartialEq for SeedKey<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.seed == other.seed
+    }
+}
+
+impl<P: ParameterSet> Eq for SeedKey<P> {}
+
+impl<P: ParameterSet> fmt::Debug for SeedKey<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print secret key material
+        f.debug_struct("SeedKey")
+            .field("params", &P::SECURITY_LEVEL)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::{Dilithium2, Dilithium3, Dilithium5};
+    use crate::pkcs8::PrivateKeyFormat;
+    use crate::types::Signature;
+    use ::pkcs8::DecodePrivateKey;
+    use signature::{Signer, Verifier};
+
+    fn roundtrip<P: ParameterSet>() {
+        let seed_key = SeedKey::<P>::generate();
+        let sk = seed_key.expand();
+
+        // 32 bytes on disk instead of the full signing key
+        let stored = seed_key.as_bytes().to_vec();
+        assert_eq!(stored.len(), 32);
+        assert!(sk.as_bytes().as_ref().len() > 2500);
+
+        // Reloading and expanding again gives the same key
+        let reloaded = SeedKey::<P>::try_from(stored.as_slice()).unwrap();
+        assert_eq!(reloaded, seed_key);
+        assert_eq!(reloaded.expand(), sk);
+        assert_eq!(sk.to_seed_key(), Some(seed_key));
+
+        let sig: Signature<P> = sk.sign(b"message");
+        assert!(reloaded.verifying_key().verify(b"message", &sig).is_ok());
+    }
+
+    #[test]
+    fn test_seed_key_roundtrip() {
+        roundtrip::<Dilithium2>();
+        roundtrip::<Dilithium3>();
+        roundtrip::<Dilithium5>();
+    }
+
+    #[test]
+    fn test_seed_key_pkcs8() {
+        let seed_key = SeedKey::<Dilithium3>::generate();
+        let sk = seed_key.expand();
+
+        // The seed-only encoding is the same whichever type produces it
+        let der = seed_key.to_pkcs8_der().unwrap();
+        assert_eq!(der.as_bytes(), sk.to_pkcs8_der().unwrap().as_bytes());
+        assert_eq!(SeedKey::<Dilithium3>::from_pkcs8_der(der.as_bytes()).unwrap(), seed_key);
+
+        // Expanded-only encodings cannot be turned back into a seed
+        let expanded = sk.to_pkcs8_der_with_format(PrivateKeyFormat::ExpandedKey).unwrap();
+        assert!(SeedKey::<Dilithium3>::from_pkcs8_der(expanded.as_bytes()).is_err());
+        let both = sk.to_pkcs8_der_with_format(PrivateKeyFormat::Both).unwrap();
+        assert_eq!(SeedKey::<Dilithium3>::from_pkcs8_der(both.as_bytes()).unwrap(), seed_key);
+    }
+
+    #[test]
+    fn test_wrong_length_rejected() {
+        let err = SeedKey::<Dilithium2>::try_from([0u8; 64].as_slice()).unwrap_err();
+        assert_eq!(err, Error::InvalidLength { expected: 32, actual: 64 });
+    }
+}
@@ -0,0 +1,618 @@
+//! Interactive identification schemes behind Dilithium (Σ-protocols with aborts)
+//!
+//! The prover knows short s₁, s₂ with t = A·s₁ + s₂ and convinces the
+//! verifier in three moves: commit w, receive a challenge c, respond with
+//! z = y + c·s. The response is only sent when it falls in a range that is
+//! independent of the secret; otherwise the prover aborts and the protocol
+//! restarts with a fresh mask y. Three variants are implemented:
+//!
+//! - [`Variant::Basic`] (Figure 5): w = A·y₁ + y₂, z₁ = y₁ + c·s₁,
+//!   z₂ = y₂ + c·s₂; the verifier checks A·z₁ + z₂ - c·t = w.
+//! - [`Variant::ReducedProof`] (Figure 8): only w₁ = HighBits(A·y) is sent
+//!   and z₂ is dropped; the verifier checks HighBits(A·z - c·t) = w₁, which
+//!   needs the extra abort ‖LowBits(A·y - c·s₂)‖∞ < γ₂ - β.
+//! - [`Variant::ReducedPublicKey`] (Figure 9): the verifier only knows t₁,
+//!   the high bits of t, and the prover sends a hint h for the carries caused
+//!   by c·t₀, as in the signature scheme.
+//!
+//! The prover counts every attempt and abort in [`AbortStats`], so the
+//! measured number of repetitions can be compared with
+//! [`expected_repetitions`]. The Fiat-Shamir transform of the last variant,
+//! with c derived from a hash of (μ, w₁), is [`crate::dsa::sign_internal`].
+
+use lattice_core::{
+    ntt::{ntt_forward, NTTParams},
+    polynomial::Polynomial,
+    sampling::{sample_challenge, sample_uniform_poly},
+    vector_matrix::{PolyMatrix, PolyVector},
+};
+use rand::Rng;
+
+use crate::dsa::{from_coeffs, get_ntt_params, inverse_ntt_vector, map_coeffs, ntt_vector, scale_vector};
+use crate::error::Error;
+use crate::params::{poly_modulus, SecurityLevel, D, N};
+use crate::rounding::{centered, high_bits, low_bits, make_hint, power2round, use_hint};
+use crate::sampling::{expand_a, expand_s};
+
+/// Which identification scheme of the paper to run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// Figure 5: full commitment w and both responses z₁, z₂
+    Basic,
+    /// Figure 8: commitment HighBits(w) and the single response z
+    ReducedProof,
+    /// Figure 9: as Figure 8, but the public key is only t₁ and a hint is sent
+    ReducedPublicKey,
+}
+
+/// The public statement: A and t = A·s₁ + s₂ = t₁·2^d + t₀
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    /// Parameter set
+    pub security_level: SecurityLevel,
+    /// The matrix A in NTT form
+    pub a_hat: PolyMatrix,
+    /// The full t, used by the Figure 5 and Figure 8 verifiers
+    pub t: PolyVector,
+    /// The high bits t₁ of t, all that the Figure 9 verifier uses
+    pub t1: PolyVector,
+}
+
+/// The prover's secret
+#[derive(Debug, Clone, PartialEq)]
+pub struct Witness {
+    /// Secret vector s₁ with coefficients in [-η, η]
+    pub s1: PolyVector,
+    /// Secret vector s₂ with coefficients in [-η, η]
+    pub s2: PolyVector,
+    /// Low bits t₀ of t, needed to compute the Figure 9 hint
+    pub t0: PolyVector,
+}
+
+/// The prover's first message
+#[derive(Debug, Clone, PartialEq)]
+pub enum Commitment {
+    /// w = A·y₁ + y₂ (Figure 5)
+    Full(PolyVector),
+    /// w₁ = HighBits(A·y) (Figures 8 and 9)
+    HighBits(Vec<Vec<i32>>),
+}
+
+/// The prover's last message
+#[derive(Debug, Clone, PartialEq)]
+pub enum Response {
+    /// z₁ = y₁ + c·s₁ and z₂ = y₂ + c·s₂ (Figure 5)
+    Basic {
+        /// Response for s₁
+        z1: PolyVector,
+        /// Response for s₂
+        z2: PolyVector,
+    },
+    /// z = y + c·s₁ (Figure 8)
+    ReducedProof {
+        /// Response for s₁
+        z: PolyVector,
+    },
+    /// z = y + c·s₁ and the hint h (Figure 9)
+    ReducedPublicKey {
+        /// Response for s₁
+        z: PolyVector,
+        /// Hint for recovering HighBits(A·z - c·t) from A·z - c·t₁·2^d
+        h: Vec<Vec<bool>>,
+    },
+}
+
+/// Why the prover refused to respond
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbortReason {
+    /// ‖z‖∞ ≥ γ₁ - β, so z could depend on the secret
+    Response,
+    /// ‖LowBits(A·y - c·s₂)‖∞ ≥ γ₂ - β, so the high bits could change
+    LowBits,
+    /// ‖c·t₀‖∞ ≥ γ₂, too large for a hint to correct
+    CtZero,
+    /// The hint has more than ω ones
+    HintWeight,
+}
+
+/// What the prover sends after seeing the challenge
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProverMessage {
+    /// The response, which the verifier should check
+    Response(Response),
+    /// An abort; both parties start over
+    Abort(AbortReason),
+}
+
+/// Attempts and aborts counted by a prover
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AbortStats {
+    /// Number of challenges answered or aborted
+    pub attempts: usize,
+    /// Aborts because of a large response
+    pub response: usize,
+    /// Aborts because of large low bits
+    pub low_bits: usize,
+    /// Aborts because of a large c·t₀
+    pub ct0: usize,
+    /// Aborts because of a heavy hint
+    pub hint_weight: usize,
+}
+
+impl AbortStats {
+    /// Total number of aborts
+    pub fn aborts(&self) -> usize {
+        self.response + self.low_bits + self.ct0 + self.hint_weight
+    }
+
+    /// Number of responses sent
+    pub fn responses(&self) -> usize {
+        self.attempts - self.aborts()
+    }
+
+    /// Fraction of attempts that ended in a response
+    pub fn acceptance_rate(&self) -> f64 {
+        if self.attempts == 0 {
+            return 0.0;
+        }
+        self.responses() as f64 / self.attempts as f64
+    }
+
+    /// Measured average number of attempts per response
+    pub fn repetitions(&self) -> f64 {
+        self.attempts as f64 / self.responses() as f64
+    }
+
+    fn record(&mut self, reason: AbortReason) {
+        match reason {
+            AbortReason::Response => self.response += 1,
+            AbortReason::LowBits => self.low_bits += 1,
+            AbortReason::CtZero => self.ct0 += 1,
+            AbortReason::HintWeight => self.hint_weight += 1,
+        }
+    }
+}
+
+/// Expected number of attempts per response, from the per-coefficient acceptance probabilities
+///
+/// A coefficient of y + c·s with y uniform in [-(γ₁ - 1), γ₁ - 1] lands in
+/// [-(γ₁ - β - 1), γ₁ - β - 1] with probability (2(γ₁ - β) - 1)/(2γ₁ - 1),
+/// and a low-bits coefficient, roughly uniform in (-γ₂, γ₂], stays below
+/// γ₂ - β with probability (2(γ₂ - β) - 1)/(2γ₂). The rare c·t₀ and hint
+/// aborts of Figure 9 are ignored.
+pub fn expected_repetitions(variant: Variant, level: SecurityLevel) -> f64 {
+    let (gamma1, gamma2, beta) = (level.gamma1() as f64, level.gamma2() as f64, level.beta() as f64);
+    let (k, l) = ((level.k() * N) as f64, (level.l() * N) as f64);
+    let response = (2.0 * (gamma1 - beta) - 1.0) / (2.0 * gamma1 - 1.0);
+    let low_bits = (2.0 * (gamma2 - beta) - 1.0) / (2.0 * gamma2);
+
+    match variant {
+        Variant::Basic => response.powf(-(k + l)),
+        Variant::ReducedProof | Variant::ReducedPublicKey => response.powf(-l) * low_bits.powf(-k),
+    }
+}
+
+/// Samples a random statement and its witness
+pub fn keygen(security_level: SecurityLevel, rng: &mut impl Rng) -> (Statement, Witness) {
+    let ntt_params = get_ntt_params();
+    let mut rho = [0u8; 32];
+    let mut rho_prime = [0u8; 64];
+    rng.fill(&mut rho);
+    rng.fill(&mut rho_prime);
+
+    let a_hat = expand_a(&rho, security_level);
+    let (s1, s2) = expand_s(&rho_prime, security_level);
+    let t = mul_a(&a_hat, &s1, &ntt_params) + s2.clone();
+    let t1 = from_coeffs(&map_coeffs(&t, |v| power2round(v).0));
+    let t0 = from_coeffs(&map_coeffs(&t, |v| power2round(v).1));
+
+    (Statement { security_level, a_hat, t, t1 }, Witness { s1, s2, t0 })
+}
+
+/// Computes A·v in the normal domain
+fn mul_a(a_hat: &PolyMatrix, v: &PolyVector, ntt_params: &NTTParams) -> PolyVector {
+    inverse_ntt_vector(&a_hat.mul_vec(&ntt_vector(v, ntt_params), Some(ntt_params)), ntt_params)
+}
+
+/// Samples `len` polynomials with coefficients in [-(γ₁ - 1), γ₁ - 1]
+fn sample_mask(len: usize, gamma1: i32, rng: &mut impl Rng) -> PolyVector {
+    let entries = (0..len).map(|_| sample_uniform_poly(gamma1 - 1, poly_modulus(), rng)).collect();
+    PolyVector::new(entries, poly_modulus())
+}
+
+/// Largest |LowBits| over all coefficients of a vector
+fn low_bits_norm(v: &PolyVector, gamma2: i32) -> i32 {
+    map_coeffs(v, |c| low_bits(c, gamma2).abs()).iter().flatten().copied().max().unwrap_or(0)
+}
+
+/// Where the prover is in the protocol
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProverState {
+    /// Ready to commit
+    Idle,
+    /// Committed to the masks y₁ (and y₂ in Figure 5), waiting for a challenge
+    Committed {
+        /// Mask for s₁
+        y1: PolyVector,
+        /// Mask for s₂, Figure 5 only
+        y2: Option<PolyVector>,
+        /// The committed w = A·y₁ (+ y₂)
+        w: PolyVector,
+    },
+}
+
+/// The honest prover of one of the three identification schemes
+#[derive(Debug, Clone)]
+pub struct Prover<'a> {
+    statement: &'a Statement,
+    witness: &'a Witness,
+    variant: Variant,
+    state: ProverState,
+    stats: AbortStats,
+    ntt_params: NTTParams,
+}
+
+impl<'a> Prover<'a> {
+    /// A prover for `statement` knowing `witness`
+    pub fn new(statement: &'a Statement, witness: &'a Witness, variant: Variant) -> Self {
+        Self { statement, witness, variant, state: ProverState::Idle, stats: AbortStats::default(), ntt_params: get_ntt_params() }
+    }
+
+    /// The current state
+    pub fn state(&self) -> &ProverState {
+        &self.state
+    }
+
+    /// Attempts and aborts so far
+    pub fn stats(&self) -> AbortStats {
+        self.stats
+    }
+
+    /// First move: sample fresh masks and commit to them
+    pub fn commit(&mut self, rng: &mut impl Rng) -> Result<Commitment, Error> {
+        if self.state != ProverState::Idle {
+            return Err(Error::OutOfTurn);
+        }
+        let level = self.statement.security_level;
+
+        let y1 = sample_mask(level.l(), level.gamma1(), rng);
+        let ay1 = mul_a(&self.statement.a_hat, &y1, &self.ntt_params);
+        let (y2, w, commitment) = match self.variant {
+            Variant::Basic => {
+                let y2 = sample_mask(level.k(), level.gamma1(), rng);
+                let w = ay1 + y2.clone();
+                (Some(y2), w.clone(), Commitment::Full(w))
+            }
+            Variant::ReducedProof | Variant::ReducedPublicKey => {
+                let w1 = map_coeffs(&ay1, |v| high_bits(v, level.gamma2()));
+                (None, ay1, Commitment::HighBits(w1))
+            }
+        };
+
+        self.state = ProverState::Committed { y1, y2, w };
+        Ok(commitment)
+    }
+
+    /// Third move: answer the challenge c, or abort if the answer would leak the secret
+    ///
+    /// Either way the prover returns to [`ProverState::Idle`], since a mask
+    /// must never be reused for a second challenge.
+    pub fn respond(&mut self, c: &Polynomial) -> Result<ProverMessage, Error> {
+        let ProverState::Committed { y1, y2, w } = std::mem::replace(&mut self.state, ProverState::Idle) else {
+            return Err(Error::OutOfTurn);
+        };
+        let level = self.statement.security_level;
+        let (gamma1, gamma2, beta) = (level.gamma1(), level.gamma2(), level.beta());
+        let ntt = &self.ntt_params;
+        let c_hat = ntt_forward(c, ntt);
+        let cs1 = scale_vector(&c_hat, &ntt_vector(&self.witness.s1, ntt), ntt);
+        let cs2 = scale_vector(&c_hat, &ntt_vector(&self.witness.s2, ntt), ntt);
+        self.stats.attempts += 1;
+
+        let z = y1 + cs1;
+        let message = match (self.variant, y2) {
+            (Variant::Basic, Some(y2)) => {
+                let z2 = y2 + cs2;
+                if z.infinity_norm() >= gamma1 - beta || z2.infinity_norm() >= gamma1 - beta {
+                    ProverMessage::Abort(AbortReason::Response)
+                } else {
+                    ProverMessage::Response(Response::Basic { z1: z, z2 })
+                }
+            }
+            (variant, _) => {
+                let w_cs2 = w - cs2;
+                if z.infinity_norm() >= gamma1 - beta {
+                    ProverMessage::Abort(AbortReason::Response)
+                } else if low_bits_norm(&w_cs2, gamma2) >= gamma2 - beta {
+                    ProverMessage::Abort(AbortReason::LowBits)
+                } else if variant == Variant::ReducedProof {
+                    ProverMessage::Response(Response::ReducedProof { z })
+                } else {
+                    self.hint(&c_hat, &w_cs2, z)
+                }
+            }
+        };
+
+        if let ProverMessage::Abort(reason) = message {
+            self.stats.record(reason);
+        }
+        Ok(message)
+    }
+
+    /// Figure 9: the hint recovering HighBits(w - c·s₂) from w - c·s₂ + c·t₀
+    fn hint(&self, c_hat: &Polynomial, w_cs2: &PolyVector, z: PolyVector) -> ProverMessage {
+        let level = self.statement.security_level;
+        let ct0 = scale_vector(c_hat, &ntt_vector(&self.witness.t0, &self.ntt_params), &self.ntt_params);
+        if ct0.infinity_norm() >= level.gamma2() {
+            return ProverMessage::Abort(AbortReason::CtZero);
+        }
+
+        let h: Vec<Vec<bool>> = ct0.entries.iter()
+            .zip(w_cs2.entries.iter())
+            .map(|(ct0_i, w_i)| {
+                ct0_i.coeffs.iter()
+                    .zip(w_i.coeffs.iter())
+                    .map(|(c, w)| make_hint(-centered(c.value()), w.value() + c.value(), level.gamma2()))
+                    .collect()
+            })
+            .collect();
+        if h.iter().flatten().filter(|&&bit| bit).count() > level.omega() {
+            return ProverMessage::Abort(AbortReason::HintWeight);
+        }
+
+        ProverMessage::Response(Response::ReducedPublicKey { z, h })
+    }
+}
+
+/// Where the verifier is in the protocol
+#[derive(Debug, Clone, PartialEq)]
+pub enum VerifierState {
+    /// Waiting for the prover's commitment
+    AwaitingCommitment,
+    /// Sent the challenge c for the commitment, waiting for the response
+    Challenged {
+        /// The prover's commitment
+        commitment: Commitment,
+        /// The challenge sent
+        c: Polynomial,
+    },
+    /// Accepted a response; the protocol is over
+    Accepted,
+    /// Rejected a response; the protocol is over
+    Rejected,
+}
+
+/// The verifier of one of the three identification schemes
+#[derive(Debug, Clone)]
+pub struct Verifier<'a> {
+    statement: &'a Statement,
+    variant: Variant,
+    state: VerifierState,
+    ntt_params: NTTParams,
+}
+
+impl<'a> Verifier<'a> {
+    /// A verifier for `statement`
+    pub fn new(statement: &'a Statement, variant: Variant) -> Self {
+        Self { statement, variant, state: VerifierState::AwaitingCommitment, ntt_params: get_ntt_params() }
+    }
+
+    /// The current state
+    pub fn state(&self) -> &VerifierState {
+        &self.state
+    }
+
+    /// Second move: store the commitment and answer with a random challenge of weight τ
+    pub fn challenge(&mut self, commitment: Commitment, rng: &mut impl Rng) -> Result<Polynomial, Error> {
+        if self.state != VerifierState::AwaitingCommitment {
+            return Err(Error::OutOfTurn);
+        }
+        let expected_full = self.variant == Variant::Basic;
+        if matches!(commitment, Commitment::Full(_)) != expected_full {
+            return Err(Error::VariantMismatch);
+        }
+
+        let c = sample_challenge(self.statement.security_level.tau(), poly_modulus(), rng);
+        self.state = VerifierState::Challenged { commitment, c: c.clone() };
+        Ok(c)
+    }
+
+    /// Handles the prover's last message: an abort restarts the protocol, a response is checked
+    ///
+    /// Returns `Some(accepted)` once a response has been checked and `None`
+    /// after an abort.
+    pub fn receive(&mut self, message: &ProverMessage) -> Result<Option<bool>, Error> {
+        let VerifierState::Challenged { commitment, c } = std::mem::replace(&mut self.state, VerifierState::AwaitingCommitment) else {
+            return Err(Error::OutOfTurn);
+        };
+        let response = match message {
+            ProverMessage::Abort(_) => return Ok(None),
+            ProverMessage::Response(response) => response,
+        };
+
+        let accepted = self.check(&commitment, &c, response)?;
+        self.state = if accepted { VerifierState::Accepted } else { VerifierState::Rejected };
+        Ok(Some(accepted))
+    }
+
+    fn check(&self, commitment: &Commitment, c: &Polynomial, response: &Response) -> Result<bool, Error> {
+        let statement = self.statement;
+        let level = statement.security_level;
+        let (gamma1, gamma2, beta) = (level.gamma1(), level.gamma2(), level.beta());
+        let ntt = &self.ntt_params;
+        let c_hat = ntt_forward(c, ntt);
+
+        match (self.variant, commitment, response) {
+            // A·z₁ + z₂ - c·t = w
+            (Variant::Basic, Commitment::Full(w), Response::Basic { z1, z2 }) => {
+                if z1.infinity_norm() >= gamma1 - beta || z2.infinity_norm() >= gamma1 - beta {
+                    return Ok(false);
+                }
+                let ct = scale_vector(&c_hat, &ntt_vector(&statement.t, ntt), ntt);
+                Ok(mul_a(&statement.a_hat, z1, ntt) + z2.clone() - ct == *w)
+            }
+            // HighBits(A·z - c·t) = w₁
+            (Variant::ReducedProof, Commitment::HighBits(w1), Response::ReducedProof { z }) => {
+                if z.infinity_norm() >= gamma1 - beta {
+                    return Ok(false);
+                }
+                let ct = scale_vector(&c_hat, &ntt_vector(&statement.t, ntt), ntt);
+                let w = mul_a(&statement.a_hat, z, ntt) - ct;
+                Ok(map_coeffs(&w, |v| high_bits(v, gamma2)) == *w1)
+            }
+            // UseHint(h, A·z - c·t₁·2^d) = w₁
+            (Variant::ReducedPublicKey, Commitment::HighBits(w1), Response::ReducedPublicKey { z, h }) => {
+                if z.infinity_norm() >= gamma1 - beta || h.len() != level.k() {
+                    return Ok(false);
+                }
+                if h.iter().flatten().filter(|&&bit| bit).count() > level.omega() {
+                    return Ok(false);
+                }
+                let t1_scaled = from_coeffs(&map_coeffs(&statement.t1, |v| v << D));
+                let ct1 = scale_vector(&c_hat, &ntt_vector(&t1_scaled, ntt), ntt);
+                let w_approx = mul_a(&statement.a_hat, z, ntt) - ct1;
+                let recovered: Vec<Vec<i32>> = w_approx.entries.iter()
+                    .zip(h.iter())
+                    .map(|(w_i, h_i)| {
+                        w_i.coeffs.iter()
+                            .zip(h_i.iter())
+                            .map(|(w, &hint)| use_hint(hint, w.value(), gamma2))
+                            .collect()
+                    })
+                    .collect();
+                Ok(recovered == *w1)
+            }
+            _ => Err(Error::VariantMismatch),
+        }
+    }
+}
+
+/// Runs the protocol between `prover` and `verifier` until the prover responds
+///
+/// Returns the verifier's decision; the attempts are added to the prover's
+/// [`AbortStats`].
+pub fn run(prover: &mut Prover, verifier: &mut Verifier, rng: &mut impl Rng) -> Result<bool, Error> {
+    loop {
+        let commitment = prover.commit(rng)?;
+        let c = verifier.challenge(commitment, rng)?;
+        if let Some(accepted) = verifier.receive(&prover.respond(&c)?)? {
+            return Ok(accepted);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    const VARIANTS: [Variant; 3] = [Variant::Basic, Variant::ReducedProof, Variant::ReducedPublicKey];
+
+    #[test]
+    fn test_honest_runs_are_accepted() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        for level in [SecurityLevel::Dilithium2, SecurityLevel::Dilithium3] {
+            let (statement, witness) = keygen(level, &mut rng);
+            for variant in VARIANTS {
+                let mut prover = Prover::new(&statement, &witness, variant);
+                let mut verifier = Verifier::new(&statement, variant);
+                assert_eq!(run(&mut prover, &mut verifier, &mut rng), Ok(true), "{:?} {:?}", level, variant);
+                assert_eq!(verifier.state(), &VerifierState::Accepted);
+                assert_eq!(prover.stats().responses(), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn test_tampered_transcripts_are_rejected() {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        let (statement, witness) = keygen(SecurityLevel::Dilithium2, &mut rng);
+        let one = Polynomial::constant(1, poly_modulus());
+
+        for variant in VARIANTS {
+            let mut prover = Prover::new(&statement, &witness, variant);
+            let (commitment, c, response) = loop {
+                let commitment = prover.commit(&mut rng).unwrap();
+                let c = Verifier::new(&statement, variant).challenge(commitment.clone(), &mut rng).unwrap();
+                if let ProverMessage::Response(response) = prover.respond(&c).unwrap() {
+                    break (commitment, c, response);
+                }
+            };
+            let replay = |commitment: Commitment, response: Response| {
+                let mut verifier = Verifier::new(&statement, variant);
+                verifier.state = VerifierState::Challenged { commitment, c: c.clone() };
+                verifier.receive(&ProverMessage::Response(response)).unwrap()
+            };
+            assert_eq!(replay(commitment.clone(), response.clone()), Some(true));
+
+            // Shift one coefficient of the response
+            let mut tampered = response.clone();
+            match &mut tampered {
+                Response::Basic { z1: z, .. } | Response::ReducedProof { z } | Response::ReducedPublicKey { z, .. } => {
+                    z.entries[0] = z.entries[0].clone() + one.clone();
+                }
+            }
+            assert_eq!(replay(commitment.clone(), tampered), Some(false), "{:?}", variant);
+
+            // Change the commitment
+            let tampered = match commitment {
+                Commitment::Full(w) => Commitment::Full(w.clone() + w),
+                Commitment::HighBits(mut w1) => {
+                    w1[0][0] += 1;
+                    Commitment::HighBits(w1)
+                }
+            };
+            assert_eq!(replay(tampered, response), Some(false), "{:?}", variant);
+        }
+    }
+
+    #[test]
+    fn test_out_of_turn_messages() {
+        let mut rng = ChaCha20Rng::seed_from_u64(3);
+        let (statement, witness) = keygen(SecurityLevel::Dilithium2, &mut rng);
+        let mut prover = Prover::new(&statement, &witness, Variant::ReducedProof);
+        let mut verifier = Verifier::new(&statement, Variant::Basic);
+        let c = sample_challenge(39, poly_modulus(), &mut rng);
+
+        assert_eq!(prover.respond(&c), Err(Error::OutOfTurn));
+        assert_eq!(verifier.receive(&ProverMessage::Abort(AbortReason::Response)), Err(Error::OutOfTurn));
+        let commitment = prover.commit(&mut rng).unwrap();
+        assert_eq!(prover.commit(&mut rng), Err(Error::OutOfTurn));
+        assert_eq!(verifier.challenge(commitment.clone(), &mut rng), Err(Error::VariantMismatch));
+
+        // After an abort both sides start over with a fresh commitment
+        let mut verifier = Verifier::new(&statement, Variant::ReducedProof);
+        let c = verifier.challenge(commitment, &mut rng).unwrap();
+        assert_eq!(verifier.receive(&ProverMessage::Abort(AbortReason::LowBits)), Ok(None));
+        assert_eq!(verifier.state(), &VerifierState::AwaitingCommitment);
+        assert!(prover.respond(&c).is_ok());
+        assert_eq!(prover.state(), &ProverState::Idle);
+        assert_eq!(prover.respond(&c), Err(Error::OutOfTurn));
+        assert!(prover.commit(&mut rng).is_ok());
+    }
+
+    #[test]
+    fn test_abort_rate_matches_expectation() {
+        let mut rng = ChaCha20Rng::seed_from_u64(4);
+        let level = SecurityLevel::Dilithium2;
+        let (statement, witness) = keygen(level, &mut rng);
+        // About 3.4 attempts per response for Figure 5 and 4.3 for Figure 8
+        assert!((expected_repetitions(Variant::Basic, level) - 3.4).abs() < 0.1);
+        assert!((expected_repetitions(Variant::ReducedProof, level) - 4.3).abs() < 0.1);
+
+        for variant in [Variant::Basic, Variant::ReducedProof] {
+            let mut prover = Prover::new(&statement, &witness, variant);
+            for _ in 0..60 {
+                let mut verifier = Verifier::new(&statement, variant);
+                assert_eq!(run(&mut prover, &mut verifier, &mut rng), Ok(true));
+            }
+            let stats = prover.stats();
+            assert_eq!(stats.responses(), 60);
+            let expected = expected_repetitions(variant, level);
+            assert!((stats.repetitions() - expected).abs() < 0.3 * expected, "{:?}: {:?}", variant, stats);
+            assert!((stats.acceptance_rate() * stats.repetitions() - 1.0).abs() < 1e-9);
+            assert_eq!(stats.low_bits > 0, variant == Variant::ReducedProof);
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use lattice_core::hashing::shake256;
+use rand::{Rng, rngs::OsRng};
+use signature::rand_core::CryptoRngCore;
+use signature::{Keypair, RandomizedSigner, SignatureEncoding, Signer, Verifier};
+
+use crate::dsa::{self, PublicKey, SecretKey};
+use crate::error::Error;
+use crate::params::{ByteArray, Dilithium2, Dilithium3, Dilithium5, ParameterSet};
+
+/// Copies a slice into a fixed-size array, checking its length
+fn to_array<A: ByteArray>(bytes: &[u8]) -> Result<A, Error> {
+    if bytes.len() != A::LEN {
+        return Err(Error::InvalidLength { expected: A::LEN, actual: bytes.len() });
+    }
+
+    let mut array = A::zeroed();
+    array.as_mut().copy_from_slice(bytes);
+    Ok(array)
+}
+
+/// ML-DSA verifying (public) key for the parameter set `P`
+pub struct VerifyingKey<P: ParameterSet> {
+    /// Canonical encoding rho || SimpleBitPack(t1)
+    bytes: P::VerifyingKeyBytes,
+    /// Decoded key
+    key: PublicKey,
+}
+
+/// ML-DSA signing (secret) key for the parameter set `P`
+pub struct SigningKey<P: ParameterSet> {
+    /// Canonical encoding rho || K || tr || s1 || s2 || t0
+    bytes: P::SigningKeyBytes,
+    /// Decoded key
+    key: SecretKey,
+    /// Matching verifying key, recomputed from the secret key
+    verifying_key: VerifyingKey<P>,
+    /// Seed ξ the key was expanded from, if known
+    seed: Option<[u8; 32]>,
+}
+
+/// ML-DSA signature for the parameter set `P`
+pub struct Signature<P: ParameterSet> {
+    /// Canonical encoding c~ || BitPack(z) || HintBitPack(h)
+    bytes: P::SignatureBytes,
+    /// Decoded signature
+    sig: dsa::Signature,
+    _params: PhantomData<P>,
+}
+
+impl<P: ParameterSet> VerifyingKey<P> {
+    /// Wraps an untyped public key, checking that it belongs to `P`
+    pub fn from_key(key: PublicKey) -> Result<Self, Error> {
+        if key.security_level != P::SECURITY_LEVEL {
+            return Err(Error::SecurityLevelMismatch);
+        }
+
+        let bytes = to_array(&dsa::pk_to_bytes(&key))?;
+        Ok(Self { bytes, key })
+    }
+
+    /// Returns the fixed-size encoding of the key
+    pub fn as_bytes(&self) -> &P::VerifyingKeyBytes {
+        &self.bytes
+    }
+
+    /// Returns the underlying untyped public key
+    pub fn as_key(&self) -> &PublicKey {
+        &self.key
+    }
+}
+
+impl<P: ParameterSet> SigningKey<P> {
+    /// Generates a fresh key pair and returns its signing key
+    pub fn generate() -> Self {
+        let mut seed = [0u8; 32];
+        OsRng.fill(&mut seed);
+        Self::from_seed(&seed)
+    }
+
+    /// Deterministically expands the 32-byte seed ξ into a signing key
+    pub fn from_seed(seed: &[u8; 32]) -> Self {
+        let (pk, sk) = dsa::keygen_internal(seed, P::SECURITY_LEVEL);
+        let bytes = to_array(&dsa::sk_to_bytes(&sk)).expect("secret key size is fixed by the parameter set");
+        let verifying_key = VerifyingKey::from_key(pk).expect("expanded key matches its parameter set");
+
+        Self { bytes, key: sk, verifying_key, seed: Some(*seed) }
+    }
+
+    /// Wraps an untyped secret key, checking that it belongs to `P`
+    pub fn from_key(key: SecretKey) -> Result<Self, Error> {
+        if key.security_level != P::SECURITY_LEVEL {
+            return Err(Error::SecurityLevelMismatch);
+        }
+
+        let bytes = to_array(&dsa::sk_to_bytes(&key))?;
+        let verifying_key = VerifyingKey::from_key(dsa::public_key_from_secret(&key))?;
+        Ok(Self { bytes, key, verifying_key, seed: None })
+    }
+
+    /// Returns the seed ξ the key was expanded from, if it is known
+    pub fn seed(&self) -> Option<&[u8; 32]> {
+        self.seed.as_ref()
+    }
+
+    /// Returns the fixed-size encoding of the key
+    pub fn as_bytes(&self) -> &P::SigningKeyBytes {
+        &self.bytes
+    }
+
+    /// Returns the underlying untyped secret key
+    pub fn as_key(&self) -> &SecretKey {
+        &self.key
+    }
+}
+
+impl<P: ParameterSet> Signature<P> {
+    /// Wraps a signature produced by the untyped API
+    fn from_signature(sig: dsa::Signature) -> Self {
+        let bytes = to_array(&dsa::signature_to_bytes(&sig))
+            .expect("signature size is fixed by the parameter set");
+
+        Self { bytes, sig, _params: PhantomData }
+    }
+
+    /// Returns the fixed-size encoding of the signature
+    pub fn as_bytes(&self) -> &P::SignatureBytes {
+        &self.bytes
+    }
+
+    /// Returns the underlying untyped signature
+    pub fn as_signature(&self) -> &dsa::Signature {
+        &self.sig
+    }
+}
+
+impl<P: ParameterSet> TryFrom<&[u8]> for VerifyingKey<P> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: P::VerifyingKeyBytes = to_array(bytes)?;
+        let key = dsa::pk_from_bytes(bytes.as_ref(), P::SECURITY_LEVEL)?;
+
+        Ok(Self { bytes, key })
+    }
+}
+
+impl<P: ParameterSet> TryFrom<&[u8]> for SigningKey<P> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: P::SigningKeyBytes = to_array(bytes)?;
+        let key = dsa::sk_from_bytes(bytes.as_ref(), P::SECURITY_LEVEL)?;
+
+        // Reject keys whose s1, s2 or t0 were reduced while decoding
+        if dsa::sk_to_bytes(&key).as_slice() != bytes.as_ref() {
+            return Err(Error::InvalidSigningKey);
+        }
+
+        // tr must be the hash of the public key implied by the secret key
+        let verifying_key = VerifyingKey::<P>::from_key(dsa::public_key_from_secret(&key))?;
+        if shake256(verifying_key.bytes.as_ref(), 64) != key.tr {
+            return Err(Error::InvalidSigningKey);
+        }
+
+        Ok(Self { bytes, key, verifying_key, seed: None })
+    }
+}
+
+impl<P: ParameterSet> TryFrom<&[u8]> for Signature<P> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: P::SignatureBytes = to_array(bytes)?;
+        let sig = dsa::signature_from_bytes(bytes.as_ref(), P::SECURITY_LEVEL)?;
+
+        Ok(Self { bytes, sig, _params: PhantomData })
+    }
+}
+
+// RustCrypto `signature` traits, so the typed keys can replace other ML-DSA crates.
+// `Signer` is the deterministic variant, `RandomizedSigner` the hedged one.
+
+impl<P: ParameterSet> Signer<Signature<P>> for SigningKey<P> {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature<P>, signature::Error> {
+        Ok(Signature::from_signature(dsa::sign_deterministic(&self.key, msg)))
+    }
+}
+
+impl<P: ParameterSet> RandomizedSigner<Signature<P>> for SigningKey<P> {
+    fn try_sign_with_rng(&self, rng: &mut impl CryptoRngCore, msg: &[u8]) -> Result<Signature<P>, signature::Error> {
+        let mut rnd = [0u8; 32];
+        rng.try_fill_bytes(&mut rnd).map_err(signature::Error::from_source)?;
+
+        let sig = dsa::sign_with_context(&self.key, msg, &[], &rnd).map_err(signature::Error::from_source)?;
+        Ok(Signature::from_signature(sig))
+    }
+}
+
+impl<P: ParameterSet> Verifier<Signature<P>> for VerifyingKey<P> {
+    fn verify(&self, msg: &[u8], signature: &Signature<P>) -> Result<(), signature::Error> {
+        if dsa::verify(&self.key, msg, &signature.sig) {
+            Ok(())
+        } else {
+            Err(signature::Error::new())
+        }
+    }
+}
+
+impl<P: ParameterSet> Keypair for SigningKey<P> {
+    type VerifyingKey = VerifyingKey<P>;
+
+    fn verifying_key(&self) -> VerifyingKey<P> {
+        self.verifying_key.clone()
+    }
+}
+
+// `SignatureEncoding` needs `From<Signature<P>>` for the byte array, which the
+// orphan rule only allows per concrete parameter set.
+macro_rules! impl_signature_encoding {
+    ($params:ty, $len:expr) => {
+        impl From<Signature<$params>> for [u8; $len] {
+            fn from(signature: Signature<$params>) -> Self {
+                signature.bytes
+            }
+        }
+
+        impl SignatureEncoding for Signature<$params> {
+            type Repr = [u8; $len];
+        }
+    };
+}
+
+impl_signature_encoding!(Dilithium2, 2420);
+impl_signature_encoding!(Dilithium3, 3309);
+impl_signature_encoding!(Dilithium5, 4627);
+
+impl<P: ParameterSet> Clone for VerifyingKey<P> {
+    fn clone(&self) -> Self {
+        Self { bytes: self.bytes.clone(), key: self.key.clone() }
+    }
+}
+
+impl<P: ParameterSet> Clone for SigningKey<P> {
+    fn clone(&self) -> Self {
+        Self {
+            bytes: self.bytes.clone(),
+            key: self.key.clone(),
+            verifying_key: self.verifying_key.clone(),
+            seed: self.seed,
+        }
+    }
+}
+
+impl<P: ParameterSet> Clone for Signature<P> {
+    fn clone(&self) -> Self {
+        Self { bytes: self.bytes.clone(), sig: self.sig.clone(), _params: PhantomData }
+    }
+}
+
+impl<P: ParameterSet> PartialEq for VerifyingKey<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl<P: ParameterSet> PartialEq for SigningKey<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl<P: ParameterSet> PartialEq for Signature<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl<P: ParameterSet> Eq for VerifyingKey<P> {}
+impl<P: ParameterSet> Eq for SigningKey<P> {}
+impl<P: ParameterSet> Eq for Signature<P> {}
+
+impl<P: ParameterSet> fmt::Debug for VerifyingKey<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("VerifyingKey")
+            .field("params", &P::SECURITY_LEVEL)
+            .field("bytes", &hex::encode(self.bytes.as_ref()))
+            .finish()
+    }
+}
+
+impl<P: ParameterSet> fmt::Debug for SigningKey<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print secret key material
+        f.debug_struct("SigningKey")
+            .field("params", &P::SECURITY_LEVEL)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P: ParameterSet> fmt::Debug for Signature<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Signature")
+            .field("params", &P::SECURITY_LEVEL)
+            .field("bytes", &hex::encode(self.bytes.as_ref()))
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::sizes;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn roundtrip<P: ParameterSet>() {
+        let sk = SigningKey::<P>::generate();
+        let vk = sk.verifying_key();
+        assert_eq!(vk.as_bytes().as_ref().len(), sizes::public_key_bytes(P::SECURITY_LEVEL));
+        assert_eq!(sk.as_bytes().as_ref().len(), sizes::secret_key_bytes(P::SECURITY_LEVEL));
+
+        let sig: Signature<P> = sk.sign(b"message");
+        assert!(vk.verify(b"message", &sig).is_ok());
+        assert!(vk.verify(b"other message", &sig).is_err());
+
+        // Decode everything from bytes and verify again
+        let vk2 = VerifyingKey::<P>::try_from(vk.as_bytes().as_ref()).unwrap();
+        let sk2 = SigningKey::<P>::try_from(sk.as_bytes().as_ref()).unwrap();
+        let sig2 = Signature::<P>::try_from(sig.as_bytes().as_ref()).unwrap();
+        assert_eq!(vk, vk2);
+        assert_eq!(sk, sk2);
+        assert_eq!(sig, sig2);
+        assert!(vk2.verify(b"message", &sig2).is_ok());
+    }
+
+    #[test]
+    fn test_typed_roundtrip() {
+        roundtrip::<Dilithium2>();
+        roundtrip::<Dilithium3>();
+        roundtrip::<Dilithium5>();
+    }
+
+    #[test]
+    fn test_signer_variants() {
+        let sk = SigningKey::<Dilithium2>::generate();
+        let vk = sk.verifying_key();
+
+        // Signer is deterministic
+        let sig1: Signature<Dilithium2> = sk.sign(b"message");
+        let sig2: Signature<Dilithium2> = sk.sign(b"message");
+        assert_eq!(sig1, sig2);
+
+        // RandomizedSigner draws rnd from the given rng
+        let hedged1: Signature<Dilithium2> = sk.sign_with_rng(&mut ChaCha20Rng::seed_from_u64(1), b"message");
+        let hedged2: Signature<Dilithium2> = sk.sign_with_rng(&mut ChaCha20Rng::seed_from_u64(2), b"message");
+        assert_ne!(hedged1, hedged2);
+        assert!(vk.verify(b"message", &hedged1).is_ok());
+        assert!(vk.verify(b"message", &hedged2).is_ok());
+    }
+
+    #[test]
+    fn test_signature_encoding() {
+        let sk = SigningKey::<Dilithium3>::generate();
+        let sig: Signature<Dilithium3> = sk.sign(b"message");
+
+        let repr: [u8; 3309] = sig.to_bytes();
+        assert_eq!(&repr, sig.as_bytes());
+        assert_eq!(sig.encoded_len(), 3309);
+    }
+
+    #[test]
+    fn test_invalid_encodings_rejected() {
+        let sk = SigningKey::<Dilithium2>::generate();
+
+        let err = VerifyingKey::<Dilithium3>::try_from(sk.verifying_key().as_bytes().as_ref()).unwrap_err();
+        assert_eq!(err, Error::InvalidLength { expected: 1952, actual: 1312 });
+
+        // Corrupting tr is detected
+        let mut bytes = *sk.as_bytes();
+        bytes[64] ^= 1;
+        assert_eq!(SigningKey::<Dilithium2>::try_from(bytes.as_ref()).unwrap_err(), Error::InvalidSigningKey);
+    }
+
+    #[test]
+    fn test_security_level_mismatch() {
+        let (pk, _) = dsa::keygen(crate::params::SecurityLevel::Dilithium3);
+        assert_eq!(VerifyingKey::<Dilithium2>::from_key(pk).unwrap_err(), Error::SecurityLevelMismatch);
+    }
+}
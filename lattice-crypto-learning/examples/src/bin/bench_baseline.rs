@@ -0,0 +1,215 @@
+//! Records and compares JSON benchmark baselines
+//!
+//! Criterion keeps its raw results under `target/criterion`, which is not
+//! committed. This tool condenses them into one sorted JSON file
+//! (`benches/baseline.json`) that lives in the repository, so a change in
+//! performance shows up as a diff in review.
+//!
+//! ```text
+//! bench_baseline record  [criterion-dir] [baseline.json]
+//! bench_baseline compare [criterion-dir] [baseline.json] [--threshold <percent>]
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use serde_json::{json, Value};
+
+const DEFAULT_CRITERION_DIR: &str = "target/criterion";
+const DEFAULT_BASELINE: &str = "benches/baseline.json";
+const DEFAULT_THRESHOLD: f64 = 10.0;
+
+/// Condensed result of one benchmark, in nanoseconds per iteration
+#[derive(Debug, Clone, Copy)]
+struct Estimate {
+    median: f64,
+    mean: f64,
+    mean_lower: f64,
+    mean_upper: f64,
+}
+
+/// Walks the Criterion output directory and collects the latest estimates
+fn collect(dir: &Path) -> Result<BTreeMap<String, Estimate>, String> {
+    let mut results = BTreeMap::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(current) = pending.pop() {
+        let entries = fs::read_dir(&current).map_err(|e| format!("{}: {}", current.display(), e))?;
+        for entry in entries {
+            let path = entry.map_err(|e| e.to_string())?.path();
+            if !path.is_dir() {
+                continue;
+            }
+            // Only the "new" directory holds the most recent run
+            if path.file_name().is_some_and(|name| name == "new") {
+                let (id, estimate) = read_estimate(&path)?;
+                results.insert(id, estimate);
+            } else if path.file_name().is_some_and(|name| name != "base" && name != "change" && name != "report") {
+                pending.push(path);
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+fn read_json(path: &Path) -> Result<Value, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    serde_json::from_str(&text).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+fn read_estimate(dir: &Path) -> Result<(String, Estimate), String> {
+    let benchmark = read_json(&dir.join("benchmark.json"))?;
+    let estimates = read_json(&dir.join("estimates.json"))?;
+
+    let id = benchmark["full_id"]
+        .as_str()
+        .ok_or_else(|| format!("{}: missing full_id", dir.display()))?
+        .to_string();
+    let number = |pointer: &str| {
+        estimates
+            .pointer(pointer)
+            .and_then(Value::as_f64)
+            .ok_or_else(|| format!("{}: missing {}", dir.display(), pointer))
+    };
+
+    let estimate = Estimate {
+        median: number("/median/point_estimate")?,
+        mean: number("/mean/point_estimate")?,
+        mean_lower: number("/mean/confidence_interval/lower_bound")?,
+        mean_upper: number("/mean/confidence_interval/upper_bound")?,
+    };
+    Ok((id, estimate))
+}
+
+fn load_baseline(path: &Path) -> Result<BTreeMap<String, Estimate>, String> {
+    let baseline = read_json(path)?;
+    let benchmarks = baseline["benchmarks"]
+        .as_object()
+        .ok_or_else(|| format!("{}: missing \"benchmarks\" object", path.display()))?;
+
+    let mut results = BTreeMap::new();
+    for (id, entry) in benchmarks {
+        let number = |key: &str| {
+            entry[key].as_f64().ok_or_else(|| format!("{}: {} has no {}", path.display(), id, key))
+        };
+        results.insert(id.clone(), Estimate {
+            median: number("median_ns")?,
+            mean: number("mean_ns")?,
+            mean_lower: number("mean_lower_ns")?,
+            mean_upper: number("mean_upper_ns")?,
+        });
+    }
+    Ok(results)
+}
+
+/// Rounds to 0.1 ns so the committed file does not churn on float noise
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+fn record(criterion_dir: &Path, baseline: &Path) -> Result<(), String> {
+    let results = collect(criterion_dir)?;
+    if results.is_empty() {
+        return Err(format!("no benchmark results under {}; run `cargo bench` first", criterion_dir.display()));
+    }
+
+    let benchmarks: serde_json::Map<String, Value> = results
+        .iter()
+        .map(|(id, e)| {
+            (id.clone(), json!({
+                "median_ns": round(e.median),
+                "mean_ns": round(e.mean),
+                "mean_lower_ns": round(e.mean_lower),
+                "mean_upper_ns": round(e.mean_upper),
+            }))
+        })
+        .collect();
+    let document = json!({
+        "unit": "nanoseconds per iteration",
+        "profile": "bench",
+        "benchmarks": benchmarks,
+    });
+
+    let text = serde_json::to_string_pretty(&document).map_err(|e| e.to_string())?;
+    if let Some(parent) = baseline.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("{}: {}", parent.display(), e))?;
+    }
+    fs::write(baseline, text + "\n").map_err(|e| format!("{}: {}", baseline.display(), e))?;
+    println!("Recorded {} benchmarks to {}", results.len(), baseline.display());
+    Ok(())
+}
+
+/// Prints a comparison table and returns whether any benchmark regressed
+fn compare(criterion_dir: &Path, baseline: &Path, threshold: f64) -> Result<bool, String> {
+    let old = load_baseline(baseline)?;
+    let new = collect(criterion_dir)?;
+    let mut regressed = false;
+
+    println!("{:<40} {:>14} {:>14} {:>9}", "benchmark", "baseline", "current", "change");
+    for (id, current) in &new {
+        let Some(base) = old.get(id) else {
+            println!("{:<40} {:>14} {:>14.1} {:>9}", id, "-", current.median, "new");
+            continue;
+        };
+
+        let change = 100.0 * (current.median - base.median) / base.median;
+        let flag = if change > threshold {
+            regressed = true;
+            "  REGRESSION"
+        } else {
+            ""
+        };
+        println!("{:<40} {:>14.1} {:>14.1} {:>+8.1}%{}", id, base.median, current.median, change, flag);
+    }
+    for id in old.keys().filter(|id| !new.contains_key(*id)) {
+        println!("{:<40} {:>14} {:>14} {:>9}", id, "", "-", "missing");
+    }
+
+    Ok(regressed)
+}
+
+fn usage() -> ExitCode {
+    eprintln!("usage: bench_baseline record  [criterion-dir] [baseline.json]");
+    eprintln!("       bench_baseline compare [criterion-dir] [baseline.json] [--threshold <percent>]");
+    ExitCode::from(2)
+}
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+    let mut threshold = DEFAULT_THRESHOLD;
+    if let Some(pos) = args.iter().position(|a| a == "--threshold") {
+        let Some(value) = args.get(pos + 1).and_then(|v| v.parse().ok()) else {
+            return usage();
+        };
+        threshold = value;
+        args.drain(pos..pos + 2);
+    }
+
+    let Some(command) = args.first().cloned() else {
+        return usage();
+    };
+    let criterion_dir = PathBuf::from(args.get(1).map_or(DEFAULT_CRITERION_DIR, String::as_str));
+    let baseline = PathBuf::from(args.get(2).map_or(DEFAULT_BASELINE, String::as_str));
+
+    let result = match command.as_str() {
+        "record" => record(&criterion_dir, &baseline).map(|_| false),
+        "compare" => compare(&criterion_dir, &baseline, threshold),
+        _ => return usage(),
+    };
+
+    match result {
+        Ok(false) => ExitCode::SUCCESS,
+        Ok(true) => {
+            eprintln!("Some benchmarks are more than {}% slower than the baseline", threshold);
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,17 @@
+//! Prints the exact decryption-failure probability of the Kyber parameter sets
+//!
+//! ```text
+//! cargo run --release -p examples --bin failure_probability
+//! ```
+
+use lattice_core::failure::{self, FailureReport, TARGET_LOG2_DELTA};
+
+fn main() {
+    println!("| Parameter set | k | η1 | η2 | du | dv | log2 δ | ≤ 2^{} |", TARGET_LOG2_DELTA);
+    println!("|---------------|---|----|----|----|----|--------|--------|");
+    for (name, report) in ["Kyber-512", "Kyber-768", "Kyber-1024"].into_iter().zip(failure::kyber_reports()) {
+        let FailureReport { params: p, log2_delta, .. } = report;
+        let verdict = if report.exceeds_target() { "NO" } else { "yes" };
+        println!("| {} | {} | {} | {} | {} | {} | {:.2} | {} |", name, p.k, p.eta1, p.eta2, p.du, p.dv, log2_delta, verdict);
+    }
+}
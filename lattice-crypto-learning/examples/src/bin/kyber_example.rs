@@ -3,7 +3,6 @@ use kyber_ml_kem::{
     cpa, kem
 };
 use rand::{Rng, rngs::OsRng};
-use hex;
 
 fn main() {
     println!("CRYSTALS-Kyber (ML-KEM) Example");
@@ -41,10 +40,9 @@ fn main() {
     
     // Verify decryption succeeded
     let mut bit_diffs = 0;
-    for i in 0..32 {
-        let x = message[i] ^ decrypted[i];
+    for (a, b) in message.iter().zip(decrypted.iter()) {
         // Count the number of 1 bits (Hamming weight)
-        bit_diffs += x.count_ones();
+        bit_diffs += (a ^ b).count_ones();
     }
 
     if bit_diffs == 0 {
@@ -72,10 +70,9 @@ fn main() {
     
     // Verify the shared secrets match
     let mut bit_diffs = 0;
-    for i in 0..32 {
-        let x = encapsulation.shared_secret[i] ^ shared_secret[i];
+    for (a, b) in encapsulation.shared_secret.iter().zip(shared_secret.iter()) {
         // Count the number of 1 bits (Hamming weight)
-        bit_diffs += x.count_ones();
+        bit_diffs += (a ^ b).count_ones();
     }
 
     if bit_diffs == 0 {
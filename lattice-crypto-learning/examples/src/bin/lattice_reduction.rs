@@ -0,0 +1,39 @@
+//! Recovers the secret of small LWE instances with LLL and BKZ
+//!
+//! ```text
+//! cargo run --release -p examples --bin lattice_reduction
+//! ```
+
+use std::time::Instant;
+
+use lattice_core::lwe::{LweSamples, LLL_DELTA, MAX_TOURS};
+use lattice_core::params::LatticeParams;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(2024);
+    println!("| n | m | q | η | β | root Hermite factor | recovered | time |");
+    println!("|---|---|---|---|---|---------------------|-----------|------|");
+    for (n, m, q) in [(30, 30, 97), (40, 40, 97), (36, 36, 67)] {
+        let params = LatticeParams { q, n, eta: 3, beta: 3, k: 1 };
+        let lwe = LweSamples::from_params(&params, m, &mut rng);
+        for beta in [0, 10, 20] {
+            let start = Instant::now();
+            let mut basis = lwe.embedding();
+            basis.bkz(beta, LLL_DELTA, MAX_TOURS);
+            let recovered = lwe.read_secret(&basis, params.eta as i64) == Some(lwe.secret.clone());
+            println!(
+                "| {} | {} | {} | {} | {} | {:.4} | {} | {:.2?} |",
+                n,
+                m,
+                q,
+                params.eta,
+                if beta < 2 { "LLL".to_string() } else { beta.to_string() },
+                basis.root_hermite_factor(),
+                if recovered { "yes" } else { "no" },
+                start.elapsed()
+            );
+        }
+    }
+}
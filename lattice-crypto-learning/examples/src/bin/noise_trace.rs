@@ -0,0 +1,20 @@
+//! Measures the CPA decryption error of every Kyber parameter set
+//!
+//! ```text
+//! cargo run --release -p examples --bin noise_trace -- [trials]
+//! ```
+//!
+//! Prints, per parameter set, the histogram of the per-coefficient error in
+//! the decoded polynomial, the largest error from each source and the margin
+//! left to q/4.
+
+use kyber_ml_kem::noise::NoiseStats;
+use kyber_ml_kem::params::SecurityLevel;
+use rand::rngs::OsRng;
+
+fn main() {
+    let trials = std::env::args().nth(1).map_or(1000, |arg| arg.parse().expect("trials must be a number"));
+    for level in [SecurityLevel::Kyber512, SecurityLevel::Kyber768, SecurityLevel::Kyber1024] {
+        println!("{}", NoiseStats::collect(level, trials, &mut OsRng));
+    }
+}
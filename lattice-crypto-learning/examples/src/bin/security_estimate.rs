@@ -0,0 +1,38 @@
+//! Prints core-SVP estimates for the Kyber and Dilithium parameter sets
+//!
+//! ```text
+//! cargo run --release -p examples --bin security_estimate
+//! ```
+
+use lattice_core::estimator::{self, Attack, CostModel, SecurityEstimate};
+use lattice_core::params::{dilithium, kyber};
+
+fn row(name: &str, estimate: &SecurityEstimate) {
+    let cell = |attack| {
+        estimate.cost(attack, CostModel::Classical).map_or("-".to_string(), |a| format!("β={} ({:.1})", a.block_size, a.bits))
+    };
+    println!(
+        "| {} | {} | {} | {} | {:.1} | {:.1} |",
+        name,
+        cell(Attack::Primal),
+        cell(Attack::Dual),
+        cell(Attack::SisLinf),
+        estimate.bits(CostModel::Classical),
+        estimate.bits(CostModel::Quantum)
+    );
+}
+
+fn main() {
+    println!("| Parameter set | Primal | Dual | SIS (ℓ∞) | Classical | Quantum |");
+    println!("|---------------|--------|------|----------|-----------|---------|");
+    for (name, params) in [("Kyber-512", kyber::kyber_512()), ("Kyber-768", kyber::kyber_768()), ("Kyber-1024", kyber::kyber_1024())] {
+        row(name, &estimator::estimate_kyber(&params));
+    }
+    for (name, params) in [
+        ("Dilithium2", dilithium::dilithium_2()),
+        ("Dilithium3", dilithium::dilithium_3()),
+        ("Dilithium5", dilithium::dilithium_5()),
+    ] {
+        row(name, &estimator::estimate_dilithium(&params));
+    }
+}
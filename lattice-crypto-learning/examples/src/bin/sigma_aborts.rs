@@ -0,0 +1,45 @@
+//! Runs the interactive identification schemes of Figures 5, 8 and 9 and counts aborts
+//!
+//! For every parameter set and variant the honest prover convinces the
+//! verifier a number of times; the measured attempts per response are
+//! printed next to the expected value.
+//!
+//! ```text
+//! cargo run --release -p examples --bin sigma_aborts
+//! ```
+
+use dilithium_ml_dsa::params::SecurityLevel;
+use dilithium_ml_dsa::sigma::{self, Prover, Variant, Verifier};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const RUNS: usize = 200;
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(50);
+
+    println!("{:<12} {:<18} {:>9} {:>9} {:>9} {:>9} {:>9} {:>9}", "level", "variant", "measured", "expected", "z", "low bits", "c·t0", "hint");
+    for level in [SecurityLevel::Dilithium2, SecurityLevel::Dilithium3, SecurityLevel::Dilithium5] {
+        let (statement, witness) = sigma::keygen(level, &mut rng);
+        for variant in [Variant::Basic, Variant::ReducedProof, Variant::ReducedPublicKey] {
+            let mut prover = Prover::new(&statement, &witness, variant);
+            for _ in 0..RUNS {
+                let mut verifier = Verifier::new(&statement, variant);
+                assert_eq!(sigma::run(&mut prover, &mut verifier, &mut rng), Ok(true));
+            }
+
+            let stats = prover.stats();
+            println!(
+                "{:<12} {:<18} {:>9.2} {:>9.2} {:>9} {:>9} {:>9} {:>9}",
+                format!("{:?}", level),
+                format!("{:?}", variant),
+                stats.repetitions(),
+                sigma::expected_repetitions(variant, level),
+                stats.response,
+                stats.low_bits,
+                stats.ct0,
+                stats.hint_weight,
+            );
+        }
+    }
+}
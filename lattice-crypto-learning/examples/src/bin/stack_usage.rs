@@ -0,0 +1,134 @@
+//! Measures the peak stack usage of the allocation-free ML-KEM API
+//!
+//! Uses the stack-painting method of pqm4: a heap buffer is filled with a
+//! known byte, the operation runs with its stack pointer switched to the top
+//! of that buffer, and the lowest overwritten byte marks the deepest point
+//! the stack reached. The buffer stays alive throughout, so the scan only
+//! reads memory this program owns. Caller-owned buffers (keys, ciphertext,
+//! shared secret) are not included; the small trampoline frame is.
+//!
+//! ```text
+//! cargo run --release -p examples --bin stack_usage
+//! ```
+//!
+//! The numbers depend on the compiler and target; the table in the
+//! `kyber_ml_kem::fixed` docs was produced with this tool on x86_64 in
+//! release mode. Only x86_64 and aarch64 are supported.
+
+use std::hint::black_box;
+
+use kyber_ml_kem::fixed;
+use kyber_ml_kem::params::{ByteArray, Kyber1024, Kyber512, Kyber768, ParameterSet};
+
+const PAINT: u8 = 0xA5;
+/// Size of the painted stack; must exceed any measured usage
+const REGION: usize = 64 * 1024;
+
+/// Runs `f` on a freshly painted stack and returns how many bytes it used
+fn measure<F: FnOnce()>(f: F) -> usize {
+    let mut stack = vec![PAINT; REGION];
+    let top = (stack.as_mut_ptr() as usize + REGION) & !15;
+    let mut f = Some(f);
+    // SAFETY: `top` is the 16-byte aligned end of `stack`, which outlives the
+    // call, and the trampoline cannot unwind out of the foreign stack
+    unsafe { on_stack(top, &mut f as *mut Option<F> as *mut u8, trampoline::<F>) };
+
+    let first_used = stack.iter().position(|&b| b != PAINT).expect("operation did not touch the stack");
+    assert!(first_used > 0, "painted stack of {} bytes overflowed", REGION);
+    top - (stack.as_ptr() as usize + first_used)
+}
+
+/// Entry point on the painted stack; panics abort because the function is `extern "C"`
+extern "C" fn trampoline<F: FnOnce()>(f: *mut u8) {
+    // SAFETY: `measure` passes a pointer to its live `Option<F>`
+    let f = unsafe { &mut *(f as *mut Option<F>) };
+    f.take().expect("trampoline runs once")()
+}
+
+/// Calls `entry(arg)` with the stack pointer set to `top`, then restores it
+#[cfg(target_arch = "x86_64")]
+unsafe fn on_stack(top: usize, arg: *mut u8, entry: extern "C" fn(*mut u8)) {
+    std::arch::asm!(
+        "mov rax, rsp",
+        "mov rsp, {top}",
+        // Save the old stack pointer and keep rsp 16-byte aligned at the call
+        "push rax",
+        "sub rsp, 8",
+        "call {entry}",
+        "add rsp, 8",
+        "pop rsp",
+        top = in(reg) top,
+        entry = in(reg) entry,
+        out("rax") _,
+        in("rdi") arg,
+        clobber_abi("C"),
+    );
+}
+
+/// Calls `entry(arg)` with the stack pointer set to `top`, then restores it
+#[cfg(target_arch = "aarch64")]
+unsafe fn on_stack(top: usize, arg: *mut u8, entry: extern "C" fn(*mut u8)) {
+    std::arch::asm!(
+        "mov x9, sp",
+        "mov sp, {top}",
+        // Save the old stack pointer in a 16-byte slot at the top
+        "str x9, [sp, #-16]!",
+        "blr {entry}",
+        "ldr x9, [sp], #16",
+        "mov sp, x9",
+        top = in(reg) top,
+        entry = in(reg) entry,
+        out("x9") _,
+        in("x0") arg,
+        clobber_abi("C"),
+    );
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+compile_error!("stack_usage switches stacks with inline assembly for x86_64 and aarch64 only");
+
+/// Peak stack bytes of keygen, encaps and decaps for one parameter set
+fn measure_level<P: ParameterSet>() -> [usize; 3] {
+    let (d, z, m) = ([1u8; 32], [2u8; 32], [3u8; 32]);
+    let mut ek = P::EncapsulationKeyBytes::zeroed();
+    let mut dk = P::DecapsulationKeyBytes::zeroed();
+    fixed::keygen_internal::<P>(&d, &z, &mut ek, &mut dk);
+    let mut ct = P::CiphertextBytes::zeroed();
+    fixed::encaps_derand::<P>(&ek, &m, &mut ct, &mut [0u8; 32]).unwrap();
+
+    let keygen = measure(move || {
+        let mut ek = Box::new(P::EncapsulationKeyBytes::zeroed());
+        let mut dk = Box::new(P::DecapsulationKeyBytes::zeroed());
+        fixed::keygen_internal::<P>(black_box(&d), &z, &mut ek, &mut dk);
+        black_box((ek, dk));
+    });
+
+    let ek_heap = Box::new(ek);
+    let encaps = measure(move || {
+        let mut ct = Box::new(P::CiphertextBytes::zeroed());
+        let mut ss = Box::new([0u8; 32]);
+        fixed::encaps_derand::<P>(&ek_heap, black_box(&m), &mut ct, &mut ss).unwrap();
+        black_box((ct, ss));
+    });
+
+    let (dk_heap, ct_heap) = (Box::new(dk), Box::new(ct));
+    let decaps = measure(move || {
+        let mut ss = Box::new([0u8; 32]);
+        fixed::decaps::<P>(&dk_heap, black_box(&ct_heap), &mut ss).unwrap();
+        black_box(ss);
+    });
+
+    [keygen, encaps, decaps]
+}
+
+fn main() {
+    println!("| Parameter set | keygen | encaps | decaps |");
+    println!("|---------------|--------|--------|--------|");
+    for (name, usage) in [
+        ("ML-KEM-512", measure_level::<Kyber512>()),
+        ("ML-KEM-768", measure_level::<Kyber768>()),
+        ("ML-KEM-1024", measure_level::<Kyber1024>()),
+    ] {
+        println!("| {} | {} B | {} B | {} B |", name, usage[0], usage[1], usage[2]);
+    }
+}
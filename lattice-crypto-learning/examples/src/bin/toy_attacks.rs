@@ -0,0 +1,43 @@
+//! Breaks weakened Kyber-shaped LWE instances by brute force, Arora–Ge and lattice reduction
+//!
+//! Each attack is run on instances derived from Kyber-512, weakened less and
+//! less until it stops working.
+//!
+//! ```text
+//! cargo run --release -p examples --bin toy_attacks
+//! ```
+
+use lattice_core::attacks::{self, Method, WeakInstance};
+use lattice_core::params::{kyber, LatticeParams};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn main() {
+    let mut rng = StdRng::seed_from_u64(48);
+    let base = kyber::kyber_512();
+    let shrunk = |n| WeakInstance::from_params(&LatticeParams { n, k: 1, ..base.clone() });
+
+    println!("Brute force: (2η+1)^n candidate secrets");
+    for n in [4, 8, 10, 12, 16] {
+        let instance = WeakInstance { samples: 2 * n, secret_eta: 1, ..shrunk(n) };
+        let report = attacks::run(&instance, Method::BruteForce { max_candidates: 1 << 24 }, &mut rng);
+        println!("  {} ({:.1} bits of secret)", report, instance.secret_space_bits());
+    }
+
+    println!("Arora-Ge: ternary errors, one cubic equation per sample");
+    for n in [4, 8, 12, 16, 20] {
+        let instance = WeakInstance { error_eta: 1, ..shrunk(n) };
+        let instance = WeakInstance { samples: instance.arora_ge_unknowns() + 20, ..instance };
+        let report = attacks::run(&instance, Method::AroraGe { max_unknowns: 1000 }, &mut rng);
+        println!("  {} ({} unknowns)", report, instance.arora_ge_unknowns());
+    }
+
+    println!("Lattice reduction: embedding of (s, e, 1), shrinking q raises the noise rate");
+    for q in [3329, 257, 97, 67] {
+        let instance = WeakInstance { q, ..shrunk(40) };
+        for beta in [0, 20] {
+            let report = attacks::run(&instance, Method::Lattice { beta }, &mut rng);
+            println!("  {} (σ/q = {:.1e})", report, instance.noise_rate());
+        }
+    }
+}
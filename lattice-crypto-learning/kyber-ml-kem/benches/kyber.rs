@@ -0,0 +1,108 @@
+//! Benchmarks for CPA encryption and the KEM at every security level
+//!
+//! Run with `cargo bench -p kyber-ml-kem --bench kyber`; see `benches/README.md`
+//! at the workspace root for recording and comparing JSON baselines.
+
+use std::time::Duration;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use kyber_ml_kem::batch;
+use kyber_ml_kem::cpa;
+use kyber_ml_kem::kem;
+use kyber_ml_kem::params::SecurityLevel;
+
+const LEVELS: [SecurityLevel; 3] = [SecurityLevel::Kyber512, SecurityLevel::Kyber768, SecurityLevel::Kyber1024];
+
+fn configure() -> Criterion {
+    Criterion::default()
+        .sample_size(30)
+        .warm_up_time(Duration::from_millis(500))
+        .measurement_time(Duration::from_secs(3))
+}
+
+fn bench_cpa(c: &mut Criterion) {
+    let msg = [0x42u8; 32];
+    let coins = [0x24u8; 32];
+
+    let mut encrypt = c.benchmark_group("cpa_encrypt");
+    for level in LEVELS {
+        let (pk, _) = cpa::keygen_internal(&[1u8; 32], level);
+        encrypt.bench_with_input(BenchmarkId::from_parameter(format!("{:?}", level)), &pk, |b, pk| {
+            b.iter(|| cpa::encrypt(black_box(pk), black_box(&msg), black_box(&coins)))
+        });
+    }
+    encrypt.finish();
+
+    let mut decrypt = c.benchmark_group("cpa_decrypt");
+    for level in LEVELS {
+        let (pk, sk) = cpa::keygen_internal(&[1u8; 32], level);
+        let ciphertext = cpa::encrypt(&pk, &msg, &coins);
+        decrypt.bench_with_input(BenchmarkId::from_parameter(format!("{:?}", level)), &ciphertext, |b, ct| {
+            b.iter(|| cpa::decrypt(black_box(&sk), black_box(ct)))
+        });
+    }
+    decrypt.finish();
+}
+
+fn bench_kem(c: &mut Criterion) {
+    let d = [1u8; 32];
+    let z = [2u8; 32];
+    let m = [3u8; 32];
+
+    let mut keygen = c.benchmark_group("kem_keygen");
+    for level in LEVELS {
+        keygen.bench_with_input(BenchmarkId::from_parameter(format!("{:?}", level)), &level, |b, &level| {
+            b.iter(|| kem::keygen_internal(black_box(&d), black_box(&z), level))
+        });
+    }
+    keygen.finish();
+
+    let mut encaps = c.benchmark_group("kem_encaps");
+    for level in LEVELS {
+        let (pk, _) = kem::keygen_internal(&d, &z, level);
+        encaps.bench_with_input(BenchmarkId::from_parameter(format!("{:?}", level)), &pk, |b, pk| {
+            b.iter(|| kem::encaps_derand(black_box(pk), black_box(&m)))
+        });
+    }
+    encaps.finish();
+
+    let mut decaps = c.benchmark_group("kem_decaps");
+    for level in LEVELS {
+        let (pk, sk) = kem::keygen_internal(&d, &z, level);
+        let ciphertext = kem::encaps_derand(&pk, &m).ciphertext;
+        decaps.bench_with_input(BenchmarkId::from_parameter(format!("{:?}", level)), &ciphertext, |b, ct| {
+            b.iter(|| kem::decaps(black_box(&sk), black_box(ct)))
+        });
+    }
+    decaps.finish();
+}
+
+fn bench_batch(c: &mut Criterion) {
+    const BATCH: u8 = 16;
+    let level = SecurityLevel::Kyber768;
+
+    let pairs: Vec<_> = (0..BATCH).map(|i| kem::keygen_internal(&[i; 32], &[i; 32], level)).collect();
+    let pks: Vec<_> = pairs.iter().map(|(pk, _)| pk.clone()).collect();
+    let ms: Vec<[u8; 32]> = (0..BATCH).map(|i| [i; 32]).collect();
+    let (_, sk) = &pairs[0];
+    let cts: Vec<_> = ms.iter().map(|m| kem::encaps_derand(&pks[0], m).ciphertext).collect();
+
+    let mut group = c.benchmark_group("kem_batch_x16");
+    group.bench_function("encaps_loop", |b| {
+        b.iter(|| pks.iter().zip(&ms).map(|(pk, m)| kem::encaps_derand(pk, m)).collect::<Vec<_>>())
+    });
+    group.bench_function("encaps_batch", |b| b.iter(|| batch::encaps_batch_derand(black_box(&pks), &ms)));
+    group.bench_function("decaps_loop", |b| {
+        b.iter(|| cts.iter().map(|ct| kem::decaps(sk, ct)).collect::<Vec<_>>())
+    });
+    let decapsulator = batch::BatchDecapsulator::new(sk);
+    group.bench_function("decaps_batch", |b| b.iter(|| decapsulator.decaps_batch(black_box(&cts))));
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = configure();
+    targets = bench_cpa, bench_kem, bench_batch
+}
+criterion_main!(benches);
@@ -0,0 +1,52 @@
+//! Compares encryption and encapsulation with and without a cached A_hat
+
+use std::time::Duration;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use kyber_ml_kem::cpa::{self, PreparedPublicKey};
+use kyber_ml_kem::kem;
+use kyber_ml_kem::params::SecurityLevel;
+
+fn configure() -> Criterion {
+    Criterion::default()
+        .sample_size(30)
+        .warm_up_time(Duration::from_millis(500))
+        .measurement_time(Duration::from_secs(3))
+}
+
+fn bench_prepared(c: &mut Criterion) {
+    let msg = [0x42u8; 32];
+    let coins = [0x24u8; 32];
+
+    for security_level in [SecurityLevel::Kyber512, SecurityLevel::Kyber768, SecurityLevel::Kyber1024] {
+        let mut group = c.benchmark_group(format!("{:?}", security_level));
+
+        let (pk, _) = cpa::keygen_internal(&[1u8; 32], security_level);
+        let prepared = PreparedPublicKey::new(pk.clone());
+        group.bench_function("cpa_encrypt", |b| {
+            b.iter(|| cpa::encrypt(black_box(&pk), black_box(&msg), black_box(&coins)))
+        });
+        group.bench_function("cpa_encrypt_prepared", |b| {
+            b.iter(|| prepared.encrypt(black_box(&msg), black_box(&coins)))
+        });
+
+        let (kem_pk, _) = kem::keygen_internal(&[1u8; 32], &[2u8; 32], security_level);
+        let mut kem_pk_prepared = kem_pk.clone();
+        kem_pk_prepared.prepare();
+        group.bench_function("kem_encaps", |b| {
+            b.iter(|| kem::encaps_derand(black_box(&kem_pk), black_box(&msg)))
+        });
+        group.bench_function("kem_encaps_prepared", |b| {
+            b.iter(|| kem::encaps_derand(black_box(&kem_pk_prepared), black_box(&msg)))
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group! {
+    name = benches;
+    config = configure();
+    targets = bench_prepared
+}
+criterion_main!(benches);
@@ -0,0 +1,161 @@
+//! Batch encapsulation and decapsulation
+//!
+//! Every single-shot `kem::encaps` / `kem::decaps` call rebuilds the NTT
+//! twiddle tables, and every decapsulation re-expands the matrix A of the
+//! server key and re-absorbs z for the implicit-rejection hash. The batch
+//! APIs do that work once and share it across the whole batch.
+//!
+//! With the `parallel` cargo feature the batches are spread over the rayon
+//! thread pool. Results are identical to looping over the single-shot
+//! functions in either case.
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+#[cfg(feature = "std")]
+use rand::{Rng, rngs::OsRng};
+use sha3::Shake256;
+use lattice_core::ntt::NTTParams;
+
+use crate::cpa::{self, Ciphertext};
+use crate::kem::{self, Encapsulation, PublicKey, SecretKey};
+
+/// Maps `f` over `items`, in parallel when the `parallel` feature is enabled
+fn map_batch<T: Sync, R: Send>(items: &[T], f: impl Fn(&T) -> R + Sync + Send) -> Vec<R> {
+    #[cfg(feature = "parallel")]
+    {
+        items.par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        items.iter().map(f).collect()
+    }
+}
+
+/// Encapsulates to many (possibly different) public keys
+///
+/// One fresh random message is drawn per key.
+#[cfg(feature = "std")]
+pub fn encaps_batch(pks: &[PublicKey]) -> Vec<Encapsulation> {
+    let mut ms = vec![[0u8; 32]; pks.len()];
+    for m in ms.iter_mut() {
+        OsRng.fill(m);
+    }
+
+    encaps_batch_derand(pks, &ms)
+}
+
+/// Deterministic batch encapsulation: entry i equals `kem::encaps_derand(&pks[i], &ms[i])`
+///
+/// Panics if `pks` and `ms` have different lengths.
+pub fn encaps_batch_derand(pks: &[PublicKey], ms: &[[u8; 32]]) -> Vec<Encapsulation> {
+    assert_eq!(pks.len(), ms.len(), "one message is needed per public key");
+
+    let ntt_params = cpa::get_ntt_params();
+    let jobs: Vec<(&PublicKey, &[u8; 32])> = pks.iter().zip(ms).collect();
+    map_batch(&jobs, |(pk, m)| kem::encaps_with(pk, m, &ntt_params))
+}
+
+/// Decapsulates many ciphertexts under one secret key
+///
+/// Entry i equals `kem::decaps(sk, &cts[i])`, including the implicit-rejection
+/// output for invalid ciphertexts.
+pub fn decaps_batch(sk: &SecretKey, cts: &[Ciphertext]) -> Vec<[u8; 32]> {
+    BatchDecapsulator::new(sk).decaps_batch(cts)
+}
+
+/// Decapsulation state shared by every ciphertext sent to one server key
+///
+/// Holds a copy of the key with A_hat cached, the NTT tables and the SHAKE256
+/// state with z already absorbed. Build it once and keep it for the lifetime
+/// of the key.
+#[derive(Debug, Clone)]
+pub struct BatchDecapsulator {
+    sk: SecretKey,
+    ntt_params: NTTParams,
+    rejection: Shake256,
+}
+
+impl BatchDecapsulator {
+    /// Prepares the shared state for `sk`
+    pub fn new(sk: &SecretKey) -> Self {
+        let mut sk = sk.clone();
+        sk.prepare();
+        let rejection = kem::rejection_hasher(&sk);
+
+        Self { sk, ntt_params: cpa::get_ntt_params(), rejection }
+    }
+
+    /// Decapsulates a single ciphertext using the shared state
+    pub fn decaps(&self, ciphertext: &Ciphertext) -> [u8; 32] {
+        kem::decaps_with(&self.sk, ciphertext, &self.ntt_params, &self.rejection)
+    }
+
+    /// Decapsulates every ciphertext of the batch
+    pub fn decaps_batch(&self, cts: &[Ciphertext]) -> Vec<[u8; 32]> {
+        map_batch(cts, |ct| self.decaps(ct))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::SecurityLevel;
+
+    fn keys(level: SecurityLevel, count: u8) -> Vec<(PublicKey, SecretKey)> {
+        (0..count).map(|i| kem::keygen_internal(&[i; 32], &[i ^ 0xFF; 32], level)).collect()
+    }
+
+    #[test]
+    fn test_encaps_batch_matches_single_shot() {
+        for level in [SecurityLevel::Kyber512, SecurityLevel::Kyber768, SecurityLevel::Kyber1024] {
+            let pks: Vec<PublicKey> = keys(level, 4).into_iter().map(|(pk, _)| pk).collect();
+            let ms: Vec<[u8; 32]> = (0..4u8).map(|i| [i.wrapping_mul(37); 32]).collect();
+
+            let batch = encaps_batch_derand(&pks, &ms);
+            assert_eq!(batch.len(), pks.len());
+            for ((pk, m), encaps) in pks.iter().zip(&ms).zip(&batch) {
+                let single = kem::encaps_derand(pk, m);
+                assert_eq!(encaps.ciphertext, single.ciphertext);
+                assert_eq!(encaps.shared_secret, single.shared_secret);
+            }
+        }
+    }
+
+    #[test]
+    fn test_decaps_batch_matches_single_shot() {
+        let (pk, sk) = kem::keygen_internal(&[7u8; 32], &[8u8; 32], SecurityLevel::Kyber768);
+        let mut cts: Vec<Ciphertext> = (0..4u8).map(|i| kem::encaps_derand(&pk, &[i; 32]).ciphertext).collect();
+
+        // Include a tampered ciphertext so the implicit-rejection path is covered too
+        let mut bytes = cpa::ciphertext_to_bytes(&cts[0]);
+        bytes[0] ^= 1;
+        cts.push(cpa::ciphertext_from_bytes(&bytes, SecurityLevel::Kyber768));
+
+        let batch = decaps_batch(&sk, &cts);
+        for (ct, ss) in cts.iter().zip(&batch) {
+            assert_eq!(*ss, kem::decaps(&sk, ct));
+        }
+        assert_ne!(batch[4], batch[0]);
+    }
+
+    #[test]
+    fn test_batch_roundtrip_with_different_keys() {
+        let pairs = keys(SecurityLevel::Kyber512, 3);
+        let pks: Vec<PublicKey> = pairs.iter().map(|(pk, _)| pk.clone()).collect();
+
+        let batch = encaps_batch(&pks);
+        for ((_, sk), encaps) in pairs.iter().zip(&batch) {
+            assert_eq!(BatchDecapsulator::new(sk).decaps(&encaps.ciphertext), encaps.shared_secret);
+        }
+        assert!(encaps_batch(&[]).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "one message is needed per public key")]
+    fn test_encaps_batch_length_mismatch() {
+        let pks: Vec<PublicKey> = keys(SecurityLevel::Kyber512, 2).into_iter().map(|(pk, _)| pk).collect();
+        encaps_batch_derand(&pks, &[[0u8; 32]]);
+    }
+}
@@ -0,0 +1,34 @@
+use core::fmt;
+
+/// Errors returned when decoding Kyber keys and ciphertexts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The input does not have the length required by the parameter set
+    InvalidLength {
+        /// Expected length in bytes
+        expected: usize,
+        /// Actual length in bytes
+        actual: usize,
+    },
+    /// The encapsulation key fails the modulus check: some 12-bit value is not reduced mod q
+    InvalidEncapsulationKey,
+    /// The decapsulation key is malformed (its embedded H(ek) does not match ek)
+    InvalidDecapsulationKey,
+    /// The key belongs to a different parameter set than requested
+    SecurityLevelMismatch,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidLength { expected, actual } => {
+                write!(f, "invalid length: expected {} bytes, got {}", expected, actual)
+            }
+            Error::InvalidEncapsulationKey => write!(f, "encapsulation key is not reduced modulo q"),
+            Error::InvalidDecapsulationKey => write!(f, "decapsulation key hash check failed"),
+            Error::SecurityLevelMismatch => write!(f, "key belongs to a different parameter set"),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
@@ -0,0 +1,674 @@
+//! Allocation-free ML-KEM on fixed-size arrays
+//!
+//! This API needs neither `std` nor `alloc`. Polynomials are `[u16; 256]`
+//! arrays on the stack, and the caller supplies the RNG and every output
+//! buffer. Keys and ciphertexts are the typed byte arrays of a
+//! [`ParameterSet`].
+//!
+//! The encodings and outputs match [`kem`](crate::kem) bit for bit, so keys
+//! and ciphertexts can be exchanged between the two implementations.
+//!
+//! # Memory
+//!
+//! The functions follow the memory-optimized strategies of pqm4:
+//!
+//! - the matrix A_hat is never stored; one entry is expanded at a time while
+//!   t_hat (keygen) or u (encryption) is computed row by row
+//! - noise is added to its target coefficient by coefficient as it is sampled
+//! - s_hat is written to the decapsulation key as soon as it is sampled and
+//!   decoded again when needed, and t_hat is decoded from ek one polynomial at
+//!   a time
+//! - decapsulation never stores the re-encrypted ciphertext: each compressed
+//!   polynomial is compared against the input as it is produced
+//!
+//! The only vector kept in full is r_hat during encryption (512 bytes per
+//! rank). Worst-case stack usage, measured by stack painting with
+//! `cargo run --release -p examples --bin stack_usage` on x86_64 (rustc 1.95),
+//! excluding the caller's key, ciphertext and shared-secret buffers:
+//!
+//! | Parameter set | `keygen_internal` | `encaps_derand` | `decaps` |
+//! |---------------|-------------------|-----------------|----------|
+//! | ML-KEM-512    | 3392 B            | 4768 B          | 4816 B   |
+//! | ML-KEM-768    | 3408 B            | 5280 B          | 5360 B   |
+//! | ML-KEM-1024   | 3424 B            | 5792 B          | 5792 B   |
+//!
+//! Other targets and compiler versions differ somewhat, and unoptimized builds
+//! use five to eight times as much, so re-measure before sizing a task stack.
+
+use rand_core::CryptoRngCore;
+use sha3::digest::{Digest, ExtendableOutput, Update, XofReader};
+use sha3::{Sha3_256, Sha3_512, Shake128, Shake256};
+
+use crate::error::Error;
+use crate::params::{ByteArray, ParameterSet, SecurityLevel, N};
+
+const Q: u32 = crate::params::Q as u32;
+
+/// A polynomial with canonical coefficients in [0, q)
+pub type Poly = [u16; N];
+
+/// Bytes of one ByteEncode_12 polynomial
+const POLY_BYTES: usize = N * 12 / 8;
+
+/// Number of NTT slots: Kyber's NTT stops at 128 quadratic factors
+const SLOTS: usize = N / 2;
+
+/// Forward twiddle factors 17^bitrev7(i) mod q
+const ZETAS: [u16; SLOTS] = twiddles(false);
+
+/// Inverse twiddle factors 17^-bitrev7(i) mod q
+const ZETAS_INV: [u16; SLOTS] = twiddles(true);
+
+/// 128^-1 mod q, the scaling factor of the inverse NTT
+const SLOTS_INV: u32 = 3303;
+
+const fn pow_mod(mut base: u32, mut exp: u32) -> u32 {
+    let mut result = 1;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % Q;
+        }
+        base = base * base % Q;
+        exp >>= 1;
+    }
+    result
+}
+
+/// Same tables as `NTTParams::new(3329, 256, 17)`; 17 has order 256, so 17^-e = 17^(256 - e)
+const fn twiddles(inverse: bool) -> [u16; SLOTS] {
+    let mut table = [0u16; SLOTS];
+    let mut i = 0;
+    while i < SLOTS {
+        let exp = (i as u8).reverse_bits() as u32 >> 1;
+        table[i] = pow_mod(17, if inverse { (256 - exp) % 256 } else { exp }) as u16;
+        i += 1;
+    }
+    table
+}
+
+fn add(a: u16, b: u16) -> u16 {
+    ((a as u32 + b as u32) % Q) as u16
+}
+
+fn sub(a: u16, b: u16) -> u16 {
+    ((a as u32 + Q - b as u32) % Q) as u16
+}
+
+fn mul(a: u16, b: u16) -> u16 {
+    (a as u32 * b as u32 % Q) as u16
+}
+
+/// In-place forward NTT (Cooley-Tukey, output in bit-reversed order)
+fn ntt(p: &mut Poly) {
+    let mut k = 1;
+    let mut len = N / 2;
+    while len >= 2 {
+        for start in (0..N).step_by(2 * len) {
+            let zeta = ZETAS[k];
+            k += 1;
+            for j in start..start + len {
+                let t = mul(p[j + len], zeta);
+                p[j + len] = sub(p[j], t);
+                p[j] = add(p[j], t);
+            }
+        }
+        len /= 2;
+    }
+}
+
+/// In-place inverse NTT (Gentleman-Sande), including the final scaling
+fn ntt_inverse(p: &mut Poly) {
+    let mut len = 2;
+    while len <= N / 2 {
+        let blocks = N / (2 * len);
+        for (block, start) in (0..N).step_by(2 * len).enumerate() {
+            let zeta_inv = ZETAS_INV[blocks + block];
+            for j in start..start + len {
+                let (even, odd) = (p[j], p[j + len]);
+                p[j] = add(even, odd);
+                p[j + len] = mul(sub(even, odd), zeta_inv);
+            }
+        }
+        len *= 2;
+    }
+
+    for coeff in p.iter_mut() {
+        *coeff = (*coeff as u32 * SLOTS_INV % Q) as u16;
+    }
+}
+
+/// Adds the NTT-domain product a ∘ b to `acc`
+///
+/// Pairs are residues modulo X^2 - γ with γ = ±ζ alternating per pair.
+fn base_mul_acc(acc: &mut Poly, a: &Poly, b: &Poly) {
+    for i in (0..N).step_by(2) {
+        let zeta = ZETAS[SLOTS / 2 + i / 4];
+        let gamma = if i % 4 == 0 { zeta } else { Q as u16 - zeta };
+        let c0 = add(mul(a[i], b[i]), mul(mul(a[i + 1], b[i + 1]), gamma));
+        let c1 = add(mul(a[i], b[i + 1]), mul(a[i + 1], b[i]));
+        acc[i] = add(acc[i], c0);
+        acc[i + 1] = add(acc[i + 1], c1);
+    }
+}
+
+/// Writes matrix entry A_hat[i][j] = SampleNTT(ρ‖j‖i)
+///
+/// SHAKE128 output is split into 12-bit candidates, two per 3 bytes, and the
+/// ones below q fill the coefficients in order. Kept out of line, like
+/// [`add_noise`], so the XOF state only occupies the stack while one entry is
+/// expanded.
+#[inline(never)]
+fn expand_a_hat_entry(rho: &[u8; 32], i: usize, j: usize, out: &mut Poly) {
+    let mut shake = Shake128::default();
+    Update::update(&mut shake, rho);
+    Update::update(&mut shake, &[j as u8, i as u8]);
+    let mut reader = shake.finalize_xof();
+
+    let mut filled = 0;
+    let mut bytes = [0u8; 3];
+    while filled < N {
+        reader.read(&mut bytes);
+        let d1 = bytes[0] as u16 | ((bytes[1] & 0x0F) as u16) << 8;
+        let d2 = (bytes[1] >> 4) as u16 | (bytes[2] as u16) << 4;
+        for d in [d1, d2] {
+            if d < Q as u16 && filled < N {
+                out[filled] = d;
+                filled += 1;
+            }
+        }
+    }
+}
+
+/// Adds the noise polynomial SamplePolyCBD_η(PRF_η(seed, nonce)) to `p`
+///
+/// PRF_η(s, b) = SHAKE256(s ‖ b) is read η bytes, i.e. four coefficients, at
+/// a time and each coefficient is added as it is sampled, so neither the PRF
+/// output nor the noise polynomial is ever stored.
+#[inline(never)]
+fn add_noise(p: &mut Poly, seed: &[u8; 32], nonce: u8, eta: usize) {
+    let mut shake = Shake256::default();
+    Update::update(&mut shake, seed);
+    Update::update(&mut shake, &[nonce]);
+    let mut reader = shake.finalize_xof();
+
+    let mask = (1u32 << eta) - 1;
+    let mut bytes = [0u8; 4];
+    for coeffs in p.chunks_exact_mut(4) {
+        reader.read(&mut bytes[..eta]);
+        let bits = u32::from_le_bytes(bytes);
+        for (k, coeff) in coeffs.iter_mut().enumerate() {
+            let x = (bits >> (2 * eta * k) & mask).count_ones() as u16;
+            let y = (bits >> (2 * eta * k + eta) & mask).count_ones() as u16;
+            *coeff = add(*coeff, sub(x, y));
+        }
+    }
+}
+
+/// (K, r) = G(m ‖ H(ek)) with G = SHA3-512
+fn hash_g(m: &[u8; 32], h_ek: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    let mut hasher = Sha3_512::new();
+    Digest::update(&mut hasher, m);
+    Digest::update(&mut hasher, h_ek);
+    let output = hasher.finalize();
+
+    let (mut k, mut r) = ([0u8; 32], [0u8; 32]);
+    k.copy_from_slice(&output[..32]);
+    r.copy_from_slice(&output[32..]);
+    (k, r)
+}
+
+/// Implicit-rejection key J(z ‖ c) = SHAKE256(z ‖ c, 32)
+fn hash_j(z: &[u8], ct: &[u8]) -> [u8; 32] {
+    let mut shake = Shake256::default();
+    Update::update(&mut shake, z);
+    Update::update(&mut shake, ct);
+    let mut out = [0u8; 32];
+    shake.finalize_xof().read(&mut out);
+    out
+}
+
+/// Returns `a` if `diff == 0` and `b` otherwise, without branching on `diff`
+fn select_secret(diff: u8, a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    // 0x00 when diff is zero, 0xFF otherwise
+    let mask = core::hint::black_box(((diff as u16).wrapping_neg() >> 8) as u8);
+    core::array::from_fn(|i| a[i] ^ (mask & (a[i] ^ b[i])))
+}
+
+fn sha3_256(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    for part in parts {
+        Digest::update(&mut hasher, part);
+    }
+    hasher.finalize().into()
+}
+
+/// ByteEncode_d: d bits per coefficient, least significant bit first
+fn byte_encode(p: &Poly, d: usize, out: &mut [u8]) {
+    out.fill(0);
+    for (i, &coeff) in p.iter().enumerate() {
+        for b in 0..d {
+            let bit = i * d + b;
+            out[bit / 8] |= (((coeff >> b) & 1) as u8) << (bit % 8);
+        }
+    }
+}
+
+/// ByteDecode_d, without reducing the values
+fn byte_decode(bytes: &[u8], d: usize) -> Poly {
+    let mut p = [0u16; N];
+    for (i, coeff) in p.iter_mut().enumerate() {
+        for b in 0..d {
+            let bit = i * d + b;
+            *coeff |= (((bytes[bit / 8] >> (bit % 8)) & 1) as u16) << b;
+        }
+    }
+    p
+}
+
+/// ByteDecode_12 reduced mod q, as the heap-based decoders do
+fn decode_reduced(bytes: &[u8]) -> Poly {
+    byte_decode(bytes, 12).map(|coeff| (coeff as u32 % Q) as u16)
+}
+
+/// In-place Compress_d(x) = ⌈(2^d / q) · x⌋ mod 2^d
+fn compress(p: &mut Poly, d: usize) {
+    for x in p.iter_mut() {
+        *x = (((((*x as u32) << d) + Q / 2) / Q) % (1 << d)) as u16;
+    }
+}
+
+/// In-place Decompress_d(y) = ⌈(q / 2^d) · y⌋
+fn decompress(p: &mut Poly, d: usize) {
+    for y in p.iter_mut() {
+        *y = ((*y as u32 * Q + (1 << (d - 1))) >> d) as u16;
+    }
+}
+
+/// Adds Decompress_1(m): each message bit becomes 0 or ⌈q/2⌋ = 1665
+fn add_message(p: &mut Poly, m: &[u8; 32]) {
+    for (i, coeff) in p.iter_mut().enumerate() {
+        let bit = ((m[i / 8] >> (i % 8)) & 1) as u16;
+        *coeff = add(*coeff, bit * Q.div_ceil(2) as u16);
+    }
+}
+
+/// Compress_1: coefficients closer to q/2 than to 0, i.e. 833 ≤ x ≤ 2496, decode to 1
+fn poly_to_message(p: &Poly) -> [u8; 32] {
+    let mut m = [0u8; 32];
+    for (i, &coeff) in p.iter().enumerate() {
+        let bit = ((2 * coeff as u32 + Q / 2) / Q % 2) as u8;
+        m[i / 8] |= bit << (i % 8);
+    }
+    m
+}
+
+/// K-PKE.KeyGen: writes ek = ByteEncode_12(t_hat) ‖ ρ and ByteEncode_12(s_hat)
+///
+/// s_hat is encoded into `dk_cpa` as soon as it is sampled and decoded again
+/// per matrix row, so only a handful of polynomials are live at a time.
+fn cpa_keygen<const K: usize>(d: &[u8; 32], level: SecurityLevel, ek: &mut [u8], dk_cpa: &mut [u8]) {
+    let mut hasher = Sha3_512::new();
+    Digest::update(&mut hasher, d);
+    Digest::update(&mut hasher, [K as u8]);
+    let expanded = hasher.finalize();
+    let mut rho = [0u8; 32];
+    let mut sigma = [0u8; 32];
+    rho.copy_from_slice(&expanded[..32]);
+    sigma.copy_from_slice(&expanded[32..]);
+
+    for (i, s_bytes) in dk_cpa.chunks_exact_mut(POLY_BYTES).enumerate() {
+        let mut s = [0u16; N];
+        add_noise(&mut s, &sigma, i as u8, level.eta1());
+        ntt(&mut s);
+        byte_encode(&s, 12, s_bytes);
+    }
+
+    let mut a_hat_ij = [0u16; N];
+    for (i, t_bytes) in ek[..K * POLY_BYTES].chunks_exact_mut(POLY_BYTES).enumerate() {
+        // t_hat[i] = Σ_j A_hat[i][j] ∘ s_hat[j] + NTT(e[i])
+        let mut t_hat = [0u16; N];
+        add_noise(&mut t_hat, &sigma, (K + i) as u8, level.eta1());
+        ntt(&mut t_hat);
+        for (j, s_bytes) in dk_cpa.chunks_exact(POLY_BYTES).enumerate() {
+            expand_a_hat_entry(&rho, i, j, &mut a_hat_ij);
+            base_mul_acc(&mut t_hat, &a_hat_ij, &decode_reduced(s_bytes));
+        }
+        byte_encode(&t_hat, 12, t_bytes);
+    }
+    ek[K * POLY_BYTES..].copy_from_slice(&rho);
+}
+
+/// Largest ciphertext chunk: one u polynomial at d_u = 11
+const MAX_CHUNK_BYTES: usize = N * 11 / 8;
+
+/// K-PKE.Encrypt of `m` under `ek` with randomness `coins`
+///
+/// The ciphertext is produced one compressed polynomial at a time, u[0], …,
+/// u[k-1] and then v, and each chunk is handed to `emit` in order. The matrix
+/// is expanded one entry at a time while computing u row by row.
+fn cpa_encrypt<const K: usize>(
+    ek: &[u8],
+    m: &[u8; 32],
+    coins: &[u8; 32],
+    level: SecurityLevel,
+    mut emit: impl FnMut(&[u8]),
+) {
+    let (du, dv) = (level.du(), level.dv());
+    let mut rho = [0u8; 32];
+    rho.copy_from_slice(&ek[K * POLY_BYTES..]);
+    let mut chunk = [0u8; MAX_CHUNK_BYTES];
+
+    let mut r_hat = [[0u16; N]; K];
+    for (i, r) in r_hat.iter_mut().enumerate() {
+        add_noise(r, coins, i as u8, level.eta1());
+        ntt(r);
+    }
+
+    // u[i] = InvNTT(Σ_j A_hat[j][i] ∘ r_hat[j]) + e1[i]
+    let u_bytes = N * du / 8;
+    let mut a_hat_ji = [0u16; N];
+    for i in 0..K {
+        let mut u = [0u16; N];
+        for (j, r_hat_j) in r_hat.iter().enumerate() {
+            expand_a_hat_entry(&rho, j, i, &mut a_hat_ji);
+            base_mul_acc(&mut u, &a_hat_ji, r_hat_j);
+        }
+        ntt_inverse(&mut u);
+        add_noise(&mut u, coins, (K + i) as u8, level.eta2());
+        compress(&mut u, du);
+        byte_encode(&u, du, &mut chunk[..u_bytes]);
+        emit(&chunk[..u_bytes]);
+    }
+
+    // v = InvNTT(Σ_i t_hat[i] ∘ r_hat[i]) + e2 + Decompress_1(m)
+    let mut v = [0u16; N];
+    for (r_hat_i, t_bytes) in r_hat.iter().zip(ek.chunks_exact(POLY_BYTES)) {
+        base_mul_acc(&mut v, &decode_reduced(t_bytes), r_hat_i);
+    }
+    ntt_inverse(&mut v);
+    add_noise(&mut v, coins, (2 * K) as u8, level.eta2());
+    add_message(&mut v, m);
+    compress(&mut v, dv);
+    let v_bytes = N * dv / 8;
+    byte_encode(&v, dv, &mut chunk[..v_bytes]);
+    emit(&chunk[..v_bytes]);
+}
+
+/// K-PKE.Decrypt of `ct` with the encoded secret vector `dk_cpa`
+fn cpa_decrypt<const K: usize>(dk_cpa: &[u8], ct: &[u8], level: SecurityLevel) -> [u8; 32] {
+    let (du, dv) = (level.du(), level.dv());
+    let u_bytes = N * du / 8;
+
+    // v - InvNTT(Σ_i s_hat[i] ∘ NTT(u[i]))
+    let mut su = [0u16; N];
+    for i in 0..K {
+        let mut u = byte_decode(&ct[i * u_bytes..(i + 1) * u_bytes], du);
+        decompress(&mut u, du);
+        ntt(&mut u);
+        base_mul_acc(&mut su, &decode_reduced(&dk_cpa[i * POLY_BYTES..(i + 1) * POLY_BYTES]), &u);
+    }
+    ntt_inverse(&mut su);
+
+    let mut v = byte_decode(&ct[K * u_bytes..], dv);
+    decompress(&mut v, dv);
+    for (x, &y) in v.iter_mut().zip(&su) {
+        *x = sub(*x, y);
+    }
+    poly_to_message(&v)
+}
+
+/// Runs `f::<K>` for the module rank of `P`
+macro_rules! with_rank {
+    ($level:expr, $f:ident($($arg:expr),*)) => {
+        match $level {
+            SecurityLevel::Kyber512 => $f::<2>($($arg),*),
+            SecurityLevel::Kyber768 => $f::<3>($($arg),*),
+            SecurityLevel::Kyber1024 => $f::<4>($($arg),*),
+        }
+    };
+}
+
+/// Generates a key pair into the caller's buffers, drawing d and z from `rng`
+pub fn keygen<P: ParameterSet>(
+    rng: &mut impl CryptoRngCore,
+    ek: &mut P::EncapsulationKeyBytes,
+    dk: &mut P::DecapsulationKeyBytes,
+) {
+    let mut d = [0u8; 32];
+    let mut z = [0u8; 32];
+    rng.fill_bytes(&mut d);
+    rng.fill_bytes(&mut z);
+
+    keygen_internal::<P>(&d, &z, ek, dk);
+}
+
+/// Deterministic key generation from the seeds d and z (ML-KEM.KeyGen_internal)
+///
+/// dk = ByteEncode_12(s_hat) ‖ ek ‖ H(ek) ‖ z.
+pub fn keygen_internal<P: ParameterSet>(
+    d: &[u8; 32],
+    z: &[u8; 32],
+    ek: &mut P::EncapsulationKeyBytes,
+    dk: &mut P::DecapsulationKeyBytes,
+) {
+    let level = P::SECURITY_LEVEL;
+    let ek = ek.as_mut();
+    let (dk_cpa, rest) = dk.as_mut().split_at_mut(level.k() * POLY_BYTES);
+    with_rank!(level, cpa_keygen(d, level, ek, dk_cpa));
+
+    let (dk_ek, rest) = rest.split_at_mut(ek.len());
+    dk_ek.copy_from_slice(ek);
+    rest[..32].copy_from_slice(&sha3_256(&[ek]));
+    rest[32..].copy_from_slice(z);
+}
+
+/// Encapsulates to `ek`, drawing the message from `rng`
+///
+/// Fails if `ek` does not pass the modulus check.
+pub fn encaps<P: ParameterSet>(
+    ek: &P::EncapsulationKeyBytes,
+    rng: &mut impl CryptoRngCore,
+    ct: &mut P::CiphertextBytes,
+    shared_secret: &mut [u8; 32],
+) -> Result<(), Error> {
+    let mut m = [0u8; 32];
+    rng.fill_bytes(&mut m);
+
+    encaps_derand::<P>(ek, &m, ct, shared_secret)
+}
+
+/// Deterministic encapsulation with the message m supplied by the caller
+pub fn encaps_derand<P: ParameterSet>(
+    ek: &P::EncapsulationKeyBytes,
+    m: &[u8; 32],
+    ct: &mut P::CiphertextBytes,
+    shared_secret: &mut [u8; 32],
+) -> Result<(), Error> {
+    let level = P::SECURITY_LEVEL;
+    let ek = ek.as_ref();
+
+    // Modulus check (FIPS 203, 7.2): every encoded t_hat coefficient must be below q
+    for chunk in ek[..level.k() * POLY_BYTES].chunks_exact(3) {
+        let first = chunk[0] as u32 | ((chunk[1] & 0x0F) as u32) << 8;
+        let second = (chunk[1] >> 4) as u32 | (chunk[2] as u32) << 4;
+        if first >= Q || second >= Q {
+            return Err(Error::InvalidEncapsulationKey);
+        }
+    }
+
+    let (k, r) = hash_g(m, &sha3_256(&[ek]));
+    let ct = ct.as_mut();
+    let mut offset = 0;
+    let emit = |chunk: &[u8]| {
+        ct[offset..offset + chunk.len()].copy_from_slice(chunk);
+        offset += chunk.len();
+    };
+    with_rank!(level, cpa_encrypt(ek, m, &r, level, emit));
+    *shared_secret = k;
+    Ok(())
+}
+
+/// Decapsulates `ct` with `dk`, including implicit rejection
+///
+/// The re-encryption is never stored: each compressed chunk is compared
+/// against `ct` as soon as it is produced, and the differences are
+/// accumulated without branching. The rejection key is always derived and
+/// the result is picked with a masked select rather than a branch. Fails if
+/// the H(ek) stored in `dk` does not match the embedded ek.
+pub fn decaps<P: ParameterSet>(
+    dk: &P::DecapsulationKeyBytes,
+    ct: &P::CiphertextBytes,
+    shared_secret: &mut [u8; 32],
+) -> Result<(), Error> {
+    let level = P::SECURITY_LEVEL;
+    let (dk_cpa, rest) = dk.as_ref().split_at(level.k() * POLY_BYTES);
+    let (ek, rest) = rest.split_at(P::EncapsulationKeyBytes::LEN);
+    let (h_ek, z) = rest.split_at(32);
+
+    // Hash check (FIPS 203, 7.3)
+    if sha3_256(&[ek]) != h_ek {
+        return Err(Error::InvalidDecapsulationKey);
+    }
+    let h_ek: &[u8; 32] = h_ek.try_into().expect("H(ek) is 32 bytes");
+
+    let m_prime = with_rank!(level, cpa_decrypt(dk_cpa, ct.as_ref(), level));
+    let (k_prime, r_prime) = hash_g(&m_prime, h_ek);
+
+    // Re-encrypt and compare in constant time, chunk by chunk
+    let ct = ct.as_ref();
+    let mut offset = 0;
+    let mut diff = 0u8;
+    let compare = |chunk: &[u8]| {
+        let expected = &ct[offset..offset + chunk.len()];
+        diff |= expected.iter().zip(chunk).fold(0u8, |acc, (a, b)| acc | (a ^ b));
+        offset += chunk.len();
+    };
+    with_rank!(level, cpa_encrypt(ek, &m_prime, &r_prime, level, compare));
+
+    // Both candidates are always computed; diff only drives a masked select
+    let k_reject = hash_j(z, ct);
+    *shared_secret = select_secret(diff, &k_prime, &k_reject);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::kem;
+    use crate::params::{Kyber1024, Kyber512, Kyber768};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    /// Checks every output against the heap-based `kem` module
+    fn matches_kem<P: ParameterSet>() {
+        let level = P::SECURITY_LEVEL;
+        let (d, z, m) = ([1u8; 32], [2u8; 32], [3u8; 32]);
+
+        let mut ek = P::EncapsulationKeyBytes::zeroed();
+        let mut dk = P::DecapsulationKeyBytes::zeroed();
+        keygen_internal::<P>(&d, &z, &mut ek, &mut dk);
+        let (pk, sk) = kem::keygen_internal(&d, &z, level);
+        assert_eq!(ek.as_ref(), kem::pk_to_bytes(&pk).as_slice());
+        assert_eq!(dk.as_ref(), kem::sk_to_bytes(&sk).as_slice());
+
+        let mut ct = P::CiphertextBytes::zeroed();
+        let mut ss = [0u8; 32];
+        encaps_derand::<P>(&ek, &m, &mut ct, &mut ss).unwrap();
+        let encaps = kem::encaps_derand(&pk, &m);
+        assert_eq!(ct.as_ref(), kem::ciphertext_to_bytes(&encaps.ciphertext).as_slice());
+        assert_eq!(ss, encaps.shared_secret);
+
+        let mut decapsulated = [0u8; 32];
+        decaps::<P>(&dk, &ct, &mut decapsulated).unwrap();
+        assert_eq!(decapsulated, ss);
+
+        // Implicit rejection agrees as well
+        ct.as_mut()[5] ^= 0x10;
+        decaps::<P>(&dk, &ct, &mut decapsulated).unwrap();
+        let tampered = kem::ciphertext_from_bytes(ct.as_ref(), level);
+        assert_eq!(decapsulated, kem::decaps(&sk, &tampered));
+        assert_ne!(decapsulated, ss);
+    }
+
+    #[test]
+    fn test_matches_heap_implementation() {
+        matches_kem::<Kyber512>();
+        matches_kem::<Kyber768>();
+        matches_kem::<Kyber1024>();
+    }
+
+    #[test]
+    fn test_rejects_tampering_in_every_chunk() {
+        let level = Kyber1024::SECURITY_LEVEL;
+        let (pk, sk) = kem::keygen_internal(&[6u8; 32], &[7u8; 32], level);
+        let mut dk = [0u8; 3168];
+        dk.copy_from_slice(&kem::sk_to_bytes(&sk));
+        let encaps = kem::encaps_derand(&pk, &[8u8; 32]);
+        let ct = kem::ciphertext_to_bytes(&encaps.ciphertext);
+
+        // First and last byte of every compressed u[i] and of v
+        let u_bytes = N * level.du() / 8;
+        let chunks = (0..level.k()).map(|i| (i * u_bytes, (i + 1) * u_bytes));
+        let positions = chunks.chain([(level.k() * u_bytes, ct.len())]).flat_map(|(start, end)| [start, end - 1]);
+
+        for position in positions {
+            let mut tampered = [0u8; 1568];
+            tampered.copy_from_slice(&ct);
+            tampered[position] ^= 0x01;
+            let mut ss = [0u8; 32];
+            decaps::<Kyber1024>(&dk, &tampered, &mut ss).unwrap();
+            assert_ne!(ss, encaps.shared_secret, "byte {} not compared", position);
+            assert_eq!(ss, kem::decaps(&sk, &kem::ciphertext_from_bytes(&tampered, level)));
+        }
+    }
+
+    #[test]
+    fn test_select_secret() {
+        let (a, b) = ([0x5Au8; 32], core::array::from_fn(|i| i as u8));
+        assert_eq!(select_secret(0, &a, &b), a);
+        for diff in [1u8, 0x80, 0xFF] {
+            assert_eq!(select_secret(diff, &a, &b), b);
+        }
+    }
+
+    #[test]
+    fn test_twiddles_match_ntt_params() {
+        let params = crate::cpa::get_ntt_params();
+        assert!(ZETAS.iter().zip(&params.roots_of_unity).all(|(&a, &b)| a as i32 == b));
+        assert!(ZETAS_INV.iter().zip(&params.inv_roots_of_unity).all(|(&a, &b)| a as i32 == b));
+        assert_eq!(SLOTS_INV as i32, params.n_inv);
+    }
+
+    #[test]
+    fn test_roundtrip_with_caller_rng() {
+        let mut rng = ChaCha20Rng::seed_from_u64(9);
+        let mut ek = [0u8; 1184];
+        let mut dk = [0u8; 2400];
+        keygen::<Kyber768>(&mut rng, &mut ek, &mut dk);
+
+        let mut ct = [0u8; 1088];
+        let (mut ss, mut ss2) = ([0u8; 32], [0u8; 32]);
+        encaps::<Kyber768>(&ek, &mut rng, &mut ct, &mut ss).unwrap();
+        decaps::<Kyber768>(&dk, &ct, &mut ss2).unwrap();
+        assert_eq!(ss, ss2);
+    }
+
+    #[test]
+    fn test_key_checks() {
+        let mut ek = [0u8; 800];
+        let mut dk = [0u8; 1632];
+        keygen_internal::<Kyber512>(&[4u8; 32], &[5u8; 32], &mut ek, &mut dk);
+        let mut ct = [0u8; 768];
+        let mut ss = [0u8; 32];
+
+        // A coefficient of 0xFFF is not reduced mod q
+        let mut bad_ek = ek;
+        bad_ek[0] = 0xFF;
+        bad_ek[1] |= 0x0F;
+        assert_eq!(encaps_derand::<Kyber512>(&bad_ek, &[0u8; 32], &mut ct, &mut ss), Err(Error::InvalidEncapsulationKey));
+
+        let mut bad_dk = dk;
+        bad_dk[1632 - 40] ^= 1;
+        assert_eq!(decaps::<Kyber512>(&bad_dk, &ct, &mut ss), Err(Error::InvalidDecapsulationKey));
+    }
+}
@@ -1,6 +1,34 @@
+//! CRYSTALS-Kyber (ML-KEM) key encapsulation
+//!
+//! With the default `std` feature everything is available. Without `std` the
+//! crate is `#![no_std]`: the `alloc` feature keeps the heap-backed API minus
+//! OS randomness, and with neither feature only the allocation-free
+//! [`fixed`] module is built.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub mod params;
+#[cfg(feature = "alloc")]
 pub mod cpa;
+#[cfg(feature = "alloc")]
 pub mod kem;
+#[cfg(feature = "alloc")]
+pub mod batch;
+#[cfg(feature = "alloc")]
+pub mod noise;
+pub mod error;
+pub mod fixed;
+#[cfg(feature = "alloc")]
+pub mod types;
+#[cfg(feature = "alloc")]
+pub mod pkcs8;
+#[cfg(feature = "alloc")]
+pub mod seed;
+#[cfg(feature = "alloc")]
+pub mod xwing;
 
 #[cfg(test)]
 mod tests {
@@ -9,4 +37,4 @@ mod tests {
         let result = 2 + 2;
         assert_eq!(result, 4);
     }
-} 
\ No newline at end of file
+}
@@ -7,7 +7,7 @@ pub const DU: usize = kyber::DU;
 pub const DV: usize = kyber::DV;
 
 /// Represents the security parameter sets for Kyber/ML-KEM
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SecurityLevel {
     /// Kyber-512 (ML-KEM 512)
     Kyber512,
@@ -45,6 +45,24 @@ impl SecurityLevel {
         }
     }
     
+    /// Returns the ciphertext compression parameter du based on security level
+    pub fn du(&self) -> usize {
+        match self {
+            SecurityLevel::Kyber512 => DU,
+            SecurityLevel::Kyber768 => DU,
+            SecurityLevel::Kyber1024 => 11,
+        }
+    }
+    
+    /// Returns the ciphertext compression parameter dv based on security level
+    pub fn dv(&self) -> usize {
+        match self {
+            SecurityLevel::Kyber512 => DV,
+            SecurityLevel::Kyber768 => DV,
+            SecurityLevel::Kyber1024 => 5,
+        }
+    }
+    
     /// Returns the internal lattice parameters
     pub fn lattice_params(&self) -> lattice_core::params::LatticeParams {
         match self {
@@ -86,19 +104,18 @@ pub mod sizes {
     
     /// Calculate public key size based on security level
     pub fn public_key_bytes(level: SecurityLevel) -> usize {
-        let _k = level.k();
-        SEED_BYTES + _k * super::N * 12 / 8  // rho + t (12 bits per coefficient)
+        let k = level.k();
+        k * super::N * 12 / 8 + SEED_BYTES  // t_hat (12 bits per coefficient) + rho
     }
     
     /// Calculate secret key size based on security level (for CPA)
     pub fn secret_key_cpa_bytes(level: SecurityLevel) -> usize {
-        let _k = level.k();
-        _k * super::N * 12 / 8  // s (12 bits per coefficient)
+        let k = level.k();
+        k * super::N * 12 / 8  // s_hat (12 bits per coefficient)
     }
     
     /// Calculate secret key size based on security level (for CCA-KEM)
     pub fn secret_key_kem_bytes(level: SecurityLevel) -> usize {
-        let _k = level.k();
         secret_key_cpa_bytes(level) + // s
         public_key_bytes(level) +     // pk
         SEED_BYTES +                  // hash of pk
@@ -107,8 +124,77 @@ pub mod sizes {
     
     /// Calculate ciphertext size based on security level
     pub fn ciphertext_bytes(level: SecurityLevel) -> usize {
-        let _k = level.k();
-        _k * super::N * super::DU / 8 + // u (du bits per coefficient)
-        super::N * super::DV / 8       // v (dv bits per coefficient)
+        let k = level.k();
+        k * super::N * level.du() / 8 + // u (du bits per coefficient)
+        super::N * level.dv() / 8       // v (dv bits per coefficient)
+    }
+} 
+/// A fixed-size byte array used for encoded keys and ciphertexts
+///
+/// Implemented for every `[u8; N]` so that each parameter set can name the exact
+/// array type of its encodings.
+pub trait ByteArray:
+    AsRef<[u8]> + AsMut<[u8]> + Clone + core::fmt::Debug + PartialEq + Eq + for<'a> TryFrom<&'a [u8]>
+{
+    /// Length of the array in bytes
+    const LEN: usize;
+    
+    /// Returns an all-zero array
+    fn zeroed() -> Self;
+}
+
+impl<const L: usize> ByteArray for [u8; L] {
+    const LEN: usize = L;
+    
+    fn zeroed() -> Self {
+        [0u8; L]
     }
-} 
\ No newline at end of file
+}
+
+/// Compile-time marker for a Kyber/ML-KEM parameter set
+///
+/// Typed keys and ciphertexts are parameterized by a marker so that mixing
+/// objects from different parameter sets is a type error.
+pub trait ParameterSet: Clone + Copy + core::fmt::Debug + PartialEq + Eq + 'static {
+    /// The runtime security level corresponding to this marker
+    const SECURITY_LEVEL: SecurityLevel;
+    /// Encoded encapsulation (public) key
+    type EncapsulationKeyBytes: ByteArray;
+    /// Encoded decapsulation (secret) key
+    type DecapsulationKeyBytes: ByteArray;
+    /// Encoded ciphertext
+    type CiphertextBytes: ByteArray;
+}
+
+/// Kyber-512 (ML-KEM-512) parameter set marker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Kyber512;
+
+/// Kyber-768 (ML-KEM-768) parameter set marker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Kyber768;
+
+/// Kyber-1024 (ML-KEM-1024) parameter set marker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Kyber1024;
+
+impl ParameterSet for Kyber512 {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Kyber512;
+    type EncapsulationKeyBytes = [u8; 800];
+    type DecapsulationKeyBytes = [u8; 1632];
+    type CiphertextBytes = [u8; 768];
+}
+
+impl ParameterSet for Kyber768 {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Kyber768;
+    type EncapsulationKeyBytes = [u8; 1184];
+    type DecapsulationKeyBytes = [u8; 2400];
+    type CiphertextBytes = [u8; 1088];
+}
+
+impl ParameterSet for Kyber1024 {
+    const SECURITY_LEVEL: SecurityLevel = SecurityLevel::Kyber1024;
+    type EncapsulationKeyBytes = [u8; 1568];
+    type DecapsulationKeyBytes = [u8; 3168];
+    type CiphertextBytes = [u8; 1568];
+}
@@ -0,0 +1,215 @@
+use ::pkcs8::spki::{self, SubjectPublicKeyInfoRef};
+use ::pkcs8::{Document, EncodePrivateKey, EncodePublicKey, ObjectIdentifier, PrivateKeyInfo, SecretDocument};
+use lattice_core::pkcs8::{decode_private_key, decode_public_key, encode_private_key, encode_public_key, SeedPrivateKey};
+
+use crate::params::{ParameterSet, SecurityLevel};
+use crate::types::{DecapsulationKey, EncapsulationKey};
+
+pub use lattice_core::pkcs8::PrivateKeyFormat;
+
+// SubjectPublicKeyInfo and PKCS#8 OneAsymmetricKey encodings following
+// draft-ietf-lamps-kyber-certificates. The CHOICE and DER plumbing live in
+// `lattice_core::pkcs8`; this module supplies the OIDs and the 64-byte seed. The
+// external crate is spelled `::pkcs8` to avoid a clash with this module.
+//
+// The keys are FIPS 203 keys, so the NIST OIDs apply: the fixtures were written
+// by OpenSSL 3.5 from the same seed and are reproduced byte for byte, and
+// `fixtures/ml-kem-kat.txt` pins key generation, encapsulation and implicit
+// rejection against it.
+
+/// OID of ML-KEM-512 (id-alg-ml-kem-512)
+pub const ML_KEM_512_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.4.1");
+/// OID of ML-KEM-768 (id-alg-ml-kem-768)
+pub const ML_KEM_768_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.4.2");
+/// OID of ML-KEM-1024 (id-alg-ml-kem-1024)
+pub const ML_KEM_1024_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.4.3");
+
+/// Returns the NIST-assigned OID of a parameter set
+pub fn oid(level: SecurityLevel) -> ObjectIdentifier {
+    match level {
+        SecurityLevel::Kyber512 => ML_KEM_512_OID,
+        SecurityLevel::Kyber768 => ML_KEM_768_OID,
+        SecurityLevel::Kyber1024 => ML_KEM_1024_OID,
+    }
+}
+
+/// Returns the parameter set identified by an OID
+pub fn security_level_from_oid(oid: ObjectIdentifier) -> Option<SecurityLevel> {
+    match oid {
+        ML_KEM_512_OID => Some(SecurityLevel::Kyber512),
+        ML_KEM_768_OID => Some(SecurityLevel::Kyber768),
+        ML_KEM_1024_OID => Some(SecurityLevel::Kyber1024),
+        _ => None,
+    }
+}
+
+impl<P: ParameterSet> SeedPrivateKey for DecapsulationKey<P> {
+    fn algorithm_oid() -> ObjectIdentifier {
+        oid(P::SECURITY_LEVEL)
+    }
+
+    fn seed_bytes(&self) -> Option<&[u8]> {
+        self.seed().map(|seed| seed.as_slice())
+    }
+
+    fn expanded_bytes(&self) -> &[u8] {
+        self.as_bytes().as_ref()
+    }
+
+    fn from_seed_bytes(seed: &[u8]) -> Option<Self> {
+        let seed: &[u8; 64] = seed.try_into().ok()?;
+        Some(Self::from_seed(seed))
+    }
+
+    fn from_expanded_bytes(bytes: &[u8]) -> Option<Self> {
+        Self::try_from(bytes).ok()
+    }
+}
+
+impl<P: ParameterSet> DecapsulationKey<P> {
+    /// Encodes the key as PKCS#8 DER using the given private key format
+    ///
+    /// The seed and both forms need the seed, so they fail for keys decoded
+    /// from an expanded encoding.
+    pub fn to_pkcs8_der_with_format(&self, format: PrivateKeyFormat) -> ::pkcs8::Result<SecretDocument> {
+        encode_private_key(self, format)
+    }
+}
+
+impl<P: ParameterSet> EncodePrivateKey for DecapsulationKey<P> {
+    /// Uses the compact seed form when the seed is known, the expanded form otherwise
+    fn to_pkcs8_der(&self) -> ::pkcs8::Result<SecretDocument> {
+        let format = if self.seed().is_some() { PrivateKeyFormat::Seed } else { PrivateKeyFormat::ExpandedKey };
+        self.to_pkcs8_der_with_format(format)
+    }
+}
+
+impl<P: ParameterSet> TryFrom<PrivateKeyInfo<'_>> for DecapsulationKey<P> {
+    type Error = ::pkcs8::Error;
+
+    fn try_from(info: PrivateKeyInfo<'_>) -> ::pkcs8::Result<Self> {
+        decode_private_key(info)
+    }
+}
+
+impl<P: ParameterSet> EncodePublicKey for EncapsulationKey<P> {
+    fn to_public_key_der(&self) -> spki::Result<Document> {
+        encode_public_key(oid(P::SECURITY_LEVEL), self.as_bytes().as_ref())
+    }
+}
+
+impl<P: ParameterSet> TryFrom<SubjectPublicKeyInfoRef<'_>> for EncapsulationKey<P> {
+    type Error = spki::Error;
+
+    fn try_from(info: SubjectPublicKeyInfoRef<'_>) -> spki::Result<Self> {
+        let bytes = decode_public_key(&info, oid(P::SECURITY_LEVEL))?;
+        Self::try_from(bytes).map_err(|_| spki::Error::KeyMalformed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::pkcs8::der::asn1::OctetStringRef;
+    use ::pkcs8::der::{AnyRef, Encode, Tag};
+    use ::pkcs8::spki::AlgorithmIdentifierRef;
+    use crate::params::{Kyber1024, Kyber512, Kyber768};
+    use ::kem::{Decapsulate, Encapsulate};
+    use ::pkcs8::{DecodePrivateKey, DecodePublicKey, LineEnding};
+    use rand::rngs::OsRng;
+
+    /// Seed 00 01 02 ... 3f used by the fixtures
+    fn fixture_seed() -> [u8; 64] {
+        core::array::from_fn(|i| i as u8)
+    }
+
+    fn check_fixtures<P: ParameterSet>(public_pem: &str, seed_pem: &str, expanded_pem: &str) {
+        let dk = DecapsulationKey::<P>::from_seed(&fixture_seed());
+        let ek = dk.encapsulation_key();
+
+        // Encoding reproduces the fixtures byte for byte
+        assert_eq!(ek.to_public_key_pem(LineEnding::LF).unwrap(), public_pem);
+        assert_eq!(dk.to_pkcs8_pem(LineEnding::LF).unwrap().as_str(), seed_pem);
+        let expanded_der = dk.to_pkcs8_der_with_format(PrivateKeyFormat::ExpandedKey).unwrap();
+        assert_eq!(expanded_der.to_pem("PRIVATE KEY", LineEnding::LF).unwrap().as_str(), expanded_pem);
+
+        // Decoding the fixtures yields the same keys
+        let ek2 = EncapsulationKey::<P>::from_public_key_pem(public_pem).unwrap();
+        let dk_seed = DecapsulationKey::<P>::from_pkcs8_pem(seed_pem).unwrap();
+        let dk_expanded = DecapsulationKey::<P>::from_pkcs8_pem(expanded_pem).unwrap();
+        assert_eq!(ek2, ek);
+        assert_eq!(dk_seed, dk);
+        assert_eq!(dk_seed.seed(), Some(&fixture_seed()));
+        assert_eq!(dk_expanded, dk);
+        assert_eq!(dk_expanded.seed(), None);
+
+        let (ct, ss) = ek2.encapsulate(&mut OsRng).unwrap();
+        assert_eq!(dk_expanded.decapsulate(&ct).unwrap(), ss);
+    }
+
+    #[test]
+    fn test_fixtures() {
+        check_fixtures::<Kyber512>(
+            include_str!("../fixtures/ml-kem-512-public.pem"),
+            include_str!("../fixtures/ml-kem-512-seed.pem"),
+            include_str!("../fixtures/ml-kem-512-expanded.pem"),
+        );
+        check_fixtures::<Kyber768>(
+            include_str!("../fixtures/ml-kem-768-public.pem"),
+            include_str!("../fixtures/ml-kem-768-seed.pem"),
+            include_str!("../fixtures/ml-kem-768-expanded.pem"),
+        );
+        check_fixtures::<Kyber1024>(
+            include_str!("../fixtures/ml-kem-1024-public.pem"),
+            include_str!("../fixtures/ml-kem-1024-seed.pem"),
+            include_str!("../fixtures/ml-kem-1024-expanded.pem"),
+        );
+    }
+
+    #[test]
+    fn test_seed_form_layout() {
+        let dk = DecapsulationKey::<Kyber768>::from_seed(&fixture_seed());
+        let der = dk.to_pkcs8_der().unwrap();
+
+        // SEQUENCE { INTEGER 0, SEQUENCE { OID }, OCTET STRING { [0] seed } }
+        let info = PrivateKeyInfo::try_from(der.as_bytes()).unwrap();
+        assert_eq!(info.algorithm.oid, ML_KEM_768_OID);
+        assert_eq!(&info.private_key[..2], &[0x80, 0x40]);
+        assert_eq!(&info.private_key[2..], &fixture_seed());
+        assert_eq!(der.as_bytes().len(), 86);
+    }
+
+    #[test]
+    fn test_both_form() {
+        let dk = DecapsulationKey::<Kyber512>::from_seed(&fixture_seed());
+        let der = dk.to_pkcs8_der_with_format(PrivateKeyFormat::Both).unwrap();
+        assert_eq!(DecapsulationKey::<Kyber512>::from_pkcs8_der(der.as_bytes()).unwrap(), dk);
+
+        // An expanded key that does not match the seed is rejected
+        let other = DecapsulationKey::<Kyber512>::generate();
+        let mut inner = OctetStringRef::new(&fixture_seed()).unwrap().to_der().unwrap();
+        inner.extend_from_slice(&OctetStringRef::new(other.as_bytes()).unwrap().to_der().unwrap());
+        let choice = AnyRef::new(Tag::Sequence, &inner).unwrap().to_der().unwrap();
+        let algorithm = AlgorithmIdentifierRef { oid: ML_KEM_512_OID, parameters: None };
+        let mismatched = SecretDocument::try_from(PrivateKeyInfo::new(algorithm, &choice)).unwrap();
+        assert!(DecapsulationKey::<Kyber512>::from_pkcs8_der(mismatched.as_bytes()).is_err());
+
+        // Keys without a seed can only be exported in expanded form
+        let expanded_only = DecapsulationKey::<Kyber512>::try_from(dk.as_bytes().as_ref()).unwrap();
+        assert!(expanded_only.to_pkcs8_der_with_format(PrivateKeyFormat::Both).is_err());
+    }
+
+    #[test]
+    fn test_wrong_oid_rejected() {
+        let dk = DecapsulationKey::<Kyber512>::generate();
+        let public_der = dk.encapsulation_key().to_public_key_der().unwrap();
+        let private_der = dk.to_pkcs8_der().unwrap();
+
+        assert_eq!(
+            EncapsulationKey::<Kyber768>::from_public_key_der(public_der.as_bytes()).unwrap_err(),
+            spki::Error::OidUnknown { oid: ML_KEM_512_OID },
+        );
+        assert!(DecapsulationKey::<Kyber1024>::from_pkcs8_der(private_der.as_bytes()).is_err());
+        assert_eq!(security_level_from_oid(ML_KEM_1024_OID), Some(SecurityLevel::Kyber1024));
+    }
+}
@@ -0,0 +1,206 @@
+use core::fmt;
+use core::marker::PhantomData;
+
+use ::pkcs8::{EncodePrivateKey, PrivateKeyInfo, SecretDocument};
+use lattice_core::pkcs8::encode_seed_private_key;
+#[cfg(feature = "std")]
+use rand::{Rng, rngs::OsRng};
+
+use crate::error::Error;
+use crate::params::ParameterSet;
+use crate::pkcs8::oid;
+use crate::types::{DecapsulationKey, EncapsulationKey};
+
+/// Size of the seed d || z in bytes
+pub const SEED_KEY_BYTES: usize = 64;
+
+/// Compact ML-KEM private key holding only the 64-byte seed d || z
+///
+/// FIPS 203 key generation is deterministic in (d, z), so the seed is all the
+/// secret material that has to be stored. Expansion is checked against the
+/// OpenSSL-generated keys in `fixtures/ml-kem-kat.txt`. The full decapsulation key (about
+/// 1.6 KB to 3.1 KB) is recomputed by [`SeedKey::expand`] when it is needed.
+pub struct SeedKey<P: ParameterSet> {
+    /// The seed d || z
+    seed: [u8; SEED_KEY_BYTES],
+    _params: PhantomData<P>,
+}
+
+impl<P: ParameterSet> SeedKey<P> {
+    /// Generates a fresh random seed
+    #[cfg(feature = "std")]
+    pub fn generate() -> Self {
+        let mut seed = [0u8; SEED_KEY_BYTES];
+        OsRng.fill(&mut seed);
+        Self::from_bytes(&seed)
+    }
+
+    /// Wraps an existing seed d || z
+    pub fn from_bytes(seed: &[u8; SEED_KEY_BYTES]) -> Self {
+        Self { seed: *seed, _params: PhantomData }
+    }
+
+    /// Returns the 64-byte encoding d || z
+    pub fn as_bytes(&self) -> &[u8; SEED_KEY_BYTES] {
+        &self.seed
+    }
+
+    /// Expands the seed into the full decapsulation key
+    pub fn expand(&self) -> DecapsulationKey<P> {
+        DecapsulationKey::from_seed(&self.seed)
+    }
+
+    /// Returns the matching encapsulation key (this expands the seed)
+    pub fn encapsulation_key(&self) -> EncapsulationKey<P> {
+        self.expand().encapsulation_key()
+    }
+}
+
+impl<P: ParameterSet> DecapsulationKey<P> {
+    /// Returns the compact seed form of the key, if the seed is known
+    pub fn to_seed_key(&self) -> Option<SeedKey<P>> {
+        self.seed().map(SeedKey::from_bytes)
+    }
+}
+
+impl<P: ParameterSet> TryFrom<&[u8]> for SeedKey<P> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let seed: &[u8; SEED_KEY_BYTES] = bytes
+            .try_into()
+            .map_err(|_| Error::InvalidLength { expected: SEED_KEY_BYTES, actual: bytes.len() })?;
+
+        Ok(Self::from_bytes(seed))
+    }
+}
+
+impl<P: ParameterSet> EncodePrivateKey for SeedKey<P> {
+    /// Always uses the seed-only `[0] IMPLICIT OCTET STRING` form
+    fn to_pkcs8_der(&self) -> ::pkcs8::Result<SecretDocument> {
+        encode_seed_private_key(oid(P::SECURITY_LEVEL), &self.seed)
+    }
+}
+
+impl<P: ParameterSet> TryFrom<PrivateKeyInfo<'_>> for SeedKey<P> {
+    type Error = ::pkcs8::Error;
+
+    /// Accepts the seed and both forms; an expanded-only key has no seed to keep
+    fn try_from(info: PrivateKeyInfo<'_>) -> ::pkcs8::Result<Self> {
+        DecapsulationKey::<P>::try_from(info)?
+            .to_seed_key()
+            .ok_or(::pkcs8::Error::KeyMalformed)
+    }
+}
+
+impl<P: ParameterSet> Clone for SeedKey<P> {
+    fn clone(&self) -> Self {
+        Self::from_bytes(&self.seed)
+    }
+}
+
+impl<P: ParameterSet> PartialEq for SeedKey<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.seed == other.seed
+    }
+}
+
+impl<P: ParameterSet> Eq for SeedKey<P> {}
+
+impl<P: ParameterSet> fmt::Debug for SeedKey<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print secret key material
+        f.debug_struct("SeedKey")
+            .field("params", &P::SECURITY_LEVEL)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::{Kyber512, Kyber768, Kyber1024};
+    use crate::pkcs8::PrivateKeyFormat;
+    use ::kem::{Decapsulate, Encapsulate};
+    use ::pkcs8::DecodePrivateKey;
+
+    fn roundtrip<P: ParameterSet>() {
+        let seed_key = SeedKey::<P>::generate();
+        let dk = seed_key.expand();
+
+        // 64 bytes on disk instead of the full decapsulation key
+        let stored = seed_key.as_bytes().to_vec();
+        assert_eq!(stored.len(), 64);
+        assert!(dk.as_bytes().as_ref().len() > 1600);
+
+        // Reloading and expanding again gives the same key
+        let reloaded = SeedKey::<P>::try_from(stored.as_slice()).unwrap();
+        assert_eq!(reloaded, seed_key);
+        assert_eq!(reloaded.expand(), dk);
+        assert_eq!(dk.to_seed_key(), Some(seed_key));
+
+        let (ct, ss) = reloaded.encapsulation_key().encapsulate(&mut OsRng).unwrap();
+        assert_eq!(dk.decapsulate(&ct).unwrap(), ss);
+    }
+
+    #[test]
+    fn test_seed_key_roundtrip() {
+        roundtrip::<Kyber512>();
+        roundtrip::<Kyber768>();
+        roundtrip::<Kyber1024>();
+    }
+
+    #[test]
+    fn test_seed_key_pkcs8() {
+        let seed_key = SeedKey::<Kyber768>::generate();
+        let dk = seed_key.expand();
+
+        // The seed-only encoding is the same whichever type produces it
+        let der = seed_key.to_pkcs8_der().unwrap();
+        assert_eq!(der.as_bytes(), dk.to_pkcs8_der().unwrap().as_bytes());
+        assert_eq!(SeedKey::<Kyber768>::from_pkcs8_der(der.as_bytes()).unwrap(), seed_key);
+
+        // Expanded-only encodings cannot be turned back into a seed
+        let expanded = dk.to_pkcs8_der_with_format(PrivateKeyFormat::ExpandedKey).unwrap();
+        assert!(SeedKey::<Kyber768>::from_pkcs8_der(expanded.as_bytes()).is_err());
+        let both = dk.to_pkcs8_der_with_format(PrivateKeyFormat::Both).unwrap();
+        assert_eq!(SeedKey::<Kyber768>::from_pkcs8_der(both.as_bytes()).unwrap(), seed_key);
+    }
+
+    /// Expands every seed of `fixtures/ml-kem-kat.txt` for parameter set `name`
+    fn check_kat<P: ParameterSet>(name: &str) {
+        let text = include_str!("../fixtures/ml-kem-kat.txt");
+        let blocks: Vec<&str> = text.split("\n\n")
+            .filter(|b| b.contains(&format!("parameter_set = {}\n", name)))
+            .collect();
+        assert_eq!(blocks.len(), 2);
+
+        for block in blocks {
+            let field = |field: &str| {
+                let line = block.lines().find(|l| l.starts_with(&format!("{} = ", field))).unwrap();
+                hex::decode(&line[field.len() + 3..]).unwrap()
+            };
+            let seed_key = SeedKey::<P>::try_from(field("seed").as_slice()).unwrap();
+            assert_eq!(seed_key.expand().as_bytes().as_ref(), field("dk").as_slice());
+            assert_eq!(seed_key.encapsulation_key().as_bytes().as_ref(), field("ek").as_slice());
+        }
+    }
+
+    #[test]
+    fn test_seed_key_kat() {
+        check_kat::<Kyber512>("ML-KEM-512");
+        check_kat::<Kyber768>("ML-KEM-768");
+        check_kat::<Kyber1024>("ML-KEM-1024");
+
+        // OpenSSL's seed-only PKCS#8 file expands to OpenSSL's expanded key
+        let seed_key = SeedKey::<Kyber768>::from_pkcs8_pem(include_str!("../fixtures/ml-kem-768-seed.pem")).unwrap();
+        let expanded = DecapsulationKey::<Kyber768>::from_pkcs8_pem(include_str!("../fixtures/ml-kem-768-expanded.pem")).unwrap();
+        assert_eq!(seed_key.expand().as_bytes(), expanded.as_bytes());
+    }
+
+    #[test]
+    fn test_wrong_length_rejected() {
+        let err = SeedKey::<Kyber512>::try_from([0u8; 32].as_slice()).unwrap_err();
+        assert_eq!(err, Error::InvalidLength { expected: 64, actual: 32 });
+    }
+}
@@ -0,0 +1,392 @@
+use core::convert::Infallible;
+use core::fmt;
+use core::marker::PhantomData;
+
+use lattice_core::hashing::sha3_256;
+#[cfg(feature = "std")]
+use rand::{Rng, rngs::OsRng};
+use rand_core::CryptoRngCore;
+
+use crate::cpa;
+use crate::error::Error;
+use crate::kem::{self, PublicKey, SecretKey};
+use crate::params::{ByteArray, ParameterSet, sizes};
+
+/// Copies a slice into a fixed-size array, checking its length
+fn to_array<A: ByteArray>(bytes: &[u8]) -> Result<A, Error> {
+    if bytes.len() != A::LEN {
+        return Err(Error::InvalidLength { expected: A::LEN, actual: bytes.len() });
+    }
+
+    let mut array = A::zeroed();
+    array.as_mut().copy_from_slice(bytes);
+    Ok(array)
+}
+
+/// ML-KEM encapsulation (public) key for the parameter set `P`
+pub struct EncapsulationKey<P: ParameterSet> {
+    /// Canonical encoding ByteEncode_12(t_hat) || rho
+    bytes: P::EncapsulationKeyBytes,
+    /// Decoded key
+    key: PublicKey,
+}
+
+/// ML-KEM decapsulation (secret) key for the parameter set `P`
+pub struct DecapsulationKey<P: ParameterSet> {
+    /// Canonical encoding dk_PKE || ek || H(ek) || z
+    bytes: P::DecapsulationKeyBytes,
+    /// Decoded key
+    key: SecretKey,
+    /// Seed d || z the key was expanded from, if known
+    seed: Option<[u8; 64]>,
+}
+
+/// ML-KEM ciphertext for the parameter set `P`
+pub struct Ciphertext<P: ParameterSet> {
+    /// Canonical encoding of the compressed (u, v)
+    bytes: P::CiphertextBytes,
+    /// Decoded ciphertext
+    ct: cpa::Ciphertext,
+    _params: PhantomData<P>,
+}
+
+impl<P: ParameterSet> EncapsulationKey<P> {
+    /// Wraps an untyped KEM public key, checking that it belongs to `P`
+    pub fn from_key(key: PublicKey) -> Result<Self, Error> {
+        if key.pk.security_level != P::SECURITY_LEVEL {
+            return Err(Error::SecurityLevelMismatch);
+        }
+
+        let bytes = to_array(&kem::pk_to_bytes(&key))?;
+        Ok(Self { bytes, key })
+    }
+
+    /// Returns the fixed-size encoding of the key
+    pub fn as_bytes(&self) -> &P::EncapsulationKeyBytes {
+        &self.bytes
+    }
+
+    /// Returns the underlying untyped KEM public key
+    pub fn as_key(&self) -> &PublicKey {
+        &self.key
+    }
+
+    /// Caches the expanded matrix A_hat so repeated encapsulations skip it
+    pub fn prepare(&mut self) {
+        self.key.prepare();
+    }
+}
+
+impl<P: ParameterSet> DecapsulationKey<P> {
+    /// Generates a fresh key pair and returns its decapsulation key
+    #[cfg(feature = "std")]
+    pub fn generate() -> Self {
+        let mut seed = [0u8; 64];
+        OsRng.fill(&mut seed);
+        Self::from_seed(&seed)
+    }
+
+    /// Deterministically expands the 64-byte seed d || z into a decapsulation key
+    ///
+    /// This is ML-KEM.KeyGen_internal(d, z) from FIPS 203, so the same seed
+    /// gives the same key as any other conforming implementation.
+    pub fn from_seed(seed: &[u8; 64]) -> Self {
+        let mut d = [0u8; 32];
+        let mut z = [0u8; 32];
+        d.copy_from_slice(&seed[..32]);
+        z.copy_from_slice(&seed[32..]);
+
+        let (_, sk) = kem::keygen_internal(&d, &z, P::SECURITY_LEVEL);
+        let mut key = Self::from_key(sk).expect("expanded key matches its parameter set");
+        key.seed = Some(*seed);
+        key
+    }
+
+    /// Wraps an untyped KEM secret key, checking that it belongs to `P`
+    pub fn from_key(key: SecretKey) -> Result<Self, Error> {
+        if key.sk.security_level != P::SECURITY_LEVEL {
+            return Err(Error::SecurityLevelMismatch);
+        }
+
+        let bytes = to_array(&kem::sk_to_bytes(&key))?;
+        Ok(Self { bytes, key, seed: None })
+    }
+
+    /// Returns the seed d || z the key was expanded from, if it is known
+    pub fn seed(&self) -> Option<&[u8; 64]> {
+        self.seed.as_ref()
+    }
+
+    /// Returns the fixed-size encoding of the key
+    pub fn as_bytes(&self) -> &P::DecapsulationKeyBytes {
+        &self.bytes
+    }
+
+    /// Returns the underlying untyped KEM secret key
+    pub fn as_key(&self) -> &SecretKey {
+        &self.key
+    }
+
+    /// Caches the expanded matrix A_hat so repeated decapsulations skip it
+    pub fn prepare(&mut self) {
+        self.key.prepare();
+    }
+
+    /// Returns the matching encapsulation key
+    pub fn encapsulation_key(&self) -> EncapsulationKey<P> {
+        let key = PublicKey { pk: self.key.pk.clone() };
+        EncapsulationKey::from_key(key).expect("embedded public key matches its parameter set")
+    }
+}
+
+impl<P: ParameterSet> Ciphertext<P> {
+    /// Wraps a ciphertext produced by the untyped API
+    pub(crate) fn from_ciphertext(ct: cpa::Ciphertext) -> Self {
+        let bytes = to_array(&cpa::ciphertext_to_bytes(&ct))
+            .expect("ciphertext size is fixed by the parameter set");
+
+        Self { bytes, ct, _params: PhantomData }
+    }
+
+    /// Returns the fixed-size encoding of the ciphertext
+    pub fn as_bytes(&self) -> &P::CiphertextBytes {
+        &self.bytes
+    }
+
+    /// Returns the underlying untyped ciphertext
+    pub fn as_ciphertext(&self) -> &cpa::Ciphertext {
+        &self.ct
+    }
+}
+
+impl<P: ParameterSet> TryFrom<&[u8]> for EncapsulationKey<P> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: P::EncapsulationKeyBytes = to_array(bytes)?;
+        let key = kem::pk_from_bytes(bytes.as_ref(), P::SECURITY_LEVEL)?;
+
+        // Modulus check (FIPS 203, 7.2): decoding must not have reduced any coefficient
+        if kem::pk_to_bytes(&key).as_slice() != bytes.as_ref() {
+            return Err(Error::InvalidEncapsulationKey);
+        }
+
+        Ok(Self { bytes, key })
+    }
+}
+
+impl<P: ParameterSet> TryFrom<&[u8]> for DecapsulationKey<P> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: P::DecapsulationKeyBytes = to_array(bytes)?;
+
+        // Hash check (FIPS 203, 7.3): H(ek) stored in dk must match the embedded ek
+        let sk_cpa_size = sizes::secret_key_cpa_bytes(P::SECURITY_LEVEL);
+        let pk_size = sizes::public_key_bytes(P::SECURITY_LEVEL);
+        let ek = &bytes.as_ref()[sk_cpa_size..sk_cpa_size + pk_size];
+        let h_pk = &bytes.as_ref()[sk_cpa_size + pk_size..sk_cpa_size + pk_size + 32];
+        if sha3_256(ek) != h_pk {
+            return Err(Error::InvalidDecapsulationKey);
+        }
+
+        let key = kem::sk_from_bytes(bytes.as_ref(), P::SECURITY_LEVEL)?;
+        Ok(Self { bytes, key, seed: None })
+    }
+}
+
+impl<P: ParameterSet> TryFrom<&[u8]> for Ciphertext<P> {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes: P::CiphertextBytes = to_array(bytes)?;
+        let ct = cpa::ciphertext_from_bytes(bytes.as_ref(), P::SECURITY_LEVEL);
+
+        Ok(Self { bytes, ct, _params: PhantomData })
+    }
+}
+
+impl<P: ParameterSet> Clone for EncapsulationKey<P> {
+    fn clone(&self) -> Self {
+        Self { bytes: self.bytes.clone(), key: self.key.clone() }
+    }
+}
+
+impl<P: ParameterSet> Clone for DecapsulationKey<P> {
+    fn clone(&self) -> Self {
+        Self { bytes: self.bytes.clone(), key: self.key.clone(), seed: self.seed }
+    }
+}
+
+impl<P: ParameterSet> Clone for Ciphertext<P> {
+    fn clone(&self) -> Self {
+        Self { bytes: self.bytes.clone(), ct: self.ct.clone(), _params: PhantomData }
+    }
+}
+
+impl<P: ParameterSet> PartialEq for EncapsulationKey<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl<P: ParameterSet> PartialEq for DecapsulationKey<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl<P: ParameterSet> PartialEq for Ciphertext<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bytes == other.bytes
+    }
+}
+
+impl<P: ParameterSet> Eq for EncapsulationKey<P> {}
+impl<P: ParameterSet> Eq for DecapsulationKey<P> {}
+impl<P: ParameterSet> Eq for Ciphertext<P> {}
+
+impl<P: ParameterSet> fmt::Debug for EncapsulationKey<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncapsulationKey")
+            .field("params", &P::SECURITY_LEVEL)
+            .field("bytes", &hex::encode(self.bytes.as_ref()))
+            .finish()
+    }
+}
+
+impl<P: ParameterSet> fmt::Debug for DecapsulationKey<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print secret key material
+        f.debug_struct("DecapsulationKey")
+            .field("params", &P::SECURITY_LEVEL)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P: ParameterSet> fmt::Debug for Ciphertext<P> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ciphertext")
+            .field("params", &P::SECURITY_LEVEL)
+            .field("bytes", &hex::encode(self.bytes.as_ref()))
+            .finish()
+    }
+}
+
+// Encapsulation and decapsulation go through the RustCrypto `kem` traits, so the
+// typed keys can replace other ML-KEM crates. The external crate is spelled
+// `::kem` to avoid a clash with `crate::kem`.
+
+impl<P: ParameterSet> ::kem::Encapsulate<Ciphertext<P>, [u8; 32]> for EncapsulationKey<P> {
+    type Error = Infallible;
+
+    fn encapsulate(&self, rng: &mut impl CryptoRngCore) -> Result<(Ciphertext<P>, [u8; 32]), Infallible> {
+        let mut m = [0u8; 32];
+        rng.fill_bytes(&mut m);
+
+        let encapsulation = kem::encaps_derand(&self.key, &m);
+        Ok((Ciphertext::from_ciphertext(encapsulation.ciphertext), encapsulation.shared_secret))
+    }
+}
+
+impl<P: ParameterSet> ::kem::Decapsulate<Ciphertext<P>, [u8; 32]> for DecapsulationKey<P> {
+    // Implicit rejection: an invalid ciphertext yields a pseudorandom secret, not an error
+    type Error = Infallible;
+
+    fn decapsulate(&self, ct: &Ciphertext<P>) -> Result<[u8; 32], Infallible> {
+        Ok(kem::decaps(&self.key, &ct.ct))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::{Kyber512, Kyber768, Kyber1024, SecurityLevel, sizes};
+    use ::kem::{Decapsulate, Encapsulate};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn roundtrip<P: ParameterSet>() {
+        let dk = DecapsulationKey::<P>::generate();
+        let ek = dk.encapsulation_key();
+
+        // Sizes are fixed by the parameter set
+        assert_eq!(ek.as_bytes().as_ref().len(), sizes::public_key_bytes(P::SECURITY_LEVEL));
+        assert_eq!(dk.as_bytes().as_ref().len(), sizes::secret_key_kem_bytes(P::SECURITY_LEVEL));
+
+        let (ct, ss) = ek.encapsulate(&mut OsRng).unwrap();
+        assert_eq!(ct.as_bytes().as_ref().len(), sizes::ciphertext_bytes(P::SECURITY_LEVEL));
+        assert_eq!(dk.decapsulate(&ct).unwrap(), ss);
+
+        // Decode everything from bytes and decapsulate again
+        let ek2 = EncapsulationKey::<P>::try_from(ek.as_bytes().as_ref()).unwrap();
+        let dk2 = DecapsulationKey::<P>::try_from(dk.as_bytes().as_ref()).unwrap();
+        let ct2 = Ciphertext::<P>::try_from(ct.as_bytes().as_ref()).unwrap();
+        assert_eq!(ek, ek2);
+        assert_eq!(dk, dk2);
+        assert_eq!(ct, ct2);
+        assert_eq!(dk2.decapsulate(&ct2).unwrap(), ss);
+    }
+
+    #[test]
+    fn test_typed_roundtrip() {
+        roundtrip::<Kyber512>();
+        roundtrip::<Kyber768>();
+        roundtrip::<Kyber1024>();
+    }
+
+    #[test]
+    fn test_wrong_length_rejected() {
+        let dk = DecapsulationKey::<Kyber512>::generate();
+        let ek = dk.encapsulation_key();
+
+        // A Kyber-512 key is not a valid Kyber-768 key
+        let err = EncapsulationKey::<Kyber768>::try_from(ek.as_bytes().as_ref()).unwrap_err();
+        assert_eq!(err, Error::InvalidLength { expected: 1184, actual: 800 });
+
+        let (ct, _) = ek.encapsulate(&mut OsRng).unwrap();
+        assert!(Ciphertext::<Kyber1024>::try_from(ct.as_bytes().as_ref()).is_err());
+        assert!(Ciphertext::<Kyber512>::try_from(&ct.as_bytes()[..767]).is_err());
+    }
+
+    #[test]
+    fn test_modulus_check() {
+        let dk = DecapsulationKey::<Kyber512>::generate();
+        let mut bytes = *dk.encapsulation_key().as_bytes();
+
+        // Set the first 12-bit coefficient to 4095 >= q
+        bytes[0] = 0xFF;
+        bytes[1] |= 0x0F;
+        let err = EncapsulationKey::<Kyber512>::try_from(bytes.as_ref()).unwrap_err();
+        assert_eq!(err, Error::InvalidEncapsulationKey);
+    }
+
+    #[test]
+    fn test_hash_check() {
+        let dk = DecapsulationKey::<Kyber512>::generate();
+        let mut bytes = *dk.as_bytes();
+
+        // Flip a bit of the embedded encapsulation key
+        bytes[sizes::secret_key_cpa_bytes(SecurityLevel::Kyber512)] ^= 1;
+        let err = DecapsulationKey::<Kyber512>::try_from(bytes.as_ref()).unwrap_err();
+        assert_eq!(err, Error::InvalidDecapsulationKey);
+    }
+
+    #[test]
+    fn test_encapsulate_uses_given_rng() {
+        let dk = DecapsulationKey::<Kyber768>::generate();
+        let ek = dk.encapsulation_key();
+
+        // The rng is the only source of randomness
+        let (ct1, ss1) = ek.encapsulate(&mut ChaCha20Rng::seed_from_u64(7)).unwrap();
+        let (ct2, ss2) = ek.encapsulate(&mut ChaCha20Rng::seed_from_u64(7)).unwrap();
+        assert_eq!((ct1, ss1), (ct2, ss2));
+    }
+
+    #[test]
+    fn test_security_level_mismatch() {
+        let (pk, _) = kem::keygen(SecurityLevel::Kyber768);
+        let err = EncapsulationKey::<Kyber512>::from_key(pk).unwrap_err();
+        assert_eq!(err, Error::SecurityLevelMismatch);
+    }
+}
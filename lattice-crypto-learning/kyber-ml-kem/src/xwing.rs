@@ -0,0 +1,374 @@
+//! X-Wing: hybrid X25519 + ML-KEM-768 key encapsulation
+//!
+//! Follows the X-Wing construction (draft-connolly-cfrg-xwing-kem): the
+//! decapsulation key is a 32-byte seed, keys and ciphertexts are the
+//! concatenation of their ML-KEM-768 and X25519 parts, and the shared secret is
+//!
+//! ```text
+//! SHA3-256(ss_M || ss_X || ct_X || pk_X || XWingLabel)
+//! ```
+//!
+//! The shared secret stays secure as long as either component is unbroken.
+//! The vectors in `fixtures/xwing-vectors.txt` were computed independently of
+//! this crate (OpenSSL for ML-KEM-768, pyca/cryptography for X25519), and the
+//! combiner and X25519 are also checked against fixed-input and RFC 7748 KATs.
+
+use core::convert::Infallible;
+use core::fmt;
+
+use lattice_core::hashing::shake256;
+#[cfg(feature = "std")]
+use rand::{Rng, rngs::OsRng};
+use rand_core::CryptoRngCore;
+use sha3::{Digest, Sha3_256};
+use x25519_dalek::{x25519, X25519_BASEPOINT_BYTES};
+
+use crate::error::Error;
+use crate::kem;
+use crate::params::Kyber768;
+use crate::types;
+
+/// Size of the seed that is the decapsulation key
+pub const DECAPSULATION_KEY_BYTES: usize = 32;
+/// Size of the encapsulation key pk_M || pk_X
+pub const ENCAPSULATION_KEY_BYTES: usize = 1184 + 32;
+/// Size of the ciphertext ct_M || ct_X
+pub const CIPHERTEXT_BYTES: usize = 1088 + 32;
+/// Size of the randomness consumed by one encapsulation: m || ek_X
+pub const ENCAPSULATION_SEED_BYTES: usize = 64;
+
+/// Domain separator `\.//^\`, appended last to the combiner input
+const XWING_LABEL: [u8; 6] = *b"\\.//^\\";
+
+/// Derives the X-Wing shared secret from both component secrets
+pub fn combiner(ss_m: &[u8; 32], ss_x: &[u8; 32], ct_x: &[u8; 32], pk_x: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha3_256::new();
+    hasher.update(ss_m);
+    hasher.update(ss_x);
+    hasher.update(ct_x);
+    hasher.update(pk_x);
+    hasher.update(XWING_LABEL);
+    hasher.finalize().into()
+}
+
+/// X-Wing encapsulation key: ML-KEM-768 encapsulation key and X25519 public key
+#[derive(Clone, PartialEq, Eq)]
+pub struct EncapsulationKey {
+    pk_m: types::EncapsulationKey<Kyber768>,
+    pk_x: [u8; 32],
+}
+
+/// X-Wing decapsulation key, stored as its seed and expanded on construction
+#[derive(Clone)]
+pub struct DecapsulationKey {
+    seed: [u8; DECAPSULATION_KEY_BYTES],
+    sk_m: types::DecapsulationKey<Kyber768>,
+    sk_x: [u8; 32],
+    ek: EncapsulationKey,
+}
+
+/// X-Wing ciphertext: ML-KEM-768 ciphertext and the ephemeral X25519 public key
+#[derive(Clone, PartialEq, Eq)]
+pub struct Ciphertext {
+    ct_m: types::Ciphertext<Kyber768>,
+    ct_x: [u8; 32],
+}
+
+impl DecapsulationKey {
+    /// Generates a fresh random decapsulation key
+    #[cfg(feature = "std")]
+    pub fn generate() -> Self {
+        let mut seed = [0u8; DECAPSULATION_KEY_BYTES];
+        OsRng.fill(&mut seed);
+        Self::from_seed(&seed)
+    }
+
+    /// Expands a 32-byte seed: SHAKE256(seed, 96) = d || z || sk_X
+    pub fn from_seed(seed: &[u8; DECAPSULATION_KEY_BYTES]) -> Self {
+        let expanded = shake256(seed, 96);
+        let mut ml_kem_seed = [0u8; 64];
+        ml_kem_seed.copy_from_slice(&expanded[..64]);
+        let mut sk_x = [0u8; 32];
+        sk_x.copy_from_slice(&expanded[64..]);
+
+        let sk_m = types::DecapsulationKey::<Kyber768>::from_seed(&ml_kem_seed);
+        let ek = EncapsulationKey {
+            pk_m: sk_m.encapsulation_key(),
+            pk_x: x25519(sk_x, X25519_BASEPOINT_BYTES),
+        };
+
+        Self { seed: *seed, sk_m, sk_x, ek }
+    }
+
+    /// Returns the seed, which is the encoding of the decapsulation key
+    pub fn as_bytes(&self) -> &[u8; DECAPSULATION_KEY_BYTES] {
+        &self.seed
+    }
+
+    /// Returns the matching encapsulation key
+    pub fn encapsulation_key(&self) -> &EncapsulationKey {
+        &self.ek
+    }
+
+    /// Recovers the shared secret from a ciphertext
+    ///
+    /// Like ML-KEM this never fails: a tampered ciphertext yields an unrelated secret.
+    pub fn decapsulate(&self, ct: &Ciphertext) -> [u8; 32] {
+        let ss_m = kem::decaps(self.sk_m.as_key(), ct.ct_m.as_ciphertext());
+        let ss_x = x25519(self.sk_x, ct.ct_x);
+        combiner(&ss_m, &ss_x, &ct.ct_x, &self.ek.pk_x)
+    }
+}
+
+impl EncapsulationKey {
+    /// Returns the encoding pk_M || pk_X
+    pub fn to_bytes(&self) -> [u8; ENCAPSULATION_KEY_BYTES] {
+        let mut bytes = [0u8; ENCAPSULATION_KEY_BYTES];
+        bytes[..1184].copy_from_slice(self.pk_m.as_bytes());
+        bytes[1184..].copy_from_slice(&self.pk_x);
+        bytes
+    }
+
+    /// Deterministic encapsulation from 64 bytes of randomness m || ek_X
+    pub fn encapsulate_derand(&self, eseed: &[u8; ENCAPSULATION_SEED_BYTES]) -> (Ciphertext, [u8; 32]) {
+        let mut m = [0u8; 32];
+        let mut ek_x = [0u8; 32];
+        m.copy_from_slice(&eseed[..32]);
+        ek_x.copy_from_slice(&eseed[32..]);
+
+        let ct_x = x25519(ek_x, X25519_BASEPOINT_BYTES);
+        let ss_x = x25519(ek_x, self.pk_x);
+        let encapsulation = kem::encaps_derand(self.pk_m.as_key(), &m);
+        let ss = combiner(&encapsulation.shared_secret, &ss_x, &ct_x, &self.pk_x);
+
+        let ct_m = types::Ciphertext::from_ciphertext(encapsulation.ciphertext);
+        (Ciphertext { ct_m, ct_x }, ss)
+    }
+}
+
+impl Ciphertext {
+    /// Returns the encoding ct_M || ct_X
+    pub fn to_bytes(&self) -> [u8; CIPHERTEXT_BYTES] {
+        let mut bytes = [0u8; CIPHERTEXT_BYTES];
+        bytes[..1088].copy_from_slice(self.ct_m.as_bytes());
+        bytes[1088..].copy_from_slice(&self.ct_x);
+        bytes
+    }
+}
+
+/// Splits off the trailing 32-byte X25519 component after checking the total length
+fn split_x25519(bytes: &[u8], expected: usize) -> Result<(&[u8], [u8; 32]), Error> {
+    if bytes.len() != expected {
+        return Err(Error::InvalidLength { expected, actual: bytes.len() });
+    }
+
+    let (ml_kem, x) = bytes.split_at(expected - 32);
+    Ok((ml_kem, x.try_into().expect("X25519 component is 32 bytes")))
+}
+
+impl TryFrom<&[u8]> for DecapsulationKey {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let seed: &[u8; DECAPSULATION_KEY_BYTES] = bytes.try_into()
+            .map_err(|_| Error::InvalidLength { expected: DECAPSULATION_KEY_BYTES, actual: bytes.len() })?;
+        Ok(Self::from_seed(seed))
+    }
+}
+
+impl TryFrom<&[u8]> for EncapsulationKey {
+    type Error = Error;
+
+    /// Decodes pk_M || pk_X; the ML-KEM part must pass the modulus check
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let (pk_m, pk_x) = split_x25519(bytes, ENCAPSULATION_KEY_BYTES)?;
+        Ok(Self { pk_m: types::EncapsulationKey::try_from(pk_m)?, pk_x })
+    }
+}
+
+impl TryFrom<&[u8]> for Ciphertext {
+    type Error = Error;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Error> {
+        let (ct_m, ct_x) = split_x25519(bytes, CIPHERTEXT_BYTES)?;
+        Ok(Self { ct_m: types::Ciphertext::try_from(ct_m)?, ct_x })
+    }
+}
+
+impl PartialEq for DecapsulationKey {
+    fn eq(&self, other: &Self) -> bool {
+        self.seed == other.seed
+    }
+}
+
+impl Eq for DecapsulationKey {}
+
+impl fmt::Debug for EncapsulationKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncapsulationKey")
+            .field("bytes", &hex::encode(self.to_bytes()))
+            .finish()
+    }
+}
+
+impl fmt::Debug for DecapsulationKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print secret key material
+        f.debug_struct("DecapsulationKey").finish_non_exhaustive()
+    }
+}
+
+impl fmt::Debug for Ciphertext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ciphertext")
+            .field("bytes", &hex::encode(self.to_bytes()))
+            .finish()
+    }
+}
+
+impl ::kem::Encapsulate<Ciphertext, [u8; 32]> for EncapsulationKey {
+    type Error = Infallible;
+
+    fn encapsulate(&self, rng: &mut impl CryptoRngCore) -> Result<(Ciphertext, [u8; 32]), Infallible> {
+        let mut eseed = [0u8; ENCAPSULATION_SEED_BYTES];
+        rng.fill_bytes(&mut eseed);
+        Ok(self.encapsulate_derand(&eseed))
+    }
+}
+
+impl ::kem::Decapsulate<Ciphertext, [u8; 32]> for DecapsulationKey {
+    type Error = Infallible;
+
+    fn decapsulate(&self, ct: &Ciphertext) -> Result<[u8; 32], Infallible> {
+        Ok(DecapsulationKey::decapsulate(self, ct))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::kem::{Decapsulate, Encapsulate};
+
+    /// One entry of `fixtures/xwing-vectors.txt`
+    struct Vector {
+        seed: [u8; 32],
+        eseed: [u8; 64],
+        ek: Vec<u8>,
+        ct: Vec<u8>,
+        ss: [u8; 32],
+    }
+
+    /// Parses the `name = hex` blocks of the vector file, one block per `count`
+    fn vectors() -> Vec<Vector> {
+        let text = include_str!("../fixtures/xwing-vectors.txt");
+        let mut vectors = Vec::new();
+        for block in text.split("\n\n").filter(|b| b.contains("count = ")) {
+            let field = |name: &str| {
+                let line = block.lines().find(|l| l.starts_with(&format!("{} = ", name))).unwrap();
+                hex::decode(&line[name.len() + 3..]).unwrap()
+            };
+            vectors.push(Vector {
+                seed: field("seed").try_into().unwrap(),
+                eseed: field("eseed").try_into().unwrap(),
+                ek: field("ek"),
+                ct: field("ct"),
+                ss: field("ss").try_into().unwrap(),
+            });
+        }
+        vectors
+    }
+
+    #[test]
+    fn test_vectors() {
+        let vectors = vectors();
+        assert_eq!(vectors.len(), 3);
+
+        for v in vectors {
+            let dk = DecapsulationKey::from_seed(&v.seed);
+            assert_eq!(dk.encapsulation_key().to_bytes().as_slice(), v.ek.as_slice());
+
+            let ek = EncapsulationKey::try_from(v.ek.as_slice()).unwrap();
+            let (ct, ss) = ek.encapsulate_derand(&v.eseed);
+            assert_eq!(ct.to_bytes().as_slice(), v.ct.as_slice());
+            assert_eq!(ss, v.ss);
+
+            let ct = Ciphertext::try_from(v.ct.as_slice()).unwrap();
+            assert_eq!(dk.decapsulate(&ct), v.ss);
+        }
+    }
+
+    #[test]
+    fn test_combiner_kat() {
+        // ss_M = 00..1f, ss_X = 20..3f, ct_X = 40..5f, pk_X = 60..7f; expected value from Python hashlib
+        let part = |start: u8| core::array::from_fn(|i| start + i as u8);
+        let ss = combiner(&part(0x00), &part(0x20), &part(0x40), &part(0x60));
+        assert_eq!(hex::encode(ss), "0acca09f2fb739bc89668dbcd01ae5aebf9b72c6fe013297e3baa96854468491");
+    }
+
+    #[test]
+    fn test_x25519_rfc7748() {
+        let bytes = |s: &str| -> [u8; 32] { hex::decode(s).unwrap().try_into().unwrap() };
+
+        // RFC 7748, 5.2: first scalar multiplication vector
+        let scalar = bytes("a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4");
+        let u = bytes("e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c");
+        assert_eq!(hex::encode(x25519(scalar, u)), "c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552");
+
+        // RFC 7748, 6.1: Diffie-Hellman between Alice and Bob
+        let alice = bytes("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+        let bob = bytes("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+        let alice_pk = x25519(alice, X25519_BASEPOINT_BYTES);
+        let bob_pk = x25519(bob, X25519_BASEPOINT_BYTES);
+        assert_eq!(hex::encode(alice_pk), "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a");
+        assert_eq!(hex::encode(bob_pk), "de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f");
+
+        let shared = "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742";
+        assert_eq!(hex::encode(x25519(alice, bob_pk)), shared);
+        assert_eq!(hex::encode(x25519(bob, alice_pk)), shared);
+    }
+
+    #[test]
+    fn test_roundtrip_and_encodings() {
+        let dk = DecapsulationKey::generate();
+        let ek = dk.encapsulation_key();
+        let (ct, ss) = ek.encapsulate(&mut OsRng).unwrap();
+        assert_eq!(Decapsulate::decapsulate(&dk, &ct).unwrap(), ss);
+
+        assert_eq!(DecapsulationKey::try_from(dk.as_bytes().as_slice()).unwrap(), dk);
+        assert_eq!(&EncapsulationKey::try_from(ek.to_bytes().as_slice()).unwrap(), ek);
+        assert_eq!(Ciphertext::try_from(ct.to_bytes().as_slice()).unwrap(), ct);
+    }
+
+    #[test]
+    fn test_combiner_binds_both_components() {
+        let dk = DecapsulationKey::from_seed(&[1u8; 32]);
+        let (ct, ss) = dk.encapsulation_key().encapsulate_derand(&[2u8; 64]);
+        let bytes = ct.to_bytes();
+
+        // Tampering with either half changes the shared secret
+        for position in [0, 1087, 1088, 1119] {
+            let mut tampered = bytes;
+            tampered[position] ^= 0x01;
+            let tampered = Ciphertext::try_from(tampered.as_slice()).unwrap();
+            assert_ne!(dk.decapsulate(&tampered), ss);
+        }
+
+        // The ML-KEM secret alone is not the X-Wing secret
+        let ss_m = kem::decaps(dk.sk_m.as_key(), ct.ct_m.as_ciphertext());
+        assert_ne!(ss_m, ss);
+    }
+
+    #[test]
+    fn test_invalid_encodings_rejected() {
+        let err = EncapsulationKey::try_from([0u8; 1184].as_slice()).unwrap_err();
+        assert_eq!(err, Error::InvalidLength { expected: 1216, actual: 1184 });
+        assert!(Ciphertext::try_from([0u8; 1088].as_slice()).is_err());
+        assert!(DecapsulationKey::try_from([0u8; 64].as_slice()).is_err());
+
+        // The ML-KEM half is subject to the modulus check
+        let mut bytes = DecapsulationKey::from_seed(&[3u8; 32]).encapsulation_key().to_bytes();
+        bytes[0] = 0xFF;
+        bytes[1] |= 0x0F;
+        assert_eq!(EncapsulationKey::try_from(bytes.as_slice()).unwrap_err(), Error::InvalidEncapsulationKey);
+    }
+}
@@ -0,0 +1,61 @@
+//! Builds lattice-core and kyber-ml-kem for a bare-metal target
+//!
+//! `thumbv7em-none-eabi` has no `std`, so these builds fail if anything in
+//! the no_std configurations pulls in the standard library. The test is
+//! skipped when the target's standard library is not installed
+//! (`rustup target add thumbv7em-none-eabi`).
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const TARGET: &str = "thumbv7em-none-eabi";
+
+fn target_installed() -> bool {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".into());
+    let Ok(output) = Command::new(rustc).args(["--print", "sysroot"]).output() else {
+        return false;
+    };
+    let sysroot = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Path::new(&sysroot).join("lib/rustlib").join(TARGET).exists()
+}
+
+/// Runs `cargo build` for `package` on the bare-metal target with `features`
+fn build(package: &str, features: &[&str]) {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let workspace = manifest_dir.parent().unwrap();
+    // A separate target directory keeps this build from contending for the lock of the outer one
+    let target_dir: PathBuf = workspace.join("target").join("no_std-check");
+
+    let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".into());
+    let mut command = Command::new(cargo);
+    command
+        .current_dir(workspace)
+        .args(["build", "--offline", "--lib", "--no-default-features", "--target", TARGET, "-p", package])
+        .env("CARGO_TARGET_DIR", &target_dir);
+    if !features.is_empty() {
+        command.args(["--features", &features.join(",")]);
+    }
+
+    let output = command.output().expect("failed to run cargo");
+    assert!(
+        output.status.success(),
+        "{package} with features {features:?} failed to build for {TARGET}:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn builds_for_thumbv7em() {
+    if !target_installed() {
+        eprintln!("skipping: the {TARGET} standard library is not installed");
+        return;
+    }
+
+    // Allocation-free: only the fixed-size API
+    build("lattice-core", &[]);
+    build("kyber-ml-kem", &[]);
+
+    // Heap-backed API on a global allocator, still without std
+    build("lattice-core", &["alloc"]);
+    build("kyber-ml-kem", &["alloc"]);
+}
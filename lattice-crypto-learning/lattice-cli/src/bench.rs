@@ -0,0 +1,81 @@
+//! `bench`: quick timing of every parameter set on this machine
+//!
+//! Reports the mean wall-clock time per operation over `--iterations` runs.
+//! For statistically sound numbers use the Criterion benches instead.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use dilithium_ml_dsa::dsa;
+use dilithium_ml_dsa::types::SigningKey;
+use kyber_ml_kem::kem;
+use kyber_ml_kem::types::DecapsulationKey;
+use signature::Keypair;
+
+use crate::kem::KemParams;
+use crate::sign::DsaParams;
+
+/// Mean time of `f` over `iterations` calls
+fn time<T>(iterations: u32, mut f: impl FnMut() -> T) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        black_box(f());
+    }
+    start.elapsed() / iterations
+}
+
+fn bench_kem<P: kyber_ml_kem::params::ParameterSet>(iterations: u32) -> [Duration; 3] {
+    let dk = DecapsulationKey::<P>::generate();
+    let ek = dk.encapsulation_key();
+    let encapsulation = kem::encaps(ek.as_key());
+    [
+        time(iterations, DecapsulationKey::<P>::generate),
+        time(iterations, || kem::encaps(ek.as_key())),
+        time(iterations, || kem::decaps(dk.as_key(), &encapsulation.ciphertext)),
+    ]
+}
+
+fn bench_dsa<P: dilithium_ml_dsa::params::ParameterSet>(iterations: u32) -> [Duration; 3] {
+    let message = [0x5a; 64];
+    let sk = SigningKey::<P>::generate();
+    let vk = sk.verifying_key();
+    let sig = dsa::sign(sk.as_key(), &message);
+    [
+        time(iterations, SigningKey::<P>::generate),
+        time(iterations, || dsa::sign(sk.as_key(), &message)),
+        time(iterations, || dsa::verify(vk.as_key(), &message, &sig)),
+    ]
+}
+
+fn micros(d: Duration) -> String {
+    format!("{:.1} us", d.as_secs_f64() * 1e6)
+}
+
+pub fn run(iterations: u32, kem_params: Option<KemParams>, dsa_params: Option<DsaParams>) {
+    use dilithium_ml_dsa::params::{Dilithium2, Dilithium3, Dilithium5};
+    use kyber_ml_kem::params::{Kyber1024, Kyber512, Kyber768};
+    let iterations = iterations.max(1);
+
+    println!("| Parameter set | keygen | encaps | decaps |");
+    println!("|---------------|--------|--------|--------|");
+    for params in kem_params.map_or(KemParams::ALL.to_vec(), |p| vec![p]) {
+        let [keygen, encaps, decaps] = match params {
+            KemParams::MlKem512 => bench_kem::<Kyber512>(iterations),
+            KemParams::MlKem768 => bench_kem::<Kyber768>(iterations),
+            KemParams::MlKem1024 => bench_kem::<Kyber1024>(iterations),
+        };
+        println!("| {} | {} | {} | {} |", params.name(), micros(keygen), micros(encaps), micros(decaps));
+    }
+
+    println!();
+    println!("| Parameter set | keygen | sign | verify |");
+    println!("|---------------|--------|------|--------|");
+    for params in dsa_params.map_or(DsaParams::ALL.to_vec(), |p| vec![p]) {
+        let [keygen, sign, verify] = match params {
+            DsaParams::MlDsa44 => bench_dsa::<Dilithium2>(iterations),
+            DsaParams::MlDsa65 => bench_dsa::<Dilithium3>(iterations),
+            DsaParams::MlDsa87 => bench_dsa::<Dilithium5>(iterations),
+        };
+        println!("| {} | {} | {} | {} |", params.name(), micros(keygen), micros(sign), micros(verify));
+    }
+}
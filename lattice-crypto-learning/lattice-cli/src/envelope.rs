@@ -0,0 +1,39 @@
+//! `encrypt` and `decrypt`: file encryption to ML-KEM recipients
+//!
+//! The format is described in `lattice_protocols::envelope`.
+
+use std::fs;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use lattice_protocols::envelope;
+use rand::rngs::OsRng;
+
+use crate::error::Error;
+use crate::kem::{self, KemParams};
+use crate::keys;
+
+pub fn encrypt(recipients: &[PathBuf], input: &Path, output: &Path, params: Option<KemParams>, force: bool) -> Result<(), Error> {
+    let recipients = recipients
+        .iter()
+        .map(|path| kem::public_key(params, &keys::read_key(path)?))
+        .collect::<Result<Vec<_>, _>>()?;
+    let reader = BufReader::new(keys::open_input(input)?);
+    let writer = BufWriter::new(keys::create_output(output, force, false)?);
+    envelope::encrypt(&recipients, reader, writer, &mut OsRng).map_err(|err| discard(output, err))
+}
+
+pub fn decrypt(secret_key: &Path, input: &Path, output: &Path, params: Option<KemParams>, force: bool) -> Result<(), Error> {
+    let identity = kem::secret_key(params, &keys::read_key(secret_key)?)?;
+    let reader = BufReader::new(keys::open_input(input)?);
+    let writer = BufWriter::new(keys::create_output(output, force, false)?);
+    envelope::decrypt(&identity, reader, writer).map_err(|err| discard(output, err))
+}
+
+/// Removes a partially written output file so no unauthenticated plaintext is left behind
+fn discard(output: &Path, err: lattice_protocols::error::Error) -> Error {
+    if output.as_os_str() != keys::STDIO {
+        let _ = fs::remove_file(output);
+    }
+    err.into()
+}
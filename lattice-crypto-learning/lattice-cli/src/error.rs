@@ -0,0 +1,107 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Errors reported by the command-line tool
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a file failed
+    Io {
+        /// The file involved
+        path: PathBuf,
+        /// The underlying error
+        source: io::Error,
+    },
+    /// An output file exists and `--force` was not given
+    FileExists(PathBuf),
+    /// A PEM or DER structure could not be decoded or encoded
+    Encoding(String),
+    /// The key's OID is not an ML-KEM or ML-DSA parameter set
+    UnknownAlgorithm(String),
+    /// The parameter set cannot be determined from the input; pass `--params`
+    UnknownParams,
+    /// The input holds a different kind of key than the command needs
+    WrongKeyType {
+        /// What the command needed
+        expected: &'static str,
+    },
+    /// The input was rejected by ML-KEM
+    Kem(kyber_ml_kem::error::Error),
+    /// The input was rejected by ML-DSA
+    Dsa(dilithium_ml_dsa::error::Error),
+    /// The signature does not verify
+    VerificationFailed,
+    /// File encryption or decryption failed
+    Envelope(lattice_protocols::error::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::FileExists(path) => write!(f, "{}: file exists (use --force to overwrite)", path.display()),
+            Error::Encoding(msg) => write!(f, "encoding error: {}", msg),
+            Error::UnknownAlgorithm(oid) => write!(f, "unsupported algorithm OID {}", oid),
+            Error::UnknownParams => write!(f, "cannot determine the parameter set; pass --params"),
+            Error::WrongKeyType { expected } => write!(f, "expected {}", expected),
+            Error::Kem(err) => write!(f, "ML-KEM: {}", err),
+            Error::Dsa(err) => write!(f, "ML-DSA: {}", err),
+            Error::VerificationFailed => write!(f, "signature verification failed"),
+            Error::Envelope(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Kem(err) => Some(err),
+            Error::Dsa(err) => Some(err),
+            Error::Envelope(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<kyber_ml_kem::error::Error> for Error {
+    fn from(err: kyber_ml_kem::error::Error) -> Self {
+        Error::Kem(err)
+    }
+}
+
+impl From<dilithium_ml_dsa::error::Error> for Error {
+    fn from(err: dilithium_ml_dsa::error::Error) -> Self {
+        Error::Dsa(err)
+    }
+}
+
+impl From<lattice_protocols::error::Error> for Error {
+    fn from(err: lattice_protocols::error::Error) -> Self {
+        Error::Envelope(err)
+    }
+}
+
+impl From<pkcs8::Error> for Error {
+    fn from(err: pkcs8::Error) -> Self {
+        Error::Encoding(err.to_string())
+    }
+}
+
+impl From<pkcs8::spki::Error> for Error {
+    fn from(err: pkcs8::spki::Error) -> Self {
+        Error::Encoding(err.to_string())
+    }
+}
+
+impl From<pkcs8::der::Error> for Error {
+    fn from(err: pkcs8::der::Error) -> Self {
+        Error::Encoding(err.to_string())
+    }
+}
+
+impl From<pkcs8::der::pem::Error> for Error {
+    fn from(err: pkcs8::der::pem::Error) -> Self {
+        Error::Encoding(err.to_string())
+    }
+}
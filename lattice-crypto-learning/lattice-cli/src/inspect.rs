@@ -0,0 +1,138 @@
+//! `inspect`: identifies a key, ciphertext or signature file
+
+use std::path::Path;
+
+use dilithium_ml_dsa::params::sizes as dsa_sizes;
+use kyber_ml_kem::params::sizes as kem_sizes;
+use pkcs8::PrivateKeyInfo;
+
+use crate::error::Error;
+use crate::kem::{self, KemParams};
+use crate::keys::{self, KeyFile};
+use crate::sign::{self, DsaParams};
+
+/// Which of the three private key CHOICE forms a PKCS#8 key uses
+fn private_key_form(der: &[u8]) -> Result<&'static str, Error> {
+    let info = PrivateKeyInfo::try_from(der)?;
+    Ok(match info.private_key.first() {
+        Some(0x80) => "seed",
+        Some(0x04) => "expanded key",
+        Some(0x30) => "seed and expanded key",
+        _ => "unknown",
+    })
+}
+
+/// Every raw encoding of the given length, as "<parameter set> <object>"
+fn raw_matches(len: usize) -> Vec<String> {
+    let mut matches = Vec::new();
+    for params in KemParams::ALL {
+        let level = params.level();
+        for (size, what) in [
+            (kem_sizes::public_key_bytes(level), "encapsulation key"),
+            (kem_sizes::secret_key_kem_bytes(level), "decapsulation key"),
+            (kem_sizes::ciphertext_bytes(level), "ciphertext"),
+        ] {
+            if size == len {
+                matches.push(format!("{} {}", params.name(), what));
+            }
+        }
+    }
+    for params in DsaParams::ALL {
+        let level = params.level();
+        for (size, what) in [
+            (dsa_sizes::public_key_bytes(level), "verifying key"),
+            (dsa_sizes::secret_key_bytes(level), "signing key"),
+            (dsa_sizes::signature_bytes(level), "signature"),
+        ] {
+            if size == len {
+                matches.push(format!("{} {}", params.name(), what));
+            }
+        }
+    }
+    match len {
+        64 => matches.push("ML-KEM seed (d || z)".into()),
+        32 => matches.push("ML-DSA seed (xi) or ML-KEM shared secret".into()),
+        _ => {}
+    }
+    matches
+}
+
+/// Prints what `path` contains; structured keys are fully decoded to check them
+pub fn run(path: &Path) -> Result<(), Error> {
+    let file = keys::read_key(path)?;
+
+    let (der, private) = match &file {
+        KeyFile::Raw(bytes) => {
+            let matches = raw_matches(bytes.len());
+            if matches.is_empty() {
+                return Err(Error::Encoding(format!("{} bytes match no known encoding", bytes.len())));
+            }
+            println!("format: raw, {} bytes", bytes.len());
+            for m in matches {
+                println!("possible: {}", m);
+            }
+            return Ok(());
+        }
+        KeyFile::Private { der, .. } => (der, true),
+        KeyFile::Public { der, .. } => (der, false),
+    };
+
+    let oid = file.oid().expect("structured keys carry an OID");
+    let structure = if private { "PKCS#8 private key" } else { "SubjectPublicKeyInfo" };
+
+    if let Some(level) = kyber_ml_kem::pkcs8::security_level_from_oid(oid) {
+        let params = KemParams::from_level(level);
+        check_kem(params, &file)?;
+        println!("format: {}, {} bytes DER", structure, der.len());
+        println!("algorithm: {} ({})", params.name(), oid);
+        if private {
+            let size = kem_sizes::secret_key_kem_bytes(level);
+            println!("key: decapsulation key ({}), {} bytes expanded", private_key_form(der)?, size);
+        } else {
+            println!("key: encapsulation key, {} bytes", kem_sizes::public_key_bytes(level));
+        }
+        return Ok(());
+    }
+
+    if let Some(level) = dilithium_ml_dsa::pkcs8::security_level_from_oid(oid) {
+        let params = DsaParams::from_level(level);
+        check_dsa(params, &file)?;
+        println!("format: {}, {} bytes DER", structure, der.len());
+        println!("algorithm: {} ({})", params.name(), oid);
+        if private {
+            let size = dsa_sizes::secret_key_bytes(level);
+            println!("key: signing key ({}), {} bytes expanded", private_key_form(der)?, size);
+        } else {
+            println!("key: verifying key, {} bytes", dsa_sizes::public_key_bytes(level));
+        }
+        return Ok(());
+    }
+
+    Err(Error::UnknownAlgorithm(oid.to_string()))
+}
+
+fn check_kem(params: KemParams, file: &KeyFile) -> Result<(), Error> {
+    use kyber_ml_kem::params::{Kyber1024, Kyber512, Kyber768};
+    let private = matches!(file, KeyFile::Private { .. });
+    match (params, private) {
+        (KemParams::MlKem512, true) => kem::decapsulation_key::<Kyber512>(file).map(drop),
+        (KemParams::MlKem768, true) => kem::decapsulation_key::<Kyber768>(file).map(drop),
+        (KemParams::MlKem1024, true) => kem::decapsulation_key::<Kyber1024>(file).map(drop),
+        (KemParams::MlKem512, false) => kem::encapsulation_key::<Kyber512>(file).map(drop),
+        (KemParams::MlKem768, false) => kem::encapsulation_key::<Kyber768>(file).map(drop),
+        (KemParams::MlKem1024, false) => kem::encapsulation_key::<Kyber1024>(file).map(drop),
+    }
+}
+
+fn check_dsa(params: DsaParams, file: &KeyFile) -> Result<(), Error> {
+    use dilithium_ml_dsa::params::{Dilithium2, Dilithium3, Dilithium5};
+    let private = matches!(file, KeyFile::Private { .. });
+    match (params, private) {
+        (DsaParams::MlDsa44, true) => sign::signing_key::<Dilithium2>(file).map(drop),
+        (DsaParams::MlDsa65, true) => sign::signing_key::<Dilithium3>(file).map(drop),
+        (DsaParams::MlDsa87, true) => sign::signing_key::<Dilithium5>(file).map(drop),
+        (DsaParams::MlDsa44, false) => sign::verifying_key::<Dilithium2>(file).map(drop),
+        (DsaParams::MlDsa65, false) => sign::verifying_key::<Dilithium3>(file).map(drop),
+        (DsaParams::MlDsa87, false) => sign::verifying_key::<Dilithium5>(file).map(drop),
+    }
+}
@@ -0,0 +1,213 @@
+//! `kem` subcommands: ML-KEM key generation, encapsulation and decapsulation
+
+use std::path::PathBuf;
+
+use clap::{Subcommand, ValueEnum};
+use kyber_ml_kem::kem;
+use kyber_ml_kem::params::{sizes, Kyber1024, Kyber512, Kyber768, ParameterSet, SecurityLevel};
+use kyber_ml_kem::pkcs8::security_level_from_oid;
+use kyber_ml_kem::types::{Ciphertext, DecapsulationKey, EncapsulationKey};
+use pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey};
+
+use crate::error::Error;
+use crate::keys::{self, KeyFile, KeyOutput};
+
+/// ML-KEM parameter sets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum KemParams {
+    #[value(name = "ml-kem-512")]
+    MlKem512,
+    #[value(name = "ml-kem-768")]
+    MlKem768,
+    #[value(name = "ml-kem-1024")]
+    MlKem1024,
+}
+
+impl KemParams {
+    pub const ALL: [KemParams; 3] = [KemParams::MlKem512, KemParams::MlKem768, KemParams::MlKem1024];
+
+    pub fn level(self) -> SecurityLevel {
+        match self {
+            KemParams::MlKem512 => SecurityLevel::Kyber512,
+            KemParams::MlKem768 => SecurityLevel::Kyber768,
+            KemParams::MlKem1024 => SecurityLevel::Kyber1024,
+        }
+    }
+
+    pub fn from_level(level: SecurityLevel) -> Self {
+        match level {
+            SecurityLevel::Kyber512 => KemParams::MlKem512,
+            SecurityLevel::Kyber768 => KemParams::MlKem768,
+            SecurityLevel::Kyber1024 => KemParams::MlKem1024,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            KemParams::MlKem512 => "ML-KEM-512",
+            KemParams::MlKem768 => "ML-KEM-768",
+            KemParams::MlKem1024 => "ML-KEM-1024",
+        }
+    }
+}
+
+/// Calls `$f::<P>($args)` with the parameter-set marker for `$params`
+macro_rules! with_params {
+    ($params:expr, $f:ident($($arg:expr),*)) => {
+        match $params {
+            KemParams::MlKem512 => $f::<Kyber512>($($arg),*),
+            KemParams::MlKem768 => $f::<Kyber768>($($arg),*),
+            KemParams::MlKem1024 => $f::<Kyber1024>($($arg),*),
+        }
+    };
+}
+
+#[derive(Debug, Subcommand)]
+pub enum KemCommand {
+    /// Generate a decapsulation (secret) and encapsulation (public) key pair
+    Keygen {
+        #[arg(long, value_enum, default_value_t = KemParams::MlKem768)]
+        params: KemParams,
+        #[command(flatten)]
+        output: KeyOutput,
+    },
+    /// Encapsulate a fresh shared secret to a public key
+    Encaps {
+        /// Encapsulation key (PEM, DER or raw)
+        #[arg(long, short = 'p')]
+        public_key: PathBuf,
+        /// Where to write the ciphertext
+        #[arg(long, short = 'c')]
+        ciphertext: PathBuf,
+        /// Where to write the 32-byte shared secret; printed as hex if omitted
+        #[arg(long)]
+        shared_secret: Option<PathBuf>,
+        /// Parameter set, if it cannot be inferred from the key
+        #[arg(long, value_enum)]
+        params: Option<KemParams>,
+        /// Overwrite existing files
+        #[arg(long)]
+        force: bool,
+    },
+    /// Recover the shared secret from a ciphertext
+    Decaps {
+        /// Decapsulation key (PEM, DER, raw expanded key or raw 64-byte seed)
+        #[arg(long, short = 's')]
+        secret_key: PathBuf,
+        /// Ciphertext from `encaps`
+        #[arg(long, short = 'c')]
+        ciphertext: PathBuf,
+        /// Where to write the 32-byte shared secret; printed as hex if omitted
+        #[arg(long)]
+        shared_secret: Option<PathBuf>,
+        /// Parameter set, if it cannot be inferred from the key
+        #[arg(long, value_enum)]
+        params: Option<KemParams>,
+        /// Overwrite existing files
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// Determines the parameter set from `--params`, the key's OID or a raw key's length
+pub fn resolve_params(params: Option<KemParams>, key: &KeyFile) -> Result<KemParams, Error> {
+    if let Some(params) = params {
+        return Ok(params);
+    }
+    match key {
+        KeyFile::Private { oid, .. } | KeyFile::Public { oid, .. } => security_level_from_oid(*oid)
+            .map(KemParams::from_level)
+            .ok_or_else(|| Error::UnknownAlgorithm(oid.to_string())),
+        KeyFile::Raw(bytes) => KemParams::ALL
+            .into_iter()
+            .find(|p| {
+                let level = p.level();
+                bytes.len() == sizes::public_key_bytes(level) || bytes.len() == sizes::secret_key_kem_bytes(level)
+            })
+            .ok_or(Error::UnknownParams),
+    }
+}
+
+pub fn decapsulation_key<P: ParameterSet>(key: &KeyFile) -> Result<DecapsulationKey<P>, Error> {
+    match key {
+        KeyFile::Private { der, .. } => Ok(DecapsulationKey::<P>::from_pkcs8_der(der)?),
+        KeyFile::Raw(bytes) => match <&[u8; 64]>::try_from(&bytes[..]) {
+            Ok(seed) => Ok(DecapsulationKey::<P>::from_seed(seed)),
+            Err(_) => Ok(DecapsulationKey::<P>::try_from(&bytes[..])?),
+        },
+        KeyFile::Public { .. } => Err(Error::WrongKeyType { expected: "an ML-KEM decapsulation key" }),
+    }
+}
+
+pub fn encapsulation_key<P: ParameterSet>(key: &KeyFile) -> Result<EncapsulationKey<P>, Error> {
+    match key {
+        KeyFile::Public { der, .. } => Ok(EncapsulationKey::<P>::from_public_key_der(der)?),
+        KeyFile::Raw(bytes) => Ok(EncapsulationKey::<P>::try_from(&bytes[..])?),
+        KeyFile::Private { .. } => Err(Error::WrongKeyType { expected: "an ML-KEM encapsulation key" }),
+    }
+}
+
+/// Reads an encapsulation key as the untyped key the protocols take
+pub fn public_key(params: Option<KemParams>, key: &KeyFile) -> Result<kem::PublicKey, Error> {
+    fn untyped<P: ParameterSet>(key: &KeyFile) -> Result<kem::PublicKey, Error> {
+        Ok(encapsulation_key::<P>(key)?.as_key().clone())
+    }
+    with_params!(resolve_params(params, key)?, untyped(key))
+}
+
+/// Reads a decapsulation key as the untyped key the protocols take
+pub fn secret_key(params: Option<KemParams>, key: &KeyFile) -> Result<kem::SecretKey, Error> {
+    fn untyped<P: ParameterSet>(key: &KeyFile) -> Result<kem::SecretKey, Error> {
+        Ok(decapsulation_key::<P>(key)?.as_key().clone())
+    }
+    with_params!(resolve_params(params, key)?, untyped(key))
+}
+
+fn keygen<P: ParameterSet>(output: &KeyOutput) -> Result<(), Error> {
+    let dk = DecapsulationKey::<P>::generate();
+    let ek = dk.encapsulation_key();
+    let secret = keys::encode(output.format, "PRIVATE KEY", dk.to_pkcs8_der()?.as_bytes(), dk.as_bytes().as_ref())?;
+    let public = keys::encode(output.format, "PUBLIC KEY", ek.to_public_key_der()?.as_bytes(), ek.as_bytes().as_ref())?;
+    output.write(&secret, &public)
+}
+
+fn encaps<P: ParameterSet>(key: &KeyFile) -> Result<(Vec<u8>, [u8; 32]), Error> {
+    let ek = encapsulation_key::<P>(key)?;
+    let encapsulation = kem::encaps(ek.as_key());
+    Ok((kem::ciphertext_to_bytes(&encapsulation.ciphertext), encapsulation.shared_secret))
+}
+
+fn decaps<P: ParameterSet>(key: &KeyFile, ciphertext: &[u8]) -> Result<[u8; 32], Error> {
+    let dk = decapsulation_key::<P>(key)?;
+    let ct = Ciphertext::<P>::try_from(ciphertext)?;
+    Ok(kem::decaps(dk.as_key(), ct.as_ciphertext()))
+}
+
+/// Writes the shared secret to a file, or prints it as hex
+fn output_shared_secret(path: Option<&PathBuf>, ss: &[u8; 32], force: bool) -> Result<(), Error> {
+    match path {
+        Some(path) => keys::write_file(path, ss, force, true),
+        None => {
+            println!("{}", hex::encode(ss));
+            Ok(())
+        }
+    }
+}
+
+pub fn run(command: KemCommand) -> Result<(), Error> {
+    match command {
+        KemCommand::Keygen { params, output } => with_params!(params, keygen(&output)),
+        KemCommand::Encaps { public_key, ciphertext, shared_secret, params, force } => {
+            let key = keys::read_key(&public_key)?;
+            let (ct, ss) = with_params!(resolve_params(params, &key)?, encaps(&key))?;
+            keys::write_file(&ciphertext, &ct, force, false)?;
+            output_shared_secret(shared_secret.as_ref(), &ss, force)
+        }
+        KemCommand::Decaps { secret_key, ciphertext, shared_secret, params, force } => {
+            let key = keys::read_key(&secret_key)?;
+            let ct = keys::read_file(&ciphertext)?;
+            let ss = with_params!(resolve_params(params, &key)?, decaps(&key, &ct))?;
+            output_shared_secret(shared_secret.as_ref(), &ss, force)
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Reading and writing key, ciphertext and signature files
+//!
+//! Keys are written as PEM or DER (SubjectPublicKeyInfo / PKCS#8) or as the
+//! raw FIPS 203/204 encodings. When reading, the format is detected from the
+//! contents: a PEM header, then a DER structure, otherwise raw bytes.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use clap::ValueEnum;
+use pkcs8::der::pem::{self, LineEnding};
+use pkcs8::spki::SubjectPublicKeyInfoRef;
+use pkcs8::{ObjectIdentifier, PrivateKeyInfo};
+
+use crate::error::Error;
+
+/// The file name that stands for standard input or output
+pub const STDIO: &str = "-";
+
+/// Output encoding of generated keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    /// PEM-armored SubjectPublicKeyInfo / PKCS#8
+    Pem,
+    /// Binary SubjectPublicKeyInfo / PKCS#8
+    Der,
+    /// Bare key encoding without algorithm identifier
+    Raw,
+}
+
+/// The contents of a key file after format detection
+pub enum KeyFile {
+    /// PKCS#8 private key, as DER
+    Private { oid: ObjectIdentifier, der: Vec<u8> },
+    /// SubjectPublicKeyInfo, as DER
+    Public { oid: ObjectIdentifier, der: Vec<u8> },
+    /// Bytes without structure; the parameter set follows from the length or `--params`
+    Raw(Vec<u8>),
+}
+
+impl KeyFile {
+    pub fn oid(&self) -> Option<ObjectIdentifier> {
+        match self {
+            KeyFile::Private { oid, .. } | KeyFile::Public { oid, .. } => Some(*oid),
+            KeyFile::Raw(_) => None,
+        }
+    }
+}
+
+fn io_error(path: &Path, source: io::Error) -> Error {
+    Error::Io { path: path.to_path_buf(), source }
+}
+
+/// Reads a file, or standard input for `-`
+pub fn read_file(path: &Path) -> Result<Vec<u8>, Error> {
+    if path.as_os_str() == STDIO {
+        let mut bytes = Vec::new();
+        io::stdin().read_to_end(&mut bytes).map_err(|e| io_error(path, e))?;
+        return Ok(bytes);
+    }
+    fs::read(path).map_err(|e| io_error(path, e))
+}
+
+/// Opens a file for streaming, or standard input for `-`
+pub fn open_input(path: &Path) -> Result<Box<dyn Read>, Error> {
+    if path.as_os_str() == STDIO {
+        return Ok(Box::new(io::stdin()));
+    }
+    Ok(Box::new(File::open(path).map_err(|e| io_error(path, e))?))
+}
+
+/// Creates a file for writing, or returns standard output for `-`
+///
+/// Existing files are only replaced with `force`. Secret files are created
+/// readable by the owner only.
+pub fn create_output(path: &Path, force: bool, secret: bool) -> Result<Box<dyn Write>, Error> {
+    if path.as_os_str() == STDIO {
+        return Ok(Box::new(io::stdout()));
+    }
+
+    let mut options = OpenOptions::new();
+    options.write(true);
+    if force {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+    #[cfg(unix)]
+    if secret {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = secret;
+
+    let file = options.open(path).map_err(|e| match e.kind() {
+        io::ErrorKind::AlreadyExists => Error::FileExists(path.to_path_buf()),
+        _ => io_error(path, e),
+    })?;
+    Ok(Box::new(file))
+}
+
+/// Writes a file, or standard output for `-`, as [`create_output`] opens it
+pub fn write_file(path: &Path, bytes: &[u8], force: bool, secret: bool) -> Result<(), Error> {
+    create_output(path, force, secret)?.write_all(bytes).map_err(|e| io_error(path, e))
+}
+
+/// Reads a key file and detects its format
+pub fn read_key(path: &Path) -> Result<KeyFile, Error> {
+    let bytes = read_file(path)?;
+
+    if bytes.starts_with(b"-----BEGIN") {
+        let (label, der) = pem::decode_vec(&bytes)?;
+        return match label {
+            "PRIVATE KEY" => Ok(KeyFile::Private { oid: PrivateKeyInfo::try_from(&der[..])?.algorithm.oid, der }),
+            "PUBLIC KEY" => Ok(KeyFile::Public { oid: SubjectPublicKeyInfoRef::try_from(&der[..])?.algorithm.oid, der }),
+            _ => Err(Error::Encoding(format!("unexpected PEM label {:?}", label))),
+        };
+    }
+
+    if let Ok(info) = PrivateKeyInfo::try_from(&bytes[..]) {
+        return Ok(KeyFile::Private { oid: info.algorithm.oid, der: bytes.clone() });
+    }
+    if let Ok(info) = SubjectPublicKeyInfoRef::try_from(&bytes[..]) {
+        return Ok(KeyFile::Public { oid: info.algorithm.oid, der: bytes.clone() });
+    }
+    Ok(KeyFile::Raw(bytes))
+}
+
+/// Encodes a DER document in the requested format; `raw` is used for [`Format::Raw`]
+pub fn encode(format: Format, label: &str, der: &[u8], raw: &[u8]) -> Result<Vec<u8>, Error> {
+    match format {
+        Format::Pem => Ok(pem::encode_string(label, LineEnding::LF, der)?.into_bytes()),
+        Format::Der => Ok(der.to_vec()),
+        Format::Raw => Ok(raw.to_vec()),
+    }
+}
+
+/// Output paths and options shared by the keygen commands
+#[derive(Debug, clap::Args)]
+pub struct KeyOutput {
+    /// Where to write the secret key
+    #[arg(long, short = 's')]
+    pub secret_key: PathBuf,
+    /// Where to write the public key
+    #[arg(long, short = 'p')]
+    pub public_key: PathBuf,
+    /// Output encoding
+    #[arg(long, value_enum, default_value_t = Format::Pem)]
+    pub format: Format,
+    /// Overwrite existing files
+    #[arg(long)]
+    pub force: bool,
+}
+
+impl KeyOutput {
+    /// Writes an encoded key pair
+    pub fn write(&self, secret: &[u8], public: &[u8]) -> Result<(), Error> {
+        write_file(&self.secret_key, secret, self.force, true)?;
+        write_file(&self.public_key, public, self.force, false)
+    }
+}
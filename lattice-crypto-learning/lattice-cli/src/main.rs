@@ -0,0 +1,129 @@
+//! `lattice-cli`: ML-KEM and ML-DSA key workflows from the shell
+//!
+//! ```text
+//! lattice-cli kem keygen --params ml-kem-768 -s dk.pem -p ek.pem
+//! lattice-cli kem encaps -p ek.pem -c ct.bin --shared-secret ss.bin
+//! lattice-cli kem decaps -s dk.pem -c ct.bin
+//! lattice-cli sign keygen --params ml-dsa-65 -s sk.pem -p vk.pem
+//! lattice-cli sign sign -s sk.pem -i message.txt -o message.sig
+//! lattice-cli sign verify -p vk.pem -i message.txt --signature message.sig
+//! lattice-cli encrypt -r ek.pem -r other-ek.pem -i report.pdf -o report.pdf.enc
+//! lattice-cli decrypt -s dk.pem -i report.pdf.enc -o report.pdf
+//! lattice-cli inspect ek.pem
+//! lattice-cli bench --iterations 20
+//! ```
+//!
+//! Exit status is 0 on success, 1 on any failure (including an invalid
+//! signature) and 2 for usage errors.
+
+mod bench;
+mod envelope;
+mod error;
+mod inspect;
+mod kem;
+mod keys;
+mod sign;
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+
+use crate::kem::{KemCommand, KemParams};
+use crate::sign::{DsaParams, SignCommand};
+
+#[derive(Debug, Parser)]
+#[command(name = "lattice-cli", version, about = "ML-KEM and ML-DSA key generation, encapsulation, signing, verification and file encryption")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// ML-KEM key encapsulation
+    #[command(subcommand)]
+    Kem(KemCommand),
+    /// ML-DSA signatures
+    #[command(subcommand)]
+    Sign(SignCommand),
+    /// Encrypt a file to one or more ML-KEM encapsulation keys
+    Encrypt {
+        /// Recipient encapsulation key (PEM, DER or raw); repeat for more recipients
+        #[arg(long = "recipient", short = 'r', required = true)]
+        recipients: Vec<PathBuf>,
+        /// File to encrypt, `-` for standard input
+        #[arg(long, short = 'i')]
+        input: PathBuf,
+        /// Where to write the encrypted file, `-` for standard output
+        #[arg(long, short = 'o')]
+        output: PathBuf,
+        /// Parameter set of raw keys, if it cannot be inferred
+        #[arg(long, value_enum)]
+        params: Option<KemParams>,
+        /// Overwrite existing files
+        #[arg(long)]
+        force: bool,
+    },
+    /// Decrypt a file from `encrypt`; the output is removed if it fails to authenticate
+    Decrypt {
+        /// Decapsulation key (PEM, DER, raw expanded key or raw 64-byte seed)
+        #[arg(long, short = 's')]
+        secret_key: PathBuf,
+        /// Encrypted file, `-` for standard input
+        #[arg(long, short = 'i')]
+        input: PathBuf,
+        /// Where to write the plaintext, `-` for standard output
+        #[arg(long, short = 'o')]
+        output: PathBuf,
+        /// Parameter set, if it cannot be inferred from the key
+        #[arg(long, value_enum)]
+        params: Option<KemParams>,
+        /// Overwrite existing files
+        #[arg(long)]
+        force: bool,
+    },
+    /// Identify and validate a key, ciphertext or signature file
+    Inspect {
+        file: PathBuf,
+    },
+    /// Time every operation on this machine
+    Bench {
+        /// Runs per operation
+        #[arg(long, short = 'n', default_value_t = 100)]
+        iterations: u32,
+        /// Only this ML-KEM parameter set
+        #[arg(long, value_enum)]
+        kem: Option<KemParams>,
+        /// Only this ML-DSA parameter set
+        #[arg(long, value_enum)]
+        dsa: Option<DsaParams>,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Kem(command) => kem::run(command),
+        Command::Sign(command) => sign::run(command),
+        Command::Encrypt { recipients, input, output, params, force } => {
+            envelope::encrypt(&recipients, &input, &output, params, force)
+        }
+        Command::Decrypt { secret_key, input, output, params, force } => {
+            envelope::decrypt(&secret_key, &input, &output, params, force)
+        }
+        Command::Inspect { file } => inspect::run(&file),
+        Command::Bench { iterations, kem, dsa } => {
+            bench::run(iterations, kem, dsa);
+            Ok(())
+        }
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! `sign` subcommands: ML-DSA key generation, signing and verification
+
+use std::path::PathBuf;
+
+use clap::{Subcommand, ValueEnum};
+use dilithium_ml_dsa::dsa;
+use dilithium_ml_dsa::params::{sizes, Dilithium2, Dilithium3, Dilithium5, ParameterSet, SecurityLevel};
+use dilithium_ml_dsa::pkcs8::security_level_from_oid;
+use dilithium_ml_dsa::types::{Signature, SigningKey, VerifyingKey};
+use pkcs8::{DecodePrivateKey, DecodePublicKey, EncodePrivateKey, EncodePublicKey};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use signature::Keypair;
+
+use crate::error::Error;
+use crate::keys::{self, KeyFile, KeyOutput};
+
+/// ML-DSA parameter sets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum DsaParams {
+    #[value(name = "ml-dsa-44")]
+    MlDsa44,
+    #[value(name = "ml-dsa-65")]
+    MlDsa65,
+    #[value(name = "ml-dsa-87")]
+    MlDsa87,
+}
+
+impl DsaParams {
+    pub const ALL: [DsaParams; 3] = [DsaParams::MlDsa44, DsaParams::MlDsa65, DsaParams::MlDsa87];
+
+    pub fn level(self) -> SecurityLevel {
+        match self {
+            DsaParams::MlDsa44 => SecurityLevel::Dilithium2,
+            DsaParams::MlDsa65 => SecurityLevel::Dilithium3,
+            DsaParams::MlDsa87 => SecurityLevel::Dilithium5,
+        }
+    }
+
+    pub fn from_level(level: SecurityLevel) -> Self {
+        match level {
+            SecurityLevel::Dilithium2 => DsaParams::MlDsa44,
+            SecurityLevel::Dilithium3 => DsaParams::MlDsa65,
+            SecurityLevel::Dilithium5 => DsaParams::MlDsa87,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DsaParams::MlDsa44 => "ML-DSA-44",
+            DsaParams::MlDsa65 => "ML-DSA-65",
+            DsaParams::MlDsa87 => "ML-DSA-87",
+        }
+    }
+}
+
+/// Calls `$f::<P>($args)` with the parameter-set marker for `$params`
+macro_rules! with_params {
+    ($params:expr, $f:ident($($arg:expr),*)) => {
+        match $params {
+            DsaParams::MlDsa44 => $f::<Dilithium2>($($arg),*),
+            DsaParams::MlDsa65 => $f::<Dilithium3>($($arg),*),
+            DsaParams::MlDsa87 => $f::<Dilithium5>($($arg),*),
+        }
+    };
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SignCommand {
+    /// Generate a signing (secret) and verifying (public) key pair
+    Keygen {
+        #[arg(long, value_enum, default_value_t = DsaParams::MlDsa65)]
+        params: DsaParams,
+        #[command(flatten)]
+        output: KeyOutput,
+    },
+    /// Sign a file
+    Sign {
+        /// Signing key (PEM, DER, raw expanded key or raw 32-byte seed)
+        #[arg(long, short = 's')]
+        secret_key: PathBuf,
+        /// File to sign, `-` for standard input
+        #[arg(long, short = 'i')]
+        input: PathBuf,
+        /// Where to write the signature
+        #[arg(long, short = 'o')]
+        signature: PathBuf,
+        /// Context string (at most 255 bytes)
+        #[arg(long, default_value = "")]
+        context: String,
+        /// Use the deterministic variant instead of hedged randomness
+        #[arg(long)]
+        deterministic: bool,
+        /// Parameter set, if it cannot be inferred from the key
+        #[arg(long, value_enum)]
+        params: Option<DsaParams>,
+        /// Overwrite existing files
+        #[arg(long)]
+        force: bool,
+    },
+    /// Verify a signature; exits with status 1 if it is invalid
+    Verify {
+        /// Verifying key (PEM, DER or raw)
+        #[arg(long, short = 'p')]
+        public_key: PathBuf,
+        /// Signed file, `-` for standard input
+        #[arg(long, short = 'i')]
+        input: PathBuf,
+        /// Signature from `sign`
+        #[arg(long)]
+        signature: PathBuf,
+        /// Context string used when signing
+        #[arg(long, default_value = "")]
+        context: String,
+        /// Parameter set, if it cannot be inferred from the key
+        #[arg(long, value_enum)]
+        params: Option<DsaParams>,
+    },
+}
+
+/// Determines the parameter set from `--params`, the key's OID or a raw key's length
+pub fn resolve_params(params: Option<DsaParams>, key: &KeyFile) -> Result<DsaParams, Error> {
+    if let Some(params) = params {
+        return Ok(params);
+    }
+    match key {
+        KeyFile::Private { oid, .. } | KeyFile::Public { oid, .. } => security_level_from_oid(*oid)
+            .map(DsaParams::from_level)
+            .ok_or_else(|| Error::UnknownAlgorithm(oid.to_string())),
+        KeyFile::Raw(bytes) => DsaParams::ALL
+            .into_iter()
+            .find(|p| {
+                let level = p.level();
+                bytes.len() == sizes::public_key_bytes(level) || bytes.len() == sizes::secret_key_bytes(level)
+            })
+            .ok_or(Error::UnknownParams),
+    }
+}
+
+pub fn signing_key<P: ParameterSet>(key: &KeyFile) -> Result<SigningKey<P>, Error> {
+    match key {
+        KeyFile::Private { der, .. } => Ok(SigningKey::<P>::from_pkcs8_der(der)?),
+        KeyFile::Raw(bytes) => match <&[u8; 32]>::try_from(&bytes[..]) {
+            Ok(seed) => Ok(SigningKey::<P>::from_seed(seed)),
+            Err(_) => Ok(SigningKey::<P>::try_from(&bytes[..])?),
+        },
+        KeyFile::Public { .. } => Err(Error::WrongKeyType { expected: "an ML-DSA signing key" }),
+    }
+}
+
+pub fn verifying_key<P: ParameterSet>(key: &KeyFile) -> Result<VerifyingKey<P>, Error> {
+    match key {
+        KeyFile::Public { der, .. } => Ok(VerifyingKey::<P>::from_public_key_der(der)?),
+        KeyFile::Raw(bytes) => Ok(VerifyingKey::<P>::try_from(&bytes[..])?),
+        KeyFile::Private { .. } => Err(Error::WrongKeyType { expected: "an ML-DSA verifying key" }),
+    }
+}
+
+fn keygen<P: ParameterSet>(output: &KeyOutput) -> Result<(), Error> {
+    let sk = SigningKey::<P>::generate();
+    let vk = sk.verifying_key();
+    let secret = keys::encode(output.format, "PRIVATE KEY", sk.to_pkcs8_der()?.as_bytes(), sk.as_bytes().as_ref())?;
+    let public = keys::encode(output.format, "PUBLIC KEY", vk.to_public_key_der()?.as_bytes(), vk.as_bytes().as_ref())?;
+    output.write(&secret, &public)
+}
+
+fn sign<P: ParameterSet>(key: &KeyFile, message: &[u8], context: &[u8], deterministic: bool) -> Result<Vec<u8>, Error> {
+    let sk = signing_key::<P>(key)?;
+    let mut rnd = [0u8; 32];
+    if !deterministic {
+        OsRng.fill_bytes(&mut rnd);
+    }
+    let sig = dsa::sign_with_context(sk.as_key(), message, context, &rnd)?;
+    Ok(dsa::signature_to_bytes(&sig))
+}
+
+fn verify<P: ParameterSet>(key: &KeyFile, message: &[u8], context: &[u8], signature: &[u8]) -> Result<(), Error> {
+    let vk = verifying_key::<P>(key)?;
+    let sig = Signature::<P>::try_from(signature).map_err(|_| Error::VerificationFailed)?;
+    if dsa::verify_with_context(vk.as_key(), message, context, sig.as_signature()) {
+        Ok(())
+    } else {
+        Err(Error::VerificationFailed)
+    }
+}
+
+pub fn run(command: SignCommand) -> Result<(), Error> {
+    match command {
+        SignCommand::Keygen { params, output } => with_params!(params, keygen(&output)),
+        SignCommand::Sign { secret_key, input, signature, context, deterministic, params, force } => {
+            let key = keys::read_key(&secret_key)?;
+            let message = keys::read_file(&input)?;
+            let sig = with_params!(resolve_params(params, &key)?, sign(&key, &message, context.as_bytes(), deterministic))?;
+            keys::write_file(&signature, &sig, force, false)
+        }
+        SignCommand::Verify { public_key, input, signature, context, params } => {
+            let key = keys::read_key(&public_key)?;
+            let message = keys::read_file(&input)?;
+            let sig = keys::read_file(&signature)?;
+            with_params!(resolve_params(params, &key)?, verify(&key, &message, context.as_bytes(), &sig))?;
+            println!("signature OK");
+            Ok(())
+        }
+    }
+}
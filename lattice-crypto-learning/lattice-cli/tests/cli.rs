@@ -0,0 +1,219 @@
+//! Runs the `lattice-cli` binary end to end in scratch directories
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// A fresh directory under the system temp dir, removed on drop
+struct Scratch(PathBuf);
+
+impl Scratch {
+    fn new(name: &str) -> Self {
+        let dir = std::env::temp_dir().join(format!("lattice-cli-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        Scratch(dir)
+    }
+
+    fn path(&self, name: &str) -> String {
+        self.0.join(name).to_str().unwrap().to_string()
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_lattice-cli")).args(args).output().expect("failed to run lattice-cli")
+}
+
+/// Runs the tool and asserts success, returning standard output
+fn ok(args: &[&str]) -> String {
+    let output = run(args);
+    assert!(output.status.success(), "{:?} failed: {}", args, String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+/// Runs the tool and asserts it exits with `code`, returning standard error
+fn fails(args: &[&str], code: i32) -> String {
+    let output = run(args);
+    assert_eq!(output.status.code(), Some(code), "{:?}", args);
+    String::from_utf8(output.stderr).unwrap()
+}
+
+#[test]
+fn kem_round_trip_every_params_and_format() {
+    let dir = Scratch::new("kem");
+    for params in ["ml-kem-512", "ml-kem-768", "ml-kem-1024"] {
+        for format in ["pem", "der", "raw"] {
+            let (dk, ek, ct, ss) = (dir.path("dk"), dir.path("ek"), dir.path("ct"), dir.path("ss"));
+            ok(&["kem", "keygen", "--params", params, "--format", format, "-s", &dk, "-p", &ek, "--force"]);
+            ok(&["kem", "encaps", "-p", &ek, "-c", &ct, "--shared-secret", &ss, "--force"]);
+            let decapsulated = ok(&["kem", "decaps", "-s", &dk, "-c", &ct]);
+            assert_eq!(decapsulated.trim(), hex::encode(fs::read(&ss).unwrap()), "{} {}", params, format);
+        }
+    }
+}
+
+#[test]
+fn sign_round_trip_and_rejections() {
+    let dir = Scratch::new("sign");
+    let (message, sig) = (dir.path("message.txt"), dir.path("message.sig"));
+    fs::write(&message, b"attack at dawn").unwrap();
+
+    for params in ["ml-dsa-44", "ml-dsa-65", "ml-dsa-87"] {
+        for format in ["pem", "der", "raw"] {
+            let (sk, vk) = (dir.path("sk"), dir.path("vk"));
+            ok(&["sign", "keygen", "--params", params, "--format", format, "-s", &sk, "-p", &vk, "--force"]);
+            ok(&["sign", "sign", "-s", &sk, "-i", &message, "-o", &sig, "--context", "ctx", "--force"]);
+            assert!(ok(&["sign", "verify", "-p", &vk, "-i", &message, "--signature", &sig, "--context", "ctx"]).contains("OK"));
+
+            // Wrong context, wrong key type
+            fails(&["sign", "verify", "-p", &vk, "-i", &message, "--signature", &sig], 1);
+            fails(&["sign", "verify", "-p", &sk, "-i", &message, "--signature", &sig, "--context", "ctx"], 1);
+        }
+    }
+
+    // Modified message and truncated signature
+    let sk = dir.path("sk.pem");
+    let vk = dir.path("vk.pem");
+    ok(&["sign", "keygen", "-s", &sk, "-p", &vk]);
+    ok(&["sign", "sign", "-s", &sk, "-i", &message, "-o", &sig, "--deterministic", "--force"]);
+    let tampered = dir.path("tampered.txt");
+    fs::write(&tampered, b"attack at dusk").unwrap();
+    let stderr = fails(&["sign", "verify", "-p", &vk, "-i", &tampered, "--signature", &sig], 1);
+    assert!(stderr.contains("verification failed"));
+
+    let signature = fs::read(&sig).unwrap();
+    fs::write(&sig, &signature[..100]).unwrap();
+    fails(&["sign", "verify", "-p", &vk, "-i", &message, "--signature", &sig], 1);
+}
+
+#[test]
+fn inspect_reports_algorithm_and_form() {
+    let dir = Scratch::new("inspect");
+    let (dk, ek, ct) = (dir.path("dk.pem"), dir.path("ek.pem"), dir.path("ct.bin"));
+    ok(&["kem", "keygen", "--params", "ml-kem-1024", "-s", &dk, "-p", &ek]);
+    ok(&["kem", "encaps", "-p", &ek, "-c", &ct]);
+
+    let out = ok(&["inspect", &dk]);
+    assert!(out.contains("ML-KEM-1024") && out.contains("decapsulation key (seed)"), "{}", out);
+    let out = ok(&["inspect", &ek]);
+    assert!(out.contains("2.16.840.1.101.3.4.4.3") && out.contains("encapsulation key, 1568 bytes"), "{}", out);
+    // 1568 bytes is both an ML-KEM-1024 ciphertext and encapsulation key
+    let out = ok(&["inspect", &ct]);
+    assert!(out.contains("ML-KEM-1024 ciphertext") && out.contains("ML-KEM-1024 encapsulation key"), "{}", out);
+
+    let (sk, vk) = (dir.path("sk.der"), dir.path("vk.der"));
+    ok(&["sign", "keygen", "--params", "ml-dsa-87", "--format", "der", "-s", &sk, "-p", &vk]);
+    assert!(ok(&["inspect", &sk]).contains("ML-DSA-87"));
+
+    let junk = dir.path("junk");
+    fs::write(&junk, [0u8; 7]).unwrap();
+    fails(&["inspect", &junk], 1);
+}
+
+#[test]
+fn errors_exit_nonzero() {
+    let dir = Scratch::new("errors");
+    let (dk, ek) = (dir.path("dk.pem"), dir.path("ek.pem"));
+    ok(&["kem", "keygen", "-s", &dk, "-p", &ek]);
+
+    // Existing files are not overwritten without --force
+    assert!(fails(&["kem", "keygen", "-s", &dk, "-p", &ek], 1).contains("file exists"));
+    // Missing input file
+    assert!(fails(&["kem", "encaps", "-p", &dir.path("missing"), "-c", &dir.path("ct")], 1).contains("missing"));
+    // Secret key where a public key is expected
+    fails(&["kem", "encaps", "-p", &dk, "-c", &dir.path("ct")], 1);
+    // Ciphertext of the wrong length
+    let ct = dir.path("short.ct");
+    fs::write(&ct, [0u8; 10]).unwrap();
+    fails(&["kem", "decaps", "-s", &dk, "-c", &ct], 1);
+    // Raw key of unknown size without --params
+    let raw = dir.path("raw");
+    fs::write(&raw, [0u8; 10]).unwrap();
+    assert!(fails(&["kem", "encaps", "-p", &raw, "-c", &dir.path("ct")], 1).contains("--params"));
+    // Usage errors come from the argument parser
+    fails(&["kem", "keygen", "--params", "ml-kem-2048", "-s", &dk, "-p", &ek], 2);
+    fails(&["frobnicate"], 2);
+}
+
+#[test]
+fn raw_seed_keys_accepted() {
+    let dir = Scratch::new("seed");
+    let (seed, sig, message) = (dir.path("seed"), dir.path("sig"), dir.path("message"));
+    fs::write(&seed, [7u8; 32]).unwrap();
+    fs::write(&message, b"hello").unwrap();
+
+    // A 32-byte seed needs --params; the deterministic signature is reproducible
+    fails(&["sign", "sign", "-s", &seed, "-i", &message, "-o", &sig], 1);
+    ok(&["sign", "sign", "--params", "ml-dsa-65", "-s", &seed, "-i", &message, "-o", &sig, "--deterministic"]);
+    let first = fs::read(&sig).unwrap();
+    ok(&["sign", "sign", "--params", "ml-dsa-65", "-s", &seed, "-i", &message, "-o", &sig, "--deterministic", "--force"]);
+    assert_eq!(fs::read(&sig).unwrap(), first);
+}
+
+#[test]
+fn bench_runs() {
+    let out = ok(&["bench", "-n", "1", "--kem", "ml-kem-512", "--dsa", "ml-dsa-44"]);
+    assert!(out.contains("ML-KEM-512") && out.contains("ML-DSA-44"));
+    assert!(!out.contains("ML-KEM-768"));
+}
+
+#[test]
+fn encrypt_to_several_recipients() {
+    let dir = Scratch::new("encrypt");
+    let (plain, enc, dec) = (dir.path("plain"), dir.path("plain.enc"), dir.path("plain.dec"));
+    let message: Vec<u8> = (0..200_000u32).map(|i| (i * 7) as u8).collect();
+    fs::write(&plain, &message).unwrap();
+
+    let (dk_a, ek_a) = (dir.path("a.dk"), dir.path("a.ek"));
+    let (dk_b, ek_b) = (dir.path("b.dk"), dir.path("b.ek"));
+    let (dk_c, ek_c) = (dir.path("c.dk"), dir.path("c.ek"));
+    ok(&["kem", "keygen", "-s", &dk_a, "-p", &ek_a]);
+    ok(&["kem", "keygen", "--params", "ml-kem-1024", "--format", "der", "-s", &dk_b, "-p", &ek_b]);
+    ok(&["kem", "keygen", "-s", &dk_c, "-p", &ek_c]);
+
+    ok(&["encrypt", "-r", &ek_a, "-r", &ek_b, "-i", &plain, "-o", &enc]);
+    for dk in [&dk_a, &dk_b] {
+        ok(&["decrypt", "-s", dk, "-i", &enc, "-o", &dec, "--force"]);
+        assert_eq!(fs::read(&dec).unwrap(), message);
+    }
+
+    // Not a recipient; nothing is left behind
+    fs::remove_file(&dec).unwrap();
+    assert!(fails(&["decrypt", "-s", &dk_c, "-i", &enc, "-o", &dec], 1).contains("no recipient"));
+    assert!(!std::path::Path::new(&dec).exists());
+    // A secret key is not a recipient
+    fails(&["encrypt", "-r", &dk_a, "-i", &plain, "-o", &dir.path("other.enc")], 1);
+    fails(&["encrypt", "-i", &plain, "-o", &dir.path("other.enc")], 2);
+}
+
+#[test]
+fn decrypt_rejects_truncated_and_modified_files() {
+    let dir = Scratch::new("decrypt");
+    let (dk, ek, plain, enc, dec) = (dir.path("dk"), dir.path("ek"), dir.path("plain"), dir.path("enc"), dir.path("dec"));
+    ok(&["kem", "keygen", "-s", &dk, "-p", &ek]);
+    fs::write(&plain, vec![42u8; 150_000]).unwrap();
+    ok(&["encrypt", "-r", &ek, "-i", &plain, "-o", &enc]);
+    let ciphertext = fs::read(&enc).unwrap();
+
+    // The last chunk holds 150000 - 2 * 65536 bytes of plaintext plus a 16-byte tag
+    let last_chunk = 150_000 - 2 * 65_536 + 16;
+    fs::write(&enc, &ciphertext[..ciphertext.len() - last_chunk]).unwrap();
+    assert!(fails(&["decrypt", "-s", &dk, "-i", &enc, "-o", &dec], 1).contains("truncated"));
+    assert!(!std::path::Path::new(&dec).exists());
+
+    let mut modified = ciphertext.clone();
+    *modified.last_mut().unwrap() ^= 1;
+    fs::write(&enc, &modified).unwrap();
+    fails(&["decrypt", "-s", &dk, "-i", &enc, "-o", &dec], 1);
+    assert!(!std::path::Path::new(&dec).exists());
+
+    fs::write(&enc, &ciphertext).unwrap();
+    ok(&["decrypt", "-s", &dk, "-i", &enc, "-o", &dec]);
+    assert_eq!(fs::read(&dec).unwrap(), fs::read(&plain).unwrap());
+}
@@ -0,0 +1,154 @@
+//! Benchmarks for the polynomial, NTT and sampling primitives
+//!
+//! Run with `cargo bench -p lattice-core`; see `benches/README.md` at the
+//! workspace root for recording and comparing JSON baselines.
+
+use std::time::Duration;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use lattice_core::keccak::StateX4;
+use lattice_core::ntt::{ntt_forward, ntt_inverse, ntt_pointwise_mul, NTTParams};
+use lattice_core::params::PolyModulusInfo;
+use lattice_core::simd::{self, Backend};
+use lattice_core::sampling::{expand_matrix, expand_poly, expand_poly_x4, sample_noise_polys, sample_uniform_poly_zq};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+const N: usize = 256;
+
+/// The two rings used in the workspace: Kyber (q = 3329) and Dilithium (q = 8380417)
+fn rings() -> [(&'static str, PolyModulusInfo, NTTParams); 2] {
+    let modulus = |q| PolyModulusInfo { degree: N, q, is_ntt_form: false };
+    [
+        ("kyber", modulus(3329), NTTParams::new(3329, N, 17)),
+        ("dilithium", modulus(8380417), NTTParams::new(8380417, N, 1753)),
+    ]
+}
+
+fn configure() -> Criterion {
+    Criterion::default()
+        .sample_size(50)
+        .warm_up_time(Duration::from_millis(500))
+        .measurement_time(Duration::from_secs(2))
+}
+
+fn bench_ntt(c: &mut Criterion) {
+    let mut rng = ChaCha20Rng::seed_from_u64(1);
+
+    let mut forward = c.benchmark_group("ntt_forward");
+    for (name, modulus, params) in rings() {
+        let poly = sample_uniform_poly_zq(modulus, &mut rng);
+        forward.bench_with_input(BenchmarkId::from_parameter(name), &poly, |b, poly| {
+            b.iter(|| ntt_forward(black_box(poly), &params))
+        });
+    }
+    forward.finish();
+
+    let mut inverse = c.benchmark_group("ntt_inverse");
+    for (name, modulus, params) in rings() {
+        let poly_hat = ntt_forward(&sample_uniform_poly_zq(modulus, &mut rng), &params);
+        inverse.bench_with_input(BenchmarkId::from_parameter(name), &poly_hat, |b, poly_hat| {
+            b.iter(|| ntt_inverse(black_box(poly_hat), &params))
+        });
+    }
+    inverse.finish();
+
+    let mut pointwise = c.benchmark_group("ntt_pointwise_mul");
+    for (name, modulus, params) in rings() {
+        let a = ntt_forward(&sample_uniform_poly_zq(modulus, &mut rng), &params);
+        let b_hat = ntt_forward(&sample_uniform_poly_zq(modulus, &mut rng), &params);
+        pointwise.bench_function(name, |b| {
+            b.iter(|| ntt_pointwise_mul(black_box(&a), black_box(&b_hat), &params))
+        });
+    }
+    pointwise.finish();
+}
+
+fn bench_schoolbook(c: &mut Criterion) {
+    let mut rng = ChaCha20Rng::seed_from_u64(2);
+
+    let mut group = c.benchmark_group("schoolbook_mul");
+    for (name, modulus, _) in rings() {
+        let a = sample_uniform_poly_zq(modulus, &mut rng);
+        let b_poly = sample_uniform_poly_zq(modulus, &mut rng);
+        group.bench_function(name, |b| b.iter(|| black_box(&a).schoolbook_mul(black_box(&b_poly))));
+    }
+    group.finish();
+}
+
+fn bench_sampling(c: &mut Criterion) {
+    let (_, kyber, _) = rings()[0];
+    let rho = [0x5Au8; 32];
+
+    let mut expand = c.benchmark_group("expand_matrix");
+    for k in [2, 3, 4] {
+        expand.bench_with_input(BenchmarkId::new("kyber", format!("{}x{}", k, k)), &k, |b, &k| {
+            b.iter(|| expand_matrix(black_box(&rho), k, k, kyber))
+        });
+    }
+    expand.finish();
+
+    let mut cbd = c.benchmark_group("sample_noise_polys");
+    let seed = [0xA5u8; 32];
+    for eta in [2, 3] {
+        cbd.bench_with_input(BenchmarkId::new("kyber", format!("eta{}", eta)), &eta, |b, &eta| {
+            b.iter(|| sample_noise_polys(black_box(&seed), 0, &[eta], kyber))
+        });
+    }
+    cbd.finish();
+}
+
+/// Compares every SIMD backend the CPU supports on the raw kernels
+fn bench_simd_backends(c: &mut Criterion) {
+    let mut rng = ChaCha20Rng::seed_from_u64(3);
+
+    for (name, modulus, params) in rings() {
+        let values: Vec<i32> = sample_uniform_poly_zq(modulus, &mut rng).coeffs.iter().map(|c| c.value()).collect();
+        let mut group = c.benchmark_group(format!("simd_{}", name));
+        for backend in Backend::available() {
+            let label = format!("{:?}", backend).to_lowercase();
+            group.bench_function(BenchmarkId::new("ntt_forward", &label), |b| {
+                b.iter(|| {
+                    let mut coeffs = values.clone();
+                    simd::ntt_forward(backend, black_box(&mut coeffs), &params);
+                    coeffs
+                })
+            });
+            group.bench_function(BenchmarkId::new("pointwise_mul", &label), |b| {
+                let mut out = vec![0; N];
+                b.iter(|| simd::pointwise_mul(backend, black_box(&values), black_box(&values), &mut out, &params))
+            });
+        }
+        group.finish();
+    }
+}
+
+/// The 4-way Keccak permutation per backend, and four matrix entries expanded one by one vs together
+fn bench_keccak(c: &mut Criterion) {
+    let (_, kyber, _) = rings()[0];
+    let rho = [0x5Au8; 32];
+    let indices = [(0, 0), (0, 1), (1, 0), (1, 1)];
+
+    let mut group = c.benchmark_group("keccak");
+    for backend in Backend::available() {
+        let label = format!("{:?}", backend).to_lowercase();
+        group.bench_function(BenchmarkId::new("f1600_x4", label), |b| {
+            let mut state: StateX4 = [[0x0123_4567_89AB_CDEF; 4]; 25];
+            b.iter(|| simd::keccak_f1600_x4(backend, black_box(&mut state)))
+        });
+    }
+    group.bench_function("expand_poly/4_sequential", |b| {
+        b.iter(|| indices.map(|(i, j)| expand_poly(black_box(&rho), i, j, kyber)))
+    });
+    group.bench_function("expand_poly/x4", |b| {
+        b.iter(|| expand_poly_x4(black_box(&rho), indices, kyber))
+    });
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = configure();
+    targets = bench_ntt, bench_schoolbook, bench_sampling, bench_simd_backends, bench_keccak
+}
+criterion_main!(benches);
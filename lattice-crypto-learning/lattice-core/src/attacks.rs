@@ -0,0 +1,362 @@
+//! Secret recovery on deliberately weakened LWE instances
+//!
+//! A [`WeakInstance`] describes a plain-LWE instance derived from a
+//! [`LatticeParams`]; shrinking n, the secret distribution or the error
+//! distribution (with struct update syntax) makes one of three attacks
+//! feasible:
+//!
+//! - [`brute_force`]: tries every secret with coefficients in [-η_s, η_s].
+//!   Costs (2η_s + 1)^n, so it needs a tiny dimension or secret.
+//! - [`arora_ge`]: every error lies in [-η_e, η_e], so each sample gives a
+//!   degree 2η_e + 1 polynomial equation Π (⟨a, s⟩ - b - e) = 0 in s.
+//!   Treating each monomial as an unknown makes the system linear; with
+//!   enough samples Gaussian elimination solves it. Needs a tiny error
+//!   support and about C(n + 2η_e + 1, 2η_e + 1) samples.
+//! - [`lattice`]: BKZ on the embedding lattice (see [`crate::lwe`]). Needs
+//!   the error to be small relative to q in a dimension BKZ can handle.
+//!
+//! [`run`] generates an instance, runs one attack and reports the time
+//! taken and whether the secret was recovered. Because everything is driven
+//! from the same parameters, the toy instances can be scaled up until each
+//! attack stops working.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::lwe::{self, LweSamples};
+use crate::params::LatticeParams;
+use crate::reduction::IntMatrix;
+
+/// Shape of a plain-LWE instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WeakInstance {
+    /// Secret dimension n
+    pub n: usize,
+    /// Number of samples m
+    pub samples: usize,
+    /// Modulus q (prime, for Arora–Ge)
+    pub q: i64,
+    /// Secret coefficients follow B_(secret_eta)
+    pub secret_eta: usize,
+    /// Error coefficients follow B_(error_eta)
+    pub error_eta: usize,
+}
+
+impl WeakInstance {
+    /// The plain-LWE form of `params`: n·k secret coefficients, as many samples, and B_η secret and error
+    pub fn from_params(params: &LatticeParams) -> Self {
+        let n = params.n * params.k;
+        Self { n, samples: n, q: i64::from(params.q), secret_eta: params.eta, error_eta: params.eta }
+    }
+
+    /// Samples an instance of this shape
+    pub fn generate(&self, rng: &mut impl Rng) -> LweSamples {
+        LweSamples::generate_with(self.n, self.samples, self.q, self.secret_eta, self.error_eta, rng)
+    }
+
+    /// log₂ of the number of candidate secrets
+    pub fn secret_space_bits(&self) -> f64 {
+        self.n as f64 * ((2 * self.secret_eta + 1) as f64).log2()
+    }
+
+    /// Number of unknowns in the linearized Arora–Ge system
+    pub fn arora_ge_unknowns(&self) -> usize {
+        arora_ge_unknowns(self.n, self.error_eta)
+    }
+
+    /// Error standard deviation divided by q
+    pub fn noise_rate(&self) -> f64 {
+        (self.error_eta as f64 / 2.0).sqrt() / self.q as f64
+    }
+}
+
+/// An attack and its limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Exhaustive search, refusing to try more than `max_candidates` secrets
+    BruteForce {
+        /// Largest search space attempted
+        max_candidates: u64,
+    },
+    /// Arora–Ge linearization, refusing systems with more than `max_unknowns` unknowns
+    AroraGe {
+        /// Largest linear system attempted
+        max_unknowns: usize,
+    },
+    /// BKZ-β on the embedding lattice (LLL for β < 2)
+    Lattice {
+        /// Block size
+        beta: usize,
+    },
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Method::BruteForce { .. } => write!(f, "brute force"),
+            Method::AroraGe { .. } => write!(f, "Arora-Ge"),
+            Method::Lattice { beta } if *beta < 2 => write!(f, "LLL"),
+            Method::Lattice { beta } => write!(f, "BKZ-{}", beta),
+        }
+    }
+}
+
+/// What an attack returned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// A secret consistent with the samples
+    Recovered(Vec<i64>),
+    /// The attack ran and found nothing
+    NotFound,
+    /// The instance exceeds the attack's limit
+    TooExpensive,
+    /// Arora–Ge needs at least `needed` samples
+    TooFewSamples {
+        /// Number of unknowns in the linear system
+        needed: usize,
+    },
+}
+
+/// Result of [`run`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttackReport {
+    /// The attacked instance's shape
+    pub instance: WeakInstance,
+    /// The attack
+    pub method: Method,
+    /// What it returned
+    pub outcome: Outcome,
+    /// Whether the returned secret is the one the instance was generated with
+    pub success: bool,
+    /// Time spent in the attack, excluding instance generation
+    pub elapsed: Duration,
+}
+
+impl fmt::Display for AttackReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let WeakInstance { n, samples, q, secret_eta, error_eta } = self.instance;
+        let outcome = match &self.outcome {
+            Outcome::Recovered(_) if self.success => "recovered".to_string(),
+            Outcome::Recovered(_) => "wrong secret".to_string(),
+            Outcome::NotFound => "not found".to_string(),
+            Outcome::TooExpensive => "too expensive".to_string(),
+            Outcome::TooFewSamples { needed } => format!("needs {} samples", needed),
+        };
+        write!(
+            f,
+            "n={} m={} q={} η_s={} η_e={}: {} {} in {:.2?}",
+            n, samples, q, secret_eta, error_eta, self.method, outcome, self.elapsed
+        )
+    }
+}
+
+/// Generates an instance of shape `instance` and attacks it with `method`
+pub fn run(instance: &WeakInstance, method: Method, rng: &mut impl Rng) -> AttackReport {
+    let lwe = instance.generate(rng);
+    let start = Instant::now();
+    let outcome = match method {
+        Method::BruteForce { max_candidates } => brute_force(&lwe, instance.secret_eta, instance.error_eta as i64, max_candidates),
+        Method::AroraGe { max_unknowns } => arora_ge(&lwe, instance.error_eta, max_unknowns),
+        Method::Lattice { beta } => lattice(&lwe, beta, instance.error_eta as i64),
+    };
+    let elapsed = start.elapsed();
+    let success = outcome == Outcome::Recovered(lwe.secret.clone());
+    AttackReport { instance: *instance, method, outcome, success, elapsed }
+}
+
+/// Tries every secret in [-secret_eta, secret_eta]^n, returning the first whose residual is within `error_bound`
+pub fn brute_force(lwe: &LweSamples, secret_eta: usize, error_bound: i64, max_candidates: u64) -> Outcome {
+    let (n, q) = (lwe.n(), lwe.q);
+    let width = 2 * secret_eta as u64 + 1;
+    if width.checked_pow(n as u32).map_or(true, |count| count > max_candidates) {
+        return Outcome::TooExpensive;
+    }
+
+    let eta = secret_eta as i64;
+    let mut candidate = vec![-eta; n];
+    loop {
+        // Most candidates already fail on the first sample
+        let consistent = (0..lwe.m()).all(|i| lwe::center(lwe.b[i] - lwe::dot_mod(lwe.a.row(i), &candidate, q), q).abs() <= error_bound);
+        if consistent {
+            return Outcome::Recovered(candidate);
+        }
+        // Next candidate, odometer style
+        let Some(pos) = candidate.iter().position(|&c| c < eta) else { return Outcome::NotFound };
+        candidate[pos] += 1;
+        for c in &mut candidate[..pos] {
+            *c = -eta;
+        }
+    }
+}
+
+/// Number of monomials of degree 1 to 2η + 1 in n variables
+pub fn arora_ge_unknowns(n: usize, error_eta: usize) -> usize {
+    let degree = 2 * error_eta + 1;
+    // C(n + D, D) - 1, or usize::MAX if that overflows
+    let mut count: u128 = 1;
+    for i in 1..=degree as u128 {
+        let Some(product) = count.checked_mul(n as u128 + i) else { return usize::MAX };
+        count = product / i;
+    }
+    usize::try_from(count - 1).unwrap_or(usize::MAX)
+}
+
+/// A polynomial in the secret coordinates: sorted variable indices (with repetition) to coefficient mod q
+type Multivariate = BTreeMap<Vec<usize>, i64>;
+
+/// Arora–Ge: linearizes Π_(|e| ≤ η) (⟨a, s⟩ - b - e) = 0 over all samples and solves mod q
+pub fn arora_ge(lwe: &LweSamples, error_eta: usize, max_unknowns: usize) -> Outcome {
+    let (n, q) = (lwe.n(), lwe.q);
+    let unknowns = arora_ge_unknowns(n, error_eta);
+    if unknowns > max_unknowns {
+        return Outcome::TooExpensive;
+    }
+    if lwe.m() < unknowns {
+        return Outcome::TooFewSamples { needed: unknowns };
+    }
+
+    // P(x) = Π (x - e) as coefficients c_0..c_D
+    let eta = error_eta as i64;
+    let mut p = vec![1i64];
+    for e in -eta..=eta {
+        let mut next = vec![0; p.len() + 1];
+        for (k, &c) in p.iter().enumerate() {
+            next[k + 1] = (next[k + 1] + c).rem_euclid(q);
+            next[k] = (next[k] - e * c).rem_euclid(q);
+        }
+        p = next;
+    }
+
+    let mut columns = BTreeMap::new();
+    monomials(n, p.len() - 1, &mut columns);
+    let mut system = IntMatrix::zero(lwe.m(), unknowns + 1);
+    for i in 0..lwe.m() {
+        // Horner: P(L) with L = ⟨a, s⟩ - b
+        let mut poly: Multivariate = BTreeMap::from([(Vec::new(), p[p.len() - 1])]);
+        for &c in p.iter().rev().skip(1) {
+            poly = mul_linear(&poly, lwe.a.row(i), lwe.b[i], q);
+            *poly.entry(Vec::new()).or_insert(0) += c;
+        }
+        for (mono, c) in poly {
+            let col = if mono.is_empty() { unknowns } else { columns[&mono] };
+            // The constant moves to the right-hand side
+            system[(i, col)] = if mono.is_empty() { -c } else { c };
+        }
+    }
+
+    let pivots = lwe::row_reduce(&mut system, q);
+    if pivots.len() < unknowns || pivots.contains(&unknowns) {
+        return Outcome::NotFound;
+    }
+    // The unknowns for s_0..s_(n-1) come first and each has its own pivot row
+    let candidate: Vec<i64> = (0..n).map(|j| lwe::center(system[(columns[&vec![j]], unknowns)], q)).collect();
+    if lwe.is_solution(&candidate, eta) { Outcome::Recovered(candidate) } else { Outcome::NotFound }
+}
+
+/// Assigns a column to every monomial of degree 1..=`degree`, in graded lexicographic order
+fn monomials(n: usize, degree: usize, columns: &mut BTreeMap<Vec<usize>, usize>) {
+    for d in 1..=degree {
+        let mut stack = vec![Vec::new()];
+        while let Some(mono) = stack.pop() {
+            if mono.len() == d {
+                let next = columns.len();
+                columns.entry(mono).or_insert(next);
+                continue;
+            }
+            let from = mono.last().copied().unwrap_or(0);
+            for v in (from..n).rev() {
+                let mut longer = mono.clone();
+                longer.push(v);
+                stack.push(longer);
+            }
+        }
+    }
+}
+
+/// poly · (⟨a, s⟩ - b) mod q
+fn mul_linear(poly: &Multivariate, a: &[i64], b: i64, q: i64) -> Multivariate {
+    let mut product = Multivariate::new();
+    for (mono, &c) in poly {
+        for (v, &av) in a.iter().enumerate() {
+            if av != 0 {
+                let mut longer = mono.clone();
+                let at = longer.partition_point(|&x| x <= v);
+                longer.insert(at, v);
+                let entry = product.entry(longer).or_insert(0);
+                *entry = (*entry + c * av).rem_euclid(q);
+            }
+        }
+        let entry = product.entry(mono.clone()).or_insert(0);
+        *entry = (*entry - c * b).rem_euclid(q);
+    }
+    product
+}
+
+/// BKZ-β on the embedding lattice, reading off a secret whose error is within `error_bound`
+pub fn lattice(lwe: &LweSamples, beta: usize, error_bound: i64) -> Outcome {
+    lwe.recover_secret(beta, error_bound).map_or(Outcome::NotFound, Outcome::Recovered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::kyber;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_instance_from_params() {
+        let weak = WeakInstance::from_params(&kyber::kyber_768());
+        assert_eq!(weak, WeakInstance { n: 768, samples: 768, q: 3329, secret_eta: 2, error_eta: 2 });
+        assert!((weak.secret_space_bits() - 768.0 * 5f64.log2()).abs() < 1e-9);
+        assert_eq!(arora_ge_unknowns(3, 1), 19);
+        assert_eq!(WeakInstance { n: 10, error_eta: 1, ..weak }.arora_ge_unknowns(), 285);
+        assert!(weak.arora_ge_unknowns() > 1 << 40);
+    }
+
+    #[test]
+    fn test_brute_force() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        // Kyber-512 shrunk to a 6-coefficient secret
+        let toy = LatticeParams { n: 6, k: 1, ..kyber::kyber_512() };
+        let instance = WeakInstance { samples: 12, ..WeakInstance::from_params(&toy) };
+        let report = run(&instance, Method::BruteForce { max_candidates: 1 << 20 }, &mut rng);
+        assert!(report.success, "{}", report);
+        assert!(report.to_string().contains("brute force recovered"));
+
+        // Full Kyber-512 is refused
+        let full = WeakInstance::from_params(&kyber::kyber_512());
+        let lwe = WeakInstance { samples: 1, ..full }.generate(&mut rng);
+        assert_eq!(brute_force(&lwe, full.secret_eta, 3, 1 << 40), Outcome::TooExpensive);
+    }
+
+    #[test]
+    fn test_arora_ge() {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        // Ternary errors: cubic equations, 83 unknowns for n = 6
+        let instance = WeakInstance { n: 6, samples: 120, q: 3329, secret_eta: 2, error_eta: 1 };
+        let report = run(&instance, Method::AroraGe { max_unknowns: 1000 }, &mut rng);
+        assert!(report.success, "{}", report);
+
+        let few = WeakInstance { samples: 40, ..instance };
+        assert_eq!(run(&few, Method::AroraGe { max_unknowns: 1000 }, &mut rng).outcome, Outcome::TooFewSamples { needed: 83 });
+        let wide = WeakInstance { error_eta: 3, ..instance };
+        assert_eq!(run(&wide, Method::AroraGe { max_unknowns: 1000 }, &mut rng).outcome, Outcome::TooExpensive);
+    }
+
+    #[test]
+    fn test_lattice_attack() {
+        let mut rng = ChaCha20Rng::seed_from_u64(3);
+        let instance = WeakInstance { n: 20, samples: 25, q: 3329, secret_eta: 1, error_eta: 1 };
+        let report = run(&instance, Method::Lattice { beta: 0 }, &mut rng);
+        assert!(report.success, "{}", report);
+        assert!(report.to_string().contains("LLL recovered"));
+        // A noise rate near 1/4 leaves nothing to find
+        let noisy = WeakInstance { n: 8, samples: 8, q: 17, secret_eta: 1, error_eta: 8 };
+        assert!(noisy.noise_rate() > instance.noise_rate());
+        assert!(!run(&noisy, Method::Lattice { beta: 0 }, &mut rng).success);
+    }
+}
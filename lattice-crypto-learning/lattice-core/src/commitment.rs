@@ -0,0 +1,229 @@
+//! Commitments from Module-SIS and Module-LWE (BDLOP-style)
+//!
+//! The key is a pair of matrices with identity blocks,
+//!
+//! ```text
+//! A₁ = [ I_h  A₁' ]          ∈ R_q^(h × k)
+//! A₂ = [ 0  I_ℓ  A₂' ]       ∈ R_q^(ℓ × k)
+//! ```
+//!
+//! and a message m ∈ R_q^ℓ is committed with randomness r ∈ R^k, ‖r‖∞ ≤ η, as
+//! t₁ = A₁·r, t₂ = A₂·r + m.
+//!
+//! - Binding: two valid openings of the same t₁ with r ≠ r' give
+//!   A₁·(r - r') = 0 with ‖r - r'‖∞ ≤ 2η, a Module-SIS solution; equal
+//!   randomness forces equal messages.
+//! - Hiding: (t₁, t₂) is a Module-LWE sample with secret the last k - h - ℓ
+//!   entries of r, so it looks uniform and reveals nothing about m.
+//! - Homomorphic: commitments and openings add. The sum of N commitments
+//!   opens to the sum of the messages with randomness bounded by N·η, so
+//!   verify it with [`CommitmentKey::verify_with_bound`].
+//!
+//! These are building blocks for zero-knowledge proofs, where a prover
+//! shows relations between committed values without opening them.
+
+use alloc::vec::Vec;
+use core::ops::Add;
+
+use rand::Rng;
+
+use crate::ntt::NTTParams;
+use crate::params::{dilithium, PolyModulusInfo};
+use crate::polynomial::Polynomial;
+use crate::sampling::{expand_matrix, sample_uniform_poly};
+use crate::vector_matrix::{PolyMatrix, PolyVector};
+
+/// Dimensions of a commitment key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitmentParams {
+    /// Ring R_q
+    pub modulus_info: PolyModulusInfo,
+    /// Rows h of A₁, the Module-SIS height behind binding
+    pub height: usize,
+    /// Number ℓ of ring elements in a message
+    pub message_len: usize,
+    /// Length k of the randomness; k - h - ℓ is the Module-LWE rank behind hiding
+    pub randomness_len: usize,
+    /// Randomness coefficients lie in [-η, η]
+    pub eta: i32,
+}
+
+impl CommitmentParams {
+    /// Over Dilithium's ring (q = 8380417, n = 256): height 4, Module-LWE rank 4, ternary randomness
+    pub fn dilithium_ring(message_len: usize) -> Self {
+        Self {
+            modulus_info: PolyModulusInfo { degree: dilithium::N, q: dilithium::Q, is_ntt_form: false },
+            height: 4,
+            message_len,
+            randomness_len: 8 + message_len,
+            eta: 1,
+        }
+    }
+}
+
+/// The public commitment key (A₁, A₂)
+#[derive(Debug, Clone)]
+pub struct CommitmentKey {
+    /// Dimensions
+    pub params: CommitmentParams,
+    /// A₁, including its identity block
+    pub a1: PolyMatrix,
+    /// A₂, including its zero and identity blocks
+    pub a2: PolyMatrix,
+    /// Multiplies through the NTT when set, schoolbook otherwise
+    pub ntt_params: Option<NTTParams>,
+}
+
+/// A commitment (t₁, t₂)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commitment {
+    /// t₁ = A₁·r, binds the randomness
+    pub t1: PolyVector,
+    /// t₂ = A₂·r + m, carries the message
+    pub t2: PolyVector,
+}
+
+/// What the committer reveals to open a commitment
+#[derive(Debug, Clone, PartialEq)]
+pub struct Opening {
+    /// The committed message m
+    pub message: PolyVector,
+    /// The randomness r
+    pub randomness: PolyVector,
+}
+
+impl CommitmentKey {
+    /// Expands A₁' and A₂' from the seed `rho`
+    pub fn from_seed(params: CommitmentParams, rho: &[u8], ntt_params: Option<NTTParams>) -> Self {
+        let CommitmentParams { modulus_info: info, height: h, message_len: l, randomness_len: k, .. } = params;
+        assert!(k > h + l, "Randomness must be longer than height + message length");
+        // One expansion, so A₁' and A₂' use different (i, j) indices
+        let mut expanded = expand_matrix(rho, h + l, k - h, info).into_iter();
+        let mut block_rows = |rows: usize, identity_at: usize, skip: usize| -> PolyMatrix {
+            let rows_vec = (0..rows)
+                .map(|i| {
+                    let random = expanded.next().unwrap();
+                    let mut entries: Vec<Polynomial> = (0..k).map(|_| Polynomial::zero(info)).collect();
+                    entries[identity_at + i] = Polynomial::constant(1, info);
+                    for (j, poly) in random.into_iter().enumerate().skip(skip) {
+                        entries[h + j] = poly;
+                    }
+                    PolyVector::new(entries, info)
+                })
+                .collect();
+            PolyMatrix::new(rows_vec, rows, k, info)
+        };
+        // A₁ = [I_h | A₁'] and A₂ = [0 | I_ℓ | A₂']
+        let a1 = block_rows(h, 0, 0);
+        let a2 = block_rows(l, h, l);
+        Self { params, a1, a2, ntt_params }
+    }
+
+    /// Commits to `message` with fresh randomness, returning the commitment and its opening
+    pub fn commit(&self, message: &PolyVector, rng: &mut impl Rng) -> (Commitment, Opening) {
+        let info = self.params.modulus_info;
+        let entries = (0..self.params.randomness_len).map(|_| sample_uniform_poly(self.params.eta, info, rng)).collect();
+        let randomness = PolyVector::new(entries, info);
+        (self.commit_with(message, &randomness), Opening { message: message.clone(), randomness })
+    }
+
+    /// Commits to `message` with the given randomness
+    pub fn commit_with(&self, message: &PolyVector, randomness: &PolyVector) -> Commitment {
+        assert_eq!(message.len(), self.params.message_len, "Message has the wrong length");
+        assert_eq!(randomness.len(), self.params.randomness_len, "Randomness has the wrong length");
+        let ntt = self.ntt_params.as_ref();
+        Commitment { t1: self.a1.mul_vec(randomness, ntt), t2: self.a2.mul_vec(randomness, ntt) + message.clone() }
+    }
+
+    /// Checks an opening of a fresh commitment: ‖r‖∞ ≤ η
+    pub fn verify(&self, commitment: &Commitment, opening: &Opening) -> bool {
+        self.verify_with_bound(commitment, opening, self.params.eta)
+    }
+
+    /// Checks an opening whose randomness may be as large as `bound`, e.g. of a sum of commitments
+    pub fn verify_with_bound(&self, commitment: &Commitment, opening: &Opening, bound: i32) -> bool {
+        opening.message.len() == self.params.message_len
+            && opening.randomness.len() == self.params.randomness_len
+            && opening.randomness.infinity_norm() <= bound
+            && self.commit_with(&opening.message, &opening.randomness) == *commitment
+    }
+}
+
+impl Add for Commitment {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self { t1: self.t1 + other.t1, t2: self.t2 + other.t2 }
+    }
+}
+
+impl Add for Opening {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self { message: self.message + other.message, randomness: self.randomness + other.randomness }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sampling::sample_uniform_poly_zq;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn key(message_len: usize) -> CommitmentKey {
+        let ntt = NTTParams::new(dilithium::Q, dilithium::N, 1753);
+        CommitmentKey::from_seed(CommitmentParams::dilithium_ring(message_len), &[3u8; 32], Some(ntt))
+    }
+
+    fn random_message(key: &CommitmentKey, rng: &mut ChaCha20Rng) -> PolyVector {
+        let info = key.params.modulus_info;
+        PolyVector::new((0..key.params.message_len).map(|_| sample_uniform_poly_zq(info, rng)).collect(), info)
+    }
+
+    #[test]
+    fn test_commit_open_verify() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        let key = key(2);
+        assert_eq!((key.a1.n_rows, key.a1.n_cols, key.a2.n_rows), (4, 10, 2));
+        // Identity blocks
+        assert_eq!(key.a1.get(1, 1), Some(&Polynomial::constant(1, key.params.modulus_info)));
+        assert_eq!(key.a2.get(1, 5), Some(&Polynomial::constant(1, key.params.modulus_info)));
+        assert_eq!(key.a2.get(0, 2), Some(&Polynomial::zero(key.params.modulus_info)));
+
+        let message = random_message(&key, &mut rng);
+        let (commitment, opening) = key.commit(&message, &mut rng);
+        assert!(key.verify(&commitment, &opening));
+        // Committing twice hides the message behind fresh randomness
+        let (again, _) = key.commit(&message, &mut rng);
+        assert_ne!(again, commitment);
+
+        // A different message, or randomness outside the bound, does not open it
+        let other = Opening { message: random_message(&key, &mut rng), ..opening.clone() };
+        assert!(!key.verify(&commitment, &other));
+        let mut large = opening.clone();
+        large.randomness.entries[0] = Polynomial::constant(2, key.params.modulus_info);
+        let forged = key.commit_with(&large.message, &large.randomness);
+        assert!(!key.verify(&forged, &large));
+        assert!(key.verify_with_bound(&forged, &large, 2));
+    }
+
+    #[test]
+    fn test_homomorphic_addition() {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        let key = key(1);
+        let (m1, m2, m3) = (random_message(&key, &mut rng), random_message(&key, &mut rng), random_message(&key, &mut rng));
+        let (c1, o1) = key.commit(&m1, &mut rng);
+        let (c2, o2) = key.commit(&m2, &mut rng);
+        let (c3, o3) = key.commit(&m3, &mut rng);
+
+        let sum = c1 + c2 + c3;
+        let opening = o1 + o2 + o3;
+        assert_eq!(opening.message, m1 + m2 + m3);
+        assert!(key.verify_with_bound(&sum, &opening, 3 * key.params.eta));
+        // Without NTT the same key gives the same commitments
+        let schoolbook = CommitmentKey { ntt_params: None, ..key.clone() };
+        assert!(schoolbook.verify_with_bound(&sum, &opening, 3));
+    }
+}
@@ -0,0 +1,449 @@
+//! Core-SVP security estimates for LWE, Module-LWE and Module-SIS
+//!
+//! Follows the methodology of the Kyber and Dilithium specifications: an
+//! attack is costed as a single call to an SVP oracle in the BKZ block size
+//! β it needs, at 2^(0.292β) classically and 2^(0.265β) with quantum
+//! sieving. Module instances are treated as plain LWE/SIS of dimension n·k.
+//!
+//! - Primal (uSVP): embed the LWE instance with m samples in dimension
+//!   d = n + m + 1 and find the smallest β with σ√β ≤ δ_β^(2β-d-1) · q^(m/d).
+//! - Dual: find a short dual vector of length δ_β^d · q^(n/d); the attack
+//!   repeats until the distinguishing advantage, amplified by the 2^(0.2075β)
+//!   vectors a sieve returns, is constant.
+//! - SIS: BKZ-β on the q-ary lattice gives the "Z-shaped" Gram–Schmidt
+//!   profile (q-vectors, a slope of -2 ln δ_β, unit vectors). In the ℓ2 norm
+//!   the attack succeeds once the first vector is short enough; in the ℓ∞
+//!   norm a sieved vector is lifted, and every coordinate must land inside
+//!   the bound.
+//!
+//! Both the number of samples m and the SIS width w are optimized for the
+//! attacker. With these models the estimates reproduce the published
+//! Kyber and Dilithium Module-LWE block sizes and Dilithium2's Module-SIS
+//! block size to within a few units. For Dilithium3 and Dilithium5 the ℓ∞
+//! SIS estimate comes out above the published one (β ≈ 690 and 1008
+//! against 638 and 909); their overall estimate is set by Module-LWE and
+//! is unaffected.
+
+use std::f64::consts::{E, PI};
+
+use crate::params::{dilithium::{self, DilithiumParams}, LatticeParams};
+
+/// Smallest block size considered
+const MIN_BLOCK_SIZE: usize = 50;
+/// Coarse step of the dimension search before it is refined around the best point
+const DIMENSION_STEP: usize = 8;
+
+/// Cost of an SVP call in block size β
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CostModel {
+    /// Classical sieving, 2^(0.292β)
+    Classical,
+    /// Quantum sieving, 2^(0.265β)
+    Quantum,
+}
+
+impl CostModel {
+    /// log₂ of the cost of one SVP call in dimension β
+    pub fn svp_bits(self, beta: usize) -> f64 {
+        let per_dimension = match self {
+            CostModel::Classical => (1.5f64).sqrt().log2(),
+            CostModel::Quantum => (13.0f64 / 9.0).sqrt().log2(),
+        };
+        per_dimension * beta as f64
+    }
+}
+
+/// The attacks the estimator models
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Attack {
+    /// Primal uSVP attack on LWE
+    Primal,
+    /// Dual distinguishing attack on LWE
+    Dual,
+    /// Short vector in the q-ary SIS lattice, in the ℓ2 norm
+    SisL2,
+    /// Short vector in the q-ary SIS lattice, in the ℓ∞ norm
+    SisLinf,
+}
+
+/// Norm in which a SIS solution is bounded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Norm {
+    /// Euclidean norm
+    L2,
+    /// Maximum absolute coordinate
+    Linf,
+}
+
+/// The cost of one attack on one instance
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AttackCost {
+    /// Which attack
+    pub attack: Attack,
+    /// Which SVP cost model
+    pub model: CostModel,
+    /// BKZ block size β
+    pub block_size: usize,
+    /// Optimal number of LWE samples m, or SIS width w
+    pub dimension: usize,
+    /// log₂ of the total cost
+    pub bits: f64,
+}
+
+/// Root Hermite factor δ_β reached by BKZ-β
+pub fn root_hermite_factor(beta: usize) -> f64 {
+    let b = beta as f64;
+    ((PI * b).powf(1.0 / b) * b / (2.0 * PI * E)).powf(1.0 / (2.0 * (b - 1.0)))
+}
+
+/// log₂ of the number of vectors a sieve in dimension β returns, (4/3)^(β/2)
+fn sieve_vectors_bits(beta: usize) -> f64 {
+    (4.0f64 / 3.0).sqrt().log2() * beta as f64
+}
+
+/// Complementary error function, with relative error below 1.2·10⁻⁷
+///
+/// Chebyshev approximation from Numerical Recipes (`erfcc`).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.26551223
+        + t * (1.00002368
+            + t * (0.37409196
+                + t * (0.09678418
+                    + t * (-0.18628806
+                        + t * (0.27886807 + t * (-1.13520398 + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277))))))));
+    let r = t * poly.exp();
+    if x >= 0.0 { r } else { 2.0 - r }
+}
+
+/// Minimizes `cost(β, dimension)` over β ≥ 50 and `dimensions`
+///
+/// Block sizes are tried in increasing order until the SVP call alone costs
+/// more than the best attack found. Each block size scans the dimensions
+/// coarsely and then refines around the best one.
+fn search(
+    attack: Attack,
+    model: CostModel,
+    dimensions: (usize, usize),
+    max_block_size: usize,
+    cost: impl Fn(usize, usize) -> f64,
+) -> Option<AttackCost> {
+    let mut best: Option<AttackCost> = None;
+    for beta in MIN_BLOCK_SIZE..=max_block_size {
+        if best.is_some_and(|best| model.svp_bits(beta) > best.bits) {
+            break;
+        }
+        let (lo, hi) = (dimensions.0.max(1), dimensions.1);
+        let coarse = (lo..=hi).step_by(DIMENSION_STEP).chain([hi]);
+        let Some((bits, dim)) = coarse.map(|dim| (cost(beta, dim), dim)).min_by(|a, b| a.0.total_cmp(&b.0)) else {
+            continue;
+        };
+        let fine = dim.saturating_sub(DIMENSION_STEP).max(lo)..=(dim + DIMENSION_STEP).min(hi);
+        let (bits, dim) = fine.map(|dim| (cost(beta, dim), dim)).fold((bits, dim), |a, b| if b.0 < a.0 { b } else { a });
+        if bits.is_finite() && best.map_or(true, |best| bits < best.bits) {
+            best = Some(AttackCost { attack, model, block_size: beta, dimension: dim, bits });
+        }
+    }
+    best
+}
+
+/// An LWE instance b = As + e mod q with s ∈ Z^n and up to m samples
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LweInstance {
+    /// Secret dimension n
+    pub n: usize,
+    /// Number of samples available to the attacker
+    pub m: usize,
+    /// Modulus q
+    pub q: f64,
+    /// Standard deviation of the secret and error coefficients
+    pub stddev: f64,
+}
+
+impl LweInstance {
+    /// Module-LWE with a `rows × cols` matrix over R_q of degree `degree`
+    pub fn module(degree: usize, rows: usize, cols: usize, q: f64, stddev: f64) -> Self {
+        Self { n: degree * cols, m: degree * rows, q, stddev }
+    }
+
+    /// Kyber-style Module-LWE: k × k, centered binomial noise with parameter `eta`
+    pub fn from_lattice(params: &LatticeParams) -> Self {
+        Self::module(params.n, params.k, params.k, params.q as f64, centered_binomial_stddev(params.eta))
+    }
+
+    /// Primal uSVP attack, or `None` if no block size up to n + m succeeds
+    pub fn primal(&self, model: CostModel) -> Option<AttackCost> {
+        let (n, q, sigma) = (self.n as f64, self.q, self.stddev);
+        search(Attack::Primal, model, (1, self.m), self.n + self.m, |beta, m| {
+            let (b, m) = (beta as f64, m as f64);
+            let d = n + m + 1.0;
+            if beta as f64 > d {
+                return f64::INFINITY;
+            }
+            let delta = root_hermite_factor(beta);
+            // Compare logarithms to stay within f64 range
+            let lhs = sigma.ln() + 0.5 * b.ln();
+            let rhs = (2.0 * b - d - 1.0) * delta.ln() + m / d * q.ln();
+            if lhs < rhs { model.svp_bits(beta) } else { f64::INFINITY }
+        })
+    }
+
+    /// Dual distinguishing attack
+    pub fn dual(&self, model: CostModel) -> Option<AttackCost> {
+        let (n, q, sigma) = (self.n as f64, self.q, self.stddev);
+        search(Attack::Dual, model, (1, self.m), self.n + self.m, |beta, m| {
+            let d = n + m as f64;
+            let delta = root_hermite_factor(beta);
+            let length = (d * delta.ln() + n / d * q.ln()).exp();
+            let tau = length * sigma / q;
+            // Advantage ε = exp(-2π²τ²); about 1/ε² samples are needed
+            let log2_advantage = -2.0 * PI * PI * tau * tau / 2f64.ln();
+            let log2_repetitions = (-2.0 * log2_advantage - sieve_vectors_bits(beta)).max(0.0);
+            model.svp_bits(beta) + log2_repetitions
+        })
+    }
+
+    /// Costs of the primal and dual attacks under both cost models
+    pub fn estimate(&self) -> SecurityEstimate {
+        let attacks = [CostModel::Classical, CostModel::Quantum]
+            .into_iter()
+            .flat_map(|model| [self.primal(model), self.dual(model)])
+            .flatten()
+            .collect();
+        SecurityEstimate { attacks }
+    }
+}
+
+/// Standard deviation of the centered binomial distribution B_η
+pub fn centered_binomial_stddev(eta: usize) -> f64 {
+    (eta as f64 / 2.0).sqrt()
+}
+
+/// Standard deviation of the uniform distribution on [-η, η]
+pub fn uniform_stddev(eta: usize) -> f64 {
+    let width = (2 * eta + 1) as f64;
+    ((width * width - 1.0) / 12.0).sqrt()
+}
+
+/// A SIS instance: find nonzero x with Ax = 0 mod q and ‖x‖ ≤ bound
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SisInstance {
+    /// Number of rows h of A
+    pub h: usize,
+    /// Number of columns of A; the attacker may use any w ≤ this
+    pub w: usize,
+    /// Modulus q
+    pub q: f64,
+    /// Norm bound on the solution
+    pub bound: f64,
+    /// Norm of the bound
+    pub norm: Norm,
+}
+
+/// Simulated BKZ-β profile of a q-ary lattice with `q_vectors` q-vectors in dimension `d`
+///
+/// Returns the number of leading q-vectors `i`, the end `j` of the slope and
+/// the log of the Gram–Schmidt length at `i`.
+fn z_shape(q: f64, q_vectors: usize, d: usize, beta: usize) -> (usize, usize, f64) {
+    let slope = 2.0 * root_hermite_factor(beta).ln();
+    let (lq, d_f) = (q.ln(), d as f64);
+    let target = q_vectors as f64 * lq;
+
+    // Continuous profile min(log q, max(0, c - slope·x)); find c preserving the volume
+    let bounds = |c: f64| {
+        let x1 = ((c - lq) / slope).clamp(0.0, d_f);
+        let x2 = (c / slope).clamp(0.0, d_f);
+        (x1, x2)
+    };
+    let volume = |c: f64| {
+        let (x1, x2) = bounds(c);
+        lq * x1 + c * (x2 - x1) - slope * (x2 * x2 - x1 * x1) / 2.0
+    };
+    let (mut lo, mut hi) = (0.0, lq + slope * d_f);
+    for _ in 0..80 {
+        let mid = (lo + hi) / 2.0;
+        if volume(mid) < target {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    let (x1, x2) = bounds(lo);
+    let (i, j) = (x1.round() as usize, x2.round() as usize);
+    (i, j, (lo - slope * i as f64).clamp(0.0, lq))
+}
+
+impl SisInstance {
+    /// Module-SIS with a `rows × cols` matrix over R_q of degree `degree`
+    pub fn module(degree: usize, rows: usize, cols: usize, q: f64, bound: f64, norm: Norm) -> Self {
+        Self { h: degree * rows, w: degree * cols, q, bound, norm }
+    }
+
+    /// Cost of finding a solution, or `None` if no block size up to w succeeds
+    pub fn attack(&self, model: CostModel) -> Option<AttackCost> {
+        let (q, bound) = (self.q, self.bound);
+        if bound >= q {
+            // (q, 0, ..., 0) is a solution
+            let attack = if self.norm == Norm::L2 { Attack::SisL2 } else { Attack::SisLinf };
+            return Some(AttackCost { attack, model, block_size: 0, dimension: self.h + 1, bits: 0.0 });
+        }
+        let h = self.h;
+        match self.norm {
+            Norm::L2 => search(Attack::SisL2, model, (h + 1, self.w), self.w, |beta, w| {
+                let (i, _, log_length) = z_shape(q, h, w, beta);
+                let first = if i > 0 { q.ln() } else { log_length };
+                if beta <= w && first <= bound.ln() { model.svp_bits(beta) } else { f64::INFINITY }
+            }),
+            Norm::Linf => search(Attack::SisLinf, model, (h + 1, self.w), self.w, |beta, w| {
+                if beta > w {
+                    return f64::INFINITY;
+                }
+                let (i, j, log_length) = z_shape(q, h, w, beta);
+                if i >= j {
+                    return f64::INFINITY;
+                }
+                // Lifted coordinates are uniform mod q; sieved ones Gaussian
+                let middle = (j - i) as f64;
+                let sigma = log_length.exp() / middle.sqrt();
+                let log2_middle = (-erfc(bound / (sigma * 2f64.sqrt()))).ln_1p() / 2f64.ln();
+                let log2_head = (2.0 * bound / q).min(1.0).log2();
+                let log2_success = middle * log2_middle + i as f64 * log2_head;
+                model.svp_bits(beta) + (-log2_success - sieve_vectors_bits(beta)).max(0.0)
+            }),
+        }
+    }
+
+    /// Cost under both cost models
+    pub fn estimate(&self) -> SecurityEstimate {
+        let attacks = [CostModel::Classical, CostModel::Quantum].into_iter().filter_map(|model| self.attack(model)).collect();
+        SecurityEstimate { attacks }
+    }
+}
+
+/// Attack costs for one parameter set
+#[derive(Debug, Clone, PartialEq)]
+pub struct SecurityEstimate {
+    /// Every attack that succeeds, under each cost model
+    pub attacks: Vec<AttackCost>,
+}
+
+impl SecurityEstimate {
+    /// The cheapest attack under `model`
+    pub fn best(&self, model: CostModel) -> Option<&AttackCost> {
+        self.attacks.iter().filter(|a| a.model == model).min_by(|a, b| a.bits.total_cmp(&b.bits))
+    }
+
+    /// The cheapest `attack` under `model`
+    pub fn cost(&self, attack: Attack, model: CostModel) -> Option<&AttackCost> {
+        self.attacks.iter().find(|a| a.attack == attack && a.model == model)
+    }
+
+    /// Estimated bit security under `model` (infinite if no attack succeeds)
+    pub fn bits(&self, model: CostModel) -> f64 {
+        self.best(model).map_or(f64::INFINITY, |a| a.bits)
+    }
+}
+
+/// Key recovery on a Kyber parameter set (Module-LWE with CBD_η₁ secrets)
+pub fn estimate_kyber(params: &LatticeParams) -> SecurityEstimate {
+    LweInstance::from_lattice(params).estimate()
+}
+
+/// Key recovery (Module-LWE) and forgery (ℓ∞ Module-SIS) on a Dilithium parameter set
+///
+/// The SIS bound is max(γ₁ - β, 2γ₂ + 1 + 2^(d-1)·τ), the one the
+/// specification uses for unforgeability, on the k × (k + l + 1) matrix of
+/// the SelfTargetMSIS reduction.
+pub fn estimate_dilithium(params: &DilithiumParams) -> SecurityEstimate {
+    let base = &params.base;
+    let q = base.q as f64;
+    let lwe = LweInstance::module(base.n, base.k, params.l, q, uniform_stddev(base.eta));
+    let bound = (params.gamma1 - params.beta).max(2 * params.gamma2 + 1 + (1 << (dilithium::D - 1)) * params.tau as i32);
+    let sis = SisInstance::module(base.n, base.k, base.k + params.l + 1, q, bound as f64, Norm::Linf);
+
+    let mut estimate = lwe.estimate();
+    estimate.attacks.extend(sis.estimate().attacks);
+    estimate
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::{dilithium, kyber};
+
+    fn assert_near(actual: f64, expected: f64, tolerance: f64) {
+        assert!((actual - expected).abs() <= tolerance, "{} vs {}", actual, expected);
+    }
+
+    #[test]
+    fn test_helpers() {
+        assert_near(root_hermite_factor(400), 1.00398, 1e-5);
+        assert_near(CostModel::Classical.svp_bits(100), 29.25, 0.01);
+        assert_near(CostModel::Quantum.svp_bits(100), 26.53, 0.01);
+        assert_near(erfc(0.0), 1.0, 1e-7);
+        assert_near(erfc(1.0), 0.157299207, 1e-7);
+        assert_near(erfc(-1.0), 1.842700793, 1e-7);
+        assert_near(erfc(5.0) / 1.5374597944e-12, 1.0, 1e-6);
+        assert_near(centered_binomial_stddev(2), 1.0, 1e-12);
+        assert_near(uniform_stddev(2), 2f64.sqrt(), 1e-12);
+    }
+
+    #[test]
+    fn test_kyber_matches_specification() {
+        // Core-SVP of the round-3 Kyber specification (primal attack), classical and quantum
+        for (params, classical, quantum) in [
+            (kyber::kyber_512(), 118.0, 107.0),
+            (kyber::kyber_768(), 182.0, 165.0),
+            (kyber::kyber_1024(), 256.0, 232.0),
+        ] {
+            let estimate = estimate_kyber(&params);
+            let primal = estimate.cost(Attack::Primal, CostModel::Classical).unwrap();
+            assert_near(primal.bits, classical, 1.0);
+            assert_near(estimate.cost(Attack::Primal, CostModel::Quantum).unwrap().bits, quantum, 1.0);
+            assert!(primal.dimension <= params.n * params.k);
+            // The dual attack is within a few bits of the primal one
+            assert_near(estimate.cost(Attack::Dual, CostModel::Classical).unwrap().bits, classical, 3.0);
+            assert!(estimate.bits(CostModel::Quantum) < estimate.bits(CostModel::Classical));
+        }
+    }
+
+    #[test]
+    fn test_dilithium_matches_specification() {
+        // Round-3 Dilithium specification: Module-LWE block sizes 423, 624, 863 and
+        // classical core-SVP 123, 182, 252; Dilithium2's SIS block size is 423
+        for (params, lwe_beta, classical) in [
+            (dilithium::dilithium_2(), 423, 123.0),
+            (dilithium::dilithium_3(), 624, 182.0),
+            (dilithium::dilithium_5(), 863, 252.0),
+        ] {
+            let estimate = estimate_dilithium(&params);
+            let primal = estimate.cost(Attack::Primal, CostModel::Classical).unwrap();
+            assert!(primal.block_size.abs_diff(lwe_beta) <= 3, "{:?}", primal);
+            assert_near(estimate.bits(CostModel::Classical), classical, 1.5);
+            let sis = estimate.cost(Attack::SisLinf, CostModel::Classical).unwrap();
+            assert!(sis.bits >= estimate.bits(CostModel::Classical));
+        }
+        let sis = estimate_dilithium(&dilithium::dilithium_2()).cost(Attack::SisLinf, CostModel::Classical).copied().unwrap();
+        assert!(sis.block_size.abs_diff(423) <= 3, "{:?}", sis);
+    }
+
+    #[test]
+    fn test_monotone_in_parameters() {
+        let base = LweInstance::module(256, 2, 2, 3329.0, 1.0);
+        let bits = |instance: LweInstance| instance.primal(CostModel::Classical).unwrap().bits;
+        // More noise, larger rank and smaller modulus all make the attack harder
+        assert!(bits(LweInstance { stddev: 2.0, ..base }) > bits(base));
+        assert!(bits(LweInstance::module(256, 3, 3, 3329.0, 1.0)) > bits(base));
+        assert!(bits(LweInstance { q: 7681.0, ..base }) < bits(base));
+
+        // A tiny instance is broken by small block sizes; a trivial SIS bound costs nothing
+        let toy = LweInstance { n: 40, m: 80, q: 3329.0, stddev: 1.0 };
+        assert_eq!(toy.primal(CostModel::Classical).unwrap().block_size, MIN_BLOCK_SIZE);
+        let sis = SisInstance { h: 10, w: 100, q: 257.0, bound: 300.0, norm: Norm::L2 };
+        assert_eq!(sis.attack(CostModel::Classical).unwrap().bits, 0.0);
+        // The ℓ2 attack needs larger β for a smaller bound
+        let l2 = |bound| SisInstance { h: 256, w: 1024, q: 3329.0, bound, norm: Norm::L2 }.attack(CostModel::Classical).unwrap();
+        assert!(l2(200.0).block_size > l2(800.0).block_size);
+    }
+}
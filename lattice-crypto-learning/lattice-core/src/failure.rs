@@ -0,0 +1,293 @@
+//! Exact decryption-failure probability of Kyber-style CPA encryption
+//!
+//! Decryption recovers `v - sᵀu = ⌈q/2⌋·m + eᵀr - sᵀ(e₁ + Δu) + e₂ + Δv`,
+//! where `Δu` and `Δv` are the errors introduced by compressing `u` to `du`
+//! bits and `v` to `dv` bits. A message bit decodes wrongly when the error
+//! coefficient exceeds q/4 in absolute value. As in the Kyber specification's
+//! analysis, each coefficient of the error is a sum of independent terms whose
+//! exact distribution is obtained by convolution, and the failure probability
+//! δ is the union bound over the n coefficients.
+//!
+//! Probabilities below 10^-90 (about 2^-299) are dropped after each
+//! convolution, as in the specification's script; this does not affect δ
+//! at the precision reported.
+
+use crate::params::{kyber, LatticeParams};
+
+/// Parameter sets with δ above 2^-128 are flagged
+pub const TARGET_LOG2_DELTA: f64 = -128.0;
+
+/// Probabilities below this are dropped from the tails of a distribution
+const PRUNE: f64 = 1e-90;
+
+/// A probability distribution over the integers `min..min + probs.len()`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Distribution {
+    min: i64,
+    probs: Vec<f64>,
+}
+
+impl Distribution {
+    /// The distribution that is 0 with certainty
+    pub fn zero() -> Self {
+        Self { min: 0, probs: vec![1.0] }
+    }
+
+    /// Builds a distribution from (value, probability) pairs
+    fn from_pairs(pairs: impl IntoIterator<Item = (i64, f64)>) -> Self {
+        let pairs: Vec<_> = pairs.into_iter().collect();
+        let min = pairs.iter().map(|&(x, _)| x).min().unwrap_or(0);
+        let max = pairs.iter().map(|&(x, _)| x).max().unwrap_or(0);
+        let mut probs = vec![0.0; (max - min + 1) as usize];
+        for (x, p) in pairs {
+            probs[(x - min) as usize] += p;
+        }
+        Self { min, probs }.pruned()
+    }
+
+    /// Centered binomial distribution B_η: the sum of η differences of fair bits
+    pub fn centered_binomial(eta: usize) -> Self {
+        let total = 4f64.powi(eta as i32);
+        let mut binom = vec![1.0f64; 2 * eta + 1];
+        for i in 1..2 * eta {
+            binom[i] = binom[i - 1] * (2 * eta + 1 - i) as f64 / i as f64;
+        }
+        Self { min: -(eta as i64), probs: binom.into_iter().map(|c| c / total).collect() }
+    }
+
+    /// Error `Decompress_d(Compress_d(x)) - x` (centered mod q) for x uniform in Z_q
+    pub fn compression_error(q: i32, d: usize) -> Self {
+        let (q, m) = (q as i64, 1i64 << d);
+        Self::from_pairs((0..q).map(|x| {
+            let y = ((m * x + (q >> 1)) / q) % m;
+            let z = (q * y + (m >> 1)) / m;
+            (center(z - x, q), 1.0 / q as f64)
+        }))
+    }
+
+    /// Probability of the value `x`
+    pub fn prob(&self, x: i64) -> f64 {
+        usize::try_from(x - self.min).ok().and_then(|i| self.probs.get(i)).copied().unwrap_or(0.0)
+    }
+
+    /// Smallest and largest value with nonzero probability
+    pub fn support(&self) -> (i64, i64) {
+        (self.min, self.min + self.probs.len() as i64 - 1)
+    }
+
+    /// Total probability mass (1 up to rounding and pruning)
+    pub fn mass(&self) -> f64 {
+        self.probs.iter().sum()
+    }
+
+    /// Variance of the distribution
+    pub fn variance(&self) -> f64 {
+        let mean: f64 = self.iter().map(|(x, p)| x as f64 * p).sum();
+        self.iter().map(|(x, p)| (x as f64 - mean).powi(2) * p).sum()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = (i64, f64)> + '_ {
+        self.probs.iter().enumerate().map(move |(i, &p)| (self.min + i as i64, p))
+    }
+
+    /// Drops negligible probabilities from both ends
+    fn pruned(mut self) -> Self {
+        let start = self.probs.iter().position(|&p| p >= PRUNE).unwrap_or(0);
+        let end = self.probs.iter().rposition(|&p| p >= PRUNE).map_or(self.probs.len(), |i| i + 1);
+        self.probs.truncate(end);
+        self.probs.drain(..start);
+        self.min += start as i64;
+        self
+    }
+
+    /// Distribution of X + Y for independent X and Y
+    pub fn add(&self, other: &Self) -> Self {
+        let mut probs = vec![0.0; self.probs.len() + other.probs.len() - 1];
+        for (i, &a) in self.probs.iter().enumerate() {
+            for (j, &b) in other.probs.iter().enumerate() {
+                probs[i + j] += a * b;
+            }
+        }
+        Self { min: self.min + other.min, probs }.pruned()
+    }
+
+    /// Distribution of X · Y for independent X and Y
+    pub fn mul(&self, other: &Self) -> Self {
+        Self::from_pairs(self.iter().flat_map(|(x, p)| other.iter().map(move |(y, q)| (x * y, p * q))))
+    }
+
+    /// Distribution of the sum of `count` independent copies of X
+    pub fn sum_of(&self, mut count: usize) -> Self {
+        let mut result = Self::zero();
+        let mut power = self.clone();
+        while count > 0 {
+            if count & 1 == 1 {
+                result = result.add(&power);
+            }
+            count >>= 1;
+            if count > 0 {
+                power = power.add(&power);
+            }
+        }
+        result
+    }
+
+    /// P(|X| > bound)
+    pub fn tail(&self, bound: f64) -> f64 {
+        // Summing from the outside in keeps the small terms accurate
+        let mut outer: Vec<f64> = self.iter().filter(|&(x, _)| x.abs() as f64 > bound).map(|(_, p)| p).collect();
+        outer.sort_by(f64::total_cmp);
+        outer.iter().sum()
+    }
+}
+
+/// Representative of `x mod q` in `(-q/2, q/2]`
+fn center(x: i64, q: i64) -> i64 {
+    let r = x.rem_euclid(q);
+    if r > q / 2 { r - q } else { r }
+}
+
+/// Everything the failure probability depends on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FailureParams {
+    /// Ring degree n
+    pub n: usize,
+    /// Module rank k
+    pub k: usize,
+    /// Modulus q
+    pub q: i32,
+    /// CBD parameter of s, e and r
+    pub eta1: usize,
+    /// CBD parameter of e₁ and e₂
+    pub eta2: usize,
+    /// Bits per coefficient of the compressed u
+    pub du: usize,
+    /// Bits per coefficient of the compressed v
+    pub dv: usize,
+}
+
+impl FailureParams {
+    /// Reads n, k, q, η₁ (`eta`) and η₂ (`beta`) from a Kyber `LatticeParams`
+    pub fn from_lattice(params: &LatticeParams, du: usize, dv: usize) -> Self {
+        Self { n: params.n, k: params.k, q: params.q, eta1: params.eta, eta2: params.beta as usize, du, dv }
+    }
+
+    /// Kyber-512 with (du, dv) = (10, 4)
+    pub fn kyber_512() -> Self {
+        Self::from_lattice(&kyber::kyber_512(), kyber::DU, kyber::DV)
+    }
+
+    /// Kyber-768 with (du, dv) = (10, 4)
+    pub fn kyber_768() -> Self {
+        Self::from_lattice(&kyber::kyber_768(), kyber::DU, kyber::DV)
+    }
+
+    /// Kyber-1024 with (du, dv) = (11, 5), as in the specification
+    pub fn kyber_1024() -> Self {
+        Self::from_lattice(&kyber::kyber_1024(), 11, 5)
+    }
+
+    /// Distribution of one coefficient of the decryption error
+    pub fn error_distribution(&self) -> Distribution {
+        let chi1 = Distribution::centered_binomial(self.eta1);
+        let chi2 = Distribution::centered_binomial(self.eta2);
+        let du = Distribution::compression_error(self.q, self.du);
+        let dv = Distribution::compression_error(self.q, self.dv);
+        let terms = self.k * self.n;
+
+        // eᵀr and sᵀ(e₁ + Δu) each sum k·n products of independent coefficients
+        let er = chi1.mul(&chi1).sum_of(terms);
+        let su = chi1.mul(&chi2.add(&du)).sum_of(terms);
+        er.add(&su).add(&chi2.add(&dv))
+    }
+
+    /// Computes δ for this parameter set
+    pub fn analyze(&self) -> FailureReport {
+        let per_coefficient = self.error_distribution().tail(self.q as f64 / 4.0);
+        let delta = (per_coefficient * self.n as f64).min(1.0);
+        FailureReport { params: *self, per_coefficient, delta, log2_delta: delta.log2() }
+    }
+}
+
+/// Result of [`FailureParams::analyze`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FailureReport {
+    /// The analyzed parameter set
+    pub params: FailureParams,
+    /// Probability that a single coefficient decodes wrongly
+    pub per_coefficient: f64,
+    /// Probability that a ciphertext fails to decrypt (union bound over n)
+    pub delta: f64,
+    /// log₂ δ
+    pub log2_delta: f64,
+}
+
+impl FailureReport {
+    /// Whether δ is above [`TARGET_LOG2_DELTA`]
+    pub fn exceeds_target(&self) -> bool {
+        self.log2_delta > TARGET_LOG2_DELTA
+    }
+}
+
+/// Failure reports for Kyber-512, -768 and -1024
+pub fn kyber_reports() -> [FailureReport; 3] {
+    [FailureParams::kyber_512().analyze(), FailureParams::kyber_768().analyze(), FailureParams::kyber_1024().analyze()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_elementary_distributions() {
+        let b2 = Distribution::centered_binomial(2);
+        assert_eq!(b2.support(), (-2, 2));
+        assert_eq!(b2.prob(0), 6.0 / 16.0);
+        assert_eq!(b2.prob(-2), 1.0 / 16.0);
+        assert!((Distribution::centered_binomial(3).variance() - 1.5).abs() < 1e-12);
+
+        // Compress/decompress error is at most ⌈q / 2^(d+1)⌋
+        for d in [1, 4, 5, 10, 11] {
+            let err = Distribution::compression_error(3329, d);
+            let bound = (3329 + (1 << d)) / (1 << (d + 1));
+            assert!((err.mass() - 1.0).abs() < 1e-12);
+            assert!(err.support().0 >= -bound && err.support().1 <= bound, "d = {}", d);
+        }
+    }
+
+    #[test]
+    fn test_convolution() {
+        let b1 = Distribution::centered_binomial(1);
+        // The sum of η copies of B_1 is B_η
+        let b3 = b1.sum_of(3);
+        for x in -3..=3 {
+            assert!((b3.prob(x) - Distribution::centered_binomial(3).prob(x)).abs() < 1e-15);
+        }
+        let product = b1.mul(&b1);
+        assert_eq!(product.prob(-1), 2.0 / 16.0);
+        assert_eq!(product.prob(0), 12.0 / 16.0);
+        assert!((b1.sum_of(100).variance() - 50.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_kyber_matches_specification() {
+        // The round-3 specification lists δ as 2^-139, 2^-164 and 2^-174, rounded
+        // towards the larger probability; its analysis script gives these values
+        let [k512, k768, k1024] = kyber_reports();
+        for (report, published, script) in [(k512, -139.0, -139.14), (k768, -164.0, -165.24), (k1024, -174.0, -175.20)] {
+            assert!((report.log2_delta - script).abs() < 0.01, "{:?}", report);
+            assert!(report.log2_delta < published && report.log2_delta > published - 1.5);
+            assert!(!report.exceeds_target());
+        }
+    }
+
+    #[test]
+    fn test_weak_parameters_flagged() {
+        // Compressing v to 2 bits or doubling the noise breaks the 2^-128 target
+        let coarse = FailureParams { dv: 2, ..FailureParams::kyber_768() }.analyze();
+        assert!(coarse.exceeds_target(), "{:?}", coarse);
+        let noisy = FailureParams { eta1: 6, eta2: 6, ..FailureParams::kyber_768() }.analyze();
+        assert!(noisy.exceeds_target(), "{:?}", noisy);
+        assert!(noisy.log2_delta > FailureParams::kyber_768().analyze().log2_delta);
+    }
+}
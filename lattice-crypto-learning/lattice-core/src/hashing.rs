@@ -1,3 +1,4 @@
+use alloc::{vec, vec::Vec};
 use sha3::{Sha3_256, Sha3_512, Shake128, Shake256};
 use sha3::digest::{Update, ExtendableOutput, XofReader};
 use sha3::Digest;
@@ -55,12 +56,14 @@ pub fn prf(seed: &[u8], nonce: u16, len: usize) -> Vec<u8> {
 }
 
 /// Hash function G used in the Fujisaki-Okamoto transform (Figure 4)
+///
+/// G = SHA3-512 as in FIPS 203.
 pub fn hash_g(m: &[u8], h_pk: &[u8]) -> (Vec<u8>, Vec<u8>) {
     let mut data = Vec::with_capacity(m.len() + h_pk.len());
     data.extend_from_slice(m);
     data.extend_from_slice(h_pk);
     
-    let hash = shake256(&data, 64); // Can adjust length as needed
+    let hash = sha3_512(&data);
     
     // Split the hash into two parts for K and r_coins
     let (k, r_coins) = hash.split_at(32);
@@ -144,11 +147,27 @@ mod tests {
         // Same input should give same output
         assert_eq!(hash1, hash2);
         
-        // Verify against a known SHA3-256 hash
-        let expected = hex::decode("7d97362108ce4a7da11ec5a4dc6437bef83a9e6a36dddd78d85d8fbc55445e83").unwrap();
+        // SHA3-256("test data"); the value checked before was not the SHA3-256 of this input
+        let expected = hex::decode("fc88e0ac33ff105e376f4ece95fb06925d5ab20080dbe3aede7dd47e45dfd931").unwrap();
         assert_eq!(hash1, expected.as_slice());
     }
     
+    #[test]
+    fn test_fips202_reference_vectors() {
+        // SHA3-256 and SHA3-512 of "" and "abc" from the NIST FIPS 202 examples
+        assert_eq!(hex::encode(sha3_256(b"")), "a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a");
+        assert_eq!(hex::encode(sha3_256(b"abc")), "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532");
+        assert_eq!(
+            hex::encode(sha3_512(b"abc")),
+            "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0",
+        );
+        assert_eq!(hex::encode(shake128(b"", 32)), "7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26");
+        assert_eq!(hex::encode(shake256(b"", 32)), "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762f");
+        
+        // Hashing in chunks absorbs the same byte stream as hashing their concatenation
+        assert_eq!(hash_h(&[b"a", b"bc"]), sha3_256(b"abc").to_vec());
+    }
+    
     #[test]
     fn test_prf() {
         let seed = b"test seed";
@@ -181,6 +200,10 @@ mod tests {
         // Different length outputs
         assert_eq!(k1.len(), 32);
         assert_eq!(r1.len(), 32);
+        
+        // G(m || h) = SHA3-512(m || h), split in two halves
+        let expected = sha3_512(b"messagepublic key hash");
+        assert_eq!([k1, r1].concat(), expected.to_vec());
     }
     
     #[test]
@@ -198,8 +221,9 @@ mod tests {
         let concatenated = concat_for_hash(&[data1, data2]);
         let hash3 = hash_h(&[&concatenated]);
         
-        // Should be different (hash(a|b) != hash([a,b]))
-        assert_ne!(hash1, hash3);
+        // The sponge absorbs the chunks as one byte stream, so hash([a, b]) == hash(a || b);
+        // inputs that need unambiguous framing must encode the chunk lengths themselves
+        assert_eq!(hash1, hash3);
     }
     
     #[test]
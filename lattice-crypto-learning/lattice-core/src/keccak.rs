@@ -0,0 +1,319 @@
+//! Keccak-f[1600] and a 4-way SHAKE sponge
+//!
+//! Matrix expansion and noise sampling hash many short, equal-length inputs
+//! (ρ‖j‖i, σ‖nonce). [`ShakeX4`] runs four SHAKE instances side by side on an
+//! interleaved state so the permutation can work on all four at once, like the
+//! reference AVX2 Kyber implementation. The outputs are exactly those of four
+//! separate SHAKE128/SHAKE256 instances.
+
+use crate::simd::{self, Backend};
+
+/// Rate of SHAKE128 in bytes
+pub const SHAKE128_RATE: usize = 168;
+/// Rate of SHAKE256 in bytes
+pub const SHAKE256_RATE: usize = 136;
+
+/// Domain separation and first padding bit for SHAKE
+const SHAKE_PAD: u8 = 0x1F;
+
+/// Four Keccak states, interleaved: `state[lane][instance]`
+pub type StateX4 = [[u64; 4]; 25];
+
+/// Round constants of the ι step
+pub(crate) const ROUND_CONSTANTS: [u64; 24] = [
+    0x0000_0000_0000_0001, 0x0000_0000_0000_8082, 0x8000_0000_0000_808A, 0x8000_0000_8000_8000,
+    0x0000_0000_0000_808B, 0x0000_0000_8000_0001, 0x8000_0000_8000_8081, 0x8000_0000_0000_8009,
+    0x0000_0000_0000_008A, 0x0000_0000_0000_0088, 0x0000_0000_8000_8009, 0x0000_0000_8000_000A,
+    0x0000_0000_8000_808B, 0x8000_0000_0000_008B, 0x8000_0000_0000_8089, 0x8000_0000_0000_8003,
+    0x8000_0000_0000_8002, 0x8000_0000_0000_0080, 0x0000_0000_0000_800A, 0x8000_0000_8000_000A,
+    0x8000_0000_8000_8081, 0x8000_0000_0000_8080, 0x0000_0000_8000_0001, 0x8000_0000_8000_8008,
+];
+
+/// One unrolled Keccak-f[1600] permutation over 25 lane values `$a`
+///
+/// Shared by the scalar and vector permutations: the lane type only needs the
+/// xor, and-not (`!x & y`), rotate-left and round-constant operations passed in.
+macro_rules! keccak_permutation {
+    ($a:ident, $xor:ident, $andnot:ident, $rotl:ident, $constant:ident) => {
+        for &round_constant in &$crate::keccak::ROUND_CONSTANTS {
+            // θ: column parities
+            let c0 = $xor($xor($xor($a[0], $a[5]), $xor($a[10], $a[15])), $a[20]);
+            let c1 = $xor($xor($xor($a[1], $a[6]), $xor($a[11], $a[16])), $a[21]);
+            let c2 = $xor($xor($xor($a[2], $a[7]), $xor($a[12], $a[17])), $a[22]);
+            let c3 = $xor($xor($xor($a[3], $a[8]), $xor($a[13], $a[18])), $a[23]);
+            let c4 = $xor($xor($xor($a[4], $a[9]), $xor($a[14], $a[19])), $a[24]);
+            let d0 = $xor(c4, $rotl(c1, 1));
+            let d1 = $xor(c0, $rotl(c2, 1));
+            let d2 = $xor(c1, $rotl(c3, 1));
+            let d3 = $xor(c2, $rotl(c4, 1));
+            let d4 = $xor(c3, $rotl(c0, 1));
+
+            // θ, ρ and π: b[y + 5(2x + 3y)] = (a[x + 5y] ^ d[x]) <<< r[x + 5y]
+            let b = [
+                $rotl($xor($a[0], d0), 0),
+                $rotl($xor($a[6], d1), 44),
+                $rotl($xor($a[12], d2), 43),
+                $rotl($xor($a[18], d3), 21),
+                $rotl($xor($a[24], d4), 14),
+                $rotl($xor($a[3], d3), 28),
+                $rotl($xor($a[9], d4), 20),
+                $rotl($xor($a[10], d0), 3),
+                $rotl($xor($a[16], d1), 45),
+                $rotl($xor($a[22], d2), 61),
+                $rotl($xor($a[1], d1), 1),
+                $rotl($xor($a[7], d2), 6),
+                $rotl($xor($a[13], d3), 25),
+                $rotl($xor($a[19], d4), 8),
+                $rotl($xor($a[20], d0), 18),
+                $rotl($xor($a[4], d4), 27),
+                $rotl($xor($a[5], d0), 36),
+                $rotl($xor($a[11], d1), 10),
+                $rotl($xor($a[17], d2), 15),
+                $rotl($xor($a[23], d3), 56),
+                $rotl($xor($a[2], d2), 62),
+                $rotl($xor($a[8], d3), 55),
+                $rotl($xor($a[14], d4), 39),
+                $rotl($xor($a[15], d0), 41),
+                $rotl($xor($a[21], d1), 2),
+            ];
+
+            // χ and ι
+            $a[0] = $xor(b[0], $andnot(b[1], b[2]));
+            $a[1] = $xor(b[1], $andnot(b[2], b[3]));
+            $a[2] = $xor(b[2], $andnot(b[3], b[4]));
+            $a[3] = $xor(b[3], $andnot(b[4], b[0]));
+            $a[4] = $xor(b[4], $andnot(b[0], b[1]));
+            $a[5] = $xor(b[5], $andnot(b[6], b[7]));
+            $a[6] = $xor(b[6], $andnot(b[7], b[8]));
+            $a[7] = $xor(b[7], $andnot(b[8], b[9]));
+            $a[8] = $xor(b[8], $andnot(b[9], b[5]));
+            $a[9] = $xor(b[9], $andnot(b[5], b[6]));
+            $a[10] = $xor(b[10], $andnot(b[11], b[12]));
+            $a[11] = $xor(b[11], $andnot(b[12], b[13]));
+            $a[12] = $xor(b[12], $andnot(b[13], b[14]));
+            $a[13] = $xor(b[13], $andnot(b[14], b[10]));
+            $a[14] = $xor(b[14], $andnot(b[10], b[11]));
+            $a[15] = $xor(b[15], $andnot(b[16], b[17]));
+            $a[16] = $xor(b[16], $andnot(b[17], b[18]));
+            $a[17] = $xor(b[17], $andnot(b[18], b[19]));
+            $a[18] = $xor(b[18], $andnot(b[19], b[15]));
+            $a[19] = $xor(b[19], $andnot(b[15], b[16]));
+            $a[20] = $xor(b[20], $andnot(b[21], b[22]));
+            $a[21] = $xor(b[21], $andnot(b[22], b[23]));
+            $a[22] = $xor(b[22], $andnot(b[23], b[24]));
+            $a[23] = $xor(b[23], $andnot(b[24], b[20]));
+            $a[24] = $xor(b[24], $andnot(b[20], b[21]));
+            $a[0] = $xor($a[0], $constant(round_constant));
+        }
+    };
+}
+#[cfg(target_arch = "x86_64")]
+pub(crate) use keccak_permutation;
+
+#[inline(always)]
+fn xor(x: u64, y: u64) -> u64 {
+    x ^ y
+}
+
+#[inline(always)]
+fn andnot(x: u64, y: u64) -> u64 {
+    !x & y
+}
+
+#[inline(always)]
+fn rotl(x: u64, n: u32) -> u64 {
+    x.rotate_left(n)
+}
+
+#[inline(always)]
+fn constant(round_constant: u64) -> u64 {
+    round_constant
+}
+
+/// The Keccak-f[1600] permutation on a single state
+pub fn f1600(state: &mut [u64; 25]) {
+    keccak_permutation!(state, xor, andnot, rotl, constant);
+}
+
+/// Four SHAKE instances absorbing and squeezing in lockstep
+///
+/// All four inputs must have the same length, and every squeeze produces the
+/// same number of bytes per instance.
+#[derive(Debug, Clone)]
+pub struct ShakeX4 {
+    state: StateX4,
+    rate: usize,
+    /// Bytes of the current output block already squeezed
+    offset: usize,
+    backend: Backend,
+}
+
+impl ShakeX4 {
+    /// Absorbs four inputs into four SHAKE128 instances
+    pub fn shake128(inputs: [&[u8]; 4]) -> Self {
+        Self::absorb(SHAKE128_RATE, inputs)
+    }
+
+    /// Absorbs four inputs into four SHAKE256 instances
+    pub fn shake256(inputs: [&[u8]; 4]) -> Self {
+        Self::absorb(SHAKE256_RATE, inputs)
+    }
+
+    fn absorb(rate: usize, inputs: [&[u8]; 4]) -> Self {
+        let len = inputs[0].len();
+        assert!(inputs.iter().all(|input| input.len() == len), "all four inputs must have the same length");
+
+        let mut sponge = ShakeX4 { state: [[0; 4]; 25], rate, offset: 0, backend: Backend::detect() };
+        let full_blocks = len / rate;
+        for block in 0..full_blocks {
+            for (instance, input) in inputs.iter().enumerate() {
+                sponge.xor_bytes(instance, &input[block * rate..(block + 1) * rate]);
+            }
+            sponge.permute();
+        }
+
+        let tail = full_blocks * rate;
+        for (instance, input) in inputs.iter().enumerate() {
+            sponge.xor_bytes(instance, &input[tail..]);
+            sponge.xor_byte(instance, len - tail, SHAKE_PAD);
+            sponge.xor_byte(instance, rate - 1, 0x80);
+        }
+        sponge.permute();
+        sponge
+    }
+
+    fn xor_byte(&mut self, instance: usize, pos: usize, byte: u8) {
+        self.state[pos / 8][instance] ^= (byte as u64) << (8 * (pos % 8));
+    }
+
+    fn xor_bytes(&mut self, instance: usize, bytes: &[u8]) {
+        for (pos, &byte) in bytes.iter().enumerate() {
+            self.xor_byte(instance, pos, byte);
+        }
+    }
+
+    fn permute(&mut self) {
+        simd::keccak_f1600_x4(self.backend, &mut self.state);
+    }
+
+    /// Fills the four outputs with the next bytes of each instance
+    pub fn squeeze(&mut self, outputs: [&mut [u8]; 4]) {
+        let len = outputs[0].len();
+        assert!(outputs.iter().all(|output| output.len() == len), "all four outputs must have the same length");
+
+        let mut outputs = outputs;
+        let mut written = 0;
+        while written < len {
+            if self.offset == self.rate {
+                self.permute();
+                self.offset = 0;
+            }
+            let take = (self.rate - self.offset).min(len - written);
+            for (instance, output) in outputs.iter_mut().enumerate() {
+                let mut block = [0u8; 200];
+                for (bytes, lane) in block.chunks_exact_mut(8).zip(&self.state) {
+                    bytes.copy_from_slice(&lane[instance].to_le_bytes());
+                }
+                output[written..written + take].copy_from_slice(&block[self.offset..self.offset + take]);
+            }
+            self.offset += take;
+            written += take;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hashing::{shake128, shake256};
+
+    fn inputs(len: usize) -> [Vec<u8>; 4] {
+        core::array::from_fn(|instance| (0..len).map(|i| (i * 31 + instance * 7) as u8).collect())
+    }
+
+    #[test]
+    fn test_f1600_zero_state() {
+        // First lanes of Keccak-f[1600] applied to the all-zero state (Keccak team test vectors)
+        let mut state = [0u64; 25];
+        f1600(&mut state);
+        assert_eq!(state[0], 0xF1258F7940E1DDE7);
+        assert_eq!(state[1], 0x84D5CCF933C0478A);
+    }
+
+    #[test]
+    fn test_f1600_x4_matches_single() {
+        let mut singles: [[u64; 25]; 4] = core::array::from_fn(|instance| {
+            core::array::from_fn(|lane| (lane as u64 + 1).wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ instance as u64)
+        });
+        let mut interleaved: StateX4 = core::array::from_fn(|lane| core::array::from_fn(|instance| singles[instance][lane]));
+
+        for backend in Backend::available() {
+            let mut state = interleaved;
+            simd::keccak_f1600_x4(backend, &mut state);
+            for (instance, single) in singles.iter().enumerate() {
+                let mut expected = *single;
+                f1600(&mut expected);
+                for lane in 0..25 {
+                    assert_eq!(state[lane][instance], expected[lane], "{:?}", backend);
+                }
+            }
+        }
+
+        // Repeated application stays in sync
+        for single in singles.iter_mut() {
+            f1600(single);
+            f1600(single);
+        }
+        simd::keccak_f1600_x4(Backend::detect(), &mut interleaved);
+        simd::keccak_f1600_x4(Backend::detect(), &mut interleaved);
+        assert_eq!(interleaved[7][2], singles[2][7]);
+    }
+
+    #[test]
+    fn test_shake_x4_matches_sha3() {
+        for input_len in [0, 34, 135, 136, 167, 168, 169, 400] {
+            let inputs = inputs(input_len);
+            let refs = inputs.each_ref().map(|input| input.as_slice());
+
+            for output_len in [0, 1, 136, 168, 500] {
+                let mut shake = ShakeX4::shake128(refs);
+                let mut outputs = [(); 4].map(|_| vec![0u8; output_len]);
+                shake.squeeze(outputs.each_mut().map(|output| output.as_mut_slice()));
+                for (input, output) in inputs.iter().zip(&outputs) {
+                    assert_eq!(*output, shake128(input, output_len));
+                }
+
+                let mut shake = ShakeX4::shake256(refs);
+                shake.squeeze(outputs.each_mut().map(|output| output.as_mut_slice()));
+                for (input, output) in inputs.iter().zip(&outputs) {
+                    assert_eq!(*output, shake256(input, output_len));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_shake_x4_incremental_squeeze() {
+        let inputs = inputs(34);
+        let mut shake = ShakeX4::shake128(inputs.each_ref().map(|input| input.as_slice()));
+
+        // Odd-sized reads crossing block boundaries concatenate to one long read
+        let mut squeezed = [(); 4].map(|_| Vec::new());
+        for chunk_len in [5, 163, 1, 200, 3] {
+            let mut chunk = [(); 4].map(|_| vec![0u8; chunk_len]);
+            shake.squeeze(chunk.each_mut().map(|c| c.as_mut_slice()));
+            for (all, part) in squeezed.iter_mut().zip(&chunk) {
+                all.extend_from_slice(part);
+            }
+        }
+        for (input, output) in inputs.iter().zip(&squeezed) {
+            assert_eq!(*output, shake128(input, 372));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "all four inputs must have the same length")]
+    fn test_shake_x4_unequal_inputs() {
+        ShakeX4::shake128([&[0u8; 2], &[0u8; 2], &[0u8; 2], &[0u8; 3]]);
+    }
+}
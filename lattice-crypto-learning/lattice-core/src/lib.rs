@@ -1,10 +1,45 @@
+//! Core lattice primitives shared by the Kyber and Dilithium crates
+//!
+//! `std` (default) enables runtime SIMD detection; `alloc` enables the
+//! heap-backed polynomial, vector and matrix types and everything built on
+//! them. With neither, the crate is `no_std` and allocation-free. `pkcs8`
+//! adds the key encodings shared by the ML-KEM and ML-DSA crates.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 pub mod params;
 pub mod zq;
+#[cfg(feature = "alloc")]
 pub mod polynomial;
+#[cfg(feature = "alloc")]
 pub mod ntt;
+pub mod simd;
+#[cfg(feature = "alloc")]
 pub mod sampling;
+#[cfg(feature = "alloc")]
 pub mod vector_matrix;
+#[cfg(feature = "alloc")]
 pub mod hashing;
+#[cfg(feature = "alloc")]
+pub mod sis;
+#[cfg(feature = "alloc")]
+pub mod commitment;
+pub mod keccak;
+#[cfg(feature = "pkcs8")]
+pub mod pkcs8;
+#[cfg(feature = "std")]
+pub mod failure;
+#[cfg(feature = "std")]
+pub mod estimator;
+#[cfg(feature = "std")]
+pub mod reduction;
+#[cfg(feature = "std")]
+pub mod lwe;
+#[cfg(feature = "std")]
+pub mod attacks;
 
 #[cfg(test)]
 mod tests {
@@ -0,0 +1,279 @@
+//! Small plain-LWE instances and the lattices attacks reduce
+//!
+//! [`LweSamples`] holds `b = A·s + e mod q` for a uniform `m × n` matrix `A`
+//! and centered-binomial `s` and `e`, sized from a [`LatticeParams`] so that
+//! a Module-LWE parameter set with tiny n and k becomes an instance small
+//! enough to break. Two lattices built from it:
+//!
+//! - [`LweSamples::embedding`]: Kannan's embedding of `b`, in the form that
+//!   keeps the secret: the rows span every `(x, y, t)` with
+//!   `A·x + y ≡ t·b (mod q)`, which contains the unusually short vector
+//!   `(s, e, 1)`. [`LweSamples::recover_secret`] finds it with BKZ.
+//! - [`q_ary_lattice`]: a basis of L⊥(A) = { x : A·x ≡ 0 (mod q) }, the
+//!   lattice in which a SIS solution is a short vector.
+
+use rand::Rng;
+
+use crate::params::LatticeParams;
+use crate::reduction::IntMatrix;
+use crate::zq::ZqElement;
+
+/// LLL parameter used before and inside BKZ
+pub const LLL_DELTA: f64 = 0.99;
+/// BKZ stops after this many tours even if a tour still changed the basis
+pub const MAX_TOURS: usize = 16;
+
+/// An LWE instance together with the secret it hides
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LweSamples {
+    /// Uniform `m × n` matrix over Z_q, entries in [0, q)
+    pub a: IntMatrix,
+    /// `A·s + e mod q`, entries in [0, q)
+    pub b: Vec<i64>,
+    /// Modulus q
+    pub q: i64,
+    /// Secret s, centered
+    pub secret: Vec<i64>,
+    /// Error e, centered
+    pub error: Vec<i64>,
+}
+
+/// Samples from the centered binomial distribution B_η
+pub fn sample_cbd(eta: usize, rng: &mut impl Rng) -> i64 {
+    (0..eta).map(|_| i64::from(rng.gen::<bool>()) - i64::from(rng.gen::<bool>())).sum()
+}
+
+/// Representative of `x mod q` in `(-q/2, q/2]`
+pub fn center(x: i64, q: i64) -> i64 {
+    let r = x.rem_euclid(q);
+    if r > q / 2 { r - q } else { r }
+}
+
+impl LweSamples {
+    /// `m` samples in dimension `n` with B_η secret and error
+    pub fn generate(n: usize, m: usize, q: i64, eta: usize, rng: &mut impl Rng) -> Self {
+        Self::generate_with(n, m, q, eta, eta, rng)
+    }
+
+    /// `m` samples in dimension `n` with a B_(secret_eta) secret and B_(error_eta) error
+    pub fn generate_with(n: usize, m: usize, q: i64, secret_eta: usize, error_eta: usize, rng: &mut impl Rng) -> Self {
+        let mut a = IntMatrix::zero(m, n);
+        for i in 0..m {
+            for j in 0..n {
+                a[(i, j)] = rng.gen_range(0..q);
+            }
+        }
+        let secret: Vec<i64> = (0..n).map(|_| sample_cbd(secret_eta, rng)).collect();
+        let error: Vec<i64> = (0..m).map(|_| sample_cbd(error_eta, rng)).collect();
+        let b = (0..m).map(|i| (dot_mod(a.row(i), &secret, q) + error[i]).rem_euclid(q)).collect();
+        Self { a, b, q, secret, error }
+    }
+
+    /// The plain-LWE form of a Module-LWE parameter set: n·k secret coefficients and `m` samples
+    pub fn from_params(params: &LatticeParams, m: usize, rng: &mut impl Rng) -> Self {
+        Self::generate(params.n * params.k, m, i64::from(params.q), params.eta, rng)
+    }
+
+    /// Secret dimension n
+    pub fn n(&self) -> usize {
+        self.a.cols()
+    }
+
+    /// Number of samples m
+    pub fn m(&self) -> usize {
+        self.a.rows()
+    }
+
+    /// The centered `b - A·s mod q` for a candidate secret
+    pub fn residual(&self, candidate: &[i64]) -> Vec<i64> {
+        (0..self.m()).map(|i| center(self.b[i] - dot_mod(self.a.row(i), candidate, self.q), self.q)).collect()
+    }
+
+    /// Whether `candidate` leaves a residual no larger than `bound` in every coordinate
+    pub fn is_solution(&self, candidate: &[i64], bound: i64) -> bool {
+        candidate.len() == self.n() && self.residual(candidate).iter().all(|e| e.abs() <= bound)
+    }
+
+    /// Basis of { (x, y, t) ∈ Z^(n+m+1) : A·x + y ≡ t·b (mod q) }
+    ///
+    /// Rows (e_i, -A·e_i, 0) for each secret coordinate, (0, q·e_j, 0) for
+    /// each sample and (0, b, 1); the determinant is q^m. The secret and
+    /// error appear as the short vector (s, e, 1).
+    pub fn embedding(&self) -> IntMatrix {
+        let (n, m) = (self.n(), self.m());
+        let mut basis = IntMatrix::zero(n + m + 1, n + m + 1);
+        for i in 0..n {
+            basis[(i, i)] = 1;
+            for j in 0..m {
+                basis[(i, n + j)] = -self.a[(j, i)];
+            }
+        }
+        for j in 0..m {
+            basis[(n + j, n + j)] = self.q;
+        }
+        for j in 0..m {
+            basis[(n + m, n + j)] = self.b[j];
+        }
+        basis[(n + m, n + m)] = 1;
+        basis
+    }
+
+    /// Reduces the embedding with BKZ-β and reads the secret off it
+    ///
+    /// `beta < 2` runs LLL only.
+    pub fn recover_secret(&self, beta: usize, bound: i64) -> Option<Vec<i64>> {
+        let mut basis = self.embedding();
+        basis.bkz(beta, LLL_DELTA, MAX_TOURS);
+        self.read_secret(&basis, bound)
+    }
+
+    /// Looks for a row ±(s, e, 1) in a reduced embedding basis
+    ///
+    /// A candidate is accepted if its error part is no larger than `bound`
+    /// and consistent with the samples.
+    pub fn read_secret(&self, basis: &IntMatrix, bound: i64) -> Option<Vec<i64>> {
+        let (n, m) = (self.n(), self.m());
+        (0..basis.rows()).find_map(|i| {
+            let row = basis.row(i);
+            let sign = row[n + m];
+            if sign.abs() != 1 {
+                return None;
+            }
+            let candidate: Vec<i64> = row[..n].iter().map(|&x| sign * x).collect();
+            self.is_solution(&candidate, bound).then_some(candidate)
+        })
+    }
+}
+
+pub(crate) fn dot_mod(row: &[i64], x: &[i64], q: i64) -> i64 {
+    row.iter().zip(x).fold(0, |acc, (&a, &s)| (acc + a * s).rem_euclid(q))
+}
+
+/// Basis of L⊥(A) = { x ∈ Z^m : A·x ≡ 0 (mod q) } for an `h × m` matrix `A` and prime `q`
+///
+/// Row-reduces A mod q; each non-pivot column f gives the kernel vector with
+/// x_f = 1 and the pivot coordinates solved for, and each pivot column p
+/// gives q·e_p. The result is an `m × m` basis of determinant q^rank(A).
+pub fn q_ary_lattice(a: &IntMatrix, q: i64) -> IntMatrix {
+    let m = a.cols();
+    let mut r = a.clone();
+    let pivots = row_reduce(&mut r, q);
+
+    let mut basis = IntMatrix::zero(m, m);
+    let mut next = 0;
+    for f in (0..m).filter(|c| !pivots.contains(c)) {
+        basis[(next, f)] = 1;
+        for (row, &p) in pivots.iter().enumerate() {
+            basis[(next, p)] = center(-r[(row, f)], q);
+        }
+        next += 1;
+    }
+    for &p in &pivots {
+        basis[(next, p)] = q;
+        next += 1;
+    }
+    basis
+}
+
+/// Brings `r` to reduced row echelon form mod a prime `q`, returning the pivot columns
+///
+/// Entries end up in [0, q).
+pub(crate) fn row_reduce(r: &mut IntMatrix, q: i64) -> Vec<usize> {
+    let (h, m) = (r.rows(), r.cols());
+    let q32 = i32::try_from(q).expect("Modulus too large");
+    for i in 0..h {
+        for j in 0..m {
+            r[(i, j)] = r[(i, j)].rem_euclid(q);
+        }
+    }
+
+    let mut pivots = Vec::new();
+    for col in 0..m {
+        let row = pivots.len();
+        if row == h {
+            break;
+        }
+        let Some(p) = (row..h).find(|&i| r[(i, col)] != 0) else { continue };
+        r.swap_rows(row, p);
+        let inv = ZqElement::new(r[(row, col)] as i32, q32).inverse().expect("q must be prime").value() as i64;
+        for j in 0..m {
+            r[(row, j)] = r[(row, j)] * inv % q;
+        }
+        for i in 0..h {
+            let factor = r[(i, col)];
+            if i != row && factor != 0 {
+                for j in 0..m {
+                    r[(i, j)] = (r[(i, j)] - factor * r[(row, j)]).rem_euclid(q);
+                }
+            }
+        }
+        pivots.push(col);
+    }
+    pivots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_generate_and_embedding() {
+        let mut rng = ChaCha20Rng::seed_from_u64(5);
+        let params = LatticeParams { q: 3329, n: 4, eta: 2, beta: 2, k: 2 };
+        let lwe = LweSamples::from_params(&params, 12, &mut rng);
+        assert_eq!((lwe.n(), lwe.m()), (8, 12));
+        assert!(lwe.secret.iter().chain(&lwe.error).all(|x| x.abs() <= 2));
+        assert_eq!(lwe.residual(&lwe.secret), lwe.error);
+        assert!(lwe.is_solution(&lwe.secret, 2));
+
+        // (s, e, 1) is an integer combination of the embedding basis
+        let basis = lwe.embedding();
+        let mut target: Vec<i64> = lwe.secret.iter().chain(&lwe.error).copied().collect();
+        target.push(1);
+        let mut coeffs = lwe.secret.clone();
+        for j in 0..lwe.m() {
+            // The y part is -(A·s)_j + q·k_j + b_j, which must equal e_j
+            let a_s: i64 = lwe.a.row(j).iter().zip(&lwe.secret).map(|(a, s)| a * s).sum();
+            coeffs.push((lwe.error[j] + a_s - lwe.b[j]) / lwe.q);
+        }
+        coeffs.push(1);
+        assert_eq!(basis.combine(&coeffs), target);
+    }
+
+    #[test]
+    fn test_q_ary_lattice() {
+        let mut rng = ChaCha20Rng::seed_from_u64(6);
+        let (h, m, q) = (3, 9, 97);
+        let rows: Vec<Vec<i64>> = (0..h).map(|_| (0..m).map(|_| rng.gen_range(0..q)).collect()).collect();
+        let a = IntMatrix::from_rows(&rows);
+        let mut basis = q_ary_lattice(&a, q);
+        let check = |basis: &IntMatrix| {
+            (0..m).all(|i| (0..h).all(|j| dot_mod(a.row(j), basis.row(i), q) == 0))
+        };
+        assert!(check(&basis));
+        // Determinant q^h: the Gram–Schmidt norms multiply to q^(2h)
+        let log_det: f64 = basis.gram_schmidt().norms.iter().map(|x| x.ln()).sum::<f64>() / 2.0;
+        assert!((log_det - h as f64 * (q as f64).ln()).abs() < 1e-6);
+
+        // Reduction finds a short SIS solution
+        basis.lll(LLL_DELTA);
+        assert!(check(&basis));
+        assert!(basis.row(0).iter().all(|x| x.abs() < 10), "{:?}", basis.row(0));
+    }
+
+    #[test]
+    fn test_recover_secret() {
+        let mut rng = ChaCha20Rng::seed_from_u64(7);
+        // LLL alone is enough in dimension 31
+        let lwe = LweSamples::generate(10, 20, 97, 1, &mut rng);
+        assert_eq!(lwe.recover_secret(0, 1), Some(lwe.secret.clone()));
+
+        // A larger instance needs BKZ
+        let lwe = LweSamples::generate(24, 30, 257, 2, &mut rng);
+        assert_eq!(lwe.recover_secret(15, 2), Some(lwe.secret.clone()));
+        // The wrong bound rejects every candidate
+        assert_eq!(lwe.recover_secret(0, -1), None);
+    }
+}
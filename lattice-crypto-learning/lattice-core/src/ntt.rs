@@ -1,49 +1,71 @@
+use alloc::{vec, vec::Vec};
 use crate::polynomial::Polynomial;
 use crate::params::PolyModulusInfo;
+use crate::simd::{self, Backend, Montgomery};
 use crate::zq::ZqElement;
 
 /// Represents precomputed values for Number Theoretic Transform
+///
+/// Two flavours of the negacyclic NTT over R_q = Z_q[X]/(X^n + 1) are supported:
+/// - if `psi` is a primitive 2n-th root of unity (e.g. Dilithium, q = 8380417, psi = 1753),
+///   X^n + 1 splits completely into linear factors and multiplication is pointwise;
+/// - if `psi` is only a primitive n-th root of unity (e.g. Kyber, q = 3329, psi = 17),
+///   X^n + 1 splits into n/2 quadratic factors X^2 - γ and the NTT stops one layer early.
+///   Multiplication in the NTT domain is then a "base multiplication" of degree-1 pairs.
 #[derive(Debug, Clone)]
 pub struct NTTParams {
     /// Modulus q
     pub q: i32,
     /// Degree of polynomial X^n + 1
     pub n: usize,
-    /// n-th primitive root of unity
+    /// Primitive 2n-th (complete NTT) or n-th (incomplete NTT) root of unity
     pub psi: i32,
-    /// Inverse of n modulo q
+    /// Inverse of the number of NTT slots modulo q (scaling factor of the inverse NTT)
     pub n_inv: i32,
-    /// Forward NTT precomputed roots (twiddle factors)
+    /// Forward NTT twiddle factors psi^bitrev(i), in bit-reversed order
     pub roots_of_unity: Vec<i32>,
-    /// Inverse NTT precomputed roots
+    /// Inverse NTT twiddle factors (element-wise inverses of `roots_of_unity`)
     pub inv_roots_of_unity: Vec<i32>,
     /// Barrett reduction precomputed factor
     pub barrett_factor: i64,
     /// Barrett reduction shift
     pub barrett_shift: u32,
+    /// Montgomery-form twiddles for the SIMD kernels
+    pub montgomery: Montgomery,
 }
 
 impl NTTParams {
     /// Creates precomputed NTT parameters for a given modulus q and degree n
-    /// Precondition: n is a power of 2
+    /// Precondition: n is a power of 2 and psi is a primitive 2n-th or n-th root of unity mod q
     pub fn new(q: i32, n: usize, psi: i32) -> Self {
         assert!(n.is_power_of_two(), "n must be a power of 2");
         
-        // Compute n_inv
-        let n_inv = self::mod_inverse(n as i32, q);
+        // psi^n = -1 means psi has order 2n (complete NTT),
+        // psi^(n/2) = -1 means psi has order n (incomplete NTT with quadratic factors)
+        let slots = if mod_pow(psi, n, q) == q - 1 {
+            n
+        } else if mod_pow(psi, n / 2, q) == q - 1 {
+            n / 2
+        } else {
+            panic!("psi must be a primitive 2n-th or n-th root of unity modulo q");
+        };
+        
+        // The inverse NTT scales by the number of butterfly layers, i.e. the number of slots
+        let n_inv = self::mod_inverse(slots as i32, q);
         
         // Precompute roots of unity
-        let roots_of_unity = self::precompute_roots(psi, n, q);
+        let roots_of_unity = self::precompute_roots(psi, slots, q);
         
         // Precompute inverse roots of unity
-        let psi_inv = self::mod_inverse(psi, q);
-        let inv_roots_of_unity = self::precompute_roots(psi_inv, n, q);
+        let inv_roots_of_unity = roots_of_unity.iter()
+            .map(|&root| self::mod_inverse(root, q))
+            .collect();
         
         // Precompute Barrett reduction factor
         let barrett_shift = 32; // Adjust as needed for performance
         let barrett_factor = ZqElement::barrett_factor(q, barrett_shift);
         
-        NTTParams {
+        let mut params = NTTParams {
             q,
             n,
             psi,
@@ -52,7 +74,20 @@ impl NTTParams {
             inv_roots_of_unity,
             barrett_factor,
             barrett_shift,
-        }
+            montgomery: Montgomery::default(),
+        };
+        params.montgomery = Montgomery::new(&params);
+        params
+    }
+    
+    /// Number of NTT slots: n for a complete NTT, n/2 when the NTT stops at quadratic factors
+    pub fn slots(&self) -> usize {
+        self.roots_of_unity.len()
+    }
+    
+    /// Returns true if the NTT splits X^n + 1 completely into linear factors
+    pub fn is_complete(&self) -> bool {
+        self.slots() == self.n
     }
 }
 
@@ -70,17 +105,12 @@ pub fn ntt_forward(poly: &Polynomial, params: &NTTParams) -> Polynomial {
         is_ntt_form: true,
     };
     
-    // Copy coefficients to work with
-    let mut coeffs: Vec<ZqElement> = poly.coeffs.clone();
-    
-    // Perform in-place FFT-like NTT
-    butterfly_ntt(&mut coeffs, params);
+    // Perform in-place FFT-like NTT on the plain coefficient values
+    let mut values = to_values(poly);
+    simd::ntt_forward(Backend::detect(), &mut values, params);
     
     // Return the transformed polynomial
-    Polynomial {
-        coeffs,
-        modulus_info: ntt_modulus_info,
-    }
+    from_values(&values, ntt_modulus_info)
 }
 
 /// Performs inverse Number Theoretic Transform (NTT) on a polynomial
@@ -99,46 +129,57 @@ pub fn ntt_inverse(poly: &Polynomial, params: &NTTParams) -> Polynomial {
         is_ntt_form: false,
     };
     
-    // Copy coefficients to work with
-    let mut coeffs: Vec<ZqElement> = poly.coeffs.clone();
-    
-    // Perform in-place inverse NTT
-    butterfly_intt(&mut coeffs, params);
+    // Perform in-place inverse NTT on the plain coefficient values
+    let mut values = to_values(poly);
+    simd::ntt_inverse(Backend::detect(), &mut values, params);
     
     // Return the transformed polynomial
-    Polynomial {
-        coeffs,
-        modulus_info: std_modulus_info,
-    }
+    from_values(&values, std_modulus_info)
 }
 
-/// Multiplies two polynomials in NTT domain (component-wise multiplication)
-pub fn ntt_pointwise_mul(poly1: &Polynomial, poly2: &Polynomial) -> Polynomial {
+/// Multiplies two polynomials in NTT domain
+///
+/// For a complete NTT this is component-wise multiplication. For an incomplete NTT
+/// (Kyber) each pair of coefficients represents a residue modulo X^2 - γ and pairs
+/// are multiplied with [`base_mul`].
+pub fn ntt_pointwise_mul(poly1: &Polynomial, poly2: &Polynomial, params: &NTTParams) -> Polynomial {
     assert!(poly1.modulus_info.is_ntt_form && poly2.modulus_info.is_ntt_form,
            "Both polynomials must be in NTT form for pointwise multiplication");
     assert_eq!(poly1.modulus_info.degree, poly2.modulus_info.degree,
               "Polynomials must have the same degree");
     assert_eq!(poly1.modulus_info.q, poly2.modulus_info.q,
               "Polynomials must have the same modulus");
+    assert_eq!(poly1.modulus_info.degree, params.n,
+              "Polynomial degree must match NTT params");
     
-    let n = poly1.modulus_info.degree;
-    let q = poly1.modulus_info.q;
-    let mut result_coeffs = Vec::with_capacity(n);
-    
-    for i in 0..n {
-        result_coeffs.push(poly1.coeffs[i] * poly2.coeffs[i]);
-    }
+    // For an incomplete NTT (Kyber) blocks of four coefficients share one twiddle
+    // factor ζ and split into the quadratic factors X^2 - ζ and X^2 + ζ
+    let mut values = vec![0; params.n];
+    simd::pointwise_mul(Backend::detect(), &to_values(poly1), &to_values(poly2), &mut values, params);
     
+    from_values(&values, poly1.modulus_info)
+}
+
+/// Extracts the canonical coefficient values for the SIMD kernels
+fn to_values(poly: &Polynomial) -> Vec<i32> {
+    poly.coeffs.iter().map(|c| c.value()).collect()
+}
+
+/// Rebuilds a polynomial from canonical coefficient values
+fn from_values(values: &[i32], modulus_info: PolyModulusInfo) -> Polynomial {
     Polynomial {
-        coeffs: result_coeffs,
-        modulus_info: PolyModulusInfo {
-            degree: n,
-            q,
-            is_ntt_form: true,
-        },
+        coeffs: values.iter().map(|&v| ZqElement::new(v, modulus_info.q)).collect(),
+        modulus_info,
     }
 }
 
+/// Multiplies (a0 + a1 X)(b0 + b1 X) modulo X^2 - γ
+pub fn base_mul(a: (ZqElement, ZqElement), b: (ZqElement, ZqElement), gamma: ZqElement) -> (ZqElement, ZqElement) {
+    let c0 = a.0 * b.0 + a.1 * b.1 * gamma;
+    let c1 = a.0 * b.1 + a.1 * b.0;
+    (c0, c1)
+}
+
 /// Implements polynomial multiplication using NTT
 /// a * b = InvNTT(NTT(a) ∘ NTT(b)) where ∘ is pointwise multiplication
 pub fn ntt_polynomial_mul(poly1: &Polynomial, poly2: &Polynomial, params: &NTTParams) -> Polynomial {
@@ -161,7 +202,7 @@ pub fn ntt_polynomial_mul(poly1: &Polynomial, poly2: &Polynomial, params: &NTTPa
     };
     
     // Pointwise multiplication in NTT domain
-    let ntt_product = ntt_pointwise_mul(&ntt_poly1, &ntt_poly2);
+    let ntt_product = ntt_pointwise_mul(&ntt_poly1, &ntt_poly2, params);
     
     // Inverse NTT to get the polynomial product
     ntt_inverse(&ntt_product, params)
@@ -169,12 +210,10 @@ pub fn ntt_polynomial_mul(poly1: &Polynomial, poly2: &Polynomial, params: &NTTPa
 
 /// Helper function to compute modular inverse using Extended Euclidean Algorithm
 fn mod_inverse(a: i32, m: i32) -> i32 {
-    let mut s = 0;
-    let mut old_s = 1;
-    let mut t = 1;
-    let mut old_t = 0;
-    let mut r = m;
-    let mut old_r = a;
+    let mut s = 0i64;
+    let mut old_s = 1i64;
+    let mut r = m as i64;
+    let mut old_r = ZqElement::normalize(a, m) as i64;
     
     while r != 0 {
         let quotient = old_r / r;
@@ -186,53 +225,42 @@ fn mod_inverse(a: i32, m: i32) -> i32 {
         let temp = s;
         s = old_s - quotient * s;
         old_s = temp;
-        
-        let temp = t;
-        t = old_t - quotient * t;
-        old_t = temp;
     }
     
     // Ensure the result is positive
-    let result = if old_s < 0 { old_s + m } else { old_s };
-    result
+    old_s.rem_euclid(m as i64) as i32
 }
 
 /// Helper function to compute modular exponentiation
 fn mod_pow(base: i32, exponent: usize, modulus: i32) -> i32 {
-    let mut result = 1;
-    let mut base = base % modulus;
+    let mut result = 1i64;
+    let mut base = ZqElement::normalize(base, modulus) as i64;
+    let modulus = modulus as i64;
     let mut exp = exponent;
     
     while exp > 0 {
         if exp & 1 == 1 {
-            result = (result as i64 * base as i64) as i32 % modulus;
+            result = result * base % modulus;
         }
-        base = (base as i64 * base as i64) as i32 % modulus;
+        base = base * base % modulus;
         exp >>= 1;
     }
     
-    result
+    result as i32
 }
 
-/// Precomputes roots of unity for NTT
-fn precompute_roots(psi: i32, n: usize, q: i32) -> Vec<i32> {
-    let mut roots = Vec::with_capacity(n);
-    let log_n = n.trailing_zeros();
-    
-    // psi^(2n/4), psi^(2n/8), ... - powers of psi for butterfly operations
-    for i in 0..n {
-        // Bit-reversed order for more efficient in-place NTT
-        let j = bit_reverse(i, log_n);
-        let power = (j * n / 2) % n;
-        let root = mod_pow(psi, power, q);
-        roots.push(root);
-    }
-    
-    roots
+/// Precomputes the twiddle factors psi^bitrev(i) for i in 0..slots
+fn precompute_roots(psi: i32, slots: usize, q: i32) -> Vec<i32> {
+    let log_slots = slots.trailing_zeros();
+    
+    // Bit-reversed order so that each butterfly layer reads its twiddles contiguously
+    (0..slots)
+        .map(|i| mod_pow(psi, bit_reverse(i, log_slots), q))
+        .collect()
 }
 
 /// Implements the bit-reversal permutation for efficient in-place NTT
-fn bit_reverse(mut index: usize, bits: u32) -> usize {
+fn bit_reverse(index: usize, bits: u32) -> usize {
     let mut reversed = 0;
     
     for i in 0..bits {
@@ -242,96 +270,6 @@ fn bit_reverse(mut index: usize, bits: u32) -> usize {
     reversed
 }
 
-/// Performs in-place forward NTT using the cooley-tukey algorithm
-fn butterfly_ntt(coeffs: &mut Vec<ZqElement>, params: &NTTParams) {
-    let n = params.n;
-    let q = params.q;
-    let log_n = n.trailing_zeros() as usize;
-    
-    // Bit-reversal permutation
-    for i in 0..n {
-        let j = bit_reverse(i, log_n as u32);
-        if i < j {
-            coeffs.swap(i, j);
-        }
-    }
-    
-    // NTT butterfly operations
-    let mut len = 2;
-    for _ in 0..log_n {
-        let half_len = len / 2;
-        
-        for j in (0..n).step_by(len) {
-            for i in 0..half_len {
-                let odd_idx = j + i + half_len;
-                let even_idx = j + i;
-                
-                let odd = coeffs[odd_idx];
-                let even = coeffs[even_idx];
-                
-                // Get twiddle factor
-                let factor = ZqElement::new(params.roots_of_unity[half_len + i], q);
-                
-                // Butterfly operation: (even, odd) -> (even + odd*factor, even - odd*factor)
-                let temp = odd * factor;
-                coeffs[odd_idx] = even - temp;
-                coeffs[even_idx] = even + temp;
-            }
-        }
-        
-        len *= 2;
-    }
-}
-
-/// Performs in-place inverse NTT using the gentlemen-sande algorithm
-fn butterfly_intt(coeffs: &mut Vec<ZqElement>, params: &NTTParams) {
-    let n = params.n;
-    let q = params.q;
-    let log_n = n.trailing_zeros() as usize;
-    
-    // Inverse NTT butterfly operations
-    let mut len = n;
-    for _ in 0..log_n {
-        let half_len = len / 2;
-        
-        for j in (0..n).step_by(len) {
-            for i in 0..half_len {
-                let odd_idx = j + i + half_len;
-                let even_idx = j + i;
-                
-                let even = coeffs[even_idx];
-                let odd = coeffs[odd_idx];
-                
-                // Butterfly operation: (even, odd) -> ((even + odd)/2, (even - odd)/2 * factor)
-                coeffs[even_idx] = even + odd;
-                
-                let diff = even - odd;
-                
-                // Get twiddle factor
-                let factor = ZqElement::new(params.inv_roots_of_unity[half_len + i], q);
-                
-                coeffs[odd_idx] = diff * factor;
-            }
-        }
-        
-        len /= 2;
-    }
-    
-    // Bit-reversal permutation
-    for i in 0..n {
-        let j = bit_reverse(i, log_n as u32);
-        if i < j {
-            coeffs.swap(i, j);
-        }
-    }
-    
-    // Multiply by n^-1 mod q
-    let n_inv = ZqElement::new(params.n_inv, q);
-    for i in 0..n {
-        coeffs[i] = coeffs[i] * n_inv;
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -342,8 +280,8 @@ mod tests {
         let q = 97;
         
         // Find a 2n-th primitive root of unity
-        // For q = 97 and n = 8, we can use psi = 13
-        let psi = 13;
+        // For q = 97 and n = 8, psi = 8 has order 16 (8^8 ≡ -1 mod 97)
+        let psi = 8;
         
         let modulus_info = PolyModulusInfo {
             degree: n,
@@ -404,6 +342,27 @@ mod tests {
         }
     }
     
+    #[test]
+    fn test_incomplete_ntt_multiplication() {
+        // For q = 17 and n = 8, psi = 9 has order 8 (9^4 ≡ -1 mod 17), mirroring
+        // Kyber's q = 3329 and psi = 17: the NTT stops at quadratic factors
+        let n = 8;
+        let q = 17;
+        let modulus_info = PolyModulusInfo { degree: n, q, is_ntt_form: false };
+        let ntt_params = NTTParams::new(q, n, 9);
+        assert!(!ntt_params.is_complete());
+        
+        let poly1 = create_test_poly(&[1, 2, 3, 4, 5, 6, 7, 8], modulus_info);
+        let poly2 = create_test_poly(&[8, 7, 6, 5, 4, 3, 2, 1], modulus_info);
+        
+        let ntt_product = ntt_polynomial_mul(&poly1, &poly2, &ntt_params);
+        let schoolbook_product = poly1.schoolbook_mul(&poly2);
+        assert_eq!(ntt_product.coeffs, schoolbook_product.coeffs);
+        
+        let reconstructed = ntt_inverse(&ntt_forward(&poly1, &ntt_params), &ntt_params);
+        assert_eq!(reconstructed.coeffs, poly1.coeffs);
+    }
+    
     #[test]
     fn test_bit_reversal() {
         assert_eq!(bit_reverse(0, 3), 0);
@@ -75,6 +75,8 @@ pub mod dilithium {
     /// Dilithium common parameters
     pub const Q: i32 = 8380417; // 2^23 - 2^13 + 1
     pub const N: usize = 256;   // Degree of polynomial X^n + 1
+    /// Number of dropped bits of t (t = t1 * 2^d + t0)
+    pub const D: usize = 13;
     
     /// Represents Dilithium-specific parameters
     #[derive(Debug, Clone)]
@@ -85,13 +87,13 @@ pub mod dilithium {
         pub l: usize,
         /// Bound for signature γ1
         pub gamma1: i32,
-        /// Bound for compression γ2 (2^γ2)
+        /// Low-order rounding range γ2 used by HighBits/LowBits
         pub gamma2: i32,
         /// Rejection parameter β = τη
         pub beta: i32,
-        /// Challenge space parameter τ
+        /// Number of ±1 coefficients in the challenge polynomial c
         pub tau: usize,
-        /// Number of 1's in challenge polynomial
+        /// Maximum number of 1's in the hint vector h
         pub omega: usize,
     }
 
@@ -107,7 +109,7 @@ pub mod dilithium {
             },
             l: 4,
             gamma1: 131072,  // 2^17
-            gamma2: 95,     // From eq (127)
+            gamma2: (Q - 1) / 88,  // 95232
             beta: 78,    // τ * η = 39 * 2 = 78
             tau: 39,
             omega: 80,
@@ -126,7 +128,7 @@ pub mod dilithium {
             },
             l: 5,
             gamma1: 524288,  // 2^19
-            gamma2: 261,
+            gamma2: (Q - 1) / 32,  // 261888
             beta: 196,   // τ * η = 49 * 4 = 196
             tau: 49,
             omega: 55,
@@ -145,7 +147,7 @@ pub mod dilithium {
             },
             l: 7,
             gamma1: 524288,  // 2^19
-            gamma2: 147,
+            gamma2: (Q - 1) / 32,  // 261888
             beta: 120,   // τ * η = 60 * 2 = 120
             tau: 60,
             omega: 75,
@@ -0,0 +1,237 @@
+use alloc::vec::Vec;
+
+use ::pkcs8::der::asn1::{BitStringRef, ContextSpecificRef, OctetStringRef};
+use ::pkcs8::der::{AnyRef, Decode, Encode, Reader, SliceReader, Tag, TagMode, TagNumber, Tagged};
+use ::pkcs8::spki::{self, AlgorithmIdentifierRef, SubjectPublicKeyInfoRef};
+use ::pkcs8::{Document, ObjectIdentifier, PrivateKeyInfo, SecretDocument};
+
+// SubjectPublicKeyInfo and PKCS#8 OneAsymmetricKey encodings shared by
+// draft-ietf-lamps-kyber-certificates and draft-ietf-lamps-dilithium-certificates.
+// Both drafts use the same private key CHOICE and differ only in their OIDs and
+// seed lengths, which the key crates supply. The external crate is spelled
+// `::pkcs8` to avoid a clash with this module.
+
+/// The ML-KEM-PrivateKey / ML-DSA-PrivateKey CHOICE carried inside the PKCS#8
+/// privateKey OCTET STRING
+///
+/// ```text
+/// PrivateKey ::= CHOICE {
+///   seed         [0] IMPLICIT OCTET STRING,
+///   expandedKey  OCTET STRING,
+///   both         SEQUENCE { seed OCTET STRING, expandedKey OCTET STRING } }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrivateKeyFormat {
+    /// Only the seed (64 bytes d || z for ML-KEM, 32 bytes ξ for ML-DSA)
+    Seed,
+    /// Only the expanded private key
+    ExpandedKey,
+    /// Both the seed and the expanded key
+    Both,
+}
+
+/// A private key that can be encoded from its seed or in expanded form
+pub trait SeedPrivateKey: Sized {
+    /// OID of the key's parameter set
+    fn algorithm_oid() -> ObjectIdentifier;
+    /// The seed, if the key was generated from or decoded with one
+    fn seed_bytes(&self) -> Option<&[u8]>;
+    /// The expanded private key encoding
+    fn expanded_bytes(&self) -> &[u8];
+    /// Rebuilds the key from a seed, or `None` if the seed has the wrong length
+    fn from_seed_bytes(seed: &[u8]) -> Option<Self>;
+    /// Decodes an expanded private key, or `None` if it is malformed
+    fn from_expanded_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+/// Checks that an AlgorithmIdentifier names `oid` and has absent parameters
+pub fn check_algorithm(algorithm: &AlgorithmIdentifierRef<'_>, oid: ObjectIdentifier) -> spki::Result<()> {
+    if algorithm.oid != oid {
+        return Err(spki::Error::OidUnknown { oid: algorithm.oid });
+    }
+    if algorithm.parameters.is_some() {
+        return Err(spki::Error::KeyMalformed);
+    }
+
+    Ok(())
+}
+
+/// Encodes a private key as PKCS#8 DER using the given private key format
+///
+/// The seed and both forms need the seed, so they fail for keys decoded
+/// from an expanded encoding.
+pub fn encode_private_key<K: SeedPrivateKey>(key: &K, format: PrivateKeyFormat) -> ::pkcs8::Result<SecretDocument> {
+    let expanded = OctetStringRef::new(key.expanded_bytes())?;
+    let seed = match (format, key.seed_bytes()) {
+        (PrivateKeyFormat::ExpandedKey, _) => None,
+        (_, Some(seed)) => Some(OctetStringRef::new(seed)?),
+        (_, None) => return Err(::pkcs8::Error::KeyMalformed),
+    };
+
+    let private_key = match (format, seed) {
+        (PrivateKeyFormat::Seed, Some(seed)) => encode_seed(&seed)?,
+        (PrivateKeyFormat::Both, Some(seed)) => {
+            let mut inner = seed.to_der()?;
+            inner.extend_from_slice(&expanded.to_der()?);
+            AnyRef::new(Tag::Sequence, &inner)?.to_der()?
+        }
+        _ => expanded.to_der()?,
+    };
+
+    encode_private_key_info(K::algorithm_oid(), &private_key)
+}
+
+/// Encodes the seed alternative `[0] IMPLICIT OCTET STRING`
+fn encode_seed(seed: &OctetStringRef<'_>) -> ::pkcs8::der::Result<Vec<u8>> {
+    ContextSpecificRef { tag_number: TagNumber::N0, tag_mode: TagMode::Implicit, value: seed }.to_der()
+}
+
+/// Wraps an encoded private key CHOICE in a PKCS#8 OneAsymmetricKey
+fn encode_private_key_info(oid: ObjectIdentifier, private_key: &[u8]) -> ::pkcs8::Result<SecretDocument> {
+    let algorithm = AlgorithmIdentifierRef { oid, parameters: None };
+    SecretDocument::try_from(PrivateKeyInfo::new(algorithm, private_key))
+}
+
+/// Encodes a bare seed as a PKCS#8 private key in the seed-only form
+pub fn encode_seed_private_key(oid: ObjectIdentifier, seed: &[u8]) -> ::pkcs8::Result<SecretDocument> {
+    encode_private_key_info(oid, &encode_seed(&OctetStringRef::new(seed)?)?)
+}
+
+/// Decodes a PKCS#8 private key in any of the three CHOICE forms
+///
+/// In the both form the expanded key must match the one derived from the seed.
+pub fn decode_private_key<K: SeedPrivateKey>(info: PrivateKeyInfo<'_>) -> ::pkcs8::Result<K> {
+    check_algorithm(&info.algorithm, K::algorithm_oid())?;
+
+    let choice = AnyRef::from_der(info.private_key)?;
+    let (seed, expanded) = match choice.tag() {
+        Tag::ContextSpecific { constructed: false, number: TagNumber::N0 } => (Some(choice.value()), None),
+        Tag::OctetString => (None, Some(choice.value())),
+        Tag::Sequence => {
+            let mut reader = SliceReader::new(choice.value())?;
+            let seed = OctetStringRef::decode(&mut reader)?;
+            let expanded = OctetStringRef::decode(&mut reader)?;
+            reader.finish(())?;
+            (Some(seed.as_bytes()), Some(expanded.as_bytes()))
+        }
+        _ => return Err(::pkcs8::Error::KeyMalformed),
+    };
+
+    let from_seed = match seed {
+        Some(seed) => Some(K::from_seed_bytes(seed).ok_or(::pkcs8::Error::KeyMalformed)?),
+        None => None,
+    };
+
+    match (from_seed, expanded) {
+        (Some(key), Some(expanded)) if key.expanded_bytes() != expanded => Err(::pkcs8::Error::KeyMalformed),
+        (Some(key), _) => Ok(key),
+        (None, Some(expanded)) => K::from_expanded_bytes(expanded).ok_or(::pkcs8::Error::KeyMalformed),
+        (None, None) => Err(::pkcs8::Error::KeyMalformed),
+    }
+}
+
+/// Encodes a raw public key as SubjectPublicKeyInfo DER with absent parameters
+pub fn encode_public_key(oid: ObjectIdentifier, public_key: &[u8]) -> spki::Result<Document> {
+    let info = SubjectPublicKeyInfoRef {
+        algorithm: AlgorithmIdentifierRef { oid, parameters: None },
+        subject_public_key: BitStringRef::from_bytes(public_key)?,
+    };
+
+    Ok(Document::encode_msg(&info)?)
+}
+
+/// Returns the raw public key of a SubjectPublicKeyInfo that names `oid`
+pub fn decode_public_key<'a>(info: &SubjectPublicKeyInfoRef<'a>, oid: ObjectIdentifier) -> spki::Result<&'a [u8]> {
+    check_algorithm(&info.algorithm, oid)?;
+    info.subject_public_key.as_bytes().ok_or(spki::Error::KeyMalformed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOY_OID: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.99999.1");
+
+    /// Toy key whose expanded form is its 4-byte seed repeated twice
+    #[derive(Debug, PartialEq)]
+    struct ToyKey {
+        seed: Option<[u8; 4]>,
+        expanded: [u8; 8],
+    }
+
+    impl SeedPrivateKey for ToyKey {
+        fn algorithm_oid() -> ObjectIdentifier {
+            TOY_OID
+        }
+
+        fn seed_bytes(&self) -> Option<&[u8]> {
+            self.seed.as_ref().map(|seed| seed.as_slice())
+        }
+
+        fn expanded_bytes(&self) -> &[u8] {
+            &self.expanded
+        }
+
+        fn from_seed_bytes(seed: &[u8]) -> Option<Self> {
+            let seed: [u8; 4] = seed.try_into().ok()?;
+            let mut expanded = [0u8; 8];
+            expanded[..4].copy_from_slice(&seed);
+            expanded[4..].copy_from_slice(&seed);
+            Some(Self { seed: Some(seed), expanded })
+        }
+
+        fn from_expanded_bytes(bytes: &[u8]) -> Option<Self> {
+            Some(Self { seed: None, expanded: bytes.try_into().ok()? })
+        }
+    }
+
+    fn decode(der: &SecretDocument) -> ::pkcs8::Result<ToyKey> {
+        decode_private_key(PrivateKeyInfo::try_from(der.as_bytes())?)
+    }
+
+    #[test]
+    fn test_private_key_formats_roundtrip() {
+        let key = ToyKey::from_seed_bytes(&[1, 2, 3, 4]).unwrap();
+
+        for format in [PrivateKeyFormat::Seed, PrivateKeyFormat::Both] {
+            assert_eq!(decode(&encode_private_key(&key, format).unwrap()).unwrap(), key);
+        }
+        let expanded = decode(&encode_private_key(&key, PrivateKeyFormat::ExpandedKey).unwrap()).unwrap();
+        assert_eq!(expanded.expanded, key.expanded);
+        assert_eq!(expanded.seed, None);
+
+        // Without a seed only the expanded form can be written
+        assert!(encode_private_key(&expanded, PrivateKeyFormat::Seed).is_err());
+        assert!(encode_private_key(&expanded, PrivateKeyFormat::Both).is_err());
+
+        let seed_only = encode_seed_private_key(TOY_OID, &[1, 2, 3, 4]).unwrap();
+        assert_eq!(seed_only.as_bytes(), encode_private_key(&key, PrivateKeyFormat::Seed).unwrap().as_bytes());
+    }
+
+    #[test]
+    fn test_malformed_private_keys_rejected() {
+        // A both encoding whose expanded key does not match the seed
+        let seed = OctetStringRef::new(&[1, 2, 3, 4]).unwrap();
+        let mut inner = seed.to_der().unwrap();
+        inner.extend_from_slice(&OctetStringRef::new(&[0u8; 8]).unwrap().to_der().unwrap());
+        let both = AnyRef::new(Tag::Sequence, &inner).unwrap().to_der().unwrap();
+        assert!(decode(&encode_private_key_info(TOY_OID, &both).unwrap()).is_err());
+
+        // A seed of the wrong length
+        assert!(decode(&encode_seed_private_key(TOY_OID, &[1, 2, 3]).unwrap()).is_err());
+
+        // A different algorithm
+        let other = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.99999.2");
+        assert!(decode(&encode_seed_private_key(other, &[1, 2, 3, 4]).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_public_key_roundtrip() {
+        let der = encode_public_key(TOY_OID, &[9u8; 16]).unwrap();
+        let info = SubjectPublicKeyInfoRef::try_from(der.as_bytes()).unwrap();
+        assert_eq!(decode_public_key(&info, TOY_OID).unwrap(), [9u8; 16]);
+
+        let other = ObjectIdentifier::new_unwrap("1.3.6.1.4.1.99999.2");
+        assert!(matches!(decode_public_key(&info, other), Err(spki::Error::OidUnknown { .. })));
+    }
+}
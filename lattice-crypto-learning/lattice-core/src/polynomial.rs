@@ -1,5 +1,6 @@
-use std::ops::{Add, Sub, Neg};
-use std::fmt;
+use alloc::{vec, vec::Vec};
+use core::ops::{Add, Sub, Neg};
+use core::fmt;
 use crate::params::PolyModulusInfo;
 use crate::zq::ZqElement;
 
@@ -97,72 +98,67 @@ impl Polynomial {
         result
     }
     
-    /// Converts a polynomial to its byte representation
+    /// Converts a polynomial to its byte representation (ByteEncode_d in FIPS 203)
+    ///
+    /// Coefficients are packed as little-endian `coeff_bits`-bit integers into a
+    /// contiguous bit stream, so a degree-n polynomial takes ⌈n·d/8⌉ bytes.
     pub fn to_bytes(&self, coeff_bits: usize) -> Vec<u8> {
         let n = self.modulus_info.degree;
-        let mut bytes = Vec::new();
-        let coeff_bytes = (coeff_bits + 7) / 8;
+        let mut bytes = vec![0u8; (n * coeff_bits).div_ceil(8)];
         
-        // Reserve space for all coefficients
-        bytes.reserve(n * coeff_bytes);
-        
-        // Fill in bytes with coefficient values
-        for coeff in &self.coeffs {
-            let mut coeff_val = coeff.value() as u32;
-            for _ in 0..coeff_bytes {
-                bytes.push((coeff_val & 0xFF) as u8);
-                coeff_val >>= 8;
+        // Fill in bytes with coefficient values, least significant bit first
+        for (i, coeff) in self.coeffs.iter().enumerate() {
+            let coeff_val = coeff.value() as u32;
+            for b in 0..coeff_bits {
+                let bit_index = i * coeff_bits + b;
+                bytes[bit_index / 8] |= (((coeff_val >> b) & 1) as u8) << (bit_index % 8);
             }
         }
         
         bytes
     }
     
-    /// Creates a polynomial from its byte representation
+    /// Creates a polynomial from its byte representation (ByteDecode_d in FIPS 203)
     pub fn from_bytes(bytes: &[u8], modulus_info: PolyModulusInfo, coeff_bits: usize) -> Self {
         let n = modulus_info.degree;
         let q = modulus_info.q;
-        let coeff_bytes = (coeff_bits + 7) / 8;
         
         // If not enough bytes, return a zero polynomial
-        if bytes.len() < n * coeff_bytes {
+        if bytes.len() < (n * coeff_bits).div_ceil(8) {
             return Polynomial::zero(modulus_info);
         }
         
         let mut coeffs = Vec::with_capacity(n);
         
-        // Extract coefficients from bytes
+        // Extract coefficients from the bit stream
         for i in 0..n {
-            let offset = i * coeff_bytes;
             let mut coeff_val = 0i32;
             
-            for j in 0..coeff_bytes {
-                if offset + j < bytes.len() {
-                    coeff_val |= (bytes[offset + j] as i32) << (8 * j);
-                }
+            for b in 0..coeff_bits {
+                let bit_index = i * coeff_bits + b;
+                coeff_val |= (((bytes[bit_index / 8] >> (bit_index % 8)) & 1) as i32) << b;
             }
             
-            // Mask to the appropriate number of bits
-            let mask = (1 << coeff_bits) - 1;
-            coeff_val &= mask;
-            
             coeffs.push(ZqElement::new(coeff_val, q));
         }
         
         Polynomial::new(coeffs, modulus_info)
     }
     
-    /// Compresses polynomial coefficients from q bits to p bits
+    /// Compresses polynomial coefficients from Z_q to d = `p` bits
+    ///
+    /// Compress_d(x) = ⌈(2^d / q) · x⌋ mod 2^d (Definition 3, [x]_{q→2^d}).
+    /// The result lives in Z_{2^d}.
     pub fn compress(&self, p: usize) -> Self {
         let q = self.modulus_info.q as i64;
         let mut result = self.clone();
-        let mod_size = 1 << p;
+        let mod_size = 1i64 << p;
         
         for i in 0..self.coeffs.len() {
             let x = self.coeffs[i].value() as i64;
-            // Compute (p/q) * x rounded
-            let compressed = ((((1 << p) as i64) * x + (q >> 1)) / q) as i32;
-            result.coeffs[i] = ZqElement::new(compressed, (1 << p) as i32);
+            // Compute (2^d/q) * x rounded to the nearest integer
+            let compressed = (((mod_size * x + (q >> 1)) / q) % mod_size) as i32;
+            result.coeffs[i] = ZqElement::new(compressed, mod_size as i32);
         }
         
         // Update modulus info to reflect the new coefficient range
@@ -171,15 +167,17 @@ impl Polynomial {
         result
     }
     
-    /// Decompresses polynomial coefficients from p bits to q bits
+    /// Decompresses polynomial coefficients from Z_{2^d} back to Z_q
+    ///
+    /// Decompress_d(y) = ⌈(q / 2^d) · y⌋, so that |Decompress_d(Compress_d(x)) - x| ≤ ⌈q / 2^{d+1}⌋.
     pub fn decompress(&self, q: i32) -> Self {
-        // We don't need to compute p as a separate variable
+        // The current modulus is 2^d
         let p_val = self.modulus_info.q;
         let mut result = self.clone();
         
         for i in 0..self.coeffs.len() {
             let x = self.coeffs[i].value() as i64;
-            // Compute (q/p) * x
+            // Compute (q/2^d) * x rounded to the nearest integer
             let decompressed = ((q as i64 * x + (p_val as i64 >> 1)) / p_val as i64) as i32;
             result.coeffs[i] = ZqElement::new(decompressed, q);
         }
@@ -424,7 +422,7 @@ mod tests {
         };
         
         let q = modulus.q;
-        let mut coeffs = vec![
+        let coeffs = vec![
             ZqElement::new(2, q),
             ZqElement::new(16, q), // This is -1 mod 17
             ZqElement::new(5, q),
@@ -0,0 +1,668 @@
+//! Lattice reduction: Gram–Schmidt, LLL and BKZ over integer bases
+//!
+//! A basis is an [`IntMatrix`] whose rows are the basis vectors. The
+//! Gram–Schmidt orthogonalization is available exactly, over [`Rational`]s,
+//! for inspecting small examples, and in floating point, which is what the
+//! reduction algorithms use:
+//!
+//! - [`IntMatrix::lll`] is the textbook LLL algorithm with parameter δ,
+//!   recomputing each vector's Gram–Schmidt data from exact inner products
+//!   when it is visited.
+//! - [`IntMatrix::bkz`] runs tours of Schnorr–Euchner enumeration over
+//!   blocks of β consecutive projected vectors and inserts any vector
+//!   shorter than the block's first one, followed by LLL.
+//!
+//! Both are meant for the toy dimensions (up to a hundred or so) in which
+//! attacks can be demonstrated, not for serious cryptanalysis.
+
+use core::cmp::Ordering;
+use core::fmt;
+use core::ops::{Add, Div, Index, IndexMut, Mul, Neg, Sub};
+
+/// Size reduction leaves |μ_ij| ≤ η; slightly above 1/2 so rounding errors cannot make it cycle
+const ETA: f64 = 0.51;
+/// A block's first vector is replaced only if the new one is shorter by this factor (squared)
+const BKZ_IMPROVEMENT: f64 = 0.99;
+
+/// A dense integer matrix, stored row by row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntMatrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<i64>,
+}
+
+impl IntMatrix {
+    /// The `rows × cols` zero matrix
+    pub fn zero(rows: usize, cols: usize) -> Self {
+        Self { rows, cols, data: vec![0; rows * cols] }
+    }
+
+    /// The `n × n` identity matrix
+    pub fn identity(n: usize) -> Self {
+        let mut m = Self::zero(n, n);
+        for i in 0..n {
+            m[(i, i)] = 1;
+        }
+        m
+    }
+
+    /// Builds a matrix from its rows, which must all have the same length
+    pub fn from_rows(rows: &[Vec<i64>]) -> Self {
+        let cols = rows.first().map_or(0, Vec::len);
+        assert!(rows.iter().all(|r| r.len() == cols), "Rows have different lengths");
+        Self { rows: rows.len(), cols, data: rows.concat() }
+    }
+
+    /// Number of rows
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Number of columns
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    /// Row `i`
+    pub fn row(&self, i: usize) -> &[i64] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    /// Mutable row `i`
+    pub fn row_mut(&mut self, i: usize) -> &mut [i64] {
+        &mut self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    /// Swaps rows `i` and `j`
+    pub fn swap_rows(&mut self, i: usize, j: usize) {
+        for c in 0..self.cols {
+            self.data.swap(i * self.cols + c, j * self.cols + c);
+        }
+    }
+
+    /// Row `i` += `factor` · row `j`
+    pub fn add_row_multiple(&mut self, i: usize, j: usize, factor: i64) {
+        for c in 0..self.cols {
+            let v = self[(j, c)];
+            self[(i, c)] += factor * v;
+        }
+    }
+
+    /// The transpose
+    pub fn transpose(&self) -> Self {
+        let mut t = Self::zero(self.cols, self.rows);
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                t[(j, i)] = self[(i, j)];
+            }
+        }
+        t
+    }
+
+    /// Matrix product `self · other`
+    pub fn mul(&self, other: &Self) -> Self {
+        assert_eq!(self.cols, other.rows, "Dimension mismatch");
+        let mut product = Self::zero(self.rows, other.cols);
+        for i in 0..self.rows {
+            for k in 0..self.cols {
+                let a = self[(i, k)];
+                if a != 0 {
+                    for j in 0..other.cols {
+                        product[(i, j)] += a * other[(k, j)];
+                    }
+                }
+            }
+        }
+        product
+    }
+
+    /// Inner product of rows `i` and `j`
+    pub fn row_dot(&self, i: usize, j: usize) -> i128 {
+        dot(self.row(i), self.row(j))
+    }
+
+    /// Squared Euclidean norm of row `i`
+    pub fn row_norm_squared(&self, i: usize) -> i128 {
+        self.row_dot(i, i)
+    }
+
+    /// Exact Gram–Schmidt orthogonalization of the rows
+    ///
+    /// Intermediate values grow quickly; this is for small matrices.
+    pub fn gram_schmidt_exact(&self) -> ExactGramSchmidt {
+        let n = self.rows;
+        let mut mu = vec![vec![Rational::zero(); n]; n];
+        let mut norms = vec![Rational::zero(); n];
+        for i in 0..n {
+            // r_ij = <b_i, b_j> - Σ_k μ_jk r_ik, μ_ij = r_ij / B_j
+            let mut r = vec![Rational::zero(); i];
+            for j in 0..i {
+                let mut rij = Rational::integer(self.row_dot(i, j));
+                for k in 0..j {
+                    rij = rij - mu[j][k] * r[k];
+                }
+                r[j] = rij;
+                mu[i][j] = rij / norms[j];
+            }
+            let mut bi = Rational::integer(self.row_norm_squared(i));
+            for j in 0..i {
+                bi = bi - mu[i][j] * r[j];
+            }
+            mu[i][i] = Rational::integer(1);
+            norms[i] = bi;
+        }
+        ExactGramSchmidt { mu, norms }
+    }
+
+    /// Floating-point Gram–Schmidt orthogonalization of the rows
+    pub fn gram_schmidt(&self) -> GramSchmidt {
+        let mut gso = GramSchmidt { mu: vec![vec![0.0; self.rows]; self.rows], norms: vec![0.0; self.rows] };
+        for i in 0..self.rows {
+            gso.update_row(self, i);
+        }
+        gso
+    }
+
+    /// Whether the rows are size-reduced (|μ_ij| ≤ 0.51) and satisfy the Lovász condition for `delta`
+    pub fn is_lll_reduced(&self, delta: f64) -> bool {
+        let gso = self.gram_schmidt();
+        (0..self.rows).all(|i| (0..i).all(|j| gso.mu[i][j].abs() <= ETA))
+            && (1..self.rows).all(|k| gso.norms[k] >= (delta - gso.mu[k][k - 1].powi(2)) * gso.norms[k - 1] * (1.0 - 1e-9))
+    }
+
+    /// LLL-reduces the rows in place; they must be linearly independent
+    ///
+    /// `delta` is the Lovász parameter in (1/4, 1); 0.99 is the usual choice.
+    pub fn lll(&mut self, delta: f64) {
+        assert!(delta > 0.25 && delta < 1.0, "LLL needs 1/4 < δ < 1");
+        let n = self.rows;
+        if n < 2 {
+            return;
+        }
+        let mut gso = GramSchmidt { mu: vec![vec![0.0; n]; n], norms: vec![0.0; n] };
+        gso.update_row(self, 0);
+        let mut valid = 1;
+        let mut k = 1;
+        while k < n {
+            for i in valid..k {
+                gso.update_row(self, i);
+            }
+            valid = k;
+            self.size_reduce(&mut gso, k);
+
+            let mu = gso.mu[k][k - 1];
+            if gso.norms[k] < (delta - mu * mu) * gso.norms[k - 1] {
+                self.swap_rows(k, k - 1);
+                valid = k - 1;
+                k = (k - 1).max(1);
+            } else {
+                k += 1;
+            }
+        }
+    }
+
+    /// Size-reduces row `k` against rows `0..k`, leaving its Gram–Schmidt data current
+    fn size_reduce(&mut self, gso: &mut GramSchmidt, k: usize) {
+        loop {
+            gso.update_row(self, k);
+            let mut changed = false;
+            for j in (0..k).rev() {
+                if gso.mu[k][j].abs() > ETA {
+                    let r = gso.mu[k][j].round();
+                    self.add_row_multiple(k, j, -(r as i64));
+                    for l in 0..j {
+                        gso.mu[k][l] -= r * gso.mu[j][l];
+                    }
+                    gso.mu[k][j] -= r;
+                    changed = true;
+                }
+            }
+            // A second pass catches what floating-point error let through
+            if !changed {
+                return;
+            }
+        }
+    }
+
+    /// BKZ-reduces the rows in place with block size `beta`
+    ///
+    /// Runs LLL with `delta`, then tours until one changes nothing or
+    /// `max_tours` is reached. Returns the number of tours run.
+    pub fn bkz(&mut self, beta: usize, delta: f64, max_tours: usize) -> usize {
+        self.lll(delta);
+        let n = self.rows;
+        if beta < 2 {
+            return 0;
+        }
+        for tour in 1..=max_tours {
+            let mut changed = false;
+            for start in 0..n.saturating_sub(1) {
+                let end = (start + beta).min(n);
+                let gso = self.gram_schmidt();
+                let radius = BKZ_IMPROVEMENT * gso.norms[start];
+                if let Some(coeffs) = enumerate(&gso, start, end, radius) {
+                    self.insert(start, &coeffs);
+                    self.lll(delta);
+                    changed = true;
+                }
+            }
+            if !changed {
+                return tour;
+            }
+        }
+        max_tours
+    }
+
+    /// Makes Σ coeffs[i]·b_(start+i) the row at `start`, by a unimodular transformation
+    ///
+    /// Runs Euclid's algorithm on the coefficients, updating the rows so the
+    /// combination is preserved, until a single coefficient ±1 is left; its
+    /// row is then the new vector and is moved to `start`.
+    fn insert(&mut self, start: usize, coeffs: &[i64]) {
+        let mut x = coeffs.to_vec();
+        loop {
+            let mut nonzero: Vec<usize> = (0..x.len()).filter(|&i| x[i] != 0).collect();
+            if nonzero.len() < 2 {
+                break;
+            }
+            nonzero.sort_by_key(|&i| x[i].unsigned_abs());
+            let (small, large) = (nonzero[0], nonzero[1]);
+            // x_l b_l + x_s b_s = (x_l - f x_s) b_l + x_s (b_s + f b_l)
+            let f = x[large] / x[small];
+            x[large] -= f * x[small];
+            self.add_row_multiple(start + small, start + large, f);
+        }
+        let Some(pos) = x.iter().position(|&c| c != 0) else { return };
+        if x[pos] < 0 {
+            for v in self.row_mut(start + pos) {
+                *v = -*v;
+            }
+        }
+        for i in (start..start + pos).rev() {
+            self.swap_rows(i, i + 1);
+        }
+    }
+
+    /// A shortest nonzero vector of the lattice spanned by the rows, by enumeration
+    ///
+    /// Exponential in the dimension; LLL-reduce first.
+    pub fn shortest_vector(&self) -> Vec<i64> {
+        let gso = self.gram_schmidt();
+        let radius = gso.norms[0] * (1.0 + 1e-9);
+        let coeffs = enumerate(&gso, 0, self.rows, radius).unwrap_or_else(|| {
+            let mut first = vec![0; self.rows];
+            first[0] = 1;
+            first
+        });
+        self.combine(&coeffs)
+    }
+
+    /// Σ coeffs[i]·row i
+    pub fn combine(&self, coeffs: &[i64]) -> Vec<i64> {
+        let mut v = vec![0; self.cols];
+        for (i, &c) in coeffs.iter().enumerate() {
+            for (vj, &bj) in v.iter_mut().zip(self.row(i)) {
+                *vj += c * bj;
+            }
+        }
+        v
+    }
+
+    /// Root Hermite factor (‖b₁‖ / vol^(1/n))^(1/n) of a square full-rank basis
+    pub fn root_hermite_factor(&self) -> f64 {
+        let gso = self.gram_schmidt();
+        let n = self.rows as f64;
+        let log_volume = gso.norms.iter().map(|b| b.ln()).sum::<f64>() / 2.0;
+        let log_first = (self.row_norm_squared(0) as f64).ln() / 2.0;
+        ((log_first - log_volume / n) / n).exp()
+    }
+}
+
+impl Index<(usize, usize)> for IntMatrix {
+    type Output = i64;
+
+    fn index(&self, (i, j): (usize, usize)) -> &i64 {
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl IndexMut<(usize, usize)> for IntMatrix {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut i64 {
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+impl fmt::Display for IntMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for i in 0..self.rows {
+            writeln!(f, "{:?}", self.row(i))?;
+        }
+        Ok(())
+    }
+}
+
+fn dot(a: &[i64], b: &[i64]) -> i128 {
+    a.iter().zip(b).map(|(&x, &y)| x as i128 * y as i128).sum()
+}
+
+/// Floating-point Gram–Schmidt data of a basis b₁, ..., b_n
+#[derive(Debug, Clone, PartialEq)]
+pub struct GramSchmidt {
+    /// μ_ij = <b_i, b*_j> / ‖b*_j‖² for j < i, 1 on the diagonal
+    pub mu: Vec<Vec<f64>>,
+    /// ‖b*_i‖²
+    pub norms: Vec<f64>,
+}
+
+impl GramSchmidt {
+    /// Recomputes row `i` from exact inner products, assuming rows `0..i` are current
+    fn update_row(&mut self, basis: &IntMatrix, i: usize) {
+        let mut r = vec![0.0; i];
+        for j in 0..i {
+            let rij = basis.row_dot(i, j) as f64 - (0..j).map(|k| self.mu[j][k] * r[k]).sum::<f64>();
+            r[j] = rij;
+            self.mu[i][j] = rij / self.norms[j];
+        }
+        self.mu[i][i] = 1.0;
+        self.norms[i] = basis.row_norm_squared(i) as f64 - (0..i).map(|j| self.mu[i][j] * r[j]).sum::<f64>();
+    }
+}
+
+/// Exact Gram–Schmidt data, as returned by [`IntMatrix::gram_schmidt_exact`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExactGramSchmidt {
+    /// μ_ij = <b_i, b*_j> / ‖b*_j‖² for j < i, 1 on the diagonal
+    pub mu: Vec<Vec<Rational>>,
+    /// ‖b*_i‖²
+    pub norms: Vec<Rational>,
+}
+
+/// Finds the shortest nonzero combination of rows `start..end`, projected orthogonally to rows `0..start`
+///
+/// Schnorr–Euchner enumeration: coordinates are fixed from the last to the
+/// first, each zig-zagging outwards from the center that minimizes the
+/// projected length, and the radius shrinks whenever a shorter vector is
+/// found. Returns the coefficients of a vector with squared projected
+/// length below `radius`, if there is one.
+fn enumerate(gso: &GramSchmidt, start: usize, end: usize, radius: f64) -> Option<Vec<i64>> {
+    let n = end - start;
+    let mu = |i: usize, j: usize| gso.mu[start + i][start + j];
+    let norm = |i: usize| gso.norms[start + i];
+
+    let mut radius = radius;
+    let mut best = None;
+    let mut x = vec![0i64; n];
+    let mut center = vec![0.0; n];
+    // Offset from the rounded center and the direction of the first step
+    let mut step = vec![0i64; n];
+    let mut first = vec![0i64; n];
+    let mut origin = vec![0i64; n];
+    // partial[i] = squared length of the projection of Σ_(j ≥ i) x_j b_j
+    let mut partial = vec![0.0; n + 1];
+
+    let mut i = n - 1;
+    loop {
+        let diff = x[i] as f64 - center[i];
+        let length = partial[i + 1] + diff * diff * norm(i);
+        if length < radius {
+            if i == 0 {
+                if length > 0.0 {
+                    radius = length;
+                    best = Some(x.clone());
+                }
+            } else {
+                // Descend: center the next coordinate
+                partial[i] = length;
+                i -= 1;
+                center[i] = -(i + 1..n).map(|j| x[j] as f64 * mu(j, i)).sum::<f64>();
+                origin[i] = center[i].round() as i64;
+                x[i] = origin[i];
+                step[i] = 0;
+                first[i] = if center[i] >= origin[i] as f64 { 1 } else { -1 };
+                continue;
+            }
+        } else {
+            i += 1;
+            if i == n {
+                return best;
+            }
+        }
+        // Next candidate for x[i]: zig-zag around the center, or only upwards
+        // while everything above is zero, so that v and -v are not both visited
+        if partial[i + 1] == 0.0 {
+            x[i] += 1;
+        } else {
+            step[i] += 1;
+            let k = (step[i] + 1) / 2;
+            x[i] = origin[i] + if step[i] % 2 == 1 { k * first[i] } else { -k * first[i] };
+        }
+    }
+}
+
+/// An exact fraction num/den with den > 0, in lowest terms
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+impl Rational {
+    /// num / den; panics if `den` is zero
+    pub fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "Zero denominator");
+        let g = gcd(num, den).max(1);
+        let sign = den.signum();
+        Self { num: sign * num / g, den: sign * den / g }
+    }
+
+    /// The integer `n`
+    pub fn integer(n: i128) -> Self {
+        Self { num: n, den: 1 }
+    }
+
+    /// Zero
+    pub fn zero() -> Self {
+        Self::integer(0)
+    }
+
+    /// Numerator
+    pub fn num(&self) -> i128 {
+        self.num
+    }
+
+    /// Denominator (always positive)
+    pub fn den(&self) -> i128 {
+        self.den
+    }
+
+    /// Nearest integer, rounding halves up
+    pub fn round(&self) -> i128 {
+        (2 * self.num + self.den).div_euclid(2 * self.den)
+    }
+
+    /// Closest `f64`
+    pub fn to_f64(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+impl Add for Rational {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.num * other.den + other.num * self.den, self.den * other.den)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self::new(self.num * other.num, self.den * other.den)
+    }
+}
+
+impl Div for Rational {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        Self::new(self.num * other.den, self.den * other.num)
+    }
+}
+
+impl Neg for Rational {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self { num: -self.num, den: self.den }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (self.num * other.den).cmp(&(other.num * self.den))
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.den == 1 { write!(f, "{}", self.num) } else { write!(f, "{}/{}", self.num, self.den) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    fn random_basis(n: usize, bound: i64, rng: &mut impl Rng) -> IntMatrix {
+        let rows: Vec<Vec<i64>> = (0..n).map(|_| (0..n).map(|_| rng.gen_range(-bound..=bound)).collect()).collect();
+        IntMatrix::from_rows(&rows)
+    }
+
+    #[test]
+    fn test_rational_and_exact_gram_schmidt() {
+        let half = Rational::new(2, -4);
+        assert_eq!((half.num(), half.den()), (-1, 2));
+        assert_eq!(Rational::new(1, 3) + Rational::new(1, 6), Rational::new(1, 2));
+        assert_eq!(Rational::new(3, 4) / Rational::new(-3, 2), Rational::new(-1, 2));
+        assert_eq!(Rational::new(5, 2).round(), 3);
+        assert_eq!(Rational::new(-5, 2).round(), -2);
+        assert!(Rational::new(1, 3) < Rational::new(1, 2));
+        assert_eq!(Rational::new(-7, 3).to_string(), "-7/3");
+
+        // b1 = (3, 1), b2 = (2, 2): μ = 8/10, b2* = (2, 2) - 4/5 (3, 1) = (-2/5, 6/5)
+        let basis = IntMatrix::from_rows(&[vec![3, 1], vec![2, 2]]);
+        let exact = basis.gram_schmidt_exact();
+        assert_eq!(exact.mu[1][0], Rational::new(4, 5));
+        assert_eq!(exact.norms, vec![Rational::integer(10), Rational::new(8, 5)]);
+
+        let mut rng = ChaCha20Rng::seed_from_u64(3);
+        let basis = random_basis(6, 20, &mut rng);
+        let (exact, float) = (basis.gram_schmidt_exact(), basis.gram_schmidt());
+        for i in 0..6 {
+            assert!((exact.norms[i].to_f64() - float.norms[i]).abs() < 1e-6 * float.norms[i]);
+            for j in 0..i {
+                assert!((exact.mu[i][j].to_f64() - float.mu[i][j]).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_matrix_operations() {
+        let a = IntMatrix::from_rows(&[vec![1, 2, 3], vec![4, 5, 6]]);
+        assert_eq!(a.transpose().row(2), &[3, 6]);
+        assert_eq!(a.mul(&a.transpose()), IntMatrix::from_rows(&[vec![14, 32], vec![32, 77]]));
+        assert_eq!(IntMatrix::identity(2).mul(&a), a);
+        assert_eq!(a.combine(&[2, -1]), vec![-2, -1, 0]);
+        assert_eq!(a.row_norm_squared(1), 77);
+    }
+
+    #[test]
+    fn test_lll() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        for n in [2, 5, 12, 25] {
+            let original = random_basis(n, 1000, &mut rng);
+            let mut reduced = original.clone();
+            reduced.lll(0.99);
+            assert!(reduced.is_lll_reduced(0.99), "n = {}", n);
+            assert!(reduced.row_norm_squared(0) <= original.row_norm_squared(0));
+            // Same lattice: same volume, and each basis is an integer combination of the other
+            let volume = |b: &IntMatrix| b.gram_schmidt().norms.iter().map(|x| x.ln()).sum::<f64>();
+            assert!((volume(&reduced) - volume(&original)).abs() < 1e-6);
+        }
+
+        // The classical 3-dimensional example
+        let mut basis = IntMatrix::from_rows(&[vec![1, 1, 1], vec![-1, 0, 2], vec![3, 5, 6]]);
+        basis.lll(0.75);
+        assert_eq!(basis, IntMatrix::from_rows(&[vec![0, 1, 0], vec![1, 0, 1], vec![-1, 0, 2]]));
+    }
+
+    #[test]
+    fn test_shortest_vector_and_bkz() {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        let mut basis = random_basis(4, 50, &mut rng);
+        basis.lll(0.99);
+        let shortest = basis.shortest_vector();
+        let norm = |v: &[i64]| dot(v, v);
+        // Exhaustive search over small coefficients of the reduced basis
+        let mut brute = i128::MAX;
+        for c in 0..11i64.pow(4) {
+            let coeffs: Vec<i64> = (0..4).map(|i| (c / 11i64.pow(i)) % 11 - 5).collect();
+            if coeffs.iter().any(|&x| x != 0) {
+                brute = brute.min(norm(&basis.combine(&coeffs)));
+            }
+        }
+        assert_eq!(norm(&shortest), brute);
+
+        // On a q-ary lattice, BKZ-10 finds a first vector at least as short as LLL
+        let (n, m, q) = (15, 40, 127);
+        let mut rows = vec![vec![0i64; m]; m];
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i < n {
+                row[i] = 1;
+                for x in &mut row[n..] {
+                    *x = rng.gen_range(0..q);
+                }
+            } else {
+                row[i] = q;
+            }
+        }
+        let mut lll = IntMatrix::from_rows(&rows);
+        lll.lll(0.99);
+        let mut bkz = lll.clone();
+        let tours = bkz.bkz(10, 0.99, 8);
+        assert!(tours >= 1);
+        assert!(bkz.is_lll_reduced(0.99));
+        assert!(bkz.row_norm_squared(0) <= lll.row_norm_squared(0));
+        assert!(bkz.root_hermite_factor() <= lll.root_hermite_factor() + 1e-12);
+        // BKZ with the full dimension solves SVP on a small lattice
+        let mut full = basis.clone();
+        full.bkz(4, 0.99, 4);
+        assert_eq!(full.row_norm_squared(0), brute);
+    }
+}
@@ -1,8 +1,10 @@
-use rand::{Rng, SeedableRng};
+use alloc::{vec, vec::Vec};
+use rand::Rng;
 use rand::distributions::{Distribution, Uniform};
-use rand_chacha::ChaCha20Rng;
+use crate::keccak::{ShakeX4, SHAKE128_RATE};
 use crate::params::PolyModulusInfo;
 use crate::polynomial::Polynomial;
+use crate::simd::{self, Backend};
 use crate::zq::ZqElement;
 use sha3::{Shake128, Shake256, digest::{Update, ExtendableOutput, XofReader}};
 
@@ -60,7 +62,7 @@ pub fn sample_binomial(eta: usize, modulus_info: PolyModulusInfo, rng: &mut impl
         }
         
         // The difference follows a binomial distribution with mean 0 and width η
-        let value = a_bits as i32 - b_bits as i32;
+        let value: i32 = a_bits - b_bits;
         coeffs.push(ZqElement::new(value, q));
     }
     
@@ -99,62 +101,103 @@ pub fn sample_challenge(tau: usize, modulus_info: PolyModulusInfo, rng: &mut imp
 }
 
 /// Deterministically generates a pseudorandom matrix A from a seed for Kyber/Dilithium
+///
+/// Entry (i, j) is [`expand_poly`]`(rho, i, j)`. Entries are expanded four at
+/// a time with [`expand_poly_x4`], which gives the same result.
 pub fn expand_matrix(rho: &[u8], k: usize, l: usize, modulus_info: PolyModulusInfo) -> Vec<Vec<Polynomial>> {
-    let mut matrix = Vec::with_capacity(k);
-    
-    for i in 0..k {
-        let mut row = Vec::with_capacity(l);
-        for j in 0..l {
-            // Use i,j,rho as a seed for the polynomial
-            let poly = expand_poly(rho, i as u8, j as u8, modulus_info);
-            row.push(poly);
+    let indices: Vec<(u8, u8)> = (0..k)
+        .flat_map(|i| (0..l).map(move |j| (i as u8, j as u8)))
+        .collect();
+    
+    let mut entries = Vec::with_capacity(k * l);
+    for (batch, count) in batches_of_four(&indices) {
+        let polys = expand_poly_x4(rho, batch, modulus_info);
+        entries.extend(polys.into_iter().take(count));
+    }
+    
+    let mut entries = entries.into_iter();
+    (0..k).map(|_| entries.by_ref().take(l).collect()).collect()
+}
+
+/// Splits `items` into groups of four, padding the last group with copies of its last item
+///
+/// Yields each group with the number of real items in it.
+fn batches_of_four<T: Copy>(items: &[T]) -> impl Iterator<Item = ([T; 4], usize)> + '_ {
+    items.chunks(4).map(|chunk| {
+        let batch = core::array::from_fn(|i| chunk[i.min(chunk.len() - 1)]);
+        (batch, chunk.len())
+    })
+}
+
+/// Rejection-samples coefficients below q from one block of XOF output
+///
+/// Moduli of up to 12 bits use the SampleNTT candidates of FIPS 203, larger
+/// ones the 23-bit candidates of RejNTTPoly in FIPS 204.
+fn rejection_sample(bytes: &[u8], q: i32, out: &mut [i32], filled: usize) -> usize {
+    if q <= 1 << 12 {
+        simd::rej_uniform12(Backend::detect(), bytes, q, out, filled)
+    } else {
+        simd::rej_uniform(Backend::detect(), bytes, q, out, filled)
+    }
+}
+
+/// Four [`expand_poly`] calls at once, on the 4-way SHAKE128
+pub fn expand_poly_x4(rho: &[u8], indices: [(u8, u8); 4], modulus_info: PolyModulusInfo) -> [Polynomial; 4] {
+    let n = modulus_info.degree;
+    let q = modulus_info.q;
+    
+    let seeds = indices.map(|(i, j)| [rho, &[j, i]].concat());
+    let mut shake = ShakeX4::shake128(seeds.each_ref().map(|seed| seed.as_slice()));
+    let mut values = [(); 4].map(|_| vec![0; n]);
+    let mut filled = [0; 4];
+    
+    // All four instances squeeze in lockstep until the slowest one is full
+    let mut blocks = [[0u8; SHAKE128_RATE]; 4];
+    while filled.iter().any(|&count| count < n) {
+        shake.squeeze(blocks.each_mut().map(|block| block.as_mut_slice()));
+        for lane in 0..4 {
+            filled[lane] = rejection_sample(&blocks[lane], q, &mut values[lane], filled[lane]);
         }
-        matrix.push(row);
     }
     
-    matrix
+    values.map(|values| {
+        let coeffs = values.into_iter().map(|v| ZqElement::new(v, q)).collect();
+        Polynomial::new(coeffs, modulus_info)
+    })
 }
 
-/// Deterministically generates a pseudorandom polynomial from a seed and indices
+/// Deterministically generates a uniform polynomial from a seed and indices
+///
+/// Rejection-samples SHAKE128(rho || j || i), the byte order FIPS 203 uses for
+/// entry (i, j) of Â. For ML-KEM's q = 3329 this is SampleNTT, so the result
+/// is read as already being in the NTT domain.
 pub fn expand_poly(rho: &[u8], i: u8, j: u8, modulus_info: PolyModulusInfo) -> Polynomial {
     let n = modulus_info.degree;
     let q = modulus_info.q;
-    let mut coeffs = Vec::with_capacity(n);
-    
-    // Create seed = rho || i || j
-    let mut seed = rho.to_vec();
-    seed.push(i);
-    seed.push(j);
     
-    // Use SHAKE-128 to expand the seed into coefficients
     let mut shake = Shake128::default();
-    shake.update(&seed);
+    shake.update(rho);
+    shake.update(&[j, i]);
     let mut reader = shake.finalize_xof();
     
-    // Extract n coefficients from the XOF
-    let mut bytes = [0u8; 2];
-    for _ in 0..n {
-        reader.read(&mut bytes);
-        
-        // Convert 2 bytes to a coefficient mod q
-        let value = u16::from_le_bytes(bytes) as i32 % q;
-        coeffs.push(ZqElement::new(value, q));
+    // Squeeze whole blocks until n candidates are accepted
+    let mut values = vec![0; n];
+    let mut filled = 0;
+    let mut block = [0u8; SHAKE128_RATE];
+    while filled < n {
+        reader.read(&mut block);
+        filled = rejection_sample(&block, q, &mut values, filled);
     }
     
+    let coeffs = values.into_iter().map(|v| ZqElement::new(v, q)).collect();
     Polynomial::new(coeffs, modulus_info)
 }
 
-/// Implements PRF(seed, nonce, len) function used in various schemes
-pub fn prf(seed: &[u8], nonce: u16, len: usize) -> Vec<u8> {
-    let mut input = seed.to_vec();
-    
-    // Append nonce in little-endian format
-    let nonce_bytes = nonce.to_le_bytes();
-    input.extend_from_slice(&nonce_bytes);
-    
-    // Use SHAKE-256 to generate output
+/// Implements PRF_η(s, b) = SHAKE256(s || b, len) from FIPS 203
+pub fn prf(seed: &[u8], nonce: u8, len: usize) -> Vec<u8> {
     let mut shake = Shake256::default();
-    shake.update(&input);
+    shake.update(seed);
+    shake.update(&[nonce]);
     let mut reader = shake.finalize_xof();
     
     let mut output = vec![0u8; len];
@@ -163,51 +206,53 @@ pub fn prf(seed: &[u8], nonce: u16, len: usize) -> Vec<u8> {
     output
 }
 
-/// Samples a polynomial with coefficients from a seed using PRF
-pub fn sample_poly_from_seed(seed: &[u8], modulus_info: PolyModulusInfo, eta: usize) -> Polynomial {
-    let n = modulus_info.degree;
+/// [`prf`] for four nonces at once, on the 4-way SHAKE256
+pub fn prf_x4(seed: &[u8], nonces: [u8; 4], len: usize) -> [Vec<u8>; 4] {
+    let inputs = nonces.map(|nonce| [seed, &[nonce]].concat());
+    let mut shake = ShakeX4::shake256(inputs.each_ref().map(|input| input.as_slice()));
+    
+    let mut outputs = [(); 4].map(|_| vec![0u8; len]);
+    shake.squeeze(outputs.each_mut().map(|output| output.as_mut_slice()));
+    outputs
+}
+
+/// Samples a polynomial from the centered binomial distribution (SamplePolyCBD_η in FIPS 203)
+///
+/// `bytes` must hold 64·η bytes for a degree-256 polynomial, e.g. the output of PRF_η.
+pub fn sample_cbd(bytes: &[u8], eta: usize, modulus_info: PolyModulusInfo) -> Polynomial {
     let q = modulus_info.q;
+    let mut values = vec![0; modulus_info.degree];
+    simd::cbd(Backend::detect(), bytes, eta, q, &mut values);
     
-    // Generate random bytes
-    let bytes_needed = n * eta.div_ceil(8); // Each coefficient needs about η bits
-    let random_bytes = prf(seed, 0, bytes_needed);
-    
-    // Derive polynomial coefficients
-    let mut coeffs = Vec::with_capacity(n);
-    let mut rng = ChaCha20Rng::from_seed([0u8; 32]); // Just a placeholder RNG
+    let coeffs = values.into_iter()
+        .map(|v| ZqElement::new(v, q))
+        .collect();
+    Polynomial::new(coeffs, modulus_info)
+}
+
+/// Samples `sample_cbd(&prf(seed, first_nonce + i, n·η_i / 4), η_i)` for every i
+///
+/// The PRF runs four polynomials at a time on the 4-way SHAKE256. A shorter
+/// SHAKE output is a prefix of a longer one, so each batch squeezes for its
+/// largest η and every polynomial takes the prefix it needs.
+pub fn sample_noise_polys(seed: &[u8], first_nonce: u8, etas: &[usize], modulus_info: PolyModulusInfo) -> Vec<Polynomial> {
+    let n = modulus_info.degree;
+    let jobs: Vec<(u8, usize)> = etas.iter()
+        .enumerate()
+        .map(|(i, &eta)| (first_nonce + i as u8, eta))
+        .collect();
     
-    // Use the random bytes to seed our RNG
-    let mut seed_array = [0u8; 32];
-    for (i, &byte) in random_bytes.iter().take(32).enumerate() {
-        seed_array[i] = byte;
+    let mut polys = Vec::with_capacity(jobs.len());
+    for (batch, count) in batches_of_four(&jobs) {
+        let max_eta = batch.iter().map(|&(_, eta)| eta).max().unwrap_or(0);
+        let outputs = prf_x4(seed, batch.map(|(nonce, _)| nonce), n * max_eta / 4);
+        let batch_polys = core::array::from_fn::<_, 4, _>(|lane| {
+            let eta = batch[lane].1;
+            sample_cbd(&outputs[lane][..n * eta / 4], eta, modulus_info)
+        });
+        polys.extend(batch_polys.into_iter().take(count));
     }
-    rng = ChaCha20Rng::from_seed(seed_array);
-    
-    // Sample coefficients according to distribution
-    if eta == 1 {
-        // Special case for η=1: direct ternary sampling {-1, 0, 1}
-        for _ in 0..n {
-            let r = rng.gen_range(0..3) as i32;
-            let value = if r == 0 { -1 } else if r == 1 { 0 } else { 1 };
-            coeffs.push(ZqElement::new(value, q));
-        }
-    } else {
-        // Use binomial sampling for η>1
-        for _ in 0..n {
-            let mut a_bits = 0;
-            let mut b_bits = 0;
-            
-            for _ in 0..eta {
-                a_bits += if rng.gen::<bool>() { 1 } else { 0 };
-                b_bits += if rng.gen::<bool>() { 1 } else { 0 };
-            }
-            
-            let value = a_bits as i32 - b_bits as i32;
-            coeffs.push(ZqElement::new(value, q));
-        }
-    }
-    
-    Polynomial::new(coeffs, modulus_info)
+    polys
 }
 
 #[cfg(test)]
@@ -232,7 +277,7 @@ mod tests {
             .collect::<Vec<_>>();
         
         // Verify range
-        assert!(samples.iter().all(|&x| x >= -5 && x <= 5));
+        assert!(samples.iter().all(|&x| (-5..=5).contains(&x)));
         
         // Verify that we have at least some of each value
         for i in -5..=5 {
@@ -255,7 +300,7 @@ mod tests {
             } else {
                 coeff.value()
             };
-            assert!(centered >= -beta && centered <= beta);
+            assert!((-beta..=beta).contains(&centered));
         }
     }
     
@@ -274,7 +319,31 @@ mod tests {
             } else {
                 coeff.value()
             };
-            assert!(centered >= -(eta as i32) && centered <= eta as i32);
+            assert!((-(eta as i32)..=eta as i32).contains(&centered));
+        }
+    }
+    
+    #[test]
+    fn test_cbd_sampling() {
+        let modulus = PolyModulusInfo { degree: 256, q: 3329, is_ntt_form: false };
+        
+        // All-zero input gives the zero polynomial
+        assert_eq!(sample_cbd(&[0u8; 128], 2, modulus), Polynomial::zero(modulus));
+        
+        // Byte 0x0F = bits 1111 0000: coefficient 0 has x = 2, y = 2 and coefficient 1 has x = 0, y = 0,
+        // byte 0x03 gives x = 2, y = 0
+        let mut bytes = [0u8; 128];
+        bytes[0] = 0x0F;
+        bytes[1] = 0x03;
+        let poly = sample_cbd(&bytes, 2, modulus);
+        assert_eq!(poly.coeffs[0].value(), 0);
+        assert_eq!(poly.coeffs[2].value(), 2);
+        
+        let mut rng = thread_rng();
+        let mut bytes = [0u8; 192];
+        rng.fill(&mut bytes[..]);
+        for coeff in &sample_cbd(&bytes, 3, modulus).coeffs {
+            assert!(coeff.value() <= 3 || coeff.value() >= 3329 - 3);
         }
     }
     
@@ -325,11 +394,67 @@ mod tests {
         }
     }
     
+    #[test]
+    fn test_batched_expansion_matches_sequential() {
+        let rho = [0x5Au8; 32];
+        for modulus in [
+            create_test_modulus(),
+            PolyModulusInfo { degree: 256, q: 3329, is_ntt_form: false },
+            PolyModulusInfo { degree: 256, q: 8380417, is_ntt_form: false },
+        ] {
+            // 3 × 3 needs a padded last batch
+            let matrix = expand_matrix(&rho, 3, 3, modulus);
+            for (i, row) in matrix.iter().enumerate() {
+                for (j, poly) in row.iter().enumerate() {
+                    assert_eq!(*poly, expand_poly(&rho, i as u8, j as u8, modulus));
+                    assert!(poly.coeffs.iter().all(|c| c.value() < modulus.q));
+                }
+            }
+        }
+        
+        let outputs = prf_x4(&rho, [0, 1, 7, 255], 200);
+        for (nonce, output) in [0, 1, 7, 255].into_iter().zip(&outputs) {
+            assert_eq!(*output, prf(&rho, nonce, 200));
+        }
+    }
+    
+    #[test]
+    fn test_expand_poly_is_sample_ntt() {
+        // SampleNTT(rho || j || i): 12-bit candidates of SHAKE128, kept while below q
+        let modulus = PolyModulusInfo { degree: 256, q: 3329, is_ntt_form: false };
+        let rho = [7u8; 32];
+        let stream = crate::hashing::shake128(&[&rho[..], &[2, 1]].concat(), 3 * SHAKE128_RATE);
+        let expected: Vec<i32> = stream.chunks_exact(3)
+            .flat_map(|b| [b[0] as i32 | (b[1] as i32 & 0x0F) << 8, (b[1] >> 4) as i32 | (b[2] as i32) << 4])
+            .filter(|&d| d < 3329)
+            .take(256)
+            .collect();
+        assert_eq!(expected.len(), 256);
+        
+        let poly = expand_poly(&rho, 1, 2, modulus);
+        assert_eq!(poly.coeffs.iter().map(|c| c.value()).collect::<Vec<_>>(), expected);
+    }
+    
+    #[test]
+    fn test_batched_noise_matches_sequential() {
+        let sigma = [0x3Cu8; 32];
+        for modulus in [create_test_modulus(), PolyModulusInfo { degree: 256, q: 3329, is_ntt_form: false }] {
+            let etas = [3, 3, 2, 2, 2, 2, 2];
+            let polys = sample_noise_polys(&sigma, 4, &etas, modulus);
+            assert_eq!(polys.len(), etas.len());
+            for (i, (poly, &eta)) in polys.iter().zip(&etas).enumerate() {
+                let bytes = prf(&sigma, 4 + i as u8, modulus.degree * eta / 4);
+                assert_eq!(*poly, sample_cbd(&bytes, eta, modulus));
+            }
+        }
+        assert!(sample_noise_polys(&sigma, 0, &[], create_test_modulus()).is_empty());
+    }
+    
     #[test]
     fn test_prf() {
         let seed = b"test_seed_for_prf";
-        let nonce1 = 1u16;
-        let nonce2 = 2u16;
+        let nonce1 = 1u8;
+        let nonce2 = 2u8;
         
         let output1 = prf(seed, nonce1, 32);
         let output2 = prf(seed, nonce1, 32);
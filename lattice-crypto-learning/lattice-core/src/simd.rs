@@ -0,0 +1,585 @@
+//! Vectorized kernels for the NTT, NTT-domain multiplication and sampling
+//!
+//! The kernels work on plain `i32` coefficient slices holding canonical
+//! representatives in [0, q). Each one has three implementations:
+//! - AVX2 on x86_64 and NEON on aarch64, selected at runtime by [`Backend::detect`];
+//! - a portable scalar version, used when no vector unit is available and as
+//!   the reference the vector code is tested against bit for bit.
+//!
+//! Every implementation computes exact results modulo q, so they agree on all
+//! inputs. The vector code multiplies with Montgomery reduction (R = 2^32),
+//! which works for any odd q < 2^31; the precomputed constants live in
+//! [`Montgomery`], built once per [`NTTParams`].
+//!
+//! Without `std` the vector backends are chosen at compile time from the
+//! enabled target features. The NTT kernels need the heap-backed tables and
+//! are only built with `alloc`; CBD, rejection sampling and Keccak are not.
+
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+use crate::keccak::StateX4;
+#[cfg(feature = "alloc")]
+use crate::ntt::NTTParams;
+
+#[cfg(target_arch = "x86_64")]
+mod avx2;
+#[cfg(target_arch = "aarch64")]
+mod neon;
+
+/// Implementation used by the kernels in this module
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// Portable scalar code
+    Scalar,
+    /// 8 × 32-bit lanes on x86_64
+    Avx2,
+    /// 4 × 32-bit lanes on aarch64
+    Neon,
+}
+
+impl Backend {
+    /// Returns the fastest backend supported by the running CPU
+    pub fn detect() -> Self {
+        [Backend::Avx2, Backend::Neon]
+            .into_iter()
+            .find(|backend| backend.is_available())
+            .unwrap_or(Backend::Scalar)
+    }
+
+    /// Returns whether the running CPU supports this backend
+    pub fn is_available(self) -> bool {
+        match self {
+            Backend::Scalar => true,
+            #[cfg(all(target_arch = "x86_64", feature = "std"))]
+            Backend::Avx2 => std::is_x86_feature_detected!("avx2"),
+            #[cfg(all(target_arch = "x86_64", not(feature = "std")))]
+            Backend::Avx2 => cfg!(target_feature = "avx2"),
+            #[cfg(all(target_arch = "aarch64", feature = "std"))]
+            Backend::Neon => std::arch::is_aarch64_feature_detected!("neon"),
+            #[cfg(all(target_arch = "aarch64", not(feature = "std")))]
+            Backend::Neon => cfg!(target_feature = "neon"),
+            #[allow(unreachable_patterns)]
+            _ => false,
+        }
+    }
+
+    /// Lists every backend supported by the running CPU, scalar first
+    #[cfg(feature = "alloc")]
+    pub fn available() -> Vec<Backend> {
+        [Backend::Scalar, Backend::Avx2, Backend::Neon]
+            .into_iter()
+            .filter(|backend| backend.is_available())
+            .collect()
+    }
+
+    /// Picks the implementation for a slice of `len` coefficients
+    ///
+    /// The vector kernels process whole registers, so other lengths (only the
+    /// tiny rings used in unit tests) fall back to the scalar code.
+    fn for_len(self, len: usize) -> Self {
+        assert!(self.is_available(), "SIMD backend {:?} is not supported by this CPU", self);
+        if len % 8 == 0 { self } else { Backend::Scalar }
+    }
+}
+
+/// Montgomery-form constants for the vector kernels (R = 2^32)
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default)]
+pub struct Montgomery {
+    /// -q^-1 mod 2^32
+    pub q_neg_inv: u32,
+    /// R^2 mod q, turns a Montgomery product back into a plain product
+    pub r2: i32,
+    /// Forward twiddle factors times R mod q
+    pub roots: Vec<i32>,
+    /// Inverse twiddle factors times R mod q
+    pub inv_roots: Vec<i32>,
+    /// Inverse NTT scaling factor times R mod q
+    pub n_inv: i32,
+    /// Per-coefficient factors applied after the first Montgomery product in
+    /// [`pointwise_mul`]: R^2 on every coefficient for a complete NTT, and
+    /// R^2 / ±ζ·R^2 on the even / odd coefficient of each pair for an incomplete one
+    pub pointwise_factors: Vec<i32>,
+}
+
+#[cfg(feature = "alloc")]
+impl Montgomery {
+    /// Precomputes the constants for the twiddle factors of an NTT
+    pub fn new(params: &NTTParams) -> Self {
+        let q = params.q;
+        assert!(q % 2 == 1 && q > 0, "Montgomery reduction needs an odd positive modulus");
+
+        // Newton iteration doubles the number of correct low bits of q^-1 each step
+        let mut inv = q as u32;
+        for _ in 0..5 {
+            inv = inv.wrapping_mul(2u32.wrapping_sub((q as u32).wrapping_mul(inv)));
+        }
+
+        let r = ((1u64 << 32) % q as u64) as i64;
+        let to_mont = |value: i32| (value as i64 * r % q as i64) as i32;
+        let r2 = to_mont(r as i32);
+
+        let n = params.n;
+        let pointwise_factors = if params.is_complete() {
+            vec![r2; n]
+        } else {
+            let offset = params.slots() / 2;
+            (0..n)
+                .map(|i| {
+                    let zeta = params.roots_of_unity[offset + i / 4] as i64;
+                    let gamma = if i % 4 < 2 { zeta } else { q as i64 - zeta };
+                    if i % 2 == 0 { r2 } else { (gamma * r2 as i64 % q as i64) as i32 }
+                })
+                .collect()
+        };
+
+        Montgomery {
+            q_neg_inv: inv.wrapping_neg(),
+            r2,
+            roots: params.roots_of_unity.iter().map(|&root| to_mont(root)).collect(),
+            inv_roots: params.inv_roots_of_unity.iter().map(|&root| to_mont(root)).collect(),
+            n_inv: to_mont(params.n_inv),
+            pointwise_factors,
+        }
+    }
+}
+
+/// In-place forward NTT (Cooley-Tukey, output in bit-reversed order)
+#[cfg(feature = "alloc")]
+pub fn ntt_forward(backend: Backend, coeffs: &mut [i32], params: &NTTParams) {
+    assert_eq!(coeffs.len(), params.n, "Coefficient count must match NTT params");
+
+    match backend.for_len(coeffs.len()) {
+        // SAFETY: `for_len` checked that the CPU supports the backend
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => unsafe { avx2::ntt_forward(coeffs, params) },
+        #[cfg(target_arch = "aarch64")]
+        Backend::Neon => unsafe { neon::ntt_forward(coeffs, params) },
+        _ => scalar::ntt_forward(coeffs, params),
+    }
+}
+
+/// In-place inverse NTT (Gentleman-Sande), including the final scaling
+#[cfg(feature = "alloc")]
+pub fn ntt_inverse(backend: Backend, coeffs: &mut [i32], params: &NTTParams) {
+    assert_eq!(coeffs.len(), params.n, "Coefficient count must match NTT params");
+
+    match backend.for_len(coeffs.len()) {
+        // SAFETY: `for_len` checked that the CPU supports the backend
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => unsafe { avx2::ntt_inverse(coeffs, params) },
+        #[cfg(target_arch = "aarch64")]
+        Backend::Neon => unsafe { neon::ntt_inverse(coeffs, params) },
+        _ => scalar::ntt_inverse(coeffs, params),
+    }
+}
+
+/// Multiplies two NTT-domain polynomials slot by slot into `out`
+#[cfg(feature = "alloc")]
+pub fn pointwise_mul(backend: Backend, a: &[i32], b: &[i32], out: &mut [i32], params: &NTTParams) {
+    assert!(a.len() == params.n && b.len() == params.n && out.len() == params.n,
+            "Coefficient count must match NTT params");
+
+    match backend.for_len(out.len()) {
+        // SAFETY: `for_len` checked that the CPU supports the backend
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => unsafe { avx2::pointwise_mul(a, b, out, params) },
+        #[cfg(target_arch = "aarch64")]
+        Backend::Neon => unsafe { neon::pointwise_mul(a, b, out, params) },
+        _ => scalar::pointwise_mul(a, b, out, params),
+    }
+}
+
+/// Centered binomial sampling (SamplePolyCBD_η in FIPS 203) for η ∈ {2, 3}
+///
+/// Coefficient i is (x - y) mod q where x and y are the sums of the bits
+/// 2iη..2iη+η and 2iη+η..2iη+2η of `bytes`, which must hold 2η/8 bytes per coefficient.
+pub fn cbd(backend: Backend, bytes: &[u8], eta: usize, q: i32, out: &mut [i32]) {
+    assert!(eta == 2 || eta == 3, "CBD is only defined here for eta = 2 or 3");
+    assert_eq!(bytes.len() * 4, out.len() * eta, "CBD needs 2 * eta bits per coefficient");
+
+    match backend.for_len(out.len()) {
+        // SAFETY: `for_len` checked that the CPU supports the backend
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => unsafe { avx2::cbd(bytes, eta, q, out) },
+        #[cfg(target_arch = "aarch64")]
+        Backend::Neon => unsafe { neon::cbd(bytes, eta, q, out) },
+        _ => scalar::cbd(bytes, eta, q, out),
+    }
+}
+
+/// Uniform rejection sampling from 3-byte candidates (RejNTTPoly in FIPS 204)
+///
+/// Each 3-byte little-endian group has its top bit cleared and is kept if it
+/// is below q (q must be at most 2^23). Accepted values are written to
+/// `out[filled..]` in order until `out` is full or `bytes` runs out of whole
+/// groups; the new fill level is returned.
+pub fn rej_uniform(backend: Backend, bytes: &[u8], q: i32, out: &mut [i32], filled: usize) -> usize {
+    assert!(q <= 1 << 23, "candidates have 23 bits");
+    assert!(filled <= out.len(), "fill level exceeds the output");
+
+    match backend.for_len(out.len()) {
+        // SAFETY: `for_len` checked that the CPU supports the backend
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => unsafe { avx2::rej_uniform(bytes, q, out, filled) },
+        #[cfg(target_arch = "aarch64")]
+        Backend::Neon => unsafe { neon::rej_uniform(bytes, q, out, filled) },
+        _ => scalar::rej_uniform(bytes, q, out, filled),
+    }
+}
+
+/// Uniform rejection sampling from 12-bit candidates (SampleNTT in FIPS 203)
+///
+/// Each 3-byte group b0, b1, b2 yields the candidates b0 + 256·(b1 mod 16) and
+/// ⌊b1 / 16⌋ + 16·b2, which are kept in that order if they are below q (q must
+/// be at most 2^12). Fills `out[filled..]` like [`rej_uniform`] and returns the
+/// new fill level.
+pub fn rej_uniform12(backend: Backend, bytes: &[u8], q: i32, out: &mut [i32], filled: usize) -> usize {
+    assert!(q <= 1 << 12, "candidates have 12 bits");
+    assert!(filled <= out.len(), "fill level exceeds the output");
+
+    match backend.for_len(out.len()) {
+        // SAFETY: `for_len` checked that the CPU supports the backend
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => unsafe { avx2::rej_uniform12(bytes, q, out, filled) },
+        #[cfg(target_arch = "aarch64")]
+        Backend::Neon => unsafe { neon::rej_uniform12(bytes, q, out, filled) },
+        _ => scalar::rej_uniform12(bytes, q, out, filled),
+    }
+}
+
+/// Keccak-f[1600] on four interleaved states at once
+///
+/// The AVX2 version keeps one lane of all four states per 256-bit register;
+/// the other backends permute the states one after the other.
+pub fn keccak_f1600_x4(backend: Backend, state: &mut StateX4) {
+    assert!(backend.is_available(), "SIMD backend {:?} is not supported by this CPU", backend);
+
+    match backend {
+        // SAFETY: the CPU supports AVX2, checked above
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => unsafe { avx2::keccak_f1600_x4(state) },
+        _ => scalar::keccak_f1600_x4(state),
+    }
+}
+
+/// Portable implementations, also used for the parts of the vector kernels
+/// that do not fill a whole register
+mod scalar {
+    use crate::keccak::{self, StateX4};
+    #[cfg(feature = "alloc")]
+    use crate::ntt::NTTParams;
+
+    #[cfg(feature = "alloc")]
+    fn mul_mod(a: i32, b: i32, q: i32) -> i32 {
+        (a as i64 * b as i64 % q as i64) as i32
+    }
+
+    #[cfg(feature = "alloc")]
+    fn add_mod(a: i32, b: i32, q: i32) -> i32 {
+        let sum = a + b;
+        if sum >= q { sum - q } else { sum }
+    }
+
+    fn sub_mod(a: i32, b: i32, q: i32) -> i32 {
+        let diff = a - b;
+        if diff < 0 { diff + q } else { diff }
+    }
+
+    /// Cooley-Tukey butterflies of one block: (x, y) -> (x + ζy, x - ζy)
+    #[cfg(feature = "alloc")]
+    pub(super) fn ct_block(coeffs: &mut [i32], start: usize, len: usize, zeta: i32, q: i32) {
+        for j in start..start + len {
+            let t = mul_mod(coeffs[j + len], zeta, q);
+            coeffs[j + len] = sub_mod(coeffs[j], t, q);
+            coeffs[j] = add_mod(coeffs[j], t, q);
+        }
+    }
+
+    /// Gentleman-Sande butterflies of one block: (x, y) -> (x + y, (x - y)ζ^-1)
+    #[cfg(feature = "alloc")]
+    pub(super) fn gs_block(coeffs: &mut [i32], start: usize, len: usize, zeta_inv: i32, q: i32) {
+        for j in start..start + len {
+            let (even, odd) = (coeffs[j], coeffs[j + len]);
+            coeffs[j] = add_mod(even, odd, q);
+            coeffs[j + len] = mul_mod(sub_mod(even, odd, q), zeta_inv, q);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn ntt_forward(coeffs: &mut [i32], params: &NTTParams) {
+        let n = params.n;
+        let min_len = n / params.slots();
+
+        let mut k = 1;
+        let mut len = n / 2;
+        while len >= min_len {
+            for start in (0..n).step_by(2 * len) {
+                ct_block(coeffs, start, len, params.roots_of_unity[k], params.q);
+                k += 1;
+            }
+            len /= 2;
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn ntt_inverse(coeffs: &mut [i32], params: &NTTParams) {
+        let n = params.n;
+        let mut len = n / params.slots();
+        while len <= n / 2 {
+            let blocks = n / (2 * len);
+            for (block, start) in (0..n).step_by(2 * len).enumerate() {
+                gs_block(coeffs, start, len, params.inv_roots_of_unity[blocks + block], params.q);
+            }
+            len *= 2;
+        }
+
+        for coeff in coeffs.iter_mut() {
+            *coeff = mul_mod(*coeff, params.n_inv, params.q);
+        }
+    }
+
+    #[cfg(feature = "alloc")]
+    pub fn pointwise_mul(a: &[i32], b: &[i32], out: &mut [i32], params: &NTTParams) {
+        let q = params.q;
+        if params.is_complete() {
+            for i in 0..out.len() {
+                out[i] = mul_mod(a[i], b[i], q);
+            }
+            return;
+        }
+
+        // Pairs are residues modulo X^2 - γ with γ = ±ζ alternating per pair
+        let offset = params.slots() / 2;
+        for i in (0..out.len()).step_by(2) {
+            let zeta = params.roots_of_unity[offset + i / 4];
+            let gamma = if i % 4 == 0 { zeta } else { q - zeta };
+            let c0 = mul_mod(a[i], b[i], q) as i64 + mul_mod(mul_mod(a[i + 1], b[i + 1], q), gamma, q) as i64;
+            let c1 = mul_mod(a[i], b[i + 1], q) as i64 + mul_mod(a[i + 1], b[i], q) as i64;
+            out[i] = (c0 % q as i64) as i32;
+            out[i + 1] = (c1 % q as i64) as i32;
+        }
+    }
+
+    pub fn cbd(bytes: &[u8], eta: usize, q: i32, out: &mut [i32]) {
+        let bit = |index: usize| ((bytes[index / 8] >> (index % 8)) & 1) as i32;
+        for (i, coeff) in out.iter_mut().enumerate() {
+            let x: i32 = (0..eta).map(|j| bit(2 * i * eta + j)).sum();
+            let y: i32 = (0..eta).map(|j| bit(2 * i * eta + eta + j)).sum();
+            *coeff = sub_mod(x, y, q);
+        }
+    }
+
+    pub fn rej_uniform(bytes: &[u8], q: i32, out: &mut [i32], mut filled: usize) -> usize {
+        for group in bytes.chunks_exact(3) {
+            if filled == out.len() {
+                break;
+            }
+            let value = group[0] as i32 | (group[1] as i32) << 8 | ((group[2] & 0x7F) as i32) << 16;
+            if value < q {
+                out[filled] = value;
+                filled += 1;
+            }
+        }
+        filled
+    }
+
+    pub fn rej_uniform12(bytes: &[u8], q: i32, out: &mut [i32], mut filled: usize) -> usize {
+        for group in bytes.chunks_exact(3) {
+            let d1 = group[0] as i32 | ((group[1] & 0x0F) as i32) << 8;
+            let d2 = (group[1] >> 4) as i32 | (group[2] as i32) << 4;
+            for value in [d1, d2] {
+                if filled == out.len() {
+                    return filled;
+                }
+                if value < q {
+                    out[filled] = value;
+                    filled += 1;
+                }
+            }
+        }
+        filled
+    }
+
+    pub fn keccak_f1600_x4(state: &mut StateX4) {
+        for instance in 0..4 {
+            let mut single: [u64; 25] = core::array::from_fn(|lane| state[lane][instance]);
+            keccak::f1600(&mut single);
+            for (lane, value) in state.iter_mut().zip(single) {
+                lane[instance] = value;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaCha20Rng;
+
+    const N: usize = 256;
+
+    fn rings() -> [NTTParams; 2] {
+        [NTTParams::new(3329, N, 17), NTTParams::new(8380417, N, 1753)]
+    }
+
+    fn random_poly(q: i32, rng: &mut impl Rng) -> Vec<i32> {
+        (0..N).map(|_| rng.gen_range(0..q)).collect()
+    }
+
+    /// Random inputs plus the extreme values 0 and q - 1
+    fn inputs(q: i32, rng: &mut impl Rng) -> Vec<Vec<i32>> {
+        let mut polys: Vec<Vec<i32>> = (0..20).map(|_| random_poly(q, rng)).collect();
+        polys.push(vec![0; N]);
+        polys.push(vec![q - 1; N]);
+        polys
+    }
+
+    #[test]
+    fn test_detected_backend_is_available() {
+        assert!(Backend::detect().is_available());
+        assert_eq!(Backend::available()[0], Backend::Scalar);
+        assert!(Backend::available().contains(&Backend::detect()));
+    }
+
+    #[test]
+    fn test_ntt_matches_scalar() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        for params in rings() {
+            for poly in inputs(params.q, &mut rng) {
+                let mut expected = poly.clone();
+                ntt_forward(Backend::Scalar, &mut expected, &params);
+                let mut expected_inv = poly.clone();
+                ntt_inverse(Backend::Scalar, &mut expected_inv, &params);
+
+                for backend in Backend::available() {
+                    let mut actual = poly.clone();
+                    ntt_forward(backend, &mut actual, &params);
+                    assert_eq!(actual, expected, "{:?} forward NTT differs for q = {}", backend, params.q);
+
+                    ntt_inverse(backend, &mut actual, &params);
+                    assert_eq!(actual, poly, "{:?} NTT round trip failed for q = {}", backend, params.q);
+
+                    let mut actual_inv = poly.clone();
+                    ntt_inverse(backend, &mut actual_inv, &params);
+                    assert_eq!(actual_inv, expected_inv, "{:?} inverse NTT differs for q = {}", backend, params.q);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_pointwise_mul_matches_scalar() {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        for params in rings() {
+            let polys = inputs(params.q, &mut rng);
+            for (a, b) in polys.iter().zip(polys.iter().rev()) {
+                let mut expected = vec![0; N];
+                pointwise_mul(Backend::Scalar, a, b, &mut expected, &params);
+
+                for backend in Backend::available() {
+                    let mut actual = vec![0; N];
+                    pointwise_mul(backend, a, b, &mut actual, &params);
+                    assert_eq!(actual, expected, "{:?} pointwise product differs for q = {}", backend, params.q);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cbd_matches_scalar() {
+        let mut rng = ChaCha20Rng::seed_from_u64(3);
+        for eta in [2, 3] {
+            let mut samples = vec![vec![0u8; 64 * eta], vec![0xFF; 64 * eta]];
+            for _ in 0..20 {
+                let mut bytes = vec![0u8; 64 * eta];
+                rng.fill(bytes.as_mut_slice());
+                samples.push(bytes);
+            }
+
+            for bytes in samples {
+                let mut expected = vec![0; N];
+                cbd(Backend::Scalar, &bytes, eta, 3329, &mut expected);
+                assert!(expected.iter().all(|&c| c <= eta as i32 || c >= 3329 - eta as i32));
+
+                for backend in Backend::available() {
+                    let mut actual = vec![0; N];
+                    cbd(backend, &bytes, eta, 3329, &mut actual);
+                    assert_eq!(actual, expected, "{:?} CBD differs for eta = {}", backend, eta);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rej_uniform_matches_scalar() {
+        let mut rng = ChaCha20Rng::seed_from_u64(4);
+        let q = 8380417;
+        for len in [0, 2, 3, 24, 25, 168, 1000] {
+            let mut bytes = vec![0u8; len];
+            rng.fill(bytes.as_mut_slice());
+            // Force a few rejections
+            if len >= 6 {
+                bytes[2] = 0xFF;
+                bytes[5] = 0x7F;
+            }
+
+            for filled in [0, 100, 250, 256] {
+                let mut expected = vec![-1; N];
+                let expected_filled = rej_uniform(Backend::Scalar, &bytes, q, &mut expected, filled);
+
+                for backend in Backend::available() {
+                    let mut actual = vec![-1; N];
+                    let actual_filled = rej_uniform(backend, &bytes, q, &mut actual, filled);
+                    assert_eq!(actual_filled, expected_filled, "{:?} accepted a different count", backend);
+                    assert_eq!(actual, expected, "{:?} rejection sampling differs", backend);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rej_uniform12_matches_scalar() {
+        let mut rng = ChaCha20Rng::seed_from_u64(5);
+        for len in [0, 2, 3, 24, 25, 168, 504, 1000] {
+            let mut bytes = vec![0u8; len];
+            rng.fill(bytes.as_mut_slice());
+            // Force rejections of both candidates of the first group
+            if len >= 3 {
+                bytes[..3].copy_from_slice(&[0xFF, 0xFF, 0xFF]);
+            }
+
+            for filled in [0, 100, 255, 256] {
+                let mut expected = vec![-1; N];
+                let expected_filled = rej_uniform12(Backend::Scalar, &bytes, 3329, &mut expected, filled);
+
+                for backend in Backend::available() {
+                    let mut actual = vec![-1; N];
+                    let actual_filled = rej_uniform12(backend, &bytes, 3329, &mut actual, filled);
+                    assert_eq!(actual_filled, expected_filled, "{:?} accepted a different count", backend);
+                    assert_eq!(actual, expected, "{:?} rejection sampling differs", backend);
+                }
+            }
+        }
+
+        // 0x01 0x23 0x45 splits into 0x301 = 769 and 0x452 = 1106; 0x452 is rejected for q = 1000
+        let mut out = [0; 8];
+        assert_eq!(rej_uniform12(Backend::Scalar, &[0x01, 0x23, 0x45], 3329, &mut out, 0), 2);
+        assert_eq!(out[..2], [769, 1106]);
+        assert_eq!(rej_uniform12(Backend::Scalar, &[0x01, 0x23, 0x45], 1000, &mut out, 0), 1);
+    }
+
+    #[test]
+    fn test_small_ring_uses_scalar() {
+        // n = 4 does not fill a vector register; every backend must still work
+        let params = NTTParams::new(97, 4, 22);
+        let poly = vec![1, 2, 3, 96];
+        for backend in Backend::available() {
+            let mut coeffs = poly.clone();
+            ntt_forward(backend, &mut coeffs, &params);
+            ntt_inverse(backend, &mut coeffs, &params);
+            assert_eq!(coeffs, poly);
+        }
+    }
+}
@@ -0,0 +1,331 @@
+//! AVX2 kernels: 8 coefficients per 256-bit register
+//!
+//! Every function requires AVX2; the dispatchers in the parent module check
+//! for it before calling in.
+
+use core::arch::x86_64::*;
+
+use super::scalar;
+use crate::keccak::{keccak_permutation, StateX4};
+#[cfg(feature = "alloc")]
+use crate::ntt::NTTParams;
+
+/// Subtracts q once if needed: [0, 2q) -> [0, q)
+///
+/// If u < q the subtraction wraps to a huge unsigned value and the minimum keeps u.
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn reduce_once(u: __m256i, q: __m256i) -> __m256i {
+    _mm256_min_epu32(u, _mm256_sub_epi32(u, q))
+}
+
+#[cfg(feature = "alloc")]
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn add_mod(a: __m256i, b: __m256i, q: __m256i) -> __m256i {
+    reduce_once(_mm256_add_epi32(a, b), q)
+}
+
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn sub_mod(a: __m256i, b: __m256i, q: __m256i) -> __m256i {
+    reduce_once(_mm256_add_epi32(_mm256_sub_epi32(a, b), q), q)
+}
+
+/// Montgomery product a·b·2^-32 mod q in [0, q) for a, b in [0, q)
+#[cfg(feature = "alloc")]
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn montmul(a: __m256i, b: __m256i, q: __m256i, q_neg_inv: __m256i) -> __m256i {
+    // 64-bit products of the even lanes and of the odd lanes
+    let t_even = _mm256_mul_epu32(a, b);
+    let t_odd = _mm256_mul_epu32(_mm256_srli_epi64::<32>(a), _mm256_srli_epi64::<32>(b));
+
+    // m = t * (-q^-1) mod 2^32 makes t + m * q divisible by 2^32
+    let m_even = _mm256_mul_epu32(t_even, q_neg_inv);
+    let m_odd = _mm256_mul_epu32(t_odd, q_neg_inv);
+    let u_even = _mm256_add_epi64(t_even, _mm256_mul_epu32(m_even, q));
+    let u_odd = _mm256_add_epi64(t_odd, _mm256_mul_epu32(m_odd, q));
+
+    // The results are the high halves: move the even ones down, the odd ones are in place
+    let u = _mm256_blend_epi32::<0b1010_1010>(_mm256_srli_epi64::<32>(u_even), u_odd);
+    reduce_once(u, q)
+}
+
+/// Swaps neighbouring lanes: [x0, x1, x2, x3, ...] -> [x1, x0, x3, x2, ...]
+#[cfg(feature = "alloc")]
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn swap_pairs(v: __m256i) -> __m256i {
+    _mm256_shuffle_epi32::<0b1011_0001>(v)
+}
+
+#[cfg(feature = "alloc")]
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn load(slice: &[i32], i: usize) -> __m256i {
+    debug_assert!(i + 8 <= slice.len());
+    _mm256_loadu_si256(slice.as_ptr().add(i) as *const __m256i)
+}
+
+#[cfg(feature = "alloc")]
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn store(slice: &mut [i32], i: usize, v: __m256i) {
+    debug_assert!(i + 8 <= slice.len());
+    _mm256_storeu_si256(slice.as_mut_ptr().add(i) as *mut __m256i, v)
+}
+
+#[cfg(feature = "alloc")]
+#[target_feature(enable = "avx2")]
+pub unsafe fn ntt_forward(coeffs: &mut [i32], params: &NTTParams) {
+    let mont = &params.montgomery;
+    let q = _mm256_set1_epi32(params.q);
+    let q_neg_inv = _mm256_set1_epi32(mont.q_neg_inv as i32);
+    let n = params.n;
+    let min_len = n / params.slots();
+
+    let mut k = 1;
+    let mut len = n / 2;
+    while len >= min_len {
+        for start in (0..n).step_by(2 * len) {
+            if len >= 8 {
+                let zeta = _mm256_set1_epi32(mont.roots[k]);
+                for j in (start..start + len).step_by(8) {
+                    let even = load(coeffs, j);
+                    let t = montmul(load(coeffs, j + len), zeta, q, q_neg_inv);
+                    store(coeffs, j + len, sub_mod(even, t, q));
+                    store(coeffs, j, add_mod(even, t, q));
+                }
+            } else {
+                // The last layers have blocks narrower than a register
+                scalar::ct_block(coeffs, start, len, params.roots_of_unity[k], params.q);
+            }
+            k += 1;
+        }
+        len /= 2;
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[target_feature(enable = "avx2")]
+pub unsafe fn ntt_inverse(coeffs: &mut [i32], params: &NTTParams) {
+    let mont = &params.montgomery;
+    let q = _mm256_set1_epi32(params.q);
+    let q_neg_inv = _mm256_set1_epi32(mont.q_neg_inv as i32);
+    let n = params.n;
+
+    let mut len = n / params.slots();
+    while len <= n / 2 {
+        let blocks = n / (2 * len);
+        for (block, start) in (0..n).step_by(2 * len).enumerate() {
+            if len >= 8 {
+                let zeta_inv = _mm256_set1_epi32(mont.inv_roots[blocks + block]);
+                for j in (start..start + len).step_by(8) {
+                    let even = load(coeffs, j);
+                    let odd = load(coeffs, j + len);
+                    store(coeffs, j, add_mod(even, odd, q));
+                    store(coeffs, j + len, montmul(sub_mod(even, odd, q), zeta_inv, q, q_neg_inv));
+                }
+            } else {
+                scalar::gs_block(coeffs, start, len, params.inv_roots_of_unity[blocks + block], params.q);
+            }
+        }
+        len *= 2;
+    }
+
+    let n_inv = _mm256_set1_epi32(mont.n_inv);
+    for i in (0..n).step_by(8) {
+        store(coeffs, i, montmul(load(coeffs, i), n_inv, q, q_neg_inv));
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[target_feature(enable = "avx2")]
+pub unsafe fn pointwise_mul(a: &[i32], b: &[i32], out: &mut [i32], params: &NTTParams) {
+    let mont = &params.montgomery;
+    let q = _mm256_set1_epi32(params.q);
+    let q_neg_inv = _mm256_set1_epi32(mont.q_neg_inv as i32);
+    let r2 = _mm256_set1_epi32(mont.r2);
+    let complete = params.is_complete();
+
+    for i in (0..out.len()).step_by(8) {
+        let va = load(a, i);
+        let vb = load(b, i);
+        let factors = load(&mont.pointwise_factors, i);
+
+        // Complete NTT: a_i * b_i. Incomplete: even lanes a0 b0, odd lanes a1 b1 γ
+        let products = montmul(montmul(va, vb, q, q_neg_inv), factors, q, q_neg_inv);
+        if complete {
+            store(out, i, products);
+            continue;
+        }
+
+        // c0 = a0 b0 + a1 b1 γ lands in the even lanes, c1 = a0 b1 + a1 b0 in the odd ones
+        let c0 = add_mod(products, swap_pairs(products), q);
+        let cross = montmul(montmul(va, swap_pairs(vb), q, q_neg_inv), r2, q, q_neg_inv);
+        let c1 = add_mod(cross, swap_pairs(cross), q);
+        store(out, i, _mm256_blend_epi32::<0b1010_1010>(c0, c1));
+    }
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn cbd(bytes: &[u8], eta: usize, q: i32, out: &mut [i32]) {
+    let qv = _mm256_set1_epi32(q);
+    let mut lanes = [0i32; 8];
+
+    if eta == 2 {
+        // 32 bytes = 8 lanes of 32 bits = 64 coefficients of 4 bits; lane l holds coefficients 8l..8l+8
+        let ones = _mm256_set1_epi32(0x5555_5555);
+        let three = _mm256_set1_epi32(3);
+        let chunks = bytes.len() / 32;
+        for chunk in 0..chunks {
+            let t = _mm256_loadu_si256(bytes.as_ptr().add(32 * chunk) as *const __m256i);
+            let d = _mm256_add_epi32(_mm256_and_si256(t, ones), _mm256_and_si256(_mm256_srli_epi32::<1>(t), ones));
+            for j in 0..8 {
+                let x = _mm256_and_si256(_mm256_srl_epi32(d, _mm_cvtsi32_si128(4 * j)), three);
+                let y = _mm256_and_si256(_mm256_srl_epi32(d, _mm_cvtsi32_si128(4 * j + 2)), three);
+                _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, sub_mod(x, y, qv));
+                for (l, &value) in lanes.iter().enumerate() {
+                    out[64 * chunk + 8 * l + j as usize] = value;
+                }
+            }
+        }
+        scalar::cbd(&bytes[32 * chunks..], eta, q, &mut out[64 * chunks..]);
+        return;
+    }
+
+    // η = 3: each lane gathers a 24-bit group holding 4 coefficients of 6 bits.
+    // The gather reads 4 bytes per lane, so the last chunk is left to the scalar code.
+    let ones = _mm256_set1_epi32(0x0024_9249);
+    let seven = _mm256_set1_epi32(7);
+    let offsets = _mm256_setr_epi32(0, 3, 6, 9, 12, 15, 18, 21);
+    let mask = _mm256_set1_epi32(0x00FF_FFFF);
+    let mut chunk = 0;
+    while 24 * chunk + 25 <= bytes.len() {
+        let base = bytes.as_ptr().add(24 * chunk) as *const i32;
+        let t = _mm256_and_si256(_mm256_i32gather_epi32::<1>(base, offsets), mask);
+        let d = _mm256_add_epi32(
+            _mm256_add_epi32(_mm256_and_si256(t, ones), _mm256_and_si256(_mm256_srli_epi32::<1>(t), ones)),
+            _mm256_and_si256(_mm256_srli_epi32::<2>(t), ones),
+        );
+        for j in 0..4 {
+            let x = _mm256_and_si256(_mm256_srl_epi32(d, _mm_cvtsi32_si128(6 * j)), seven);
+            let y = _mm256_and_si256(_mm256_srl_epi32(d, _mm_cvtsi32_si128(6 * j + 3)), seven);
+            _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, sub_mod(x, y, qv));
+            for (l, &value) in lanes.iter().enumerate() {
+                out[32 * chunk + 4 * l + j as usize] = value;
+            }
+        }
+        chunk += 1;
+    }
+    scalar::cbd(&bytes[24 * chunk..], eta, q, &mut out[32 * chunk..]);
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn rej_uniform(bytes: &[u8], q: i32, out: &mut [i32], mut filled: usize) -> usize {
+    let qv = _mm256_set1_epi32(q);
+    let offsets = _mm256_setr_epi32(0, 3, 6, 9, 12, 15, 18, 21);
+    let mask = _mm256_set1_epi32(0x007F_FFFF);
+    let mut candidates = [0i32; 8];
+
+    // Eight candidates per gather; like in `cbd` the gather needs one byte of slack
+    let mut pos = 0;
+    while pos + 25 <= bytes.len() && filled < out.len() {
+        let t = _mm256_and_si256(_mm256_i32gather_epi32::<1>(bytes.as_ptr().add(pos) as *const i32, offsets), mask);
+        let accepted = _mm256_movemask_ps(_mm256_castsi256_ps(_mm256_cmpgt_epi32(qv, t))) as u32;
+        _mm256_storeu_si256(candidates.as_mut_ptr() as *mut __m256i, t);
+
+        for (l, &value) in candidates.iter().enumerate() {
+            if accepted >> l & 1 == 1 {
+                if filled == out.len() {
+                    return filled;
+                }
+                out[filled] = value;
+                filled += 1;
+            }
+        }
+        pos += 24;
+    }
+
+    scalar::rej_uniform(&bytes[pos..], q, out, filled)
+}
+
+#[target_feature(enable = "avx2")]
+pub unsafe fn rej_uniform12(bytes: &[u8], q: i32, out: &mut [i32], mut filled: usize) -> usize {
+    let qv = _mm256_set1_epi32(q);
+    let offsets = _mm256_setr_epi32(0, 3, 6, 9, 12, 15, 18, 21);
+    let mask = _mm256_set1_epi32(0x0FFF);
+    let mut low = [0i32; 8];
+    let mut high = [0i32; 8];
+
+    // Sixteen candidates per gather, two from each 24-bit group; the gather needs one byte of slack
+    let mut pos = 0;
+    while pos + 25 <= bytes.len() && filled < out.len() {
+        let t = _mm256_i32gather_epi32::<1>(bytes.as_ptr().add(pos) as *const i32, offsets);
+        let d1 = _mm256_and_si256(t, mask);
+        let d2 = _mm256_and_si256(_mm256_srli_epi32::<12>(t), mask);
+        let accepted1 = _mm256_movemask_ps(_mm256_castsi256_ps(_mm256_cmpgt_epi32(qv, d1))) as u32;
+        let accepted2 = _mm256_movemask_ps(_mm256_castsi256_ps(_mm256_cmpgt_epi32(qv, d2))) as u32;
+        _mm256_storeu_si256(low.as_mut_ptr() as *mut __m256i, d1);
+        _mm256_storeu_si256(high.as_mut_ptr() as *mut __m256i, d2);
+
+        for l in 0..8 {
+            for (value, accepted) in [(low[l], accepted1), (high[l], accepted2)] {
+                if accepted >> l & 1 == 1 {
+                    if filled == out.len() {
+                        return filled;
+                    }
+                    out[filled] = value;
+                    filled += 1;
+                }
+            }
+        }
+        pos += 24;
+    }
+
+    scalar::rej_uniform12(&bytes[pos..], q, out, filled)
+}
+
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn xor64(x: __m256i, y: __m256i) -> __m256i {
+    _mm256_xor_si256(x, y)
+}
+
+/// !x & y
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn andnot64(x: __m256i, y: __m256i) -> __m256i {
+    _mm256_andnot_si256(x, y)
+}
+
+/// 64-bit rotation of every lane by `n` bits (shifts by 64 yield zero, so n = 0 works)
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn rotate_left64(v: __m256i, n: u32) -> __m256i {
+    let left = _mm256_sll_epi64(v, _mm_cvtsi32_si128(n as i32));
+    let right = _mm256_srl_epi64(v, _mm_cvtsi32_si128(64 - n as i32));
+    _mm256_or_si256(left, right)
+}
+
+#[inline]
+#[target_feature(enable = "avx2")]
+unsafe fn broadcast64(value: u64) -> __m256i {
+    _mm256_set1_epi64x(value as i64)
+}
+
+/// Keccak-f[1600] on four states; register i holds lane i of every state
+#[target_feature(enable = "avx2")]
+pub unsafe fn keccak_f1600_x4(state: &mut StateX4) {
+    let mut a = [_mm256_setzero_si256(); 25];
+    for (register, lane) in a.iter_mut().zip(state.iter()) {
+        *register = _mm256_loadu_si256(lane.as_ptr() as *const __m256i);
+    }
+
+    keccak_permutation!(a, xor64, andnot64, rotate_left64, broadcast64);
+
+    for (register, lane) in a.iter().zip(state.iter_mut()) {
+        _mm256_storeu_si256(lane.as_mut_ptr() as *mut __m256i, *register);
+    }
+}
@@ -0,0 +1,293 @@
+//! NEON kernels: 4 coefficients per 128-bit register
+//!
+//! Every function requires NEON; the dispatchers in the parent module check
+//! for it before calling in. Coefficients are handled as unsigned lanes since
+//! they are canonical values in [0, q).
+
+use core::arch::aarch64::*;
+
+use super::scalar;
+#[cfg(feature = "alloc")]
+use crate::ntt::NTTParams;
+
+/// Subtracts q once if needed: [0, 2q) -> [0, q)
+///
+/// If u < q the subtraction wraps to a huge unsigned value and the minimum keeps u.
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn reduce_once(u: uint32x4_t, q: uint32x4_t) -> uint32x4_t {
+    vminq_u32(u, vsubq_u32(u, q))
+}
+
+#[cfg(feature = "alloc")]
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn add_mod(a: uint32x4_t, b: uint32x4_t, q: uint32x4_t) -> uint32x4_t {
+    reduce_once(vaddq_u32(a, b), q)
+}
+
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn sub_mod(a: uint32x4_t, b: uint32x4_t, q: uint32x4_t) -> uint32x4_t {
+    reduce_once(vaddq_u32(vsubq_u32(a, b), q), q)
+}
+
+/// Montgomery product a·b·2^-32 mod q in [0, q) for a, b in [0, q)
+#[cfg(feature = "alloc")]
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn montmul(a: uint32x4_t, b: uint32x4_t, q: uint32x4_t, q_neg_inv: uint32x4_t) -> uint32x4_t {
+    // 64-bit products of the low and the high two lanes
+    let t_low = vmull_u32(vget_low_u32(a), vget_low_u32(b));
+    let t_high = vmull_high_u32(a, b);
+
+    // m = t * (-q^-1) mod 2^32 makes t + m * q divisible by 2^32
+    let m = vmulq_u32(vmovn_high_u64(vmovn_u64(t_low), t_high), q_neg_inv);
+    let u_low = vmlal_u32(t_low, vget_low_u32(m), vget_low_u32(q));
+    let u_high = vmlal_high_u32(t_high, m, q);
+
+    reduce_once(vshrn_high_n_u64::<32>(vshrn_n_u64::<32>(u_low), u_high), q)
+}
+
+#[cfg(feature = "alloc")]
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn load(slice: &[i32], i: usize) -> uint32x4_t {
+    debug_assert!(i + 4 <= slice.len());
+    vld1q_u32(slice.as_ptr().add(i) as *const u32)
+}
+
+#[cfg(feature = "alloc")]
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn store(slice: &mut [i32], i: usize, v: uint32x4_t) {
+    debug_assert!(i + 4 <= slice.len());
+    vst1q_u32(slice.as_mut_ptr().add(i) as *mut u32, v)
+}
+
+#[cfg(feature = "alloc")]
+#[target_feature(enable = "neon")]
+pub unsafe fn ntt_forward(coeffs: &mut [i32], params: &NTTParams) {
+    let mont = &params.montgomery;
+    let q = vdupq_n_u32(params.q as u32);
+    let q_neg_inv = vdupq_n_u32(mont.q_neg_inv);
+    let n = params.n;
+    let min_len = n / params.slots();
+
+    let mut k = 1;
+    let mut len = n / 2;
+    while len >= min_len {
+        for start in (0..n).step_by(2 * len) {
+            if len >= 4 {
+                let zeta = vdupq_n_u32(mont.roots[k] as u32);
+                for j in (start..start + len).step_by(4) {
+                    let even = load(coeffs, j);
+                    let t = montmul(load(coeffs, j + len), zeta, q, q_neg_inv);
+                    store(coeffs, j + len, sub_mod(even, t, q));
+                    store(coeffs, j, add_mod(even, t, q));
+                }
+            } else {
+                // The last layers have blocks narrower than a register
+                scalar::ct_block(coeffs, start, len, params.roots_of_unity[k], params.q);
+            }
+            k += 1;
+        }
+        len /= 2;
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[target_feature(enable = "neon")]
+pub unsafe fn ntt_inverse(coeffs: &mut [i32], params: &NTTParams) {
+    let mont = &params.montgomery;
+    let q = vdupq_n_u32(params.q as u32);
+    let q_neg_inv = vdupq_n_u32(mont.q_neg_inv);
+    let n = params.n;
+
+    let mut len = n / params.slots();
+    while len <= n / 2 {
+        let blocks = n / (2 * len);
+        for (block, start) in (0..n).step_by(2 * len).enumerate() {
+            if len >= 4 {
+                let zeta_inv = vdupq_n_u32(mont.inv_roots[blocks + block] as u32);
+                for j in (start..start + len).step_by(4) {
+                    let even = load(coeffs, j);
+                    let odd = load(coeffs, j + len);
+                    store(coeffs, j, add_mod(even, odd, q));
+                    store(coeffs, j + len, montmul(sub_mod(even, odd, q), zeta_inv, q, q_neg_inv));
+                }
+            } else {
+                scalar::gs_block(coeffs, start, len, params.inv_roots_of_unity[blocks + block], params.q);
+            }
+        }
+        len *= 2;
+    }
+
+    let n_inv = vdupq_n_u32(mont.n_inv as u32);
+    for i in (0..n).step_by(4) {
+        store(coeffs, i, montmul(load(coeffs, i), n_inv, q, q_neg_inv));
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[target_feature(enable = "neon")]
+pub unsafe fn pointwise_mul(a: &[i32], b: &[i32], out: &mut [i32], params: &NTTParams) {
+    let mont = &params.montgomery;
+    let q = vdupq_n_u32(params.q as u32);
+    let q_neg_inv = vdupq_n_u32(mont.q_neg_inv);
+    let r2 = vdupq_n_u32(mont.r2 as u32);
+    let odd_lanes = vld1q_u32([0, u32::MAX, 0, u32::MAX].as_ptr());
+    let complete = params.is_complete();
+
+    for i in (0..out.len()).step_by(4) {
+        let va = load(a, i);
+        let vb = load(b, i);
+        let factors = load(&mont.pointwise_factors, i);
+
+        // Complete NTT: a_i * b_i. Incomplete: even lanes a0 b0, odd lanes a1 b1 γ
+        let products = montmul(montmul(va, vb, q, q_neg_inv), factors, q, q_neg_inv);
+        if complete {
+            store(out, i, products);
+            continue;
+        }
+
+        // c0 = a0 b0 + a1 b1 γ lands in the even lanes, c1 = a0 b1 + a1 b0 in the odd ones;
+        // vrev64q swaps neighbouring lanes
+        let c0 = add_mod(products, vrev64q_u32(products), q);
+        let cross = montmul(montmul(va, vrev64q_u32(vb), q, q_neg_inv), r2, q, q_neg_inv);
+        let c1 = add_mod(cross, vrev64q_u32(cross), q);
+        store(out, i, vbslq_u32(odd_lanes, c1, c0));
+    }
+}
+
+/// Loads four little-endian 24-bit groups starting at `bytes[0]`
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn load_24bit_groups(bytes: &[u8]) -> uint32x4_t {
+    let mut groups = [0u32; 4];
+    for (group, chunk) in groups.iter_mut().zip(bytes.chunks_exact(3)) {
+        *group = chunk[0] as u32 | (chunk[1] as u32) << 8 | (chunk[2] as u32) << 16;
+    }
+    vld1q_u32(groups.as_ptr())
+}
+
+/// Right shift of every lane by a runtime amount
+#[inline]
+#[target_feature(enable = "neon")]
+unsafe fn shr(v: uint32x4_t, shift: i32) -> uint32x4_t {
+    vshlq_u32(v, vdupq_n_s32(-shift))
+}
+
+#[target_feature(enable = "neon")]
+pub unsafe fn cbd(bytes: &[u8], eta: usize, q: i32, out: &mut [i32]) {
+    let qv = vdupq_n_u32(q as u32);
+    let mut lanes = [0u32; 4];
+
+    if eta == 2 {
+        // 16 bytes = 4 lanes of 32 bits = 32 coefficients of 4 bits; lane l holds coefficients 8l..8l+8
+        let ones = vdupq_n_u32(0x5555_5555);
+        let three = vdupq_n_u32(3);
+        let chunks = bytes.len() / 16;
+        for chunk in 0..chunks {
+            let t = vld1q_u32(bytes.as_ptr().add(16 * chunk) as *const u32);
+            let d = vaddq_u32(vandq_u32(t, ones), vandq_u32(vshrq_n_u32::<1>(t), ones));
+            for j in 0..8 {
+                let x = vandq_u32(shr(d, 4 * j), three);
+                let y = vandq_u32(shr(d, 4 * j + 2), three);
+                vst1q_u32(lanes.as_mut_ptr(), sub_mod(x, y, qv));
+                for (l, &value) in lanes.iter().enumerate() {
+                    out[32 * chunk + 8 * l + j as usize] = value as i32;
+                }
+            }
+        }
+        scalar::cbd(&bytes[16 * chunks..], eta, q, &mut out[32 * chunks..]);
+        return;
+    }
+
+    // η = 3: each lane holds a 24-bit group of 4 coefficients of 6 bits
+    let ones = vdupq_n_u32(0x0024_9249);
+    let seven = vdupq_n_u32(7);
+    let chunks = bytes.len() / 12;
+    for chunk in 0..chunks {
+        let t = load_24bit_groups(&bytes[12 * chunk..12 * chunk + 12]);
+        let d = vaddq_u32(
+            vaddq_u32(vandq_u32(t, ones), vandq_u32(vshrq_n_u32::<1>(t), ones)),
+            vandq_u32(vshrq_n_u32::<2>(t), ones),
+        );
+        for j in 0..4 {
+            let x = vandq_u32(shr(d, 6 * j), seven);
+            let y = vandq_u32(shr(d, 6 * j + 3), seven);
+            vst1q_u32(lanes.as_mut_ptr(), sub_mod(x, y, qv));
+            for (l, &value) in lanes.iter().enumerate() {
+                out[16 * chunk + 4 * l + j as usize] = value as i32;
+            }
+        }
+    }
+    scalar::cbd(&bytes[12 * chunks..], eta, q, &mut out[16 * chunks..]);
+}
+
+#[target_feature(enable = "neon")]
+pub unsafe fn rej_uniform(bytes: &[u8], q: i32, out: &mut [i32], mut filled: usize) -> usize {
+    let qv = vdupq_n_u32(q as u32);
+    let mask = vdupq_n_u32(0x007F_FFFF);
+    let mut candidates = [0u32; 4];
+    let mut accepted = [0u32; 4];
+
+    // Four candidates per register
+    let mut pos = 0;
+    while pos + 12 <= bytes.len() && filled < out.len() {
+        let t = vandq_u32(load_24bit_groups(&bytes[pos..pos + 12]), mask);
+        vst1q_u32(candidates.as_mut_ptr(), t);
+        vst1q_u32(accepted.as_mut_ptr(), vcltq_u32(t, qv));
+
+        for (&value, &keep) in candidates.iter().zip(&accepted) {
+            if keep != 0 {
+                if filled == out.len() {
+                    return filled;
+                }
+                out[filled] = value as i32;
+                filled += 1;
+            }
+        }
+        pos += 12;
+    }
+
+    scalar::rej_uniform(&bytes[pos..], q, out, filled)
+}
+
+#[target_feature(enable = "neon")]
+pub unsafe fn rej_uniform12(bytes: &[u8], q: i32, out: &mut [i32], mut filled: usize) -> usize {
+    let qv = vdupq_n_u32(q as u32);
+    let mask = vdupq_n_u32(0x0FFF);
+    let mut low = [0u32; 4];
+    let mut high = [0u32; 4];
+    let mut accepted_low = [0u32; 4];
+    let mut accepted_high = [0u32; 4];
+
+    // Eight candidates per register, two from each 24-bit group
+    let mut pos = 0;
+    while pos + 12 <= bytes.len() && filled < out.len() {
+        let t = load_24bit_groups(&bytes[pos..pos + 12]);
+        let d1 = vandq_u32(t, mask);
+        let d2 = vshrq_n_u32::<12>(t);
+        vst1q_u32(low.as_mut_ptr(), d1);
+        vst1q_u32(high.as_mut_ptr(), d2);
+        vst1q_u32(accepted_low.as_mut_ptr(), vcltq_u32(d1, qv));
+        vst1q_u32(accepted_high.as_mut_ptr(), vcltq_u32(d2, qv));
+
+        for l in 0..4 {
+            for (value, keep) in [(low[l], accepted_low[l]), (high[l], accepted_high[l])] {
+                if keep != 0 {
+                    if filled == out.len() {
+                        return filled;
+                    }
+                    out[filled] = value as i32;
+                    filled += 1;
+                }
+            }
+        }
+        pos += 12;
+    }
+
+    scalar::rej_uniform12(&bytes[pos..], q, out, filled)
+}
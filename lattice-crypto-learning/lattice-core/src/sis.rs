@@ -0,0 +1,156 @@
+//! Ajtai's collision-resistant hash function h_A(x) = A·x mod q
+//!
+//! A is a uniform `k × l` matrix over R_q and inputs are vectors in R^l with
+//! coefficients in {0, 1}. Two different inputs with the same hash give
+//! z = x - x' with A·z = 0 and ‖z‖∞ ≤ 1, a solution to Module-SIS, so
+//! finding collisions is as hard as Module-SIS. The function compresses
+//! once l·n input bits exceed the k·n·⌈log₂ q⌉ output bits, and it is
+//! linear: h_A(x + y) = h_A(x) + h_A(y).
+
+use alloc::vec::Vec;
+
+use rand::Rng;
+
+use crate::ntt::NTTParams;
+use crate::params::PolyModulusInfo;
+use crate::polynomial::Polynomial;
+use crate::sampling::{expand_matrix, sample_uniform_poly_zq};
+use crate::vector_matrix::{PolyMatrix, PolyVector};
+use crate::zq::ZqElement;
+
+/// The hash function h_A for a fixed matrix A
+#[derive(Debug, Clone)]
+pub struct AjtaiHash {
+    /// The public matrix A
+    pub a: PolyMatrix,
+    /// Multiplies through the NTT when set, schoolbook otherwise
+    pub ntt_params: Option<NTTParams>,
+}
+
+impl AjtaiHash {
+    /// h_A for a given A, in the normal (non-NTT) domain
+    pub fn new(a: PolyMatrix, ntt_params: Option<NTTParams>) -> Self {
+        assert!(!a.modulus_info.is_ntt_form, "A must be in the normal domain");
+        Self { a, ntt_params }
+    }
+
+    /// A `rows × cols` matrix expanded from the seed `rho` with SHAKE-128
+    pub fn from_seed(rho: &[u8], rows: usize, cols: usize, modulus_info: PolyModulusInfo, ntt_params: Option<NTTParams>) -> Self {
+        let entries = expand_matrix(rho, rows, cols, modulus_info);
+        let rows_vec = entries.into_iter().map(|row| PolyVector::new(row, modulus_info)).collect();
+        Self::new(PolyMatrix::new(rows_vec, rows, cols, modulus_info), ntt_params)
+    }
+
+    /// A uniformly random `rows × cols` matrix
+    pub fn random(rows: usize, cols: usize, modulus_info: PolyModulusInfo, ntt_params: Option<NTTParams>, rng: &mut impl Rng) -> Self {
+        let rows_vec = (0..rows)
+            .map(|_| PolyVector::new((0..cols).map(|_| sample_uniform_poly_zq(modulus_info, rng)).collect(), modulus_info))
+            .collect();
+        Self::new(PolyMatrix::new(rows_vec, rows, cols, modulus_info), ntt_params)
+    }
+
+    /// Number of input bits, l·n
+    pub fn input_bits(&self) -> usize {
+        self.a.n_cols * self.a.modulus_info.degree
+    }
+
+    /// Number of output bits, k·n·⌈log₂ q⌉
+    pub fn output_bits(&self) -> usize {
+        let q = self.a.modulus_info.q as u32;
+        let bits = (u32::BITS - (q - 1).leading_zeros()) as usize;
+        self.a.n_rows * self.a.modulus_info.degree * bits
+    }
+
+    /// Whether the output is shorter than the input, so collisions must exist
+    pub fn is_compressing(&self) -> bool {
+        self.input_bits() > self.output_bits()
+    }
+
+    /// A·x mod q
+    ///
+    /// Collision resistance only covers inputs with coefficients in {0, 1}
+    /// (or, more generally, small ones); use [`AjtaiHash::hash_bytes`] for
+    /// byte strings.
+    pub fn hash(&self, x: &PolyVector) -> PolyVector {
+        self.a.mul_vec(x, self.ntt_params.as_ref())
+    }
+
+    /// The binary input vector holding the bits of `bytes`, least significant bit first, zero-padded
+    pub fn encode(&self, bytes: &[u8]) -> PolyVector {
+        let info = self.a.modulus_info;
+        assert!(bytes.len() * 8 <= self.input_bits(), "Input longer than {} bits", self.input_bits());
+        let mut bits = bytes.iter().flat_map(|&byte| (0..8).map(move |i| i32::from((byte >> i) & 1)));
+        let entries = (0..self.a.n_cols)
+            .map(|_| {
+                let coeffs: Vec<ZqElement> = (0..info.degree).map(|_| ZqElement::new(bits.next().unwrap_or(0), info.q)).collect();
+                Polynomial::new(coeffs, info)
+            })
+            .collect();
+        PolyVector::new(entries, info)
+    }
+
+    /// h_A of the bits of `bytes`
+    ///
+    /// Inputs of different lengths that agree up to zero padding collide;
+    /// fix the length or append a length encoding to avoid this.
+    pub fn hash_bytes(&self, bytes: &[u8]) -> PolyVector {
+        self.hash(&self.encode(bytes))
+    }
+
+    /// Turns a collision h_A(x) = h_A(y), x ≠ y, into a short z ≠ 0 with A·z = 0
+    pub fn sis_solution(&self, x: &PolyVector, y: &PolyVector) -> Option<PolyVector> {
+        (x != y && self.hash(x) == self.hash(y)).then(|| x.clone() - y.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::params::dilithium;
+    use alloc::collections::BTreeMap;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    #[test]
+    fn test_hash_is_linear_and_compressing() {
+        let info = PolyModulusInfo { degree: dilithium::N, q: dilithium::Q, is_ntt_form: false };
+        let ntt = NTTParams::new(dilithium::Q, dilithium::N, 1753);
+        // 4 × 24 over q ≈ 2^23: 6144 input bits, 4 · 256 · 23 = 23552 output bits
+        let h = AjtaiHash::from_seed(&[7u8; 32], 4, 24, info, Some(ntt));
+        assert_eq!((h.input_bits(), h.output_bits()), (6144, 23552));
+        assert!(!h.is_compressing());
+        let h = AjtaiHash::from_seed(&[7u8; 32], 1, 24, info, h.ntt_params);
+        assert!(h.is_compressing());
+
+        let (x, y) = (h.encode(b"first message"), h.encode(b"second message"));
+        assert_eq!(h.hash(&(x.clone() + y.clone())), h.hash(&x) + h.hash(&y));
+        assert_eq!(h.hash_bytes(b"first message"), h.hash(&x));
+        assert_ne!(h.hash(&x), h.hash(&y));
+        assert!(x.infinity_norm() <= 1);
+
+        // NTT and schoolbook multiplication agree
+        let schoolbook = AjtaiHash::new(h.a.clone(), None);
+        assert_eq!(schoolbook.hash(&x), h.hash(&x));
+    }
+
+    #[test]
+    fn test_toy_collision_is_sis_solution() {
+        // Over Z_5[X]/(X^4 + 1) with a 1 × 4 matrix: 16 input bits, 12 output bits
+        let info = PolyModulusInfo { degree: 4, q: 5, is_ntt_form: false };
+        let mut rng = ChaCha20Rng::seed_from_u64(9);
+        let h = AjtaiHash::random(1, 4, info, None, &mut rng);
+        assert!(h.is_compressing());
+
+        let mut seen = BTreeMap::new();
+        let collision = (0u16..=u16::MAX).find_map(|input| {
+            let x = h.encode(&input.to_le_bytes());
+            let digest: Vec<i32> = h.hash(&x).entries.iter().flat_map(|p| p.coeffs.iter().map(|c| c.value())).collect();
+            seen.insert(digest, x.clone()).map(|y| (x, y))
+        });
+        let (x, y) = collision.expect("a compressing function has collisions");
+        let z = h.sis_solution(&x, &y).unwrap();
+        assert!(z.infinity_norm() == 1);
+        assert!(h.hash(&z).entries.iter().all(|p| p.coeffs.iter().all(|c| c.value() == 0)));
+        assert_eq!(h.sis_solution(&x, &x), None);
+    }
+}
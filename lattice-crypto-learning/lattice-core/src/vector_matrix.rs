@@ -1,4 +1,5 @@
-use std::ops::{Add, Sub, Mul};
+use alloc::vec::Vec;
+use core::ops::{Add, Sub};
 use crate::polynomial::Polynomial;
 use crate::params::PolyModulusInfo;
 use crate::ntt::{ntt_forward, ntt_inverse, ntt_pointwise_mul, NTTParams};
@@ -73,23 +74,23 @@ impl PolyVector {
         // If NTT params are provided, use NTT-based multiplication
         if let Some(params) = ntt_params {
             // Check if polynomials are already in NTT form
-            let use_ntt = !self.entries[0].modulus_info.is_ntt_form;
-            
-            for i in 0..self.len() {
-                let product = if use_ntt {
-                    // Convert to NTT domain, multiply, convert back
+            if self.entries[0].modulus_info.is_ntt_form {
+                // Already in NTT domain: accumulate pointwise products and stay there
+                for i in 0..self.len() {
+                    result = result + ntt_pointwise_mul(&self.entries[i], &other.entries[i], params);
+                }
+            } else {
+                // Convert to NTT domain, accumulate, convert back once at the end
+                let mut acc = Polynomial::zero(PolyModulusInfo {
+                    is_ntt_form: true,
+                    ..self.modulus_info
+                });
+                for i in 0..self.len() {
                     let a_ntt = ntt_forward(&self.entries[i], params);
                     let b_ntt = ntt_forward(&other.entries[i], params);
-                    let prod_ntt = ntt_pointwise_mul(&a_ntt, &b_ntt);
-                    ntt_inverse(&prod_ntt, params)
-                } else {
-                    // Already in NTT domain, just do pointwise multiplication
-                    let prod_ntt = ntt_pointwise_mul(&self.entries[i], &other.entries[i]);
-                    ntt_inverse(&prod_ntt, params)
-                };
-                
-                // Add to result
-                result = result + product;
+                    acc = acc + ntt_pointwise_mul(&a_ntt, &b_ntt, params);
+                }
+                result = ntt_inverse(&acc, params);
             }
         } else {
             // Use schoolbook multiplication
@@ -132,7 +133,7 @@ impl PolyVector {
     /// Creates from bytes
     pub fn from_bytes(bytes: &[u8], modulus_info: PolyModulusInfo, length: usize, coeff_bits: usize) -> Self {
         let n = modulus_info.degree;
-        let bytes_per_poly = n * ((coeff_bits + 7) / 8);
+        let bytes_per_poly = n * coeff_bits / 8;
         
         assert!(bytes.len() >= length * bytes_per_poly, "Not enough bytes");
         
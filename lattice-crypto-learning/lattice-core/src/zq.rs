@@ -1,5 +1,5 @@
-use std::ops::{Add, Sub, Mul, Neg};
-use std::fmt;
+use core::ops::{Add, Sub, Mul, Neg};
+use core::fmt;
 
 /// Represents an element in the finite field Z_q
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -112,7 +112,9 @@ impl Mul for ZqElement {
 
     fn mul(self, other: Self) -> Self {
         assert_eq!(self.q, other.q, "Moduli must be the same");
-        ZqElement::new((self.value as i64 * other.value as i64) as i32, self.q)
+        // Reduce in 64 bits: the product overflows i32 for moduli above 2^15.5
+        let product = (self.value as i64 * other.value as i64) % self.q as i64;
+        ZqElement::new(product as i32, self.q)
     }
 }
 
@@ -155,6 +157,15 @@ mod tests {
         assert_eq!(a * b, ZqElement::new(9, 13));
     }
 
+    #[test]
+    fn test_multiplication_large_modulus() {
+        // Dilithium's q = 2^23 - 2^13 + 1: products need 46 bits
+        let q = 8380417;
+        let a = ZqElement::new(q - 1, q);
+        assert_eq!((a * a).value(), 1);
+        assert_eq!((ZqElement::new(4_000_000, q) * ZqElement::new(4_000_000, q)).value(), 7_298_596);
+    }
+    
     #[test]
     fn test_negation() {
         let a = ZqElement::new(5, 13);
@@ -0,0 +1,604 @@
+//! SIGMA-style authenticated key exchange from ML-KEM and ML-DSA
+//!
+//! Both parties hold long-term ML-DSA-65 identity keys. The initiator sends an
+//! ephemeral ML-KEM-768 encapsulation key, the responder encapsulates to it,
+//! and each side signs the transcript and MACs it with a key derived from the
+//! KEM secret ("SIGMA": sign-and-MAC). Session keys are bound to the whole
+//! transcript.
+//!
+//! ```text
+//! Initiator                                              Responder
+//! ClientHello: nonce_I, ek_e                      -->
+//!                                                 <--   ServerHello: nonce_R, ct, vk_R,
+//!                                                         Sign_R(th_2), MAC_R(th_2)
+//! Finished: vk_I, Sign_I(th_3), MAC_I(th_3)       -->
+//! ```
+//!
+//! `th_2` and `th_3` are running SHA-256 transcript hashes that include every
+//! earlier field. Identities are sent in the clear; this variant does not
+//! provide identity protection. Responders keep a bounded cache of the most
+//! recent initiator nonces so a replayed ClientHello is refused; once the cache
+//! is full the oldest nonce is forgotten.
+
+use std::collections::{HashSet, VecDeque};
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::ChaCha20Poly1305;
+use dilithium_ml_dsa::dsa;
+use dilithium_ml_dsa::params::Dilithium3;
+use dilithium_ml_dsa::types::{Signature, SigningKey, VerifyingKey};
+use hkdf::Hkdf;
+use hmac::{Hmac, Mac};
+use kyber_ml_kem::kem;
+use kyber_ml_kem::params::Kyber768;
+use kyber_ml_kem::types::{Ciphertext, EncapsulationKey};
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha256};
+use signature::Keypair;
+
+use crate::error::Error;
+
+/// ML-DSA-65 identity signing key
+pub type IdentityKey = SigningKey<Dilithium3>;
+/// ML-DSA-65 identity verifying key
+pub type PeerKey = VerifyingKey<Dilithium3>;
+
+const NONCE_BYTES: usize = 32;
+/// ML-KEM-768 encapsulation key
+const EK_BYTES: usize = 1184;
+/// ML-KEM-768 ciphertext
+const CT_BYTES: usize = 1088;
+/// ML-DSA-65 verifying key
+const VK_BYTES: usize = 1952;
+/// ML-DSA-65 signature
+const SIG_BYTES: usize = 3309;
+const MAC_BYTES: usize = 32;
+/// Initiator nonces a responder remembers by default
+pub const NONCE_CACHE_CAPACITY: usize = 4096;
+
+/// Size of the ClientHello message
+pub const CLIENT_HELLO_BYTES: usize = NONCE_BYTES + EK_BYTES;
+/// Size of the ServerHello message
+pub const SERVER_HELLO_BYTES: usize = NONCE_BYTES + CT_BYTES + VK_BYTES + SIG_BYTES + MAC_BYTES;
+/// Size of the Finished message
+pub const FINISHED_BYTES: usize = VK_BYTES + SIG_BYTES + MAC_BYTES;
+
+/// Protocol label that starts the transcript
+const PROTOCOL_LABEL: &[u8] = b"lattice-ake v1 ML-KEM-768 ML-DSA-65";
+/// ML-DSA context strings keep the two roles' signatures apart
+const INITIATOR_CONTEXT: &[u8] = b"lattice-ake initiator";
+const RESPONDER_CONTEXT: &[u8] = b"lattice-ake responder";
+
+/// Splits `bytes` into consecutive fields of the given sizes, checking the total length
+fn split_fields<const F: usize>(bytes: &[u8], sizes: [usize; F]) -> Result<[&[u8]; F], Error> {
+    let expected = sizes.iter().sum();
+    if bytes.len() != expected {
+        return Err(Error::InvalidLength { expected, actual: bytes.len() });
+    }
+
+    let mut rest = bytes;
+    Ok(sizes.map(|size| {
+        let (field, tail) = rest.split_at(size);
+        rest = tail;
+        field
+    }))
+}
+
+/// Keys derived from the KEM secret before the identities are known
+struct HandshakeKeys {
+    prk: [u8; 32],
+    initiator_mac: [u8; 32],
+    responder_mac: [u8; 32],
+}
+
+impl HandshakeKeys {
+    /// prk = HKDF-Extract(th_1, ss), with th_1 covering both hellos up to the ciphertext
+    fn derive(shared_secret: &[u8; 32], th_1: &[u8; 32]) -> Self {
+        let (prk, hkdf) = Hkdf::<Sha256>::extract(Some(th_1), shared_secret);
+        let mut keys = Self { prk: prk.into(), initiator_mac: [0u8; 32], responder_mac: [0u8; 32] };
+        hkdf.expand(b"initiator mac", &mut keys.initiator_mac).expect("32 bytes is a valid HKDF length");
+        hkdf.expand(b"responder mac", &mut keys.responder_mac).expect("32 bytes is a valid HKDF length");
+        keys
+    }
+
+    /// Session keys bound to the final transcript hash
+    fn session(&self, th_final: &[u8; 32], peer: PeerKey, is_initiator: bool) -> Session {
+        let hkdf = Hkdf::<Sha256>::from_prk(&self.prk).expect("PRK has the hash length");
+        let expand = |label: &[u8]| {
+            let mut key = [0u8; 32];
+            hkdf.expand_multi_info(&[label, th_final], &mut key).expect("32 bytes is a valid HKDF length");
+            key
+        };
+
+        let (i2r, r2i) = (expand(b"initiator to responder"), expand(b"responder to initiator"));
+        let (send_key, recv_key) = if is_initiator { (i2r, r2i) } else { (r2i, i2r) };
+        Session { session_id: expand(b"session id"), peer, send_key, recv_key, send_seq: 0, recv_seq: 0 }
+    }
+}
+
+fn mac(key: &[u8; 32], transcript_hash: &[u8; 32]) -> [u8; MAC_BYTES] {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(transcript_hash);
+    mac.finalize().into_bytes().into()
+}
+
+fn verify_mac(key: &[u8; 32], transcript_hash: &[u8; 32], tag: &[u8]) -> Result<(), Error> {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts any key length");
+    mac.update(transcript_hash);
+    mac.verify_slice(tag).map_err(|_| Error::InvalidMac)
+}
+
+fn sign(identity: &IdentityKey, context: &[u8], transcript_hash: &[u8; 32], rng: &mut impl CryptoRngCore) -> Vec<u8> {
+    let mut rnd = [0u8; 32];
+    rng.fill_bytes(&mut rnd);
+    let sig = dsa::sign_with_context(identity.as_key(), transcript_hash, context, &rnd)
+        .expect("context strings are shorter than 256 bytes");
+    dsa::signature_to_bytes(&sig)
+}
+
+fn verify(peer: &PeerKey, context: &[u8], transcript_hash: &[u8; 32], sig: &[u8]) -> Result<(), Error> {
+    let sig = Signature::<Dilithium3>::try_from(sig).map_err(|_| Error::InvalidSignature)?;
+    if dsa::verify_with_context(peer.as_key(), transcript_hash, context, sig.as_signature()) {
+        Ok(())
+    } else {
+        Err(Error::InvalidSignature)
+    }
+}
+
+/// Encoded verifying key of an identity, as sent on the wire
+fn identity_bytes(identity: &IdentityKey) -> Vec<u8> {
+    identity.verifying_key().as_bytes().to_vec()
+}
+
+/// Decodes a peer identity and checks that it is trusted
+fn trusted_peer(vk: &[u8], trusted: &[PeerKey]) -> Result<PeerKey, Error> {
+    let peer = PeerKey::try_from(vk).map_err(|_| Error::InvalidKey)?;
+    if trusted.contains(&peer) {
+        Ok(peer)
+    } else {
+        Err(Error::UntrustedPeer)
+    }
+}
+
+/// Current value of a running transcript hash
+fn snapshot(transcript: &Sha256) -> [u8; 32] {
+    transcript.clone().finalize().into()
+}
+
+/// Initiator that has sent its ClientHello and awaits the ServerHello
+pub struct Initiator {
+    identity: IdentityKey,
+    trusted_responders: Vec<PeerKey>,
+    dk_e: kem::SecretKey,
+    transcript: Sha256,
+}
+
+impl Initiator {
+    /// Starts a handshake and returns the ClientHello to send
+    pub fn start(identity: IdentityKey, trusted_responders: Vec<PeerKey>, rng: &mut impl CryptoRngCore) -> (Self, Vec<u8>) {
+        let mut seed = [0u8; 64];
+        rng.fill_bytes(&mut seed);
+        let dk_e = kyber_ml_kem::types::DecapsulationKey::<Kyber768>::from_seed(&seed);
+
+        let mut client_hello = vec![0u8; NONCE_BYTES];
+        rng.fill_bytes(&mut client_hello);
+        client_hello.extend_from_slice(dk_e.encapsulation_key().as_bytes());
+
+        let mut transcript = Sha256::new();
+        transcript.update(PROTOCOL_LABEL);
+        transcript.update(&client_hello);
+
+        let initiator = Self { identity, trusted_responders, dk_e: dk_e.as_key().clone(), transcript };
+        (initiator, client_hello)
+    }
+
+    /// Processes the ServerHello; returns the Finished message and the established session
+    pub fn finish(mut self, server_hello: &[u8], rng: &mut impl CryptoRngCore) -> Result<(Vec<u8>, Session), Error> {
+        let [nonce_r, ct, vk_r, sig_r, mac_r] =
+            split_fields(server_hello, [NONCE_BYTES, CT_BYTES, VK_BYTES, SIG_BYTES, MAC_BYTES])?;
+
+        self.transcript.update(nonce_r);
+        self.transcript.update(ct);
+        let ct = Ciphertext::<Kyber768>::try_from(ct).map_err(|_| Error::InvalidLength { expected: CT_BYTES, actual: ct.len() })?;
+        let keys = HandshakeKeys::derive(&kem::decaps(&self.dk_e, ct.as_ciphertext()), &snapshot(&self.transcript));
+
+        // Authenticate the responder: trusted identity, signature and MAC over th_2
+        let responder = trusted_peer(vk_r, &self.trusted_responders)?;
+        self.transcript.update(vk_r);
+        let th_2 = snapshot(&self.transcript);
+        verify(&responder, RESPONDER_CONTEXT, &th_2, sig_r)?;
+        verify_mac(&keys.responder_mac, &th_2, mac_r)?;
+        self.transcript.update(sig_r);
+        self.transcript.update(mac_r);
+
+        let vk_i = identity_bytes(&self.identity);
+        self.transcript.update(&vk_i);
+        let th_3 = snapshot(&self.transcript);
+        let sig_i = sign(&self.identity, INITIATOR_CONTEXT, &th_3, rng);
+        let mac_i = mac(&keys.initiator_mac, &th_3);
+        self.transcript.update(&sig_i);
+        self.transcript.update(mac_i);
+
+        let finished = [&vk_i[..], &sig_i, &mac_i].concat();
+        let session = keys.session(&snapshot(&self.transcript), responder, true);
+        Ok((finished, session))
+    }
+}
+
+/// Long-lived responder: answers ClientHellos and refuses replayed ones
+pub struct Responder {
+    identity: IdentityKey,
+    trusted_initiators: Vec<PeerKey>,
+    seen_nonces: NonceCache,
+}
+
+/// Fixed-capacity set of initiator nonces that evicts the oldest entry
+struct NonceCache {
+    capacity: usize,
+    set: HashSet<[u8; NONCE_BYTES]>,
+    order: VecDeque<[u8; NONCE_BYTES]>,
+}
+
+impl NonceCache {
+    fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "nonce cache needs room for at least one nonce");
+        Self { capacity, set: HashSet::new(), order: VecDeque::new() }
+    }
+
+    /// Records `nonce`, returning false if it is already cached
+    fn insert(&mut self, nonce: [u8; NONCE_BYTES]) -> bool {
+        if !self.set.insert(nonce) {
+            return false;
+        }
+        if self.order.len() == self.capacity {
+            let oldest = self.order.pop_front().expect("cache is full");
+            self.set.remove(&oldest);
+        }
+        self.order.push_back(nonce);
+        true
+    }
+}
+
+/// Responder side of a handshake that awaits the initiator's Finished
+pub struct PendingSession {
+    trusted_initiators: Vec<PeerKey>,
+    keys: HandshakeKeys,
+    transcript: Sha256,
+}
+
+impl Responder {
+    /// Creates a responder that accepts the given initiator identities
+    pub fn new(identity: IdentityKey, trusted_initiators: Vec<PeerKey>) -> Self {
+        Self::with_nonce_capacity(identity, trusted_initiators, NONCE_CACHE_CAPACITY)
+    }
+
+    /// Like `new`, remembering at most `capacity` (> 0) initiator nonces
+    pub fn with_nonce_capacity(identity: IdentityKey, trusted_initiators: Vec<PeerKey>, capacity: usize) -> Self {
+        Self { identity, trusted_initiators, seen_nonces: NonceCache::new(capacity) }
+    }
+
+    /// Processes a ClientHello and returns the ServerHello to send
+    pub fn respond(&mut self, client_hello: &[u8], rng: &mut impl CryptoRngCore) -> Result<(Vec<u8>, PendingSession), Error> {
+        let [nonce_i, ek] = split_fields(client_hello, [NONCE_BYTES, EK_BYTES])?;
+        let ek = EncapsulationKey::<Kyber768>::try_from(ek).map_err(|_| Error::InvalidKey)?;
+        if !self.seen_nonces.insert(nonce_i.try_into().expect("nonce field has a fixed size")) {
+            return Err(Error::Replay);
+        }
+
+        let mut transcript = Sha256::new();
+        transcript.update(PROTOCOL_LABEL);
+        transcript.update(client_hello);
+
+        let mut nonce_r = [0u8; NONCE_BYTES];
+        let mut m = [0u8; 32];
+        rng.fill_bytes(&mut nonce_r);
+        rng.fill_bytes(&mut m);
+        let encapsulation = kem::encaps_derand(ek.as_key(), &m);
+        let ct = kem::ciphertext_to_bytes(&encapsulation.ciphertext);
+        transcript.update(nonce_r);
+        transcript.update(&ct);
+        let keys = HandshakeKeys::derive(&encapsulation.shared_secret, &snapshot(&transcript));
+
+        let vk_r = identity_bytes(&self.identity);
+        transcript.update(&vk_r);
+        let th_2 = snapshot(&transcript);
+        let sig_r = sign(&self.identity, RESPONDER_CONTEXT, &th_2, rng);
+        let mac_r = mac(&keys.responder_mac, &th_2);
+        transcript.update(&sig_r);
+        transcript.update(mac_r);
+
+        let server_hello = [&nonce_r[..], &ct, &vk_r, &sig_r, &mac_r].concat();
+        let pending = PendingSession { trusted_initiators: self.trusted_initiators.clone(), keys, transcript };
+        Ok((server_hello, pending))
+    }
+}
+
+impl PendingSession {
+    /// Processes the initiator's Finished and returns the established session
+    pub fn finish(mut self, finished: &[u8]) -> Result<Session, Error> {
+        let [vk_i, sig_i, mac_i] = split_fields(finished, [VK_BYTES, SIG_BYTES, MAC_BYTES])?;
+
+        let initiator = trusted_peer(vk_i, &self.trusted_initiators)?;
+        self.transcript.update(vk_i);
+        let th_3 = snapshot(&self.transcript);
+        verify(&initiator, INITIATOR_CONTEXT, &th_3, sig_i)?;
+        verify_mac(&self.keys.initiator_mac, &th_3, mac_i)?;
+        self.transcript.update(sig_i);
+        self.transcript.update(mac_i);
+
+        Ok(self.keys.session(&snapshot(&self.transcript), initiator, false))
+    }
+}
+
+/// An established session: authenticated peer and directional record keys
+///
+/// Records are sealed with ChaCha20-Poly1305 under a per-direction sequence
+/// number, so replayed, reordered or dropped records fail to open.
+pub struct Session {
+    session_id: [u8; 32],
+    peer: PeerKey,
+    send_key: [u8; 32],
+    recv_key: [u8; 32],
+    send_seq: u64,
+    recv_seq: u64,
+}
+
+impl Session {
+    /// Identifier that both parties derive identically
+    pub fn session_id(&self) -> &[u8; 32] {
+        &self.session_id
+    }
+
+    /// The authenticated identity of the other party
+    pub fn peer(&self) -> &PeerKey {
+        &self.peer
+    }
+
+    fn nonce(seq: u64) -> [u8; 12] {
+        let mut nonce = [0u8; 12];
+        nonce[4..].copy_from_slice(&seq.to_be_bytes());
+        nonce
+    }
+
+    /// Encrypts the next outgoing record
+    pub fn seal(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let cipher = ChaCha20Poly1305::new(&self.send_key.into());
+        let nonce = Self::nonce(self.send_seq);
+        let record = cipher.encrypt(&nonce.into(), Payload { msg: plaintext, aad: &self.session_id })
+            .map_err(|_| Error::MessageLimitReached)?;
+        self.send_seq = self.send_seq.checked_add(1).ok_or(Error::MessageLimitReached)?;
+        Ok(record)
+    }
+
+    /// Decrypts the next incoming record
+    pub fn open(&mut self, record: &[u8]) -> Result<Vec<u8>, Error> {
+        let cipher = ChaCha20Poly1305::new(&self.recv_key.into());
+        let nonce = Self::nonce(self.recv_seq);
+        let plaintext = cipher.decrypt(&nonce.into(), Payload { msg: record, aad: &self.session_id })
+            .map_err(|_| Error::OpenError)?;
+        self.recv_seq = self.recv_seq.checked_add(1).ok_or(Error::MessageLimitReached)?;
+        Ok(plaintext)
+    }
+}
+
+/// The two ends of a connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Initiator,
+    Responder,
+}
+
+/// In-memory message transport between an initiator and a responder
+///
+/// Messages queue up per recipient. The queues are public so that tests can
+/// play a network attacker that drops, modifies, reorders or replays messages.
+#[derive(Debug, Default)]
+pub struct MemoryTransport {
+    /// Messages waiting for the initiator
+    pub to_initiator: VecDeque<Vec<u8>>,
+    /// Messages waiting for the responder
+    pub to_responder: VecDeque<Vec<u8>>,
+}
+
+impl MemoryTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues a message from `from` to the other side
+    pub fn send(&mut self, from: Role, message: Vec<u8>) {
+        match from {
+            Role::Initiator => self.to_responder.push_back(message),
+            Role::Responder => self.to_initiator.push_back(message),
+        }
+    }
+
+    /// Takes the next message addressed to `to`
+    pub fn receive(&mut self, to: Role) -> Option<Vec<u8>> {
+        match to {
+            Role::Initiator => self.to_initiator.pop_front(),
+            Role::Responder => self.to_responder.pop_front(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    struct Parties {
+        alice: IdentityKey,
+        bob: IdentityKey,
+        rng: ChaCha20Rng,
+    }
+
+    fn parties(seed: u64) -> Parties {
+        Parties {
+            alice: IdentityKey::from_seed(&[1u8; 32]),
+            bob: IdentityKey::from_seed(&[2u8; 32]),
+            rng: ChaCha20Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Runs a handshake over `transport`, letting `attack` act on each message in flight
+    fn handshake(
+        p: &mut Parties,
+        responder: &mut Responder,
+        transport: &mut MemoryTransport,
+        mut attack: impl FnMut(usize, &mut MemoryTransport),
+    ) -> Result<(Session, Session), Error> {
+        let (initiator, client_hello) = Initiator::start(p.alice.clone(), vec![p.bob.verifying_key()], &mut p.rng);
+        transport.send(Role::Initiator, client_hello);
+        attack(1, transport);
+
+        let (server_hello, pending) = responder.respond(&transport.receive(Role::Responder).unwrap(), &mut p.rng)?;
+        transport.send(Role::Responder, server_hello);
+        attack(2, transport);
+
+        let (finished, initiator_session) = initiator.finish(&transport.receive(Role::Initiator).unwrap(), &mut p.rng)?;
+        transport.send(Role::Initiator, finished);
+        attack(3, transport);
+
+        let responder_session = pending.finish(&transport.receive(Role::Responder).unwrap())?;
+        Ok((initiator_session, responder_session))
+    }
+
+    fn responder(p: &Parties) -> Responder {
+        Responder::new(p.bob.clone(), vec![p.alice.verifying_key()])
+    }
+
+    #[test]
+    fn test_handshake_and_records() {
+        let mut p = parties(1);
+        let mut bob = responder(&p);
+        let mut transport = MemoryTransport::new();
+        let (mut alice_session, mut bob_session) = handshake(&mut p, &mut bob, &mut transport, |_, _| {}).unwrap();
+
+        assert_eq!(alice_session.session_id(), bob_session.session_id());
+        assert_eq!(alice_session.peer(), &p.bob.verifying_key());
+        assert_eq!(bob_session.peer(), &p.alice.verifying_key());
+
+        for i in 0..3u8 {
+            let record = alice_session.seal(&[i; 5]).unwrap();
+            assert_eq!(bob_session.open(&record).unwrap(), [i; 5]);
+            let reply = bob_session.seal(b"ack").unwrap();
+            assert_eq!(alice_session.open(&reply).unwrap(), b"ack");
+        }
+
+        // A second handshake yields an unrelated session
+        let (other, _) = handshake(&mut p, &mut bob, &mut transport, |_, _| {}).unwrap();
+        assert_ne!(other.session_id(), alice_session.session_id());
+    }
+
+    #[test]
+    fn test_tampering_detected() {
+        // (message, byte offset, expected error) for one byte in each field
+        let cases = [
+            (1, CLIENT_HELLO_BYTES - 1, None),
+            (2, 0, Some(Error::InvalidSignature)),
+            (2, NONCE_BYTES + 5, Some(Error::InvalidSignature)),
+            (2, NONCE_BYTES + CT_BYTES + 5, Some(Error::UntrustedPeer)),
+            (2, NONCE_BYTES + CT_BYTES + VK_BYTES + 5, Some(Error::InvalidSignature)),
+            (2, SERVER_HELLO_BYTES - 1, Some(Error::InvalidMac)),
+            (3, 5, Some(Error::UntrustedPeer)),
+            (3, VK_BYTES + 5, Some(Error::InvalidSignature)),
+            (3, FINISHED_BYTES - 1, Some(Error::InvalidMac)),
+        ];
+
+        for (i, (message, offset, expected)) in cases.into_iter().enumerate() {
+            let mut p = parties(10 + i as u64);
+            let mut bob = responder(&p);
+            let result = handshake(&mut p, &mut bob, &mut MemoryTransport::new(), |step, transport| {
+                if step == message {
+                    let queue = if step == 2 { &mut transport.to_initiator } else { &mut transport.to_responder };
+                    queue.back_mut().unwrap()[offset] ^= 0x01;
+                }
+            });
+
+            match expected {
+                Some(err) => assert_eq!(result.err(), Some(err), "message {} offset {}", message, offset),
+                // A modified ephemeral key leaves the sides with different KEM
+                // secrets, so the responder's MAC or signature no longer matches
+                None => assert!(result.is_err(), "message {} offset {}", message, offset),
+            }
+        }
+    }
+
+    #[test]
+    fn test_replay_rejected() {
+        let mut p = parties(2);
+        let mut bob = responder(&p);
+        let mut recorded = Vec::new();
+        let mut transport = MemoryTransport::new();
+        let (mut alice_session, mut bob_session) = handshake(&mut p, &mut bob, &mut transport, |step, transport| {
+            let queue = if step == 2 { &transport.to_initiator } else { &transport.to_responder };
+            recorded.push(queue.back().unwrap().clone());
+        })
+        .unwrap();
+
+        // Replaying the ClientHello is refused outright
+        assert_eq!(bob.respond(&recorded[0], &mut p.rng).err(), Some(Error::Replay));
+
+        // A recorded Finished does not complete a new session: its signature covers the old transcript
+        let (_, client_hello) = Initiator::start(p.alice.clone(), vec![p.bob.verifying_key()], &mut p.rng);
+        let (_, pending) = bob.respond(&client_hello, &mut p.rng).unwrap();
+        assert_eq!(pending.finish(&recorded[2]).err(), Some(Error::InvalidSignature));
+
+        // A recorded ServerHello does not fit a new initiator's ephemeral key
+        let (initiator, _) = Initiator::start(p.alice.clone(), vec![p.bob.verifying_key()], &mut p.rng);
+        assert!(initiator.finish(&recorded[1], &mut p.rng).is_err());
+
+        // Replayed and reordered records fail to open
+        let first = alice_session.seal(b"first").unwrap();
+        let second = alice_session.seal(b"second").unwrap();
+        assert_eq!(bob_session.open(&second), Err(Error::OpenError));
+        assert_eq!(bob_session.open(&first).unwrap(), b"first");
+        assert_eq!(bob_session.open(&first), Err(Error::OpenError));
+    }
+
+    #[test]
+    fn test_nonce_cache_bounded() {
+        let mut p = parties(4);
+        let mut bob = Responder::with_nonce_capacity(p.bob.clone(), vec![p.alice.verifying_key()], 2);
+        let hellos: Vec<_> =
+            (0..3).map(|_| Initiator::start(p.alice.clone(), vec![p.bob.verifying_key()], &mut p.rng).1).collect();
+        for hello in &hellos {
+            bob.respond(hello, &mut p.rng).unwrap();
+        }
+
+        // Only the two most recent nonces are kept
+        assert_eq!(bob.seen_nonces.order.len(), 2);
+        assert_eq!(bob.seen_nonces.set.len(), 2);
+        assert_eq!(bob.respond(&hellos[2], &mut p.rng).err(), Some(Error::Replay));
+        assert_eq!(bob.respond(&hellos[1], &mut p.rng).err(), Some(Error::Replay));
+
+        // The evicted nonce is accepted again and pushes out the next oldest
+        assert!(bob.respond(&hellos[0], &mut p.rng).is_ok());
+        assert!(bob.respond(&hellos[1], &mut p.rng).is_ok());
+        assert_eq!(bob.seen_nonces.set.len(), 2);
+    }
+
+    #[test]
+    fn test_untrusted_identities_rejected() {
+        let mut p = parties(3);
+        let mallory = IdentityKey::from_seed(&[3u8; 32]);
+
+        // Bob does not know Mallory
+        let mut bob = responder(&p);
+        let (initiator, client_hello) = Initiator::start(mallory.clone(), vec![p.bob.verifying_key()], &mut p.rng);
+        let (server_hello, pending) = bob.respond(&client_hello, &mut p.rng).unwrap();
+        let (finished, _) = initiator.finish(&server_hello, &mut p.rng).unwrap();
+        assert_eq!(pending.finish(&finished).err(), Some(Error::UntrustedPeer));
+
+        // Mallory impersonating a responder Alice does not trust
+        let mut fake = Responder::new(mallory, vec![p.alice.verifying_key()]);
+        let (initiator, client_hello) = Initiator::start(p.alice.clone(), vec![p.bob.verifying_key()], &mut p.rng);
+        let (server_hello, _) = fake.respond(&client_hello, &mut p.rng).unwrap();
+        assert_eq!(initiator.finish(&server_hello, &mut p.rng).err(), Some(Error::UntrustedPeer));
+
+        // Truncated messages
+        assert!(matches!(bob.respond(&client_hello[..100], &mut p.rng), Err(Error::InvalidLength { .. })));
+    }
+}
@@ -0,0 +1,79 @@
+use std::fmt;
+
+/// Errors returned by the protocol implementations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// An encoded key, encapsulation or message does not have the expected length
+    InvalidLength {
+        /// Expected length in bytes
+        expected: usize,
+        /// Actual length in bytes
+        actual: usize,
+    },
+    /// A public key fails validation or belongs to a different KEM than the suite
+    InvalidKey,
+    /// The PSK and PSK ID must be given together, and only in PSK mode
+    InconsistentPsk,
+    /// The AEAD rejected the ciphertext or associated data
+    OpenError,
+    /// The sequence number of an encryption context is exhausted
+    MessageLimitReached,
+    /// The export-only AEAD cannot seal or open messages
+    ExportOnly,
+    /// The requested export is longer than the KDF can produce
+    ExportTooLong,
+    /// A handshake signature does not verify under the peer's identity key
+    InvalidSignature,
+    /// A handshake MAC does not match the transcript
+    InvalidMac,
+    /// The peer's identity key is not in the trusted set
+    UntrustedPeer,
+    /// A handshake message was already processed
+    Replay,
+    /// A key required by the handshake pattern was not provided or received
+    MissingKey,
+    /// A handshake message was written or read out of turn
+    UnexpectedMessage,
+    /// An encrypted file needs between 1 and `MAX_RECIPIENTS` recipients
+    InvalidRecipientCount,
+    /// An encrypted file's header is malformed or fails its MAC
+    InvalidHeader,
+    /// No recipient stanza in the header can be opened with the identity
+    NoMatchingRecipient,
+    /// The encrypted payload ends before its last chunk
+    Truncated,
+    /// Data follows the last chunk of the encrypted payload
+    TrailingData,
+    /// Reading the input or writing the output failed
+    Io(std::io::ErrorKind),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidLength { expected, actual } => {
+                write!(f, "invalid length: expected {} bytes, got {}", expected, actual)
+            }
+            Error::InvalidKey => write!(f, "invalid key for this suite"),
+            Error::InconsistentPsk => write!(f, "inconsistent PSK inputs"),
+            Error::OpenError => write!(f, "decryption failed"),
+            Error::MessageLimitReached => write!(f, "message limit reached"),
+            Error::ExportOnly => write!(f, "suite uses the export-only AEAD"),
+            Error::ExportTooLong => write!(f, "export length exceeds 255 * Nh"),
+            Error::InvalidSignature => write!(f, "invalid handshake signature"),
+            Error::InvalidMac => write!(f, "invalid handshake MAC"),
+            Error::UntrustedPeer => write!(f, "peer identity is not trusted"),
+            Error::Replay => write!(f, "replayed handshake message"),
+            Error::MissingKey => write!(f, "key required by the handshake pattern is missing"),
+            Error::UnexpectedMessage => write!(f, "handshake message out of turn"),
+            Error::InvalidRecipientCount => write!(f, "invalid number of recipients"),
+            Error::InvalidHeader => write!(f, "invalid encrypted file header"),
+            Error::NoMatchingRecipient => write!(f, "no recipient matches the identity"),
+            Error::Truncated => write!(f, "encrypted file is truncated"),
+            Error::TrailingData => write!(f, "trailing data after the encrypted payload"),
+            Error::Io(kind) => write!(f, "I/O error: {}", kind),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,626 @@
+//! Hybrid Public Key Encryption (RFC 9180) with ML-KEM as the KEM
+//!
+//! Supports the base and PSK modes with the HKDF-SHA256 key schedule, the
+//! AES-128-GCM, AES-256-GCM and ChaCha20-Poly1305 AEADs, the export-only
+//! AEAD and the secret export interface. The authenticated modes are not
+//! available because ML-KEM has no AuthEncap.
+//!
+//! ML-KEM-768 and ML-KEM-1024 use the code points 0x0041 and 0x0042. As in
+//! the ML-KEM HPKE drafts, the KEM shared secret is used directly, `enc` is the
+//! ML-KEM ciphertext and DeriveKeyPair expands 64 bytes of seed (d || z) with
+//! the RFC 9180 labeled KDF. Private keys serialize to the full decapsulation
+//! key encoding.
+
+use aes_gcm::aead::{Aead as _, KeyInit, Payload};
+use aes_gcm::{Aes128Gcm, Aes256Gcm};
+use chacha20poly1305::ChaCha20Poly1305;
+use hkdf::Hkdf;
+use kyber_ml_kem::kem::{self, PublicKey, SecretKey};
+use kyber_ml_kem::params::{Kyber1024, Kyber768, SecurityLevel, sizes};
+use kyber_ml_kem::types;
+use rand_core::CryptoRngCore;
+use sha2::Sha256;
+
+use crate::error::Error;
+
+/// Version label prefixed to every labeled KDF input
+const VERSION_LABEL: &[u8] = b"HPKE-v1";
+/// Output size Nh of HKDF-SHA256
+const NH: usize = 32;
+/// Nonce size Nn of every supported AEAD
+const NN: usize = 12;
+
+/// KEM algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kem {
+    /// ML-KEM-768 (0x0041)
+    MlKem768,
+    /// ML-KEM-1024 (0x0042)
+    MlKem1024,
+}
+
+/// KDF algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kdf {
+    /// HKDF-SHA256 (0x0001)
+    HkdfSha256,
+}
+
+/// AEAD algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Aead {
+    /// AES-128-GCM (0x0001)
+    Aes128Gcm,
+    /// AES-256-GCM (0x0002)
+    Aes256Gcm,
+    /// ChaCha20-Poly1305 (0x0003)
+    ChaCha20Poly1305,
+    /// Export-only (0xFFFF): the context can only export secrets
+    ExportOnly,
+}
+
+/// A ciphersuite: one KEM, KDF and AEAD
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Suite {
+    pub kem: Kem,
+    pub kdf: Kdf,
+    pub aead: Aead,
+}
+
+/// Pre-shared key and its identifier for the PSK mode
+#[derive(Debug, Clone, Copy)]
+pub struct Psk<'a> {
+    /// The pre-shared key; it should have at least 32 bytes of entropy
+    pub psk: &'a [u8],
+    /// Identifier of the pre-shared key
+    pub psk_id: &'a [u8],
+}
+
+/// The HPKE modes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Base = 0x00,
+    Psk = 0x01,
+}
+
+impl Kem {
+    /// Returns the IANA KEM identifier
+    pub fn id(self) -> u16 {
+        match self {
+            Kem::MlKem768 => 0x0041,
+            Kem::MlKem1024 => 0x0042,
+        }
+    }
+
+    /// Returns the ML-KEM parameter set
+    pub fn security_level(self) -> SecurityLevel {
+        match self {
+            Kem::MlKem768 => SecurityLevel::Kyber768,
+            Kem::MlKem1024 => SecurityLevel::Kyber1024,
+        }
+    }
+
+    /// Size Nenc of an encapsulated key
+    pub fn enc_len(self) -> usize {
+        sizes::ciphertext_bytes(self.security_level())
+    }
+
+    /// suite_id = "KEM" || I2OSP(kem_id, 2)
+    fn suite_id(self) -> [u8; 5] {
+        let id = self.id().to_be_bytes();
+        [b'K', b'E', b'M', id[0], id[1]]
+    }
+
+    /// Generates a random key pair
+    pub fn generate_key_pair(self, rng: &mut impl CryptoRngCore) -> (PublicKey, SecretKey) {
+        let mut d = [0u8; 32];
+        let mut z = [0u8; 32];
+        rng.fill_bytes(&mut d);
+        rng.fill_bytes(&mut z);
+        kem::keygen_internal(&d, &z, self.security_level())
+    }
+
+    /// Deterministically derives a key pair from input keying material
+    ///
+    /// The 64-byte seed d || z is LabeledExpand(LabeledExtract("", "dkp_prk", ikm), "sk", "", 64).
+    pub fn derive_key_pair(self, ikm: &[u8]) -> (PublicKey, SecretKey) {
+        let suite_id = self.suite_id();
+        let dkp_prk = labeled_extract(&suite_id, b"", b"dkp_prk", ikm);
+        let seed = labeled_expand(&suite_id, &dkp_prk, b"sk", b"", 64).expect("64 bytes is a valid HKDF length");
+
+        let mut d = [0u8; 32];
+        let mut z = [0u8; 32];
+        d.copy_from_slice(&seed[..32]);
+        z.copy_from_slice(&seed[32..]);
+        kem::keygen_internal(&d, &z, self.security_level())
+    }
+
+    /// SerializePublicKey: the ML-KEM encapsulation key encoding
+    pub fn serialize_public_key(self, pk: &PublicKey) -> Vec<u8> {
+        kem::pk_to_bytes(pk)
+    }
+
+    /// DeserializePublicKey, including the ML-KEM modulus check
+    pub fn deserialize_public_key(self, bytes: &[u8]) -> Result<PublicKey, Error> {
+        let key = match self {
+            Kem::MlKem768 => types::EncapsulationKey::<Kyber768>::try_from(bytes).map(|k| k.as_key().clone()),
+            Kem::MlKem1024 => types::EncapsulationKey::<Kyber1024>::try_from(bytes).map(|k| k.as_key().clone()),
+        };
+        key.map_err(kem_error)
+    }
+
+    /// SerializePrivateKey: the ML-KEM decapsulation key encoding
+    pub fn serialize_private_key(self, sk: &SecretKey) -> Vec<u8> {
+        kem::sk_to_bytes(sk)
+    }
+
+    /// DeserializePrivateKey, including the ML-KEM hash check
+    pub fn deserialize_private_key(self, bytes: &[u8]) -> Result<SecretKey, Error> {
+        let key = match self {
+            Kem::MlKem768 => types::DecapsulationKey::<Kyber768>::try_from(bytes).map(|k| k.as_key().clone()),
+            Kem::MlKem1024 => types::DecapsulationKey::<Kyber1024>::try_from(bytes).map(|k| k.as_key().clone()),
+        };
+        key.map_err(kem_error)
+    }
+
+    /// Encap(pkR): the shared secret and the ML-KEM ciphertext as `enc`
+    fn encap(self, pk_r: &PublicKey, rng: &mut impl CryptoRngCore) -> Result<([u8; 32], Vec<u8>), Error> {
+        if pk_r.pk.security_level != self.security_level() {
+            return Err(Error::InvalidKey);
+        }
+
+        let mut m = [0u8; 32];
+        rng.fill_bytes(&mut m);
+        let encapsulation = kem::encaps_derand(pk_r, &m);
+        Ok((encapsulation.shared_secret, kem::ciphertext_to_bytes(&encapsulation.ciphertext)))
+    }
+
+    /// Decap(enc, skR)
+    fn decap(self, enc: &[u8], sk_r: &SecretKey) -> Result<[u8; 32], Error> {
+        if sk_r.sk.security_level != self.security_level() {
+            return Err(Error::InvalidKey);
+        }
+        if enc.len() != self.enc_len() {
+            return Err(Error::InvalidLength { expected: self.enc_len(), actual: enc.len() });
+        }
+
+        let ct = kem::ciphertext_from_bytes(enc, self.security_level());
+        Ok(kem::decaps(sk_r, &ct))
+    }
+}
+
+/// Maps key decoding errors of the ML-KEM crate
+fn kem_error(err: kyber_ml_kem::error::Error) -> Error {
+    match err {
+        kyber_ml_kem::error::Error::InvalidLength { expected, actual } => Error::InvalidLength { expected, actual },
+        _ => Error::InvalidKey,
+    }
+}
+
+impl Kdf {
+    /// Returns the IANA KDF identifier
+    pub fn id(self) -> u16 {
+        match self {
+            Kdf::HkdfSha256 => 0x0001,
+        }
+    }
+}
+
+impl Aead {
+    /// Returns the IANA AEAD identifier
+    pub fn id(self) -> u16 {
+        match self {
+            Aead::Aes128Gcm => 0x0001,
+            Aead::Aes256Gcm => 0x0002,
+            Aead::ChaCha20Poly1305 => 0x0003,
+            Aead::ExportOnly => 0xFFFF,
+        }
+    }
+
+    /// Key size Nk
+    pub fn key_len(self) -> usize {
+        match self {
+            Aead::Aes128Gcm => 16,
+            Aead::Aes256Gcm | Aead::ChaCha20Poly1305 => 32,
+            Aead::ExportOnly => 0,
+        }
+    }
+
+    fn seal(self, key: &[u8], nonce: &[u8; NN], aad: &[u8], pt: &[u8]) -> Result<Vec<u8>, Error> {
+        let payload = Payload { msg: pt, aad };
+        let ct = match self {
+            Aead::Aes128Gcm => Aes128Gcm::new_from_slice(key).unwrap().encrypt(nonce.into(), payload),
+            Aead::Aes256Gcm => Aes256Gcm::new_from_slice(key).unwrap().encrypt(nonce.into(), payload),
+            Aead::ChaCha20Poly1305 => ChaCha20Poly1305::new_from_slice(key).unwrap().encrypt(nonce.into(), payload),
+            Aead::ExportOnly => return Err(Error::ExportOnly),
+        };
+        // Encryption only fails for messages beyond the AEAD's length limit
+        ct.map_err(|_| Error::MessageLimitReached)
+    }
+
+    fn open(self, key: &[u8], nonce: &[u8; NN], aad: &[u8], ct: &[u8]) -> Result<Vec<u8>, Error> {
+        let payload = Payload { msg: ct, aad };
+        let pt = match self {
+            Aead::Aes128Gcm => Aes128Gcm::new_from_slice(key).unwrap().decrypt(nonce.into(), payload),
+            Aead::Aes256Gcm => Aes256Gcm::new_from_slice(key).unwrap().decrypt(nonce.into(), payload),
+            Aead::ChaCha20Poly1305 => ChaCha20Poly1305::new_from_slice(key).unwrap().decrypt(nonce.into(), payload),
+            Aead::ExportOnly => return Err(Error::ExportOnly),
+        };
+        pt.map_err(|_| Error::OpenError)
+    }
+}
+
+impl Suite {
+    /// Creates a suite with HKDF-SHA256
+    pub fn new(kem: Kem, aead: Aead) -> Self {
+        Self { kem, kdf: Kdf::HkdfSha256, aead }
+    }
+
+    /// suite_id = "HPKE" || I2OSP(kem_id, 2) || I2OSP(kdf_id, 2) || I2OSP(aead_id, 2)
+    fn id(&self) -> [u8; 10] {
+        hpke_suite_id(self.kem.id(), self.kdf.id(), self.aead.id())
+    }
+}
+
+fn hpke_suite_id(kem_id: u16, kdf_id: u16, aead_id: u16) -> [u8; 10] {
+    let mut id = [0u8; 10];
+    id[..4].copy_from_slice(b"HPKE");
+    id[4..6].copy_from_slice(&kem_id.to_be_bytes());
+    id[6..8].copy_from_slice(&kdf_id.to_be_bytes());
+    id[8..].copy_from_slice(&aead_id.to_be_bytes());
+    id
+}
+
+/// LabeledExtract(salt, label, ikm) = Extract(salt, "HPKE-v1" || suite_id || label || ikm)
+fn labeled_extract(suite_id: &[u8], salt: &[u8], label: &[u8], ikm: &[u8]) -> [u8; NH] {
+    let labeled_ikm = [VERSION_LABEL, suite_id, label, ikm].concat();
+    let (prk, _) = Hkdf::<Sha256>::extract(Some(salt), &labeled_ikm);
+    prk.into()
+}
+
+/// LabeledExpand(prk, label, info, L) = Expand(prk, I2OSP(L, 2) || "HPKE-v1" || suite_id || label || info, L)
+fn labeled_expand(suite_id: &[u8], prk: &[u8; NH], label: &[u8], info: &[u8], len: usize) -> Result<Vec<u8>, Error> {
+    let len_bytes = u16::try_from(len).map_err(|_| Error::ExportTooLong)?.to_be_bytes();
+    let hkdf = Hkdf::<Sha256>::from_prk(prk).expect("PRK has the hash length");
+
+    let mut out = vec![0u8; len];
+    hkdf.expand_multi_info(&[&len_bytes, VERSION_LABEL, suite_id, label, info], &mut out)
+        .map_err(|_| Error::ExportTooLong)?;
+    Ok(out)
+}
+
+/// Encryption context shared by the sender and the recipient
+#[derive(Clone)]
+struct Context {
+    suite_id: [u8; 10],
+    aead: Aead,
+    key: Vec<u8>,
+    base_nonce: [u8; NN],
+    seq: u64,
+    exporter_secret: [u8; NH],
+}
+
+/// KeySchedule<ROLE>(mode, shared_secret, info, psk, psk_id)
+fn key_schedule(suite_id: [u8; 10], aead: Aead, shared_secret: &[u8], info: &[u8], psk: Option<Psk<'_>>) -> Result<Context, Error> {
+    // VerifyPSKInputs: the mode follows from whether a PSK is given
+    let (mode, psk, psk_id) = match psk {
+        None => (Mode::Base, &[][..], &[][..]),
+        Some(Psk { psk, psk_id }) if !psk.is_empty() && !psk_id.is_empty() => (Mode::Psk, psk, psk_id),
+        Some(_) => return Err(Error::InconsistentPsk),
+    };
+
+    let psk_id_hash = labeled_extract(&suite_id, b"", b"psk_id_hash", psk_id);
+    let info_hash = labeled_extract(&suite_id, b"", b"info_hash", info);
+    let context = [&[mode as u8][..], &psk_id_hash, &info_hash].concat();
+
+    let secret = labeled_extract(&suite_id, shared_secret, b"secret", psk);
+    let key = labeled_expand(&suite_id, &secret, b"key", &context, aead.key_len())?;
+    let mut base_nonce = [0u8; NN];
+    base_nonce.copy_from_slice(&labeled_expand(&suite_id, &secret, b"base_nonce", &context, NN)?);
+    let mut exporter_secret = [0u8; NH];
+    exporter_secret.copy_from_slice(&labeled_expand(&suite_id, &secret, b"exp", &context, NH)?);
+
+    Ok(Context { suite_id, aead, key, base_nonce, seq: 0, exporter_secret })
+}
+
+impl Context {
+    /// ComputeNonce(seq) = base_nonce XOR I2OSP(seq, Nn)
+    fn nonce(&self) -> [u8; NN] {
+        let mut nonce = self.base_nonce;
+        for (n, s) in nonce[NN - 8..].iter_mut().zip(self.seq.to_be_bytes()) {
+            *n ^= s;
+        }
+        nonce
+    }
+
+    fn increment_seq(&mut self) -> Result<(), Error> {
+        // Nn = 12 allows 2^96 - 1 messages, more than a u64 counter can reach
+        self.seq = self.seq.checked_add(1).ok_or(Error::MessageLimitReached)?;
+        Ok(())
+    }
+
+    fn seal(&mut self, aad: &[u8], pt: &[u8]) -> Result<Vec<u8>, Error> {
+        if self.seq == u64::MAX {
+            return Err(Error::MessageLimitReached);
+        }
+        let ct = self.aead.seal(&self.key, &self.nonce(), aad, pt)?;
+        self.increment_seq()?;
+        Ok(ct)
+    }
+
+    fn open(&mut self, aad: &[u8], ct: &[u8]) -> Result<Vec<u8>, Error> {
+        if self.seq == u64::MAX {
+            return Err(Error::MessageLimitReached);
+        }
+        let pt = self.aead.open(&self.key, &self.nonce(), aad, ct)?;
+        self.increment_seq()?;
+        Ok(pt)
+    }
+
+    fn export(&self, exporter_context: &[u8], len: usize) -> Result<Vec<u8>, Error> {
+        labeled_expand(&self.suite_id, &self.exporter_secret, b"sec", exporter_context, len)
+    }
+}
+
+/// Sender side of an HPKE context: seals messages in order
+#[derive(Clone)]
+pub struct SenderContext(Context);
+
+/// Recipient side of an HPKE context: opens messages in the order they were sealed
+#[derive(Clone)]
+pub struct RecipientContext(Context);
+
+impl SenderContext {
+    /// Encrypts the next message
+    pub fn seal(&mut self, aad: &[u8], pt: &[u8]) -> Result<Vec<u8>, Error> {
+        self.0.seal(aad, pt)
+    }
+
+    /// Derives a secret of `len` bytes bound to `exporter_context`
+    pub fn export(&self, exporter_context: &[u8], len: usize) -> Result<Vec<u8>, Error> {
+        self.0.export(exporter_context, len)
+    }
+}
+
+impl RecipientContext {
+    /// Decrypts the next message
+    ///
+    /// A failed decryption does not advance the sequence number.
+    pub fn open(&mut self, aad: &[u8], ct: &[u8]) -> Result<Vec<u8>, Error> {
+        self.0.open(aad, ct)
+    }
+
+    /// Derives a secret of `len` bytes bound to `exporter_context`
+    pub fn export(&self, exporter_context: &[u8], len: usize) -> Result<Vec<u8>, Error> {
+        self.0.export(exporter_context, len)
+    }
+}
+
+/// SetupBaseS / SetupPSKS: encapsulates to `pk_r` and returns `enc` and the sender context
+///
+/// The mode is PSK when `psk` is given and base otherwise.
+pub fn setup_sender(
+    suite: Suite,
+    pk_r: &PublicKey,
+    info: &[u8],
+    psk: Option<Psk<'_>>,
+    rng: &mut impl CryptoRngCore,
+) -> Result<(Vec<u8>, SenderContext), Error> {
+    let (shared_secret, enc) = suite.kem.encap(pk_r, rng)?;
+    let context = key_schedule(suite.id(), suite.aead, &shared_secret, info, psk)?;
+    Ok((enc, SenderContext(context)))
+}
+
+/// SetupBaseR / SetupPSKR: decapsulates `enc` and returns the recipient context
+///
+/// ML-KEM decapsulation never fails, so a forged `enc` only shows up as
+/// failing [`RecipientContext::open`] calls.
+pub fn setup_receiver(
+    suite: Suite,
+    enc: &[u8],
+    sk_r: &SecretKey,
+    info: &[u8],
+    psk: Option<Psk<'_>>,
+) -> Result<RecipientContext, Error> {
+    let shared_secret = suite.kem.decap(enc, sk_r)?;
+    let context = key_schedule(suite.id(), suite.aead, &shared_secret, info, psk)?;
+    Ok(RecipientContext(context))
+}
+
+/// Single-shot encryption: returns `enc` and the ciphertext
+pub fn seal(
+    suite: Suite,
+    pk_r: &PublicKey,
+    info: &[u8],
+    aad: &[u8],
+    pt: &[u8],
+    psk: Option<Psk<'_>>,
+    rng: &mut impl CryptoRngCore,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let (enc, mut context) = setup_sender(suite, pk_r, info, psk, rng)?;
+    let ct = context.seal(aad, pt)?;
+    Ok((enc, ct))
+}
+
+/// Single-shot decryption of a ciphertext produced by [`seal`]
+pub fn open(
+    suite: Suite,
+    enc: &[u8],
+    sk_r: &SecretKey,
+    info: &[u8],
+    aad: &[u8],
+    ct: &[u8],
+    psk: Option<Psk<'_>>,
+) -> Result<Vec<u8>, Error> {
+    setup_receiver(suite, enc, sk_r, info, psk)?.open(aad, ct)
+}
+
+/// Single-shot secret export for the sender: returns `enc` and the exported secret
+pub fn send_export(
+    suite: Suite,
+    pk_r: &PublicKey,
+    info: &[u8],
+    exporter_context: &[u8],
+    len: usize,
+    psk: Option<Psk<'_>>,
+    rng: &mut impl CryptoRngCore,
+) -> Result<(Vec<u8>, Vec<u8>), Error> {
+    let (enc, context) = setup_sender(suite, pk_r, info, psk, rng)?;
+    Ok((enc, context.export(exporter_context, len)?))
+}
+
+/// Single-shot secret export for the recipient
+pub fn receive_export(
+    suite: Suite,
+    enc: &[u8],
+    sk_r: &SecretKey,
+    info: &[u8],
+    exporter_context: &[u8],
+    len: usize,
+    psk: Option<Psk<'_>>,
+) -> Result<Vec<u8>, Error> {
+    setup_receiver(suite, enc, sk_r, info, psk)?.export(exporter_context, len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    const PSK: Psk<'static> = Psk { psk: b"0247fd33b913760fa1fa51e1892d9f307fbe65eb171e8132c2af18555a738b82", psk_id: b"Ennyn Durin aran Moria" };
+
+    fn suites() -> Vec<Suite> {
+        let mut suites = Vec::new();
+        for kem in [Kem::MlKem768, Kem::MlKem1024] {
+            for aead in [Aead::Aes128Gcm, Aead::Aes256Gcm, Aead::ChaCha20Poly1305] {
+                suites.push(Suite::new(kem, aead));
+            }
+        }
+        suites
+    }
+
+    /// RFC 9180, A.1.1: DHKEM(X25519, HKDF-SHA256), HKDF-SHA256, AES-128-GCM, base mode
+    ///
+    /// The key schedule only depends on the KEM through its identifier, so the
+    /// published shared secret exercises it without the DHKEM.
+    #[test]
+    fn test_rfc9180_key_schedule() {
+        let shared_secret = hex::decode("fe0e18c9f024ce43799ae393c7e8fe8fce9d218875e8227b0187c04e7d2ea1fc").unwrap();
+        let info = hex::decode("4f6465206f6e2061204772656369616e2055726e").unwrap();
+        let mut context = key_schedule(hpke_suite_id(0x0020, 0x0001, 0x0001), Aead::Aes128Gcm, &shared_secret, &info, None).unwrap();
+
+        assert_eq!(hex::encode(&context.key), "4531685d41d65f03dc48f6b8302c05b0");
+        assert_eq!(hex::encode(context.base_nonce), "56d890e5accaaf011cff4b7d");
+        assert_eq!(hex::encode(context.exporter_secret), "45ff1c2e220db587171952c0592d5f5ebe103f1561a2614e38f2ffd47e99e3f8");
+
+        let pt = hex::decode("4265617574792069732074727574682c20747275746820626561757479").unwrap();
+        let ct = context.seal(b"Count-0", &pt).unwrap();
+        assert_eq!(hex::encode(ct), "f938558b5d72f1a23810b4be2ab4f84331acc02fc97babc53a52ae8218a355a96d8770ac83d07bea87e13c512a");
+
+        assert_eq!(hex::encode(context.export(b"", 32).unwrap()), "3853fe2b4035195a573ffc53856e77058e15d9ea064de3e59f4961d0095250ee");
+        assert_eq!(hex::encode(context.export(&[0x00], 32).unwrap()), "2e8f0b54673c7029649d4eb9d5e33bf1872cf76d623ff164ac185da9e88c21a5");
+        assert_eq!(hex::encode(context.export(b"TestContext", 32).unwrap()), "e9e43065102c3836401bed8c3c3c75ae46be1639869391d62c61f1ec7af54931");
+    }
+
+    #[test]
+    fn test_multi_message_every_suite_and_mode() {
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        for suite in suites() {
+            let (pk, sk) = suite.kem.generate_key_pair(&mut rng);
+            for psk in [None, Some(PSK)] {
+                let (enc, mut sender) = setup_sender(suite, &pk, b"info", psk, &mut rng).unwrap();
+                assert_eq!(enc.len(), suite.kem.enc_len());
+                let mut receiver = setup_receiver(suite, &enc, &sk, b"info", psk).unwrap();
+
+                for i in 0..3u8 {
+                    let ct = sender.seal(&[i], b"message").unwrap();
+                    assert_eq!(receiver.open(&[i], &ct).unwrap(), b"message");
+                }
+                assert_eq!(sender.export(b"ctx", 48).unwrap(), receiver.export(b"ctx", 48).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn test_single_shot_and_export() {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        let suite = Suite::new(Kem::MlKem1024, Aead::ChaCha20Poly1305);
+        let (pk, sk) = suite.kem.generate_key_pair(&mut rng);
+
+        let (enc, ct) = seal(suite, &pk, b"info", b"aad", b"payload", Some(PSK), &mut rng).unwrap();
+        assert_eq!(open(suite, &enc, &sk, b"info", b"aad", &ct, Some(PSK)).unwrap(), b"payload");
+
+        let export_only = Suite::new(Kem::MlKem768, Aead::ExportOnly);
+        let (pk, sk) = export_only.kem.generate_key_pair(&mut rng);
+        let (enc, secret) = send_export(export_only, &pk, b"", b"label", 64, None, &mut rng).unwrap();
+        assert_eq!(receive_export(export_only, &enc, &sk, b"", b"label", 64, None).unwrap(), secret);
+        assert_eq!(seal(export_only, &pk, b"", b"", b"x", None, &mut rng).unwrap_err(), Error::ExportOnly);
+    }
+
+    #[test]
+    fn test_mismatched_inputs_fail_to_open() {
+        let mut rng = ChaCha20Rng::seed_from_u64(3);
+        let suite = Suite::new(Kem::MlKem768, Aead::Aes256Gcm);
+        let (pk, sk) = suite.kem.generate_key_pair(&mut rng);
+        let (enc, ct) = seal(suite, &pk, b"info", b"aad", b"payload", Some(PSK), &mut rng).unwrap();
+
+        let other_psk = Psk { psk: &[7u8; 32], psk_id: PSK.psk_id };
+        assert_eq!(open(suite, &enc, &sk, b"other", b"aad", &ct, Some(PSK)), Err(Error::OpenError));
+        assert_eq!(open(suite, &enc, &sk, b"info", b"other", &ct, Some(PSK)), Err(Error::OpenError));
+        assert_eq!(open(suite, &enc, &sk, b"info", b"aad", &ct, Some(other_psk)), Err(Error::OpenError));
+        assert_eq!(open(suite, &enc, &sk, b"info", b"aad", &ct, None), Err(Error::OpenError));
+
+        // A tampered enc decapsulates to an unrelated secret (implicit rejection)
+        let mut bad_enc = enc.clone();
+        bad_enc[0] ^= 1;
+        assert_eq!(open(suite, &bad_enc, &sk, b"info", b"aad", &ct, Some(PSK)), Err(Error::OpenError));
+
+        // Messages must be opened in order
+        let (enc, mut sender) = setup_sender(suite, &pk, b"", None, &mut rng).unwrap();
+        let mut receiver = setup_receiver(suite, &enc, &sk, b"", None).unwrap();
+        let first = sender.seal(b"", b"first").unwrap();
+        let second = sender.seal(b"", b"second").unwrap();
+        assert_eq!(receiver.open(b"", &second), Err(Error::OpenError));
+        assert_eq!(receiver.open(b"", &first).unwrap(), b"first");
+        assert_eq!(receiver.open(b"", &second).unwrap(), b"second");
+    }
+
+    #[test]
+    fn test_invalid_inputs_rejected() {
+        let mut rng = ChaCha20Rng::seed_from_u64(4);
+        let suite = Suite::new(Kem::MlKem768, Aead::Aes128Gcm);
+        let (pk, sk) = suite.kem.derive_key_pair(b"ikm");
+
+        // Deterministic derivation and key serialization
+        assert_eq!(suite.kem.serialize_public_key(&suite.kem.derive_key_pair(b"ikm").0), suite.kem.serialize_public_key(&pk));
+        let pk_bytes = suite.kem.serialize_public_key(&pk);
+        assert_eq!(suite.kem.serialize_public_key(&suite.kem.deserialize_public_key(&pk_bytes).unwrap()), pk_bytes);
+        let sk_bytes = suite.kem.serialize_private_key(&sk);
+        assert_eq!(suite.kem.serialize_private_key(&suite.kem.deserialize_private_key(&sk_bytes).unwrap()), sk_bytes);
+        assert!(Kem::MlKem1024.deserialize_public_key(&pk_bytes).is_err());
+
+        let missing_id = Psk { psk: PSK.psk, psk_id: b"" };
+        assert_eq!(setup_sender(suite, &pk, b"", Some(missing_id), &mut rng).err(), Some(Error::InconsistentPsk));
+
+        // A key of the wrong parameter set and a truncated enc
+        let (pk_1024, _) = Kem::MlKem1024.generate_key_pair(&mut rng);
+        assert_eq!(setup_sender(suite, &pk_1024, b"", None, &mut rng).err(), Some(Error::InvalidKey));
+        assert!(matches!(setup_receiver(suite, &[0u8; 10], &sk, b"", None), Err(Error::InvalidLength { .. })));
+
+        let (_, context) = setup_sender(suite, &pk, b"", None, &mut rng).unwrap();
+        assert_eq!(context.export(b"", 255 * 32 + 1).unwrap_err(), Error::ExportTooLong);
+    }
+
+    #[test]
+    fn test_message_limit() {
+        let mut rng = ChaCha20Rng::seed_from_u64(5);
+        let suite = Suite::new(Kem::MlKem768, Aead::Aes128Gcm);
+        let (pk, _) = suite.kem.generate_key_pair(&mut rng);
+        let (_, mut sender) = setup_sender(suite, &pk, b"", None, &mut rng).unwrap();
+
+        sender.0.seq = u64::MAX - 1;
+        assert!(sender.seal(b"", b"last").is_ok());
+        assert_eq!(sender.seal(b"", b"one too many"), Err(Error::MessageLimitReached));
+    }
+}
@@ -0,0 +1,5 @@
+pub mod ake;
+pub mod envelope;
+pub mod error;
+pub mod hpke;
+pub mod noise;
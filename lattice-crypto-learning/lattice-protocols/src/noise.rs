@@ -0,0 +1,600 @@
+//! Noise protocol framework with KEM-based handshakes (PQNoise)
+//!
+//! Implements the CipherState, SymmetricState and HandshakeState objects of
+//! the Noise specification (revision 34) with ChaCha20-Poly1305 and SHA-256,
+//! and the KEM patterns of PQNoise (Angel et al., CCS 2022) in which
+//! ML-KEM-768 encapsulation replaces Diffie-Hellman:
+//!
+//! - `e`: send a fresh ephemeral encapsulation key in the clear
+//! - `s`: send the static encapsulation key, encrypted once a key is set
+//! - `ekem`: encapsulate to the peer's ephemeral key, send the ciphertext and
+//!   mix the shared secret into the chaining key
+//! - `skem`: the same for the peer's static key, with the ciphertext encrypted
+//!
+//! Protocol names have the form `Noise_pqXX_MLKEM768_ChaChaPoly_SHA256`.
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::ChaCha20Poly1305;
+use hkdf::Hkdf;
+use kyber_ml_kem::kem;
+use kyber_ml_kem::params::Kyber768;
+use kyber_ml_kem::types::{Ciphertext, DecapsulationKey, EncapsulationKey};
+use rand_core::CryptoRngCore;
+use sha2::{Digest, Sha256};
+
+use crate::error::Error;
+
+/// Maximum size of any Noise message
+pub const MAX_MESSAGE_BYTES: usize = 65535;
+/// Size of the AEAD authentication tag
+pub const TAG_BYTES: usize = 16;
+/// ML-KEM-768 encapsulation key
+const EK_BYTES: usize = 1184;
+/// ML-KEM-768 ciphertext
+const CT_BYTES: usize = 1088;
+/// Output size of SHA-256
+const HASHLEN: usize = 32;
+
+/// A handshake token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    E,
+    S,
+    Ekem,
+    Skem,
+}
+
+/// A KEM handshake pattern: pre-messages and the handshake message tokens
+///
+/// Messages alternate between initiator and responder, starting with the
+/// initiator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HandshakePattern {
+    pub name: &'static str,
+    /// Keys the responder has already made known to the initiator
+    pub responder_pre_message: &'static [Token],
+    pub messages: &'static [&'static [Token]],
+}
+
+/// Unauthenticated: ephemeral keys only
+pub const PQ_NN: HandshakePattern = HandshakePattern {
+    name: "pqNN",
+    responder_pre_message: &[],
+    messages: &[&[Token::E], &[Token::Ekem]],
+};
+
+/// Initiator knows the responder's static key in advance
+pub const PQ_NK: HandshakePattern = HandshakePattern {
+    name: "pqNK",
+    responder_pre_message: &[Token::S],
+    messages: &[&[Token::Skem, Token::E], &[Token::Ekem]],
+};
+
+/// Mutual authentication with static keys exchanged during the handshake
+pub const PQ_XX: HandshakePattern = HandshakePattern {
+    name: "pqXX",
+    responder_pre_message: &[],
+    messages: &[&[Token::E], &[Token::Ekem, Token::S], &[Token::Skem, Token::S], &[Token::Skem]],
+};
+
+/// Mutual authentication where the initiator knows the responder's static key
+pub const PQ_IK: HandshakePattern = HandshakePattern {
+    name: "pqIK",
+    responder_pre_message: &[Token::S],
+    messages: &[&[Token::Skem, Token::E, Token::S], &[Token::Ekem, Token::Skem]],
+};
+
+/// An AEAD key and nonce counter
+#[derive(Clone, Default)]
+pub struct CipherState {
+    k: Option<[u8; 32]>,
+    n: u64,
+}
+
+impl CipherState {
+    /// InitializeKey(key): sets the key and resets the nonce
+    pub fn initialize_key(&mut self, k: Option<[u8; 32]>) {
+        self.k = k;
+        self.n = 0;
+    }
+
+    pub fn has_key(&self) -> bool {
+        self.k.is_some()
+    }
+
+    /// The nonce is 32 zero bits followed by the little-endian 64-bit counter
+    fn nonce(&self) -> Result<[u8; 12], Error> {
+        // 2^64 - 1 is reserved by the specification
+        if self.n == u64::MAX {
+            return Err(Error::MessageLimitReached);
+        }
+        let mut nonce = [0u8; 12];
+        nonce[4..].copy_from_slice(&self.n.to_le_bytes());
+        Ok(nonce)
+    }
+
+    /// Encrypts with the current nonce, or returns the plaintext if no key is set
+    pub fn encrypt_with_ad(&mut self, ad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let Some(k) = self.k else { return Ok(plaintext.to_vec()) };
+        let nonce = self.nonce()?;
+        let ciphertext = ChaCha20Poly1305::new(&k.into())
+            .encrypt(&nonce.into(), Payload { msg: plaintext, aad: ad })
+            .map_err(|_| Error::MessageLimitReached)?;
+        self.n += 1;
+        Ok(ciphertext)
+    }
+
+    /// Decrypts with the current nonce; a failure leaves the nonce unchanged
+    pub fn decrypt_with_ad(&mut self, ad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        let Some(k) = self.k else { return Ok(ciphertext.to_vec()) };
+        let nonce = self.nonce()?;
+        let plaintext = ChaCha20Poly1305::new(&k.into())
+            .decrypt(&nonce.into(), Payload { msg: ciphertext, aad: ad })
+            .map_err(|_| Error::OpenError)?;
+        self.n += 1;
+        Ok(plaintext)
+    }
+
+    /// Rekey(): replaces the key with ENCRYPT(k, 2^64 - 1, "", zeros)
+    pub fn rekey(&mut self) {
+        if let Some(k) = self.k {
+            let mut nonce = [0u8; 12];
+            nonce[4..].copy_from_slice(&u64::MAX.to_le_bytes());
+            let out = ChaCha20Poly1305::new(&k.into())
+                .encrypt(&nonce.into(), &[0u8; 32][..])
+                .expect("32-byte plaintext is within the AEAD limits");
+            self.k = Some(out[..32].try_into().expect("ciphertext has at least 32 bytes"));
+        }
+    }
+}
+
+/// Chaining key, handshake hash and the handshake CipherState
+#[derive(Clone)]
+pub struct SymmetricState {
+    cipher: CipherState,
+    ck: [u8; HASHLEN],
+    h: [u8; HASHLEN],
+}
+
+/// HKDF(chaining_key, ikm) with two outputs, as defined by Noise
+fn noise_hkdf(ck: &[u8; HASHLEN], ikm: &[u8]) -> ([u8; HASHLEN], [u8; HASHLEN]) {
+    // Noise's HKDF is RFC 5869 with the chaining key as salt and empty info
+    let mut okm = [0u8; 2 * HASHLEN];
+    Hkdf::<Sha256>::new(Some(ck), ikm).expand(&[], &mut okm).expect("64 bytes is a valid HKDF length");
+    let (a, b) = okm.split_at(HASHLEN);
+    (a.try_into().unwrap(), b.try_into().unwrap())
+}
+
+impl SymmetricState {
+    /// InitializeSymmetric(protocol_name)
+    pub fn new(protocol_name: &str) -> Self {
+        let mut h = [0u8; HASHLEN];
+        if protocol_name.len() <= HASHLEN {
+            h[..protocol_name.len()].copy_from_slice(protocol_name.as_bytes());
+        } else {
+            h = Sha256::digest(protocol_name.as_bytes()).into();
+        }
+        Self { cipher: CipherState::default(), ck: h, h }
+    }
+
+    pub fn mix_key(&mut self, ikm: &[u8]) {
+        let (ck, temp_k) = noise_hkdf(&self.ck, ikm);
+        self.ck = ck;
+        self.cipher.initialize_key(Some(temp_k));
+    }
+
+    pub fn mix_hash(&mut self, data: &[u8]) {
+        self.h = Sha256::new().chain_update(self.h).chain_update(data).finalize().into();
+    }
+
+    pub fn handshake_hash(&self) -> &[u8; HASHLEN] {
+        &self.h
+    }
+
+    pub fn encrypt_and_hash(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, Error> {
+        let ciphertext = self.cipher.encrypt_with_ad(&self.h, plaintext)?;
+        self.mix_hash(&ciphertext);
+        Ok(ciphertext)
+    }
+
+    pub fn decrypt_and_hash(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        let plaintext = self.cipher.decrypt_with_ad(&self.h, ciphertext)?;
+        self.mix_hash(ciphertext);
+        Ok(plaintext)
+    }
+
+    /// Split(): the initiator-to-responder and responder-to-initiator CipherStates
+    pub fn split(&self) -> (CipherState, CipherState) {
+        let (k1, k2) = noise_hkdf(&self.ck, &[]);
+        (CipherState { k: Some(k1), n: 0 }, CipherState { k: Some(k2), n: 0 })
+    }
+
+    /// Length on the wire of `len` bytes passed through EncryptAndHash
+    fn encrypted_len(&self, len: usize) -> usize {
+        if self.cipher.has_key() { len + TAG_BYTES } else { len }
+    }
+}
+
+/// Reads the next `len` bytes of a handshake message
+fn take<'a>(message: &mut &'a [u8], len: usize) -> Result<&'a [u8], Error> {
+    if message.len() < len {
+        return Err(Error::InvalidLength { expected: len, actual: message.len() });
+    }
+    let (field, rest) = message.split_at(len);
+    *message = rest;
+    Ok(field)
+}
+
+fn check_message_len(len: usize) -> Result<(), Error> {
+    if len > MAX_MESSAGE_BYTES {
+        return Err(Error::InvalidLength { expected: MAX_MESSAGE_BYTES, actual: len });
+    }
+    Ok(())
+}
+
+/// The handshake state machine of one party
+pub struct HandshakeState {
+    symmetric: SymmetricState,
+    pattern: HandshakePattern,
+    initiator: bool,
+    s: Option<DecapsulationKey<Kyber768>>,
+    e: Option<DecapsulationKey<Kyber768>>,
+    rs: Option<EncapsulationKey<Kyber768>>,
+    re: Option<EncapsulationKey<Kyber768>>,
+    next_message: usize,
+}
+
+impl HandshakeState {
+    /// Initialize(): `s` is the local static key, `rs` the responder's
+    /// pre-shared static key where the pattern requires one
+    pub fn new(
+        pattern: HandshakePattern,
+        initiator: bool,
+        prologue: &[u8],
+        s: Option<DecapsulationKey<Kyber768>>,
+        rs: Option<EncapsulationKey<Kyber768>>,
+    ) -> Result<Self, Error> {
+        let name = format!("Noise_{}_MLKEM768_ChaChaPoly_SHA256", pattern.name);
+        let mut symmetric = SymmetricState::new(&name);
+        symmetric.mix_hash(prologue);
+
+        if pattern.responder_pre_message.contains(&Token::S) {
+            let static_key = if initiator {
+                rs.clone().ok_or(Error::MissingKey)?
+            } else {
+                s.as_ref().ok_or(Error::MissingKey)?.encapsulation_key()
+            };
+            symmetric.mix_hash(static_key.as_bytes());
+        }
+
+        Ok(Self { symmetric, pattern, initiator, s, e: None, rs, re: None, next_message: 0 })
+    }
+
+    /// Whether it is this party's turn to write
+    pub fn is_my_turn(&self) -> bool {
+        (self.next_message % 2 == 0) == self.initiator
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_message == self.pattern.messages.len()
+    }
+
+    /// The peer's static key, once received or pre-shared
+    pub fn remote_static(&self) -> Option<&EncapsulationKey<Kyber768>> {
+        self.rs.as_ref()
+    }
+
+    /// The current handshake hash, usable for channel binding after the handshake
+    pub fn handshake_hash(&self) -> &[u8; HASHLEN] {
+        self.symmetric.handshake_hash()
+    }
+
+    fn next_tokens(&mut self, writing: bool) -> Result<&'static [Token], Error> {
+        if self.is_finished() || self.is_my_turn() != writing {
+            return Err(Error::UnexpectedMessage);
+        }
+        let tokens = self.pattern.messages[self.next_message];
+        self.next_message += 1;
+        Ok(tokens)
+    }
+
+    /// Encapsulates to `pk`, returning the shared secret and the encoded ciphertext
+    fn encapsulate(&mut self, pk: &EncapsulationKey<Kyber768>, rng: &mut impl CryptoRngCore) -> ([u8; 32], Vec<u8>) {
+        let mut m = [0u8; 32];
+        rng.fill_bytes(&mut m);
+        let encapsulation = kem::encaps_derand(pk.as_key(), &m);
+        (encapsulation.shared_secret, kem::ciphertext_to_bytes(&encapsulation.ciphertext))
+    }
+
+    /// WriteMessage(payload): returns the next handshake message
+    pub fn write_message(&mut self, payload: &[u8], rng: &mut impl CryptoRngCore) -> Result<Vec<u8>, Error> {
+        let tokens = self.next_tokens(true)?;
+        let mut message = Vec::new();
+
+        for &token in tokens {
+            match token {
+                Token::E => {
+                    let mut seed = [0u8; 64];
+                    rng.fill_bytes(&mut seed);
+                    let e = DecapsulationKey::<Kyber768>::from_seed(&seed);
+                    let pk = e.encapsulation_key();
+                    message.extend_from_slice(pk.as_bytes());
+                    self.symmetric.mix_hash(pk.as_bytes());
+                    self.e = Some(e);
+                }
+                Token::S => {
+                    let pk = self.s.as_ref().ok_or(Error::MissingKey)?.encapsulation_key();
+                    let ciphertext = self.symmetric.encrypt_and_hash(pk.as_bytes())?;
+                    message.extend_from_slice(&ciphertext);
+                }
+                Token::Ekem => {
+                    let re = self.re.clone().ok_or(Error::MissingKey)?;
+                    let (ss, ct) = self.encapsulate(&re, rng);
+                    message.extend_from_slice(&ct);
+                    self.symmetric.mix_hash(&ct);
+                    self.symmetric.mix_key(&ss);
+                }
+                Token::Skem => {
+                    let rs = self.rs.clone().ok_or(Error::MissingKey)?;
+                    let (ss, ct) = self.encapsulate(&rs, rng);
+                    let ciphertext = self.symmetric.encrypt_and_hash(&ct)?;
+                    message.extend_from_slice(&ciphertext);
+                    self.symmetric.mix_key(&ss);
+                }
+            }
+        }
+
+        message.extend_from_slice(&self.symmetric.encrypt_and_hash(payload)?);
+        check_message_len(message.len())?;
+        Ok(message)
+    }
+
+    /// ReadMessage(message): processes the peer's handshake message and returns its payload
+    ///
+    /// A failed read leaves the handshake unusable; start a new one.
+    pub fn read_message(&mut self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        check_message_len(message.len())?;
+        let tokens = self.next_tokens(false)?;
+        let mut rest = message;
+
+        for &token in tokens {
+            match token {
+                Token::E => {
+                    let bytes = take(&mut rest, EK_BYTES)?;
+                    let re = EncapsulationKey::<Kyber768>::try_from(bytes).map_err(|_| Error::InvalidKey)?;
+                    self.symmetric.mix_hash(bytes);
+                    self.re = Some(re);
+                }
+                Token::S => {
+                    let ciphertext = take(&mut rest, self.symmetric.encrypted_len(EK_BYTES))?;
+                    let bytes = self.symmetric.decrypt_and_hash(ciphertext)?;
+                    let rs = EncapsulationKey::<Kyber768>::try_from(&bytes[..]).map_err(|_| Error::InvalidKey)?;
+                    self.rs = Some(rs);
+                }
+                Token::Ekem => {
+                    let ct = take(&mut rest, CT_BYTES)?;
+                    self.symmetric.mix_hash(ct);
+                    let e = self.e.as_ref().ok_or(Error::MissingKey)?;
+                    let ss = decapsulate(e, ct)?;
+                    self.symmetric.mix_key(&ss);
+                }
+                Token::Skem => {
+                    let ciphertext = take(&mut rest, self.symmetric.encrypted_len(CT_BYTES))?;
+                    let ct = self.symmetric.decrypt_and_hash(ciphertext)?;
+                    let s = self.s.as_ref().ok_or(Error::MissingKey)?;
+                    let ss = decapsulate(s, &ct)?;
+                    self.symmetric.mix_key(&ss);
+                }
+            }
+        }
+
+        self.symmetric.decrypt_and_hash(rest)
+    }
+
+    /// Splits the finished handshake into transport CipherStates
+    pub fn into_transport(self) -> Result<TransportState, Error> {
+        if !self.is_finished() {
+            return Err(Error::UnexpectedMessage);
+        }
+        let (i2r, r2i) = self.symmetric.split();
+        let (send, recv) = if self.initiator { (i2r, r2i) } else { (r2i, i2r) };
+        Ok(TransportState { send, recv, handshake_hash: self.symmetric.h, remote_static: self.rs })
+    }
+}
+
+/// ML-KEM decapsulation of an encoded ciphertext; invalid ciphertexts yield the implicit-rejection secret
+fn decapsulate(dk: &DecapsulationKey<Kyber768>, ct: &[u8]) -> Result<[u8; 32], Error> {
+    let ct = Ciphertext::<Kyber768>::try_from(ct).map_err(|_| Error::InvalidLength { expected: CT_BYTES, actual: ct.len() })?;
+    Ok(kem::decaps(dk.as_key(), ct.as_ciphertext()))
+}
+
+/// Transport phase after a completed handshake
+pub struct TransportState {
+    send: CipherState,
+    recv: CipherState,
+    handshake_hash: [u8; HASHLEN],
+    remote_static: Option<EncapsulationKey<Kyber768>>,
+}
+
+impl TransportState {
+    /// Encrypts a transport message
+    pub fn write_message(&mut self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        check_message_len(payload.len() + TAG_BYTES)?;
+        self.send.encrypt_with_ad(&[], payload)
+    }
+
+    /// Decrypts a transport message; messages must arrive in order
+    pub fn read_message(&mut self, message: &[u8]) -> Result<Vec<u8>, Error> {
+        check_message_len(message.len())?;
+        self.recv.decrypt_with_ad(&[], message)
+    }
+
+    /// Rekeys the sending direction; the peer must rekey its receiving direction
+    pub fn rekey_outgoing(&mut self) {
+        self.send.rekey();
+    }
+
+    pub fn rekey_incoming(&mut self) {
+        self.recv.rekey();
+    }
+
+    pub fn handshake_hash(&self) -> &[u8; HASHLEN] {
+        &self.handshake_hash
+    }
+
+    pub fn remote_static(&self) -> Option<&EncapsulationKey<Kyber768>> {
+        self.remote_static.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ake::{MemoryTransport, Role};
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha20Rng;
+
+    fn static_key(seed: u8) -> DecapsulationKey<Kyber768> {
+        DecapsulationKey::from_seed(&[seed; 64])
+    }
+
+    /// Runs a handshake over an in-memory transport, letting `attack` modify messages in flight
+    fn run_handshake(
+        pattern: HandshakePattern,
+        initiator_static: Option<DecapsulationKey<Kyber768>>,
+        responder_static: Option<DecapsulationKey<Kyber768>>,
+        mut attack: impl FnMut(usize, &mut Vec<u8>),
+    ) -> Result<(TransportState, TransportState), Error> {
+        let mut rng = ChaCha20Rng::seed_from_u64(7);
+        let rs = responder_static.as_ref().map(|s| s.encapsulation_key());
+        let mut initiator = HandshakeState::new(pattern, true, b"prologue", initiator_static, rs)?;
+        let mut responder = HandshakeState::new(pattern, false, b"prologue", responder_static, None)?;
+        let mut transport = MemoryTransport::new();
+
+        for i in 0..pattern.messages.len() {
+            let (writer, reader, from, to) = if i % 2 == 0 {
+                (&mut initiator, &mut responder, Role::Initiator, Role::Responder)
+            } else {
+                (&mut responder, &mut initiator, Role::Responder, Role::Initiator)
+            };
+            let payload = format!("handshake payload {}", i);
+            transport.send(from, writer.write_message(payload.as_bytes(), &mut rng)?);
+
+            let mut message = transport.receive(to).unwrap();
+            attack(i, &mut message);
+            assert_eq!(reader.read_message(&message)?, payload.as_bytes());
+        }
+
+        assert_eq!(initiator.handshake_hash(), responder.handshake_hash());
+        Ok((initiator.into_transport()?, responder.into_transport()?))
+    }
+
+    #[test]
+    fn test_patterns_complete_and_transport_works() {
+        for pattern in [PQ_NN, PQ_NK, PQ_XX, PQ_IK] {
+            let (mut alice, mut bob) =
+                run_handshake(pattern, Some(static_key(1)), Some(static_key(2)), |_, _| {}).unwrap();
+
+            for i in 0..3u8 {
+                let message = alice.write_message(&[i; 100]).unwrap();
+                assert_eq!(message.len(), 100 + TAG_BYTES);
+                assert_eq!(bob.read_message(&message).unwrap(), [i; 100]);
+                let reply = bob.write_message(b"reply").unwrap();
+                assert_eq!(alice.read_message(&reply).unwrap(), b"reply");
+            }
+
+            alice.rekey_outgoing();
+            bob.rekey_incoming();
+            let message = alice.write_message(b"after rekey").unwrap();
+            assert_eq!(bob.read_message(&message).unwrap(), b"after rekey");
+        }
+    }
+
+    #[test]
+    fn test_static_keys_learned() {
+        let (alice, bob) = run_handshake(PQ_XX, Some(static_key(1)), Some(static_key(2)), |_, _| {}).unwrap();
+        assert_eq!(alice.remote_static(), Some(&static_key(2).encapsulation_key()));
+        assert_eq!(bob.remote_static(), Some(&static_key(1).encapsulation_key()));
+
+        let (alice, bob) = run_handshake(PQ_NN, None, None, |_, _| {}).unwrap();
+        assert!(alice.remote_static().is_none() && bob.remote_static().is_none());
+    }
+
+    #[test]
+    fn test_message_sizes() {
+        // pqXX: e | ekem, s + tag, payload + tag | skem + tag, s + tag, payload + tag | skem + tag, payload + tag
+        let mut sizes = Vec::new();
+        run_handshake(PQ_XX, Some(static_key(1)), Some(static_key(2)), |_, m| sizes.push(m.len())).unwrap();
+        let payload = "handshake payload 0".len();
+        assert_eq!(
+            sizes,
+            [
+                EK_BYTES + payload,
+                CT_BYTES + EK_BYTES + 2 * TAG_BYTES + payload,
+                CT_BYTES + EK_BYTES + 3 * TAG_BYTES + payload,
+                CT_BYTES + 2 * TAG_BYTES + payload,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tampering_and_wrong_keys_fail() {
+        for pattern in [PQ_NN, PQ_NK, PQ_XX, PQ_IK] {
+            for target in 0..pattern.messages.len() {
+                // Unencrypted fields such as `e` are bound through the handshake
+                // hash, so the change surfaces at the next decryption
+                let result = run_handshake(pattern, Some(static_key(1)), Some(static_key(2)), |i, m| {
+                    if i == target {
+                        m[0] ^= 0x01;
+                    }
+                });
+                assert!(result.is_err(), "{} message {}", pattern.name, target);
+            }
+        }
+
+        // The initiator expects a different responder static key
+        let mut rng = ChaCha20Rng::seed_from_u64(1);
+        let mut initiator =
+            HandshakeState::new(PQ_IK, true, b"", Some(static_key(1)), Some(static_key(3).encapsulation_key())).unwrap();
+        let mut responder = HandshakeState::new(PQ_IK, false, b"", Some(static_key(2)), None).unwrap();
+        let message = initiator.write_message(b"", &mut rng).unwrap();
+        assert_eq!(responder.read_message(&message), Err(Error::OpenError));
+
+        // Different prologues diverge at the first encrypted payload
+        let mut initiator = HandshakeState::new(PQ_NN, true, b"a", None, None).unwrap();
+        let mut responder = HandshakeState::new(PQ_NN, false, b"b", None, None).unwrap();
+        responder.read_message(&initiator.write_message(b"", &mut rng).unwrap()).unwrap();
+        let message = responder.write_message(b"", &mut rng).unwrap();
+        assert_eq!(initiator.read_message(&message), Err(Error::OpenError));
+    }
+
+    #[test]
+    fn test_state_machine_misuse() {
+        let mut rng = ChaCha20Rng::seed_from_u64(2);
+        assert_eq!(HandshakeState::new(PQ_NK, true, b"", None, None).err(), Some(Error::MissingKey));
+
+        let mut initiator = HandshakeState::new(PQ_XX, true, b"", None, None).unwrap();
+        assert_eq!(initiator.read_message(&[]), Err(Error::UnexpectedMessage));
+        let message = initiator.write_message(b"", &mut rng).unwrap();
+        assert_eq!(initiator.write_message(b"", &mut rng), Err(Error::UnexpectedMessage));
+        assert_eq!(initiator.into_transport().err(), Some(Error::UnexpectedMessage));
+
+        let mut responder = HandshakeState::new(PQ_XX, false, b"", None, None).unwrap();
+        responder.read_message(&message).unwrap();
+        assert_eq!(responder.write_message(b"", &mut rng), Err(Error::MissingKey));
+
+        let mut responder = HandshakeState::new(PQ_XX, false, b"", None, None).unwrap();
+        assert!(matches!(responder.read_message(&message[..100]), Err(Error::InvalidLength { .. })));
+    }
+
+    #[test]
+    fn test_transport_replay_and_reorder_rejected() {
+        let (mut alice, mut bob) = run_handshake(PQ_NN, None, None, |_, _| {}).unwrap();
+        let first = alice.write_message(b"first").unwrap();
+        let second = alice.write_message(b"second").unwrap();
+        assert_eq!(bob.read_message(&second), Err(Error::OpenError));
+        assert_eq!(bob.read_message(&first).unwrap(), b"first");
+        assert_eq!(bob.read_message(&first), Err(Error::OpenError));
+        assert!(alice.write_message(&[0u8; MAX_MESSAGE_BYTES]).is_err());
+    }
+}